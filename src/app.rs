@@ -1,8 +1,35 @@
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::converter::{ConversionMode, ConversionProgress, VideoConverter, VideoFormat, VideoSettings, Resolution, Bitrate, FrameRate};
-use crate::file_browser::FileBrowser;
+use rust_tui_video_convert::converter::{ConversionMode, ConversionProgress, ConversionStage, ConversionStats, VideoConverter, VideoFormat, VideoSettings, Resolution, Crop, Ladder, Bitrate, FrameRate, FrameRateMode, ImageQuality, ExternalAudio, ExternalSubtitles, ExtraOutput, TrackKind, Trim, H264Profile, H264Level, KeyframeInterval, PixelFormat, EncoderTune, Downmix, EcoMode, IoPriority, LogLevel, QualityTarget, checksum_path, is_url, parse_position};
+use rust_tui_video_convert::disc;
+use rust_tui_video_convert::ffmpeg::{FFmpegCapabilities, FFmpegConverter, MediaTrack};
+use rust_tui_video_convert::file_browser::{is_convertible, is_subtitle_file, FileBrowser};
+use rust_tui_video_convert::jobs::{JobId, JobManager};
+use rust_tui_video_convert::metadata;
+use rust_tui_video_convert::paths::{self, AccessKind, AccessProblem};
+use rust_tui_video_convert::plugins::Plugin;
+use rust_tui_video_convert::upload::UploadTarget;
+use rust_tui_video_convert::ytdlp;
+use serde::Serialize;
+
+use crate::config::{Config, FormatDefaults};
+use crate::history::{self, HistoryEntry, HistoryStats};
+use crate::launch;
+use crate::durations::{DurationProbe, PROBE_WINDOW};
+use crate::preview::{FramingPreview, PreviewLoader, TrimEditor};
+use crate::stills::{StillExporter, StillFormat, StillPrompt};
+use crate::diagnostics::Diagnostics;
+use crate::subtitles::{self, SubtitleExtractor, SubtitlePicker};
+use crate::probe::{ProbeRequest, Probed, SourceProbe};
+use crate::tracks::TrackPicker;
+use crate::watcher::DirWatcher;
+
+// How often the reduced-flicker display redraws for progress alone
+const PROGRESS_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
 
 // Application tabs
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -12,6 +39,7 @@ pub enum AppTab {
     Converting,
     Complete,
     Settings,
+    Stats,
     Help,
 }
 
@@ -22,20 +50,494 @@ pub struct App {
     pub selected_format: Option<VideoFormat>,
     pub selected_format_idx: usize,
     pub should_quit: bool,
+    // Quitting with conversions running asks first; once confirmed, the app
+    // waits for the cancelled jobs to clean up their partial output
+    pub quit_confirm: bool,
+    pub quitting: bool,
     pub show_popup: bool,
-    pub conversion_progress: Option<ConversionProgress>,
-    pub converter_rx: Option<mpsc::Receiver<ConversionProgress>>,
+    // Every job the manager runs for us, local and remote, oldest first with
+    // waiting ones last in the order they will run; and the one the
+    // Converting tab follows
+    pub job_manager: JobManager,
+    pub jobs: Vec<Job>,
+    pub current_job: Option<JobId>,
+    // When progress last asked for a redraw, for the reduced-flicker display
+    progress_drawn: Option<Instant>,
+    // Every conversion that ended since the last 'n', listed on the Complete tab,
+    // the one picked there and whether its details are open
+    pub results: Vec<ConversionResult>,
+    pub selected_result: usize,
+    pub result_details: bool,
     
     // Advanced video settings
     pub video_settings: VideoSettings,
     pub selected_setting: AdvancedSetting,
+    pub bitrate_calculator: Option<BitrateCalculator>,
+    pub framing_preview: Option<FramingPreview>,
+    // Stepping through the selected file to set the trim's in and out points
+    pub trim_editor: Option<TrimEditor>,
+    
+    // What the local FFmpeg build can produce, if FFmpeg is installed
+    pub ffmpeg_capabilities: Option<FFmpegCapabilities>,
+    // Whether this build links libav for in-process conversion
+    pub native_available: bool,
+    
+    // External converters loaded from plugin manifests, and the one in use (if any)
+    pub plugins: Vec<Arc<Plugin>>,
+    pub plugin_errors: Vec<String>,
+    pub selected_plugin: Option<usize>,
+    
+    pub config: Config,
+    // Text being typed into a path setting, while it is being edited
+    pub path_input: Option<String>,
+    
+    // Still frames of the files selected in the browser
+    pub preview: PreviewLoader,
+    // The destination pane of the dual-pane layout, listing folders only; while it is
+    // open, outputs go to the folder it shows. And whether keys go to it
+    pub destination: Option<FileBrowser>,
+    pub destination_focused: bool,
+    
+    // Notices files appearing in or leaving the browser's folder
+    pub dir_watcher: DirWatcher,
+    // Lengths of the videos in the file list
+    pub durations: DurationProbe,
+    
+    // Result of the last open-folder/play request on the Complete screen
+    pub launch_message: Option<String>,
+    // The outputs waiting on a yes/no before they are deleted from the Complete screen
+    pub delete_confirm: Option<DeleteConfirm>,
+    // Asks whether to remux or re-encode when the target is the source's own format
+    pub same_format_prompt: bool,
+    // Why the selected file was refused as a source, until the message is dismissed
+    pub not_playable: Option<String>,
+    // A source that can't be read or an output folder that can't be written, found
+    // before the conversion started or after it failed
+    pub access_prompt: Option<AccessPrompt>,
+    // Settings the target container can't take, shown before the conversion starts
+    pub conflict_prompt: Option<ConflictPrompt>,
+    
+    // Subtitle extraction from the browser: the track picker while it is open
+    pub subtitle_picker: Option<SubtitlePicker>,
+    pub subtitles: SubtitleExtractor,
+    
+    // A network source used instead of the browser's file: the URL while it is
+    // being typed, and the one chosen
+    pub url_input: Option<String>,
+    pub source_url: Option<PathBuf>,
+    // Fetch the URL with yt-dlp before converting it; whether yt-dlp is
+    // installed is checked when the URL popup first opens
+    pub url_fetch: bool,
+    pub ytdlp_available: Option<bool>,
+    
+    // Still frame export: the timestamp prompt while it is open, the image type
+    // it last used, and the exports running in the background
+    pub still_prompt: Option<StillPrompt>,
+    pub still_format: StillFormat,
+    pub stills: StillExporter,
+    
+    // The diagnostics screen for bug reports, while it is open
+    pub diagnostics: Option<Diagnostics>,
+    
+    // Latest subtitle or still frame outcome for a file (shown while that file is highlighted)
+    pub browser_message: Option<(PathBuf, String)>,
+    
+    // Audio/subtitle track choice: the picker while it is open, the source whose
+    // tracks are being read for it, and the file the choice in
+    // video_settings.tracks was made for (track numbers are per file)
+    pub track_picker: Option<TrackPicker>,
+    pub tracks_reading: Option<PathBuf>,
+    pub tracks_source: Option<PathBuf>,
+    pub track_message: Option<String>,
+    
+    // Reads tracks and lengths for the popups without holding up the interface
+    pub source_probe: SourceProbe,
+    
+    // Where the HTTP API listens (if it was started)
+    pub serve_addr: Option<SocketAddr>,
+    // The queued job that the reorder and priority keys act on, and why the
+    // last of those keys did nothing
+    pub selected_queued: Option<JobId>,
+    pub queue_message: Option<String>,
+    
+    // Lifetime totals from the history file, read when the Stats tab is opened
+    pub history_stats: Option<Result<HistoryStats, String>>,
+}
+
+// The popup for a source that can't be read or an output that can't be written,
+// with a folder to write to instead when the source's own folder refused
+pub struct AccessPrompt {
+    pub problem: AccessProblem,
+    pub redirect: Option<PathBuf>,
+    // How the refused conversion was started, so it can be started again redirected
+    sample: bool,
+    remux: bool,
+}
+
+// The popup listing settings the target container can't honour, before a
+// conversion starts; it can go ahead with them adjusted
+pub struct ConflictPrompt {
+    pub conflicts: Vec<String>,
+    sample: bool,
+}
+
+// A conversion the app is following, started in the interface or submitted from
+// outside it. Remote jobs run one at a time, alongside whatever is converting in
+// the TUI, and are listed in the jobs panel.
+pub struct Job {
+    pub id: JobId,
+    pub source_file: PathBuf,
+    pub target_format: VideoFormat,
+    // The backend it was started with, and its label
+    pub mode: ConversionMode,
+    pub backend: String,
+    // Submitted over HTTP or by another invocation, and whether only a test encode
+    pub remote: bool,
+    pub sample: bool,
+    // The stages it will go through, for the Converting tab
+    pub stages: Vec<ConversionStage>,
+    state: JobState,
+    pub progress: Option<ConversionProgress>,
+    // Whether it jumps ahead of normal jobs while queued
+    pub high_priority: bool,
+    // When it started running, and when it finished
+    pub started_at: Option<SystemTime>,
+    pub finished_at: Option<SystemTime>,
+}
+
+// Where a job is in its life: Queued ⇄ Paused while it waits its turn, then
+// Running, then one of Completed, Failed or Cancelled. Pausing only holds a job
+// back in the queue; once running it can't be paused, but it can be cancelled
+// at any point before it finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    // How a job that has ended went
+    pub fn finished(progress: &ConversionProgress) -> Self {
+        if !progress.has_error {
+            JobState::Completed
+        } else if progress.cancelled {
+            JobState::Cancelled
+        } else {
+            JobState::Failed
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobState::Queued => "Queued",
+            JobState::Running => "Running",
+            JobState::Paused => "Paused",
+            JobState::Completed => "Completed",
+            JobState::Failed => "Failed",
+            JobState::Cancelled => "Cancelled",
+        }
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobState::Completed | JobState::Failed | JobState::Cancelled)
+    }
+    
+    // Still waiting for its turn, held back or not
+    pub fn is_waiting(&self) -> bool {
+        matches!(self, JobState::Queued | JobState::Paused)
+    }
+}
+
+impl Job {
+    // A job handed to the manager: submitted ones start at once, queued ones wait
+    fn new(id: JobId, source_file: PathBuf, target_format: VideoFormat, mode: ConversionMode, backend: String, queued: bool) -> Self {
+        Self {
+            id,
+            source_file,
+            target_format,
+            mode,
+            backend,
+            remote: false,
+            sample: false,
+            stages: Vec::new(),
+            state: if queued { JobState::Queued } else { JobState::Running },
+            progress: None,
+            high_priority: false,
+            started_at: (!queued).then(SystemTime::now),
+            finished_at: None,
+        }
+    }
+    
+    pub fn state(&self) -> JobState {
+        self.state
+    }
+    
+    // Follow the manager's queue while waiting: paused, queued again, or let out to run
+    fn sync_queue(&mut self, queued: bool, paused: bool) {
+        if !self.state.is_waiting() {
+            return;
+        }
+        self.state = match (queued, paused) {
+            (true, true) => JobState::Paused,
+            (true, false) => JobState::Queued,
+            (false, _) => {
+                self.started_at.get_or_insert_with(SystemTime::now);
+                JobState::Running
+            },
+        };
+    }
+    
+    // Take in an update from the converter; the last one decides how the job ended
+    fn report(&mut self, progress: ConversionProgress) {
+        if self.state.is_finished() {
+            return;
+        }
+        let now = SystemTime::now();
+        // A queued job cancelled before its turn never ran
+        if !(progress.is_complete && self.state.is_waiting()) {
+            self.started_at.get_or_insert(now);
+        }
+        self.state = if progress.is_complete {
+            self.finished_at = Some(now);
+            JobState::finished(&progress)
+        } else {
+            JobState::Running
+        };
+        self.progress = Some(progress);
+    }
+    
+    // Time spent running so far, or in all once finished; None while queued
+    pub fn elapsed(&self) -> Option<Duration> {
+        let started = self.started_at?;
+        let until = self.finished_at.unwrap_or_else(SystemTime::now);
+        Some(until.duration_since(started).unwrap_or_default())
+    }
+}
+
+// Everything a finished job left that deleting its output removes: the files it
+// wrote (main output first), the checksum/metadata sidecars found beside them,
+// and the folder its HLS playlists were packaged into
+pub struct DeleteConfirm {
+    pub outputs: Vec<PathBuf>,
+    pub sidecars: Vec<PathBuf>,
+    pub hls_dir: Option<PathBuf>,
+}
+
+impl DeleteConfirm {
+    fn for_result(result: &ConversionResult) -> Option<Self> {
+        result.output()?;
+        let outputs: Vec<PathBuf> = result.progress.output_files().into_iter().filter(|output| output.is_file()).collect();
+        let sidecars = outputs.iter()
+            .flat_map(|output| [checksum_path(output), metadata::sidecar_path(output)])
+            .filter(|sidecar| sidecar.is_file())
+            .collect();
+        let hls_dir = result.progress.hls_playlist.as_deref()
+            .and_then(Path::parent)
+            .filter(|dir| dir.is_dir())
+            .map(Path::to_path_buf);
+        Some(Self { outputs, sidecars, hls_dir })
+    }
+}
+
+// A conversion that has ended, started here or submitted remotely
+pub struct ConversionResult {
+    pub progress: ConversionProgress,
+    pub stats: ConversionStats,
+    pub mode: ConversionMode,
+    pub remote: bool,
+}
+
+impl ConversionResult {
+    pub fn state(&self) -> JobState {
+        JobState::finished(&self.progress)
+    }
+    
+    // The converted file, if the job actually wrote one
+    pub fn output(&self) -> Option<&Path> {
+        (self.state() == JobState::Completed && !self.mode.is_simulation() && self.progress.output_file.is_file())
+            .then_some(self.progress.output_file.as_path())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AdvancedSetting {
+    Backend,
     Resolution,
+    Crop,
+    Ladder,
     Bitrate,
+    SmartQuality,
     FrameRate,
+    FrameRateMode,
+    ImageQuality,
+    Subtitles,
+    ExternalAudio,
+    FileTimes,
+    Checksum,
+    Metadata,
+    SourceCheck,
+    H264Profile,
+    H264Level,
+    Tune,
+    PixelFormat,
+    KeyframeInterval,
+    SceneCut,
+    AudioPassthrough,
+    Downmix,
+    IoPriority,
+    EcoMode,
+    LogLevel,
+    FFmpegPath,
+    FFprobePath,
+    UploadTo,
+    FallbackOutput,
+    ExtraExtensions,
+}
+
+impl AdvancedSetting {
+    // The setting's name as its row shows it
+    pub fn name(&self) -> &'static str {
+        match self {
+            AdvancedSetting::Backend => "Conversion Tool",
+            AdvancedSetting::Resolution => "Resolution",
+            AdvancedSetting::Crop => "Crop",
+            AdvancedSetting::Ladder => "ABR Ladder",
+            AdvancedSetting::Bitrate => "Bitrate",
+            AdvancedSetting::SmartQuality => "Smart Quality",
+            AdvancedSetting::FrameRate => "Frame Rate",
+            AdvancedSetting::FrameRateMode => "Frame Rate Mode",
+            AdvancedSetting::ImageQuality => "Image Quality",
+            AdvancedSetting::Subtitles => "Subtitles",
+            AdvancedSetting::ExternalAudio => "Audio File",
+            AdvancedSetting::FileTimes => "File Times",
+            AdvancedSetting::Checksum => "Checksum",
+            AdvancedSetting::Metadata => "Metadata",
+            AdvancedSetting::SourceCheck => "Source Check",
+            AdvancedSetting::H264Profile => "H.264 Profile",
+            AdvancedSetting::H264Level => "H.264 Level",
+            AdvancedSetting::Tune => "H.264 Tune",
+            AdvancedSetting::PixelFormat => "Pixel Format",
+            AdvancedSetting::KeyframeInterval => "Keyframe Interval",
+            AdvancedSetting::SceneCut => "Scene-cut Keyframes",
+            AdvancedSetting::AudioPassthrough => "Surround Passthrough",
+            AdvancedSetting::Downmix => "Surround Downmix",
+            AdvancedSetting::IoPriority => "I/O Priority",
+            AdvancedSetting::EcoMode => "Eco Mode",
+            AdvancedSetting::LogLevel => "FFmpeg Log",
+            AdvancedSetting::FFmpegPath => "FFmpeg",
+            AdvancedSetting::FFprobePath => "FFprobe",
+            AdvancedSetting::UploadTo => "Upload To",
+            AdvancedSetting::FallbackOutput => "Fallback Output",
+            AdvancedSetting::ExtraExtensions => "Extra Extensions",
+        }
+    }
+    
+    // What the setting is for, shown beneath the settings while it is selected
+    pub fn hint(&self) -> &'static str {
+        match self {
+            AdvancedSetting::Backend => "Which converter runs the job: linked libav, an external FFmpeg, or a plugin's tool",
+            AdvancedSetting::Resolution => "Output frame size. Downscaling saves the most space; upscaling never adds detail",
+            AdvancedSetting::Crop => "Trims the picture to another shape, keeping the centre. Press v to preview the framing on the highlighted file",
+            AdvancedSetting::Ladder => "Several sizes of the video in one job for adaptive streaming, in place of the one output. Replaces Resolution and Bitrate",
+            AdvancedSetting::Bitrate => "How many bits per second the video gets. A fixed rate makes the size predictable, not the quality",
+            AdvancedSetting::SmartQuality => "Finds the lowest quality setting (highest CRF) that still reaches a perceptual quality score, by test encoding samples first",
+            AdvancedSetting::FrameRate => "Frames per second of the output. Lowering it saves space but makes motion choppier",
+            AdvancedSetting::FrameRateMode => "Whether frames come at a fixed rate or keep their own timing",
+            AdvancedSetting::ImageQuality => "Quality of WEBP and APNG output, where a bitrate has no meaning",
+            AdvancedSetting::Subtitles => "An external subtitle file, added as a track or burned into the picture. Pick one in the file browser",
+            AdvancedSetting::ExternalAudio => "A voiceover or music file used instead of the source's audio, or mixed over it. Press a on one in the file browser",
+            AdvancedSetting::FileTimes => "Give the output the source's access/modification times (and permissions on Unix), so archives keep their order",
+            AdvancedSetting::Checksum => "Write <output>.sha256 beside the output, for `sha256sum -c` integrity checks later",
+            AdvancedSetting::Metadata => "Write <output>.json with the output's streams, these settings and the FFmpeg command line",
+            AdvancedSetting::SourceCheck => "Decode the first and last seconds of the source before a long encode, so truncated downloads fail straight away",
+            AdvancedSetting::H264Profile => "The H.264 feature set. Lower profiles play on older devices but compress worse",
+            AdvancedSetting::H264Level => "The largest frame size, frame rate and bitrate a decoder must handle. A file above it is refused",
+            AdvancedSetting::Tune => "Adjusts x264 for the kind of content being encoded",
+            AdvancedSetting::PixelFormat => "Bit depth and chroma layout of the output video",
+            AdvancedSetting::KeyframeInterval => "How often a full frame is stored. Seeking and stream segments can only start at one",
+            AdvancedSetting::SceneCut => "Extra keyframes at scene changes improve quality; turn off for the fixed GOP streaming servers require",
+            AdvancedSetting::AudioPassthrough => "Copy AC3, E-AC3, DTS and TrueHD audio untouched for home-theater receivers where the format can carry it",
+            AdvancedSetting::Downmix => "Folds surround audio to stereo; takes precedence over passthrough",
+            AdvancedSetting::IoPriority => "Disk priority of the encoder (ionice on Linux), so a conversion doesn't stall other work on the same disk",
+            AdvancedSetting::EcoMode => "Half the cores and faster x264/VP9 presets, so a laptop doesn't heat up and throttle partway through a batch. Checked as each job starts",
+            AdvancedSetting::LogLevel => "Above Normal, the next jobs' encodes write an FFmpeg report to the log folder, for tracking down codec problems",
+            AdvancedSetting::FFmpegPath => "The ffmpeg executable to use instead of searching for one. Relative paths are looked up next to this app",
+            AdvancedSetting::FFprobePath => "The ffprobe executable to use instead of searching for one. Relative paths are looked up next to this app",
+            AdvancedSetting::UploadTo => "Copy each finished output to s3://bucket/prefix (AWS CLI) or sftp://user@host/dir (curl) as the job's last stage",
+            AdvancedSetting::FallbackOutput => "Offered as the output folder when the source's own can't be written (a DVD, a read-only share). Unset offers the temp folder",
+            AdvancedSetting::ExtraExtensions => "More file types for the browser to list, such as mpg, divx or rmvb sources. The output formats, VOB and M2TS are always listed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalculatorField {
+    Duration,
+    TargetSize,
+    AudioBitrate,
+}
+
+// Input state for the bitrate calculator popup
+pub struct BitrateCalculator {
+    pub duration: String,
+    pub target_size_mb: String,
+    pub audio_kbps: String,
+    pub focused: CalculatorField,
+    // The selected file while its length is being read to fill in the duration
+    pub reading: Option<PathBuf>,
+}
+
+impl BitrateCalculator {
+    pub fn new() -> Self {
+        Self {
+            duration: String::new(),
+            target_size_mb: String::new(),
+            audio_kbps: "128".to_string(),
+            focused: CalculatorField::Duration,
+            reading: None,
+        }
+    }
+    
+    // The selected file's length, unless a duration was typed in the meantime
+    pub fn fill_duration(&mut self, duration_secs: f64) {
+        if !self.duration.is_empty() {
+            return;
+        }
+        self.duration = format_duration_input(duration_secs);
+        if self.focused == CalculatorField::Duration {
+            self.focused = CalculatorField::TargetSize;
+        }
+    }
+    
+    // The video bitrate for the current inputs, if they are complete and achievable
+    pub fn result(&self) -> Option<Bitrate> {
+        let duration = parse_position(&self.duration)?;
+        let target_size = self.target_size_mb.trim().parse::<f64>().ok()?;
+        let audio = if self.audio_kbps.trim().is_empty() {
+            0
+        } else {
+            self.audio_kbps.trim().parse::<u32>().ok()?
+        };
+        Bitrate::for_target_size(duration, target_size, audio)
+    }
+    
+    fn focused_input(&mut self) -> &mut String {
+        match self.focused {
+            CalculatorField::Duration => &mut self.duration,
+            CalculatorField::TargetSize => &mut self.target_size_mb,
+            CalculatorField::AudioBitrate => &mut self.audio_kbps,
+        }
+    }
+}
+
+fn format_duration_input(duration_secs: f64) -> String {
+    let secs = duration_secs.round() as u64;
+    format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
 }
 
 impl App {
@@ -43,60 +545,187 @@ impl App {
         // Start in the current directory
         let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
         
+        // Binary paths must be in place before probing FFmpeg's capabilities
+        let config = Config::load().unwrap_or_default();
+        config.apply();
+        let mut video_settings = VideoSettings {
+            upload_to: config.upload_target(),
+            hooks: config.hooks(),
+            log_level: config.log_level,
+            eco_mode: config.eco_mode,
+            log_dir: Config::log_dir(),
+            output_dir: config.output_dir.clone(),
+            ..VideoSettings::default()
+        };
+        // The preset's format (or MP4) is picked until another one is chosen
+        let preset = config.preset_format();
+        if let Some(defaults) = config.formats.get(preset.unwrap_or(VideoFormat::MP4).extension()) {
+            defaults.apply(&mut video_settings);
+        }
+        
+        let (plugins, plugin_errors) = Config::plugin_dir()
+            .map(|dir| Plugin::load_dir(&dir))
+            .unwrap_or_default();
+        
+        let mut file_browser = FileBrowser::new(current_dir);
+        if !config.extra_extensions.is_empty() {
+            file_browser.set_extra_extensions(&config.extra_extensions);
+        }
+        
         Self {
             current_tab: AppTab::FileBrowser,
-            file_browser: FileBrowser::new(current_dir),
-            selected_format: None,
-            selected_format_idx: 0,
+            file_browser,
+            selected_format: preset,
+            selected_format_idx: preset.and_then(|preset| VideoFormat::ALL.iter().position(|format| *format == preset)).unwrap_or(0),
             should_quit: false,
+            quit_confirm: false,
+            quitting: false,
             show_popup: false,
-            conversion_progress: None,
-            converter_rx: None,
+            job_manager: JobManager::new(),
+            jobs: Vec::new(),
+            current_job: None,
+            progress_drawn: None,
+            results: Vec::new(),
+            selected_result: 0,
+            result_details: false,
             
             // Default video settings
-            video_settings: VideoSettings {
-                resolution: Resolution::Original,
-                bitrate: Bitrate::Auto,
-                frame_rate: FrameRate::Original,
-            },
+            video_settings,
             selected_setting: AdvancedSetting::Resolution,
+            bitrate_calculator: None,
+            framing_preview: None,
+            trim_editor: None,
+            ffmpeg_capabilities: FFmpegCapabilities::detect(),
+            native_available: ConversionMode::native_available(),
+            plugins: plugins.into_iter().map(Arc::new).collect(),
+            plugin_errors,
+            selected_plugin: None,
+            config,
+            path_input: None,
+            preview: PreviewLoader::new(),
+            destination: None,
+            destination_focused: false,
+            dir_watcher: DirWatcher::new(),
+            durations: DurationProbe::new(),
+            launch_message: None,
+            delete_confirm: None,
+            same_format_prompt: false,
+            not_playable: None,
+            access_prompt: None,
+            conflict_prompt: None,
+            subtitle_picker: None,
+            subtitles: SubtitleExtractor::new(),
+            url_input: None,
+            source_url: None,
+            url_fetch: false,
+            ytdlp_available: None,
+            still_prompt: None,
+            diagnostics: None,
+            still_format: StillFormat::Png,
+            stills: StillExporter::new(),
+            browser_message: None,
+            track_picker: None,
+            tracks_reading: None,
+            tracks_source: None,
+            track_message: None,
+            source_probe: SourceProbe::new(),
+            selected_queued: None,
+            queue_message: None,
+            serve_addr: None,
+            history_stats: None,
         }
     }
-
+    
     pub fn next_format(&mut self) {
-        self.selected_format_idx = (self.selected_format_idx + 1) % 5;
+        self.selected_format_idx = (self.selected_format_idx + 1) % VideoFormat::ALL.len();
         self.update_selected_format();
     }
-
+    
     pub fn previous_format(&mut self) {
         if self.selected_format_idx > 0 {
             self.selected_format_idx -= 1;
         } else {
-            self.selected_format_idx = 4;
+            self.selected_format_idx = VideoFormat::ALL.len() - 1;
         }
         self.update_selected_format();
     }
     
     fn update_selected_format(&mut self) {
-        self.selected_format = Some(match self.selected_format_idx {
-            0 => VideoFormat::MP4,
-            1 => VideoFormat::MKV,
-            2 => VideoFormat::AVI,
-            3 => VideoFormat::MOV,
-            _ => VideoFormat::WEBM,
-        });
+        let previous = self.get_current_format();
+        self.selected_format = Some(VideoFormat::ALL[self.selected_format_idx]);
+        
+        // Keep what was set up for the format being left, and bring back what
+        // was last used with the new one (if it has been used before)
+        let format = self.get_current_format();
+        if format != previous {
+            self.remember_format_settings(previous);
+            if let Some(defaults) = self.config.formats.get(format.extension()) {
+                defaults.apply(&mut self.video_settings);
+            }
+        }
+    }
+    
+    // Mark the highlighted format as another output of the next conversion, or unmark it.
+    // Whichever format is highlighted when it starts is the main output; the other
+    // marked ones are written alongside it.
+    pub fn toggle_extra_output(&mut self) {
+        let format = VideoFormat::ALL[self.selected_format_idx];
+        let extras = &mut self.video_settings.extra_outputs;
+        match extras.iter().position(|extra| extra.format == format) {
+            Some(idx) => {
+                extras.remove(idx);
+            },
+            None => extras.push(ExtraOutput { format, resolution: None }),
+        }
+    }
+    
+    // Step a marked format through its own resolutions, starting from the job's
+    pub fn next_extra_resolution(&mut self) {
+        let format = VideoFormat::ALL[self.selected_format_idx];
+        if let Some(extra) = self.video_settings.extra_outputs.iter_mut().find(|extra| extra.format == format) {
+            extra.resolution = match extra.resolution {
+                None => Some(Resolution::Original),
+                Some(Resolution::Original) => Some(Resolution::SD360p),
+                Some(Resolution::SD360p) => Some(Resolution::SD480p),
+                Some(Resolution::SD480p) => Some(Resolution::HD720p),
+                Some(Resolution::HD720p) => Some(Resolution::HD1080p),
+                Some(Resolution::HD1080p) => Some(Resolution::UHD4K),
+                Some(Resolution::UHD4K) => None,
+            };
+        }
+    }
+    
+    pub fn extra_output(&self, format: VideoFormat) -> Option<&ExtraOutput> {
+        self.video_settings.extra_outputs.iter().find(|extra| extra.format == format)
+    }
+    
+    // What a conversion to the current format writes besides it: the other marked
+    // formats, less the current one unless it's marked at a size of its own
+    pub fn extra_outputs(&self) -> Vec<ExtraOutput> {
+        let format = self.get_current_format();
+        self.video_settings.extra_outputs
+            .iter()
+            .filter(|extra| extra.format != format || extra.resolution.is_some_and(|resolution| resolution != self.video_settings.resolution))
+            .copied()
+            .collect()
+    }
+    
+    fn remember_format_settings(&mut self, format: VideoFormat) {
+        self.config.formats.insert(format.extension().to_string(), FormatDefaults::from_settings(&self.video_settings));
     }
     
     pub fn next_tab(&mut self) {
         self.current_tab = match self.current_tab {
             AppTab::FileBrowser => AppTab::FormatSelection,
             AppTab::FormatSelection => AppTab::Settings,
-            AppTab::Settings => AppTab::Help,
+            AppTab::Settings => AppTab::Stats,
+            AppTab::Stats => AppTab::Help,
             AppTab::Help => AppTab::FileBrowser,
             // Don't change tabs during conversion or when complete
             AppTab::Converting => AppTab::Converting,
             AppTab::Complete => AppTab::Complete,
         };
+        self.refresh_history_stats();
     }
     
     pub fn previous_tab(&mut self) {
@@ -104,134 +733,1355 @@ impl App {
             AppTab::FileBrowser => AppTab::Help,
             AppTab::FormatSelection => AppTab::FileBrowser,
             AppTab::Settings => AppTab::FormatSelection,
-            AppTab::Help => AppTab::Settings,
+            AppTab::Stats => AppTab::Settings,
+            AppTab::Help => AppTab::Stats,
             // Don't change tabs during conversion or when complete
             AppTab::Converting => AppTab::Converting,
             AppTab::Complete => AppTab::Complete,
         };
+        self.refresh_history_stats();
+    }
+    
+    // Reread the history each time the Stats tab is opened, so it includes
+    // conversions finished by headless runs in the meantime
+    fn refresh_history_stats(&mut self) {
+        if self.current_tab == AppTab::Stats {
+            self.history_stats = Some(history::load()
+                .map(|entries| HistoryStats::from_entries(&entries))
+                .map_err(|e| e.to_string()));
+        }
+    }
+    
+    // Dual-pane layout: source files on the left, the output folder on the right
+    pub fn toggle_dual_pane(&mut self) {
+        self.destination_focused = false;
+        match self.destination.take() {
+            Some(_) => self.video_settings.output_dir = self.config.output_dir.clone(),
+            None => {
+                let destination = self.file_browser.folder_picker();
+                self.video_settings.output_dir = Some(destination.get_current_dir().clone());
+                self.destination = Some(destination);
+            },
+        }
+    }
+    
+    pub fn switch_pane(&mut self) {
+        self.destination_focused = self.destination.is_some() && !self.destination_focused;
+    }
+    
+    // Open the highlighted folder in the destination pane, which makes it the output folder
+    pub fn enter_destination_dir(&mut self) {
+        if let Some(destination) = &mut self.destination {
+            if destination.enter_directory() {
+                self.video_settings.output_dir = Some(destination.get_current_dir().clone());
+            }
+        }
+    }
+    
+    // `~` and `\`: jump the focused pane to the home folder, or to the root of the
+    // drive it's on, wherever the app was started
+    pub fn browse_home(&mut self) {
+        if let Some(home) = dirs::home_dir() {
+            self.browse_to(home);
+        }
+    }
+    
+    pub fn browse_root(&mut self) {
+        let browser = match &self.destination {
+            Some(destination) if self.destination_focused => destination,
+            _ => &self.file_browser,
+        };
+        if let Some(root) = browser.get_current_dir().ancestors().last() {
+            self.browse_to(root.to_path_buf());
+        }
+    }
+    
+    fn browse_to(&mut self, dir: PathBuf) {
+        match &mut self.destination {
+            Some(destination) if self.destination_focused => {
+                if destination.go_to(dir) {
+                    self.video_settings.output_dir = Some(destination.get_current_dir().clone());
+                }
+            },
+            _ => {
+                self.file_browser.go_to(dir);
+            },
+        }
+    }
+    
+    // Keep the watcher on whichever folder the browser is showing
+    pub fn watch_browser_dir(&mut self) {
+        self.dir_watcher.watch(self.file_browser.get_current_dir());
+    }
+    
+    // The browser's folder changed on disk: list it again, keeping the selection
+    pub fn reload_browser(&mut self) {
+        self.file_browser.reload();
+    }
+    
+    // Take in entries read by the browsers' listing threads; true when either list changed
+    pub fn poll_browsers(&mut self) -> bool {
+        let destination_changed = self.destination.as_mut().is_some_and(|destination| destination.poll());
+        if !self.file_browser.poll() {
+            return destination_changed;
+        }
+        
+        self.update_preview();
+        self.update_durations();
+        true
+    }
+    
+    // Look up the lengths of the videos around the selection, nearest first
+    pub fn update_durations(&mut self) {
+        if self.current_tab != AppTab::FileBrowser || self.ffmpeg_capabilities.is_none() {
+            return;
+        }
+        
+        let files = self.file_browser.get_files();
+        let selected = self.file_browser.get_selected_idx();
+        let mut window: Vec<usize> = (selected.saturating_sub(PROBE_WINDOW)..files.len().min(selected + PROBE_WINDOW)).collect();
+        window.sort_by_key(|idx| idx.abs_diff(selected));
+        let videos: Vec<PathBuf> = window.into_iter()
+            .map(|idx| &files[idx])
+            .filter(|entry| entry.is_convertible())
+            .map(|entry| entry.path.clone())
+            .collect();
+        self.durations.request(&videos);
+    }
+    
+    // Collect previews and the frames the open popups were waiting on
+    pub fn poll_previews(&mut self) {
+        self.preview.poll();
+        
+        if let Some(preview) = &mut self.framing_preview {
+            preview.poll();
+        }
+        if let Some(Err(e)) = self.trim_editor.as_mut().map(TrimEditor::poll) {
+            self.trim_editor = None;
+            self.track_message = Some(format!("Couldn't open the file for trimming: {}", e));
+        }
+    }
+    
+    // Start extracting a preview of the highlighted file; needs FFmpeg
+    pub fn update_preview(&mut self) {
+        if self.current_tab != AppTab::FileBrowser || self.ffmpeg_capabilities.is_none() {
+            return;
+        }
+        
+        if let Some(entry) = self.file_browser.get_selected_entry().filter(|entry| entry.is_convertible()) {
+            let path = entry.path.clone();
+            self.preview.show(&path);
+        }
     }
     
     pub fn toggle_popup(&mut self) {
         self.show_popup = !self.show_popup;
     }
-
+    
+    // Enter in the confirmation popup starts what it describes; with nothing
+    // convertible selected it only closes
+    pub fn confirm_popup(&mut self) {
+        self.show_popup = false;
+        let ready = self.selected_source().is_some_and(|source| is_url(source) || source.is_file() || disc::is_video_ts(source));
+        if ready {
+            self.start_conversion();
+        }
+    }
+    
+    // Versions and paths are looked up again each time, so a fixed setup shows as such
+    pub fn open_diagnostics(&mut self) {
+        self.diagnostics = Some(Diagnostics::collect(self));
+    }
+    
+    pub fn close_diagnostics(&mut self) {
+        self.diagnostics = None;
+    }
+    
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
     
+    // Quit at once when nothing is converting; otherwise ask. A second request
+    // while cancelled jobs are still stopping quits without waiting for them
+    pub fn request_quit(&mut self) {
+        if self.job_manager.active_jobs() == 0 || self.quitting {
+            self.quit();
+        } else {
+            self.quit_confirm = true;
+        }
+    }
+    
+    pub fn cancel_quit(&mut self) {
+        self.quit_confirm = false;
+    }
+    
+    pub fn confirm_quit(&mut self) {
+        self.quit_confirm = false;
+        self.quitting = true;
+        self.job_manager.cancel_all();
+        self.finish_quitting();
+    }
+    
+    fn finish_quitting(&mut self) {
+        if self.quitting && self.job_manager.active_jobs() == 0 {
+            self.quit();
+        }
+    }
+    
     pub fn get_current_format(&self) -> VideoFormat {
         self.selected_format.unwrap_or(VideoFormat::MP4)
     }
     
-    pub fn start_conversion(&mut self) {
-        if let Some(file_path) = self.file_browser.get_selected_file() {
-            if file_path.is_file() {
-                let format = self.get_current_format();
-                
-                // First try to use native FFmpeg library
-                let native_available = match crate::native_converter::NativeConverter::check_available() {
-                    Ok(available) => available,
-                    Err(_) => false
-                };
-                
-                // If native library not available, check for external FFmpeg
-                let ffmpeg_available = if !native_available {
-                    match crate::ffmpeg::FFmpegConverter::check_ffmpeg_available() {
-                        Ok(available) => available,
-                        Err(_) => false
-                    }
-                } else {
-                    false // Skip external FFmpeg check if native is available
-                };
-                
-                // Create converter with appropriate mode
-                let mode = if native_available {
-                    ConversionMode::NativeFFmpeg
-                } else if ffmpeg_available {
-                    ConversionMode::FFmpeg
-                } else {
-                    ConversionMode::Simulation
-                };
-                
-                let (converter, rx) = VideoConverter::new(mode);
-                self.converter_rx = Some(rx);
-                
-                // Start conversion with video settings
-                // We'll need to modify the VideoConverter to accept these settings
-                // For now, we'll just pass the file and format
-                converter.convert(file_path.clone(), format);
-                
-                // Switch to converting tab
-                self.current_tab = AppTab::Converting;
-            }
+    // Encoders/muxers the conversion backend lacks for a format (empty if unknown)
+    pub fn missing_components(&self, format: VideoFormat) -> Vec<&'static str> {
+        self.missing_components_for(self.available_mode(), format)
+    }
+    
+    fn missing_components_for(&self, mode: ConversionMode, format: VideoFormat) -> Vec<&'static str> {
+        match mode {
+            ConversionMode::Plugin => match self.active_plugin() {
+                Some(plugin) if !plugin.supports(format) => vec!["plugin support"],
+                _ => Vec::new(),
+            },
+            #[cfg(feature = "native")]
+            ConversionMode::Native => rust_tui_video_convert::native_converter::NativeConverter::missing_for(format),
+            _ => self.ffmpeg_capabilities
+                .as_ref()
+                .map(|capabilities| capabilities.missing_for(format))
+                .unwrap_or_default(),
         }
     }
     
-    pub fn check_conversion_progress(&mut self) {
-        if let Some(rx) = &self.converter_rx {
-            if let Ok(progress) = rx.try_recv() {
-                self.conversion_progress = Some(progress.clone());
-                
-                if progress.is_complete {
-                    self.current_tab = AppTab::Complete;
-                }
-            }
+    // Encoders the FFmpeg backend will replace with a stand-in for this format, as (wanted, used)
+    pub fn encoder_substitutions(&self, format: VideoFormat) -> Vec<(&'static str, &'static str)> {
+        match (&self.ffmpeg_capabilities, self.available_mode()) {
+            (Some(capabilities), ConversionMode::FFmpeg) => capabilities.substitutions(format),
+            _ => Vec::new(),
         }
     }
     
-    pub fn reset(&mut self) {
-        self.current_tab = AppTab::FileBrowser;
-        self.conversion_progress = None;
-        self.converter_rx = None;
+    // What the next conversion needs that this FFmpeg is too old for
+    pub fn ffmpeg_warnings(&self) -> Vec<String> {
+        match (&self.ffmpeg_capabilities, self.available_mode()) {
+            (Some(capabilities), ConversionMode::FFmpeg) => capabilities.compatibility_warnings(self.get_current_format(), &self.video_settings),
+            _ => Vec::new(),
+        }
     }
     
-    // Advanced video settings methods
+    // A selected plugin takes over full conversions
+    pub fn available_mode(&self) -> ConversionMode {
+        if self.active_plugin().is_some() {
+            ConversionMode::Plugin
+        } else {
+            self.builtin_mode()
+        }
+    }
     
-    pub fn next_setting(&mut self) {
-        self.selected_setting = match self.selected_setting {
-            AdvancedSetting::Resolution => AdvancedSetting::Bitrate,
-            AdvancedSetting::Bitrate => AdvancedSetting::FrameRate,
-            AdvancedSetting::FrameRate => AdvancedSetting::Resolution,
-        };
+    // Prefer linked libav, then an external FFmpeg; without either runs are only simulated
+    pub fn builtin_mode(&self) -> ConversionMode {
+        if self.native_available {
+            ConversionMode::Native
+        } else if self.ffmpeg_capabilities.is_some() {
+            ConversionMode::FFmpeg
+        } else {
+            ConversionMode::Simulation
+        }
     }
     
-    pub fn previous_setting(&mut self) {
-        self.selected_setting = match self.selected_setting {
-            AdvancedSetting::Resolution => AdvancedSetting::FrameRate,
-            AdvancedSetting::Bitrate => AdvancedSetting::Resolution,
-            AdvancedSetting::FrameRate => AdvancedSetting::Bitrate,
-        };
+    pub fn active_plugin(&self) -> Option<&Arc<Plugin>> {
+        self.selected_plugin.and_then(|idx| self.plugins.get(idx))
     }
     
-    pub fn next_resolution(&mut self) {
-        self.video_settings.resolution = match self.video_settings.resolution {
-            Resolution::Original => Resolution::HD720p,
-            Resolution::HD720p => Resolution::HD1080p,
-            Resolution::HD1080p => Resolution::UHD4K,
-            Resolution::UHD4K => Resolution::Original,
-        };
+    // Display name for a backend, using the plugin's own name for plugin runs
+    pub fn mode_label(&self, mode: ConversionMode) -> String {
+        match (mode, self.active_plugin()) {
+            (ConversionMode::Plugin, Some(plugin)) => format!("Plugin: {}", plugin.name()),
+            _ => mode.label().to_string(),
+        }
     }
     
-    pub fn next_bitrate(&mut self) {
-        self.video_settings.bitrate = match self.video_settings.bitrate {
-            Bitrate::Auto => Bitrate::Low,
-            Bitrate::Low => Bitrate::Medium,
-            Bitrate::Medium => Bitrate::High,
-            Bitrate::High => Bitrate::Auto,
+    // Cycle built-in -> each plugin -> built-in
+    pub fn next_backend(&mut self) {
+        self.selected_plugin = match self.selected_plugin {
+            None if !self.plugins.is_empty() => Some(0),
+            Some(idx) if idx + 1 < self.plugins.len() => Some(idx + 1),
+            _ => None,
         };
     }
     
-    pub fn next_framerate(&mut self) {
-        self.video_settings.frame_rate = match self.video_settings.frame_rate {
-            FrameRate::Original => FrameRate::FPS24,
-            FrameRate::FPS24 => FrameRate::FPS30,
-            FrameRate::FPS30 => FrameRate::FPS60,
-            FrameRate::FPS60 => FrameRate::Original,
+    pub fn previous_backend(&mut self) {
+        self.selected_plugin = match self.selected_plugin {
+            None => self.plugins.len().checked_sub(1),
+            Some(0) => None,
+            Some(idx) => Some(idx - 1),
         };
     }
     
-    pub fn change_selected_setting(&mut self, increase: bool) {
-        match self.selected_setting {
+    // Re-encoding into the source's own format only loses quality unless settings
+    // change, so ask first whether a remux is what's wanted
+    pub fn start_conversion(&mut self) {
+        if self.refuse_unplayable() {
+            return;
+        }
+        if self.is_same_format() {
+            self.same_format_prompt = true;
+        } else {
+            self.begin_conversion(false, false, None, false);
+        }
+    }
+    
+    // The local source's format: what ffprobe found inside it once the preview
+    // has probed it, what its extension says until then
+    pub fn source_format(&self) -> Option<VideoFormat> {
+        let source = self.selected_source().filter(|source| !is_url(source))?;
+        let by_extension = source.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(VideoFormat::from_extension);
+        match self.preview.get(source).and_then(|preview| preview.container.as_ref()) {
+            Some(Ok(container)) => container.format(by_extension),
+            _ => by_extension,
+        }
+    }
+    
+    // Where a full conversion of the selected source would be written
+    pub fn planned_output(&self) -> Option<PathBuf> {
+        let source = self.selected_source()?;
+        Some(VideoConverter::generate_output_path(
+            source,
+            self.video_settings.output_name.as_deref(),
+            self.video_settings.output_dir.as_deref(),
+            self.get_current_format(),
+            false,
+        ))
+    }
+    
+    pub fn is_same_format(&self) -> bool {
+        self.source_format() == Some(self.get_current_format())
+    }
+    
+    // Remuxing is done by FFmpeg whichever backend is selected
+    pub fn remux_available(&self) -> bool {
+        self.ffmpeg_capabilities.is_some()
+    }
+    
+    pub fn confirm_remux(&mut self) {
+        if self.remux_available() {
+            self.same_format_prompt = false;
+            self.begin_conversion(false, true, None, false);
+        }
+    }
+    
+    pub fn confirm_reencode(&mut self) {
+        self.same_format_prompt = false;
+        self.begin_conversion(false, false, None, false);
+    }
+    
+    pub fn cancel_same_format_prompt(&mut self) {
+        self.same_format_prompt = false;
+    }
+    
+    // Encode only a short window from the middle of the file with the current
+    // settings, so quality and size can be checked before a full conversion
+    pub fn start_sample_conversion(&mut self) {
+        if !self.refuse_unplayable() {
+            self.begin_conversion(true, false, None, false);
+        }
+    }
+    
+    // Why ffprobe says the selected local file can't be converted to the chosen format,
+    // as the preview found it; None without FFmpeg to ask. A file the preview hasn't
+    // probed yet isn't probed here, which would stall the interface on a slow share:
+    // the job makes the same check before it converts anything.
+    pub fn unplayable_reason(&self) -> Option<String> {
+        let source = self.selected_source().filter(|source| !is_url(source) && self.ffmpeg_capabilities.is_some())?;
+        let probe = self.preview.get(source)?.container.as_ref()?;
+        FFmpegConverter::unplayable_reason(probe, self.get_current_format())
+    }
+    
+    // Show why the selected file can't be converted instead of starting a job that would fail
+    fn refuse_unplayable(&mut self) -> bool {
+        self.not_playable = self.unplayable_reason();
+        self.not_playable.is_some()
+    }
+    
+    pub fn dismiss_not_playable(&mut self) {
+        self.not_playable = None;
+    }
+    
+    pub fn dismiss_access_prompt(&mut self) {
+        self.access_prompt = None;
+    }
+    
+    // Start the refused conversion again, writing to the folder the popup offered
+    pub fn accept_output_redirect(&mut self) {
+        if let Some(AccessPrompt { redirect: Some(dir), sample, remux, .. }) = self.access_prompt.take() {
+            self.begin_conversion(sample, remux, Some(dir), true);
+        }
+    }
+    
+    // What the current format's container can't take of the settings the selected
+    // file would be converted with by the built-in backends
+    pub fn container_conflicts(&self) -> Vec<String> {
+        let Some(source) = self.selected_source() else {
+            return Vec::new();
+        };
+        let mut settings = self.video_settings.clone();
+        // As in begin_conversion, tracks chosen for another file don't apply
+        if self.tracks_source.as_ref() != Some(source) {
+            settings.tracks.clear();
+        }
+        settings.container_conflicts(self.get_current_format())
+    }
+    
+    // Go ahead with the conflicting settings adjusted by the backend
+    pub fn confirm_conflicts(&mut self) {
+        if let Some(ConflictPrompt { sample, .. }) = self.conflict_prompt.take() {
+            self.begin_conversion(sample, false, None, true);
+        }
+    }
+    
+    pub fn cancel_conflict_prompt(&mut self) {
+        self.conflict_prompt = None;
+    }
+    
+    // Back out to the Settings tab to fix them instead
+    pub fn review_conflicts(&mut self) {
+        self.conflict_prompt = None;
+        self.current_tab = AppTab::Settings;
+    }
+    
+    // Where an output the source's folder refused could go instead: the configured
+    // fallback folder, else the system's temp folder, whichever takes a test write
+    fn output_redirect(&self, source: &Path, format: VideoFormat, sample: bool) -> Option<PathBuf> {
+        [self.config.fallback_output_dir.clone(), Some(std::env::temp_dir())]
+            .into_iter()
+            .flatten()
+            .find(|dir| {
+                let output_file = VideoConverter::generate_output_path(source, self.video_settings.output_name.as_deref(), Some(dir), format, sample);
+                paths::check_access(source, &output_file).is_ok()
+            })
+    }
+    
+    // `redirect` overrides the output folder, for a conversion restarted from the access popup;
+    // `checked` once the user has seen any container conflicts
+    fn begin_conversion(&mut self, sample: bool, remux: bool, redirect: Option<PathBuf>, checked: bool) {
+        if let Some(file_path) = self.selected_source() {
+            if is_url(file_path) || is_convertible(file_path) {
+                let format = self.get_current_format();
+                
+                // Plugins can't cut a sample window, so test encodes use the built-in backend
+                let mode = if remux {
+                    ConversionMode::FFmpeg
+                } else if sample {
+                    self.builtin_mode()
+                } else {
+                    self.available_mode()
+                };
+                
+                // A test encode or a remux writes only the one file, and plugins
+                // convert to a single format
+                let extra_outputs = if sample || remux || mode == ConversionMode::Plugin { Vec::new() } else { self.extra_outputs() };
+                
+                // Don't start a job the backend can't complete; a remux needs no encoders
+                let mut formats = std::iter::once(format).chain(extra_outputs.iter().map(|extra| extra.format));
+                if !remux && formats.any(|format| !self.missing_components_for(mode, format).is_empty()) {
+                    return;
+                }
+                
+                // Settings the container can't take are pointed out before anything starts
+                let plugin_job = mode == ConversionMode::Plugin && !remux;
+                if !checked && !remux && !plugin_job {
+                    let conflicts = self.container_conflicts();
+                    if !conflicts.is_empty() {
+                        self.conflict_prompt = Some(ConflictPrompt { conflicts, sample });
+                        return;
+                    }
+                }
+                
+                // A refused read or write gets its own popup rather than a failed job
+                if !is_url(file_path) {
+                    let output_file = if plugin_job {
+                        VideoConverter::generate_output_path(file_path, None, None, format, false)
+                    } else {
+                        VideoConverter::generate_output_path(
+                            file_path,
+                            self.video_settings.output_name.as_deref(),
+                            redirect.as_deref().or(self.video_settings.output_dir.as_deref()),
+                            format,
+                            sample,
+                        )
+                    };
+                    if let Err(problem) = paths::check_access(file_path, &output_file) {
+                        // Plugins always write beside the source, so only the built-in backends can go elsewhere
+                        let redirect = (problem.kind == AccessKind::UnwritableOutput && !plugin_job)
+                            .then(|| self.output_redirect(file_path, format, sample))
+                            .flatten();
+                        self.access_prompt = Some(AccessPrompt { problem, redirect, sample, remux });
+                        return;
+                    }
+                }
+                
+                // Start conversion with video settings
+                let source = file_path.clone();
+                let plugin = self.active_plugin().cloned().filter(|_| !remux);
+                let (job, stages) = match (mode, plugin) {
+                    (ConversionMode::Plugin, Some(plugin)) => {
+                        let stages = ConversionStage::planned(mode, file_path, &self.video_settings, sample);
+                        (self.job_manager.submit_plugin(plugin, file_path.clone(), format), stages)
+                    },
+                    _ => {
+                        // A track choice made for another file doesn't apply here
+                        let mut settings = VideoSettings { remux, extra_outputs, ..self.video_settings.clone() };
+                        if self.tracks_source.as_ref() != Some(file_path) {
+                            settings.tracks.clear();
+                        }
+                        if redirect.is_some() {
+                            settings.output_dir = redirect;
+                        }
+                        // A URL has no folder of its own, so its output goes to the browser's
+                        // unless a destination was picked
+                        if is_url(file_path) {
+                            settings.output_dir.get_or_insert_with(|| self.file_browser.get_current_dir().clone());
+                            settings.fetch_with_ytdlp = self.url_fetch;
+                        }
+                        let stages = ConversionStage::planned(mode, file_path, &settings, sample);
+                        (self.job_manager.submit_conversion(mode, file_path.clone(), format, settings, sample), stages)
+                    },
+                };
+                let mut job = Job::new(job, source, format, mode, self.mode_label(mode), false);
+                job.sample = sample;
+                job.stages = stages;
+                self.current_job = Some(job.id);
+                self.jobs.push(job);
+                
+                // These become the format's settings next time, in later sessions too
+                self.remember_format_settings(format);
+                let _ = self.config.save();
+                
+                // Switch to converting tab
+                self.current_tab = AppTab::Converting;
+            }
+        }
+    }
+    
+    // Start a conversion on behalf of the control API, with the current settings and backend
+    pub fn submit_remote_job(&mut self, source_file: PathBuf, format: VideoFormat) -> Result<JobId, String> {
+        if !is_url(&source_file) && !source_file.is_file() && !disc::is_video_ts(&source_file) {
+            return Err(format!("Not a file: {}", source_file.display()));
+        }
+        
+        let mode = self.available_mode();
+        let missing = self.missing_components_for(mode, format);
+        if !missing.is_empty() {
+            return Err(format!("{} can't produce {} (missing {})", self.mode_label(mode), format.as_str(), missing.join(", ")));
+        }
+        
+        let id = match (mode, self.active_plugin().cloned()) {
+            (ConversionMode::Plugin, Some(plugin)) => {
+                self.job_manager.queue_plugin(plugin, source_file.clone(), format)
+            },
+            _ => {
+                // Attached subtitles and audio, chosen tracks and marked outputs belong to the file
+                // picked in the interface; a remote job writes the one format it asked for
+                let settings = VideoSettings {
+                    subtitles: None,
+                    external_audio: None,
+                    trim: Trim::default(),
+                    tracks: Vec::new(),
+                    extra_outputs: Vec::new(),
+                    ..self.video_settings.clone()
+                };
+                self.job_manager.queue_conversion(mode, source_file.clone(), format, settings)
+            },
+        };
+        
+        let mut job = Job::new(id, source_file, format, mode, self.mode_label(mode), self.job_manager.is_queued(id));
+        job.remote = true;
+        self.jobs.push(job);
+        self.sync_queue();
+        Ok(id)
+    }
+    
+    // Mirror the manager's queue into the jobs panel, in the order the jobs will run
+    fn sync_queue(&mut self) {
+        for job in &mut self.jobs {
+            job.sync_queue(self.job_manager.is_queued(job.id), self.job_manager.is_paused(job.id));
+            job.high_priority = self.job_manager.is_high_priority(job.id);
+        }
+        let order: Vec<JobId> = self.job_manager.queued().collect();
+        let (mut queued, started): (Vec<Job>, Vec<Job>) = std::mem::take(&mut self.jobs)
+            .into_iter()
+            .partition(|job| job.state().is_waiting());
+        queued.sort_by_key(|job| order.iter().position(|id| *id == job.id));
+        self.jobs = started;
+        self.jobs.extend(queued);
+        
+        if !self.selected_queued.is_some_and(|id| self.job_manager.is_queued(id)) {
+            self.selected_queued = order.first().copied();
+        }
+    }
+    
+    // Queue reordering: pick a waiting job, move it, or mark it urgent
+    
+    pub fn select_queued(&mut self, next: bool) {
+        self.queue_message = None;
+        let order: Vec<JobId> = self.job_manager.queued().collect();
+        let current = self.selected_queued.and_then(|id| order.iter().position(|queued| *queued == id));
+        let idx = match current {
+            Some(idx) if next => (idx + 1).min(order.len().saturating_sub(1)),
+            Some(idx) => idx.saturating_sub(1),
+            None => 0,
+        };
+        self.selected_queued = order.get(idx).copied();
+    }
+    
+    pub fn move_selected_queued(&mut self, earlier: bool) {
+        self.queue_message = None;
+        if let Some(id) = self.selected_queued {
+            self.job_manager.move_queued(id, earlier);
+            self.sync_queue();
+        }
+    }
+    
+    pub fn toggle_selected_priority(&mut self) {
+        self.queue_message = None;
+        if let Some(id) = self.selected_queued {
+            let high_priority = !self.job_manager.is_high_priority(id);
+            self.job_manager.set_high_priority(id, high_priority);
+            self.sync_queue();
+        }
+    }
+    
+    // Hold the selected job back, or let it run again when its turn comes; a job
+    // that has started can't be paused
+    pub fn toggle_selected_paused(&mut self) {
+        let Some(id) = self.selected_queued else {
+            if self.remote_jobs().any(|job| job.state() == JobState::Running) {
+                self.queue_message = Some("Only waiting jobs can be paused; DELETE /jobs/{id} cancels a running one".to_string());
+            }
+            return;
+        };
+        
+        let paused = !self.job_manager.is_paused(id);
+        self.queue_message = (!self.job_manager.set_paused(id, paused))
+            .then(|| "That job has already started; only waiting jobs can be paused".to_string());
+        self.sync_queue();
+    }
+    
+    // The job the Converting and Complete tabs follow
+    pub fn current_conversion(&self) -> Option<&Job> {
+        self.jobs.iter().find(|job| Some(job.id) == self.current_job)
+    }
+    
+    pub fn remote_jobs(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter().filter(|job| job.remote)
+    }
+    
+    pub fn remote_job(&self, id: JobId) -> Option<&Job> {
+        self.remote_jobs().find(|job| job.id == id)
+    }
+    
+    // Cancel a waiting or running remote job, or forget one that has already finished
+    pub fn remove_remote_job(&mut self, id: JobId) -> bool {
+        let Some(idx) = self.jobs.iter().position(|job| job.remote && job.id == id) else {
+            return false;
+        };
+        
+        if self.jobs[idx].state().is_finished() {
+            self.jobs.remove(idx);
+        } else {
+            self.job_manager.cancel(id);
+        }
+        true
+    }
+    
+    // Take in job progress; true when it changed what's on screen enough to redraw.
+    // The reduced-flicker display skips updates that change nothing shown and
+    // redraws for the rest at most once a second, except when a job finishes.
+    pub fn check_conversion_progress(&mut self) -> bool {
+        let updates = self.job_manager.poll();
+        if updates.is_empty() {
+            self.finish_quitting();
+            return false;
+        }
+        self.sync_queue();
+        
+        let mut changed = false;
+        let mut finished = false;
+        for (id, progress) in updates {
+            let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) else {
+                continue;
+            };
+            changed |= job.progress.as_ref().is_none_or(|shown| shown.percent != progress.percent || shown.current_step != progress.current_step);
+            finished |= progress.is_complete;
+            
+            let was_finished = job.state().is_finished();
+            job.report(progress.clone());
+            if was_finished || !job.state().is_finished() {
+                continue;
+            }
+            let stats = ConversionStats::collect(&progress, job.elapsed().unwrap_or_default());
+            let (mode, sample, remote) = (job.mode, job.sample, job.remote);
+            
+            // Remote jobs are listed on the Complete tab with whatever ends here
+            if remote {
+                self.results.push(ConversionResult { progress, stats, mode, remote });
+                continue;
+            }
+            if Some(id) != self.current_job {
+                continue;
+            }
+            
+            self.record_history(&progress, &stats, mode, sample);
+            // Permissions can change while a job waits or runs, so a failure is checked too
+            if JobState::finished(&progress) == JobState::Failed {
+                self.access_prompt = paths::check_access(&progress.source_file, &progress.output_file)
+                    .err()
+                    .map(|problem| AccessPrompt { problem, redirect: None, sample: false, remux: false });
+            }
+            self.results.push(ConversionResult { progress, stats, mode, remote });
+            self.selected_result = self.results.len() - 1;
+            self.result_details = false;
+            self.current_tab = AppTab::Complete;
+        }
+        self.finish_quitting();
+        
+        if !self.config.reduced_flicker || finished {
+            return true;
+        }
+        let due = self.progress_drawn.is_none_or(|drawn| drawn.elapsed() >= PROGRESS_REDRAW_INTERVAL);
+        if changed && due {
+            self.progress_drawn = Some(Instant::now());
+        }
+        changed && due
+    }
+    
+    // Whether the Converting tab is showing a job's elapsed time, which needs redrawing as it ticks
+    pub fn shows_job_clock(&self) -> bool {
+        self.current_tab == AppTab::Converting && self.current_conversion().is_some()
+    }
+    
+    // Test encodes and simulated runs don't produce real outputs, so they aren't counted
+    fn record_history(&mut self, progress: &ConversionProgress, stats: &ConversionStats, mode: ConversionMode, sample: bool) {
+        if progress.has_error || mode.is_simulation() || sample {
+            return;
+        }
+        
+        let plugin = match mode {
+            ConversionMode::Plugin => self.active_plugin().map(|plugin| plugin.name().to_string()),
+            _ => None,
+        };
+        if let Err(e) = history::record(&HistoryEntry::new(progress, stats, plugin.as_deref())) {
+            self.launch_message = Some(format!("Couldn't update the conversion history: {}", e));
+        }
+    }
+    
+    pub fn cancel_conversion(&mut self) {
+        if let Some(job) = self.current_job {
+            self.job_manager.cancel(job);
+        }
+    }
+    
+    pub fn reset(&mut self) {
+        self.current_tab = AppTab::FileBrowser;
+        self.current_job = None;
+        // Finished jobs from the interface are only kept for the Complete tab
+        self.jobs.retain(|job| job.remote || !job.state().is_finished());
+        self.results.clear();
+        self.selected_result = 0;
+        self.result_details = false;
+        self.launch_message = None;
+        self.delete_confirm = None;
+        // Subtitles are picked for one video, so don't carry them into the next
+        self.video_settings.subtitles = None;
+        self.video_settings.external_audio = None;
+        self.video_settings.trim = Trim::default();
+        self.video_settings.tracks.clear();
+        self.tracks_source = None;
+        self.track_message = None;
+        self.source_url = None;
+    }
+    
+    // The file or URL a conversion would use; a chosen URL wins over the browser
+    pub fn selected_source(&self) -> Option<&PathBuf> {
+        self.source_url.as_ref().or_else(|| self.file_browser.get_selected_file())
+    }
+    
+    // URL source methods
+    
+    pub fn open_url_input(&mut self) {
+        let current = self.source_url.as_ref().map(|url| url.to_string_lossy().into_owned());
+        self.url_input = Some(current.unwrap_or_default());
+        if self.ytdlp_available.is_none() {
+            self.ytdlp_available = Some(ytdlp::is_available());
+        }
+    }
+    
+    // Download a web page's video with yt-dlp first, or stream the URL into FFmpeg
+    pub fn toggle_url_fetch(&mut self) {
+        if self.ytdlp_available == Some(true) {
+            self.url_fetch = !self.url_fetch;
+        }
+    }
+    
+    pub fn cancel_url_input(&mut self) {
+        self.url_input = None;
+    }
+    
+    pub fn url_input_char(&mut self, c: char) {
+        if let Some(input) = &mut self.url_input {
+            input.push(c);
+        }
+    }
+    
+    pub fn url_input_backspace(&mut self) {
+        if let Some(input) = &mut self.url_input {
+            input.pop();
+        }
+    }
+    
+    // Use the typed URL as the source and move on to format selection;
+    // anything but an http(s) URL keeps the popup open
+    pub fn commit_url_input(&mut self) {
+        let Some(url) = self.url_input.as_deref().map(|input| PathBuf::from(input.trim())) else {
+            return;
+        };
+        if !is_url(&url) {
+            return;
+        }
+        
+        self.url_input = None;
+        self.source_url = Some(url);
+        self.current_tab = AppTab::FormatSelection;
+    }
+    
+    // The result the Complete tab shows, or has highlighted when several jobs ended
+    pub fn selected_result(&self) -> Option<&ConversionResult> {
+        self.results.get(self.selected_result)
+    }
+    
+    // Only a list of several results needs picking from, and Enter opens one
+    pub fn lists_results(&self) -> bool {
+        self.results.len() > 1 && !self.result_details
+    }
+    
+    pub fn next_result(&mut self) {
+        if self.selected_result + 1 < self.results.len() {
+            self.selected_result += 1;
+        }
+    }
+    
+    pub fn previous_result(&mut self) {
+        self.selected_result = self.selected_result.saturating_sub(1);
+    }
+    
+    pub fn toggle_result_details(&mut self) {
+        self.result_details = !self.result_details && self.results.len() > 1;
+    }
+    
+    // The converted file, if the job the Complete tab shows actually wrote one
+    pub fn finished_output(&self) -> Option<&Path> {
+        self.selected_result()?.output()
+    }
+    
+    pub fn open_output_folder(&mut self) {
+        let Some(output) = self.finished_output() else {
+            return;
+        };
+        
+        self.launch_message = Some(match launch::open_folder(output) {
+            Ok(()) => "Opened the output folder".to_string(),
+            Err(e) => format!("Couldn't open the output folder: {}", e),
+        });
+    }
+    
+    pub fn play_output(&mut self) {
+        let Some(output) = self.finished_output() else {
+            return;
+        };
+        
+        self.launch_message = Some(match launch::play(output) {
+            Ok(player) => format!("Playing in {}", player),
+            Err(e) => format!("Couldn't start a player: {}", e),
+        });
+    }
+    
+    // Ask before deleting the converted files, for when the settings turn out wrong
+    pub fn open_delete_confirm(&mut self) {
+        self.delete_confirm = self.selected_result().and_then(DeleteConfirm::for_result);
+    }
+    
+    pub fn cancel_delete_output(&mut self) {
+        self.delete_confirm = None;
+    }
+    
+    // Remove the job's outputs along with the sidecars and HLS folder that go with them
+    pub fn confirm_delete_output(&mut self) {
+        let Some(confirm) = self.delete_confirm.take() else {
+            return;
+        };
+        let Some((output, others)) = confirm.outputs.split_first() else {
+            return;
+        };
+        
+        let name = paths::escape(output.file_name().unwrap_or_default()).to_string();
+        if let Err(e) = fs::remove_file(output) {
+            self.launch_message = Some(format!("Couldn't delete {}: {}", name, e));
+            return;
+        }
+        
+        let leftover = others.iter().chain(&confirm.sidecars)
+            .filter(|file| fs::remove_file(file).is_err())
+            .count()
+            + confirm.hls_dir.iter().filter(|dir| fs::remove_dir_all(dir).is_err()).count();
+        
+        self.launch_message = Some(match leftover {
+            0 if others.is_empty() => format!("Deleted {}", name),
+            0 => format!("Deleted {} and the job's {} other outputs", name, others.len()),
+            _ => format!("Deleted {}, but couldn't remove everything that went with it", name),
+        });
+    }
+    
+    // Subtitle extraction methods
+    
+    // Extract the highlighted file's subtitles; asks which track first when there are several
+    pub fn extract_subtitles(&mut self) {
+        let Some(source) = self.file_browser.get_selected_file().filter(|path| path.is_file() && !is_subtitle_file(path)).cloned() else {
+            return;
+        };
+        
+        if self.ffmpeg_capabilities.is_none() {
+            self.browser_message = Some((source, "Extracting subtitles needs FFmpeg".to_string()));
+            return;
+        }
+        
+        let tracks = match FFmpegConverter::subtitle_tracks(&source) {
+            Ok(tracks) => tracks,
+            Err(e) => {
+                self.browser_message = Some((source, format!("Couldn't read subtitle tracks: {}", e)));
+                return;
+            },
+        };
+        
+        match tracks.len() {
+            0 => self.browser_message = Some((source, "No subtitle tracks in this file".to_string())),
+            1 => self.start_subtitle_extraction(source, tracks),
+            _ => self.subtitle_picker = Some(SubtitlePicker::new(source, tracks)),
+        }
+    }
+    
+    // Add the highlighted .srt/.ass/.vtt to the next conversion as a soft track
+    pub fn attach_subtitles(&mut self) {
+        let Some(path) = self.file_browser.get_selected_file().filter(|path| is_subtitle_file(path)).cloned() else {
+            return;
+        };
+        
+        self.video_settings.subtitles = Some(ExternalSubtitles { path: path.clone(), burn_in: false });
+        self.browser_message = Some((path, "Subtitles attached to the next conversion; now pick the video".to_string()));
+    }
+    
+    // Use the highlighted file's audio in the next conversion, in place of the source's
+    pub fn attach_audio(&mut self) {
+        let Some(path) = self.file_browser.get_selected_file().filter(|path| path.is_file() && !is_subtitle_file(path)).cloned() else {
+            return;
+        };
+        
+        self.video_settings.external_audio = Some(ExternalAudio { path: path.clone(), mix: false });
+        self.browser_message = Some((path, "Audio attached to the next conversion; now pick the video".to_string()));
+    }
+    
+    pub fn confirm_subtitle_picker(&mut self) {
+        if let Some(picker) = self.subtitle_picker.take() {
+            let tracks = picker.chosen();
+            self.start_subtitle_extraction(picker.source, tracks);
+        }
+    }
+    
+    pub fn close_subtitle_picker(&mut self) {
+        self.subtitle_picker = None;
+    }
+    
+    fn start_subtitle_extraction(&mut self, source: PathBuf, tracks: Vec<MediaTrack>) {
+        let names: Vec<String> = subtitles::sidecar_paths(&source, &tracks)
+            .into_iter()
+            .flatten()
+            .map(|path| paths::escape(path.file_name().unwrap_or_default()).into_owned())
+            .collect();
+        
+        let message = if names.is_empty() {
+            "Image-based subtitles (PGS/VobSub) can't be saved as text".to_string()
+        } else {
+            format!("Extracting subtitles to {}...", names.join(", "))
+        };
+        self.browser_message = Some((source.clone(), message));
+        
+        if !names.is_empty() {
+            self.subtitles.start(source, tracks);
+        }
+    }
+    
+    // Still frame methods
+    
+    // Ask for the timestamp of the frame to save from the highlighted file
+    pub fn open_still_prompt(&mut self) {
+        let Some(source) = self.file_browser.get_selected_file().filter(|path| path.is_file() && !is_subtitle_file(path)).cloned() else {
+            return;
+        };
+        
+        if self.ffmpeg_capabilities.is_none() {
+            self.browser_message = Some((source, "Saving a still frame needs FFmpeg".to_string()));
+            return;
+        }
+        
+        self.still_prompt = Some(StillPrompt::new(source, self.still_format));
+    }
+    
+    pub fn close_still_prompt(&mut self) {
+        self.still_prompt = None;
+    }
+    
+    // Save the frame in the background, reporting on the status line; a timestamp
+    // that can't be read keeps the prompt open with the reason
+    pub fn confirm_still_prompt(&mut self) {
+        let Some(prompt) = self.still_prompt.as_mut() else {
+            return;
+        };
+        
+        let Some(at_secs) = parse_position(&prompt.timestamp) else {
+            prompt.error = Some("Enter seconds, mm:ss or hh:mm:ss".to_string());
+            return;
+        };
+        
+        let Some(prompt) = self.still_prompt.take() else {
+            return;
+        };
+        self.still_format = prompt.format;
+        self.stills.start(prompt.source.clone(), at_secs, prompt.format);
+        self.browser_message = Some((prompt.source, "Saving frame...".to_string()));
+    }
+    
+    pub fn poll_stills(&mut self) {
+        for result in self.stills.poll() {
+            self.browser_message = Some(result);
+        }
+    }
+    
+    // Track selection methods
+    
+    // Choose which audio/subtitle tracks of the selected file to keep and how they're flagged
+    pub fn open_track_picker(&mut self) {
+        let Some(source) = self.selected_source().filter(|path| path.is_file() || disc::is_video_ts(path) || is_url(path)).cloned() else {
+            return;
+        };
+        
+        if self.ffmpeg_capabilities.is_none() {
+            self.track_message = Some("Choosing tracks needs FFmpeg".to_string());
+            return;
+        }
+        
+        self.track_message = Some("Reading tracks...".to_string());
+        self.source_probe.start(source.clone(), ProbeRequest::Tracks);
+        self.tracks_reading = Some(source);
+    }
+    
+    // Open the picker once the tracks are read, unless another file was picked meanwhile
+    fn show_track_picker(&mut self, source: PathBuf, tracks: Result<Vec<MediaTrack>, String>) {
+        if self.tracks_reading.as_ref() != Some(&source) {
+            return;
+        }
+        self.tracks_reading = None;
+        if self.selected_source() != Some(&source) {
+            self.track_message = None;
+            return;
+        }
+        
+        match tracks {
+            Ok(tracks) if tracks.is_empty() => self.track_message = Some("No audio or subtitle tracks in this file".to_string()),
+            Ok(tracks) => {
+                self.track_message = None;
+                let current = if self.tracks_source.as_ref() == Some(&source) { self.video_settings.tracks.as_slice() } else { &[] };
+                self.track_picker = Some(TrackPicker::new(source, tracks, current));
+            },
+            Err(e) => self.track_message = Some(format!("Couldn't read tracks: {}", e)),
+        }
+    }
+    
+    pub fn poll_source_probe(&mut self) {
+        for (source, probed) in self.source_probe.poll() {
+            match probed {
+                Probed::Tracks(tracks) => self.show_track_picker(source, tracks),
+                Probed::Duration(duration) => {
+                    if let Some(calculator) = self.bitrate_calculator.as_mut().filter(|calculator| calculator.reading.as_ref() == Some(&source)) {
+                        calculator.reading = None;
+                        if let Some(duration) = duration {
+                            calculator.fill_duration(duration);
+                        }
+                    }
+                },
+            }
+        }
+    }
+    
+    pub fn apply_track_picker(&mut self) {
+        if let Some(picker) = self.track_picker.take() {
+            self.video_settings.tracks = picker.selections();
+            self.tracks_source = Some(picker.source);
+            self.track_message = None;
+        }
+    }
+    
+    pub fn close_track_picker(&mut self) {
+        self.track_picker = None;
+    }
+    
+    // Short description of the chosen tracks, e.g. "2 audio, 1 subtitle"
+    pub fn tracks_label(&self) -> Option<String> {
+        let source = self.selected_source()?;
+        if self.tracks_source.as_ref() != Some(source) || self.video_settings.tracks.is_empty() {
+            return None;
+        }
+        
+        let count = |kind| self.video_settings.tracks.iter().filter(|track| track.kind == kind).count();
+        Some(format!("{} audio, {} subtitle", count(TrackKind::Audio), count(TrackKind::Subtitle)))
+    }
+    
+    // Collect finished extractions
+    pub fn poll_subtitles(&mut self) {
+        for result in self.subtitles.poll() {
+            self.browser_message = Some(result);
+        }
+    }
+    
+    // Advanced video settings methods
+    
+    // The Settings tab's hint line for the selected setting, for the ones with a few
+    // fixed choices: each choice's description() says what it means and what it
+    // trades off
+    pub fn selected_value_description(&self) -> Option<&'static str> {
+        let settings = &self.video_settings;
+        match self.selected_setting {
+            AdvancedSetting::Resolution => Some(settings.resolution.description()),
+            AdvancedSetting::Crop => Some(settings.crop.description()),
+            AdvancedSetting::Ladder => Some(settings.ladder.description()),
+            AdvancedSetting::Bitrate => Some(settings.bitrate.description()),
+            AdvancedSetting::SmartQuality => Some(settings.quality_target.description()),
+            AdvancedSetting::FrameRate => Some(settings.frame_rate.description()),
+            AdvancedSetting::FrameRateMode => Some(settings.frame_rate_mode.description()),
+            AdvancedSetting::ImageQuality => Some(settings.image_quality.description()),
+            AdvancedSetting::H264Profile => Some(settings.h264_profile.description()),
+            AdvancedSetting::H264Level => Some(settings.h264_level.description()),
+            AdvancedSetting::Tune => Some(settings.tune.description()),
+            AdvancedSetting::PixelFormat => Some(settings.pixel_format.description()),
+            AdvancedSetting::KeyframeInterval => Some(settings.keyframe_interval.description()),
+            AdvancedSetting::Downmix => Some(settings.downmix.description()),
+            AdvancedSetting::IoPriority => Some(settings.io_priority.description()),
+            AdvancedSetting::EcoMode => Some(settings.eco_mode.description()),
+            AdvancedSetting::LogLevel => Some(settings.log_level.description()),
+            _ => None,
+        }
+    }
+    
+    pub fn next_setting(&mut self) {
+        self.selected_setting = match self.selected_setting {
+            AdvancedSetting::Backend => AdvancedSetting::Resolution,
+            AdvancedSetting::Resolution => AdvancedSetting::Crop,
+            AdvancedSetting::Crop => AdvancedSetting::Ladder,
+            AdvancedSetting::Ladder => AdvancedSetting::Bitrate,
+            AdvancedSetting::Bitrate => AdvancedSetting::SmartQuality,
+            AdvancedSetting::SmartQuality => AdvancedSetting::FrameRate,
+            AdvancedSetting::FrameRate => AdvancedSetting::FrameRateMode,
+            AdvancedSetting::FrameRateMode => AdvancedSetting::ImageQuality,
+            AdvancedSetting::ImageQuality => AdvancedSetting::Subtitles,
+            AdvancedSetting::Subtitles => AdvancedSetting::ExternalAudio,
+            AdvancedSetting::ExternalAudio => AdvancedSetting::FileTimes,
+            AdvancedSetting::FileTimes => AdvancedSetting::Checksum,
+            AdvancedSetting::Checksum => AdvancedSetting::Metadata,
+            AdvancedSetting::Metadata => AdvancedSetting::SourceCheck,
+            AdvancedSetting::SourceCheck => AdvancedSetting::H264Profile,
+            AdvancedSetting::H264Profile => AdvancedSetting::H264Level,
+            AdvancedSetting::H264Level => AdvancedSetting::Tune,
+            AdvancedSetting::Tune => AdvancedSetting::PixelFormat,
+            AdvancedSetting::PixelFormat => AdvancedSetting::KeyframeInterval,
+            AdvancedSetting::KeyframeInterval => AdvancedSetting::SceneCut,
+            AdvancedSetting::SceneCut => AdvancedSetting::AudioPassthrough,
+            AdvancedSetting::AudioPassthrough => AdvancedSetting::Downmix,
+            AdvancedSetting::Downmix => AdvancedSetting::IoPriority,
+            AdvancedSetting::IoPriority => AdvancedSetting::EcoMode,
+            AdvancedSetting::EcoMode => AdvancedSetting::LogLevel,
+            AdvancedSetting::LogLevel => AdvancedSetting::FFmpegPath,
+            AdvancedSetting::FFmpegPath => AdvancedSetting::FFprobePath,
+            AdvancedSetting::FFprobePath => AdvancedSetting::UploadTo,
+            AdvancedSetting::UploadTo => AdvancedSetting::FallbackOutput,
+            AdvancedSetting::FallbackOutput => AdvancedSetting::ExtraExtensions,
+            AdvancedSetting::ExtraExtensions => AdvancedSetting::Backend,
+        };
+    }
+    
+    pub fn previous_setting(&mut self) {
+        self.selected_setting = match self.selected_setting {
+            AdvancedSetting::Backend => AdvancedSetting::ExtraExtensions,
+            AdvancedSetting::Resolution => AdvancedSetting::Backend,
+            AdvancedSetting::Crop => AdvancedSetting::Resolution,
+            AdvancedSetting::Ladder => AdvancedSetting::Crop,
+            AdvancedSetting::Bitrate => AdvancedSetting::Ladder,
+            AdvancedSetting::SmartQuality => AdvancedSetting::Bitrate,
+            AdvancedSetting::FrameRate => AdvancedSetting::SmartQuality,
+            AdvancedSetting::FrameRateMode => AdvancedSetting::FrameRate,
+            AdvancedSetting::ImageQuality => AdvancedSetting::FrameRateMode,
+            AdvancedSetting::Subtitles => AdvancedSetting::ImageQuality,
+            AdvancedSetting::ExternalAudio => AdvancedSetting::Subtitles,
+            AdvancedSetting::FileTimes => AdvancedSetting::ExternalAudio,
+            AdvancedSetting::Checksum => AdvancedSetting::FileTimes,
+            AdvancedSetting::Metadata => AdvancedSetting::Checksum,
+            AdvancedSetting::SourceCheck => AdvancedSetting::Metadata,
+            AdvancedSetting::H264Profile => AdvancedSetting::SourceCheck,
+            AdvancedSetting::H264Level => AdvancedSetting::H264Profile,
+            AdvancedSetting::Tune => AdvancedSetting::H264Level,
+            AdvancedSetting::PixelFormat => AdvancedSetting::Tune,
+            AdvancedSetting::KeyframeInterval => AdvancedSetting::PixelFormat,
+            AdvancedSetting::SceneCut => AdvancedSetting::KeyframeInterval,
+            AdvancedSetting::AudioPassthrough => AdvancedSetting::SceneCut,
+            AdvancedSetting::Downmix => AdvancedSetting::AudioPassthrough,
+            AdvancedSetting::IoPriority => AdvancedSetting::Downmix,
+            AdvancedSetting::EcoMode => AdvancedSetting::IoPriority,
+            AdvancedSetting::LogLevel => AdvancedSetting::EcoMode,
+            AdvancedSetting::FFmpegPath => AdvancedSetting::LogLevel,
+            AdvancedSetting::FFprobePath => AdvancedSetting::FFmpegPath,
+            AdvancedSetting::UploadTo => AdvancedSetting::FFprobePath,
+            AdvancedSetting::FallbackOutput => AdvancedSetting::UploadTo,
+            AdvancedSetting::ExtraExtensions => AdvancedSetting::FallbackOutput,
+        };
+    }
+    
+    pub fn next_resolution(&mut self) {
+        self.video_settings.resolution = match self.video_settings.resolution {
+            Resolution::Original => Resolution::SD360p,
+            Resolution::SD360p => Resolution::SD480p,
+            Resolution::SD480p => Resolution::HD720p,
+            Resolution::HD720p => Resolution::HD1080p,
+            Resolution::HD1080p => Resolution::UHD4K,
+            Resolution::UHD4K => Resolution::Original,
+        };
+    }
+    
+    // Crops step through Crop::ALL, wrapping at either end
+    pub fn step_crop(&mut self, increase: bool) {
+        let crops = Crop::ALL;
+        let current = crops.iter().position(|crop| *crop == self.video_settings.crop).unwrap_or(0);
+        let next = if increase { (current + 1) % crops.len() } else { (current + crops.len() - 1) % crops.len() };
+        self.video_settings.crop = crops[next];
+    }
+    
+    pub fn next_bitrate(&mut self) {
+        self.video_settings.bitrate = match self.video_settings.bitrate {
+            Bitrate::Auto => Bitrate::Low,
+            Bitrate::Low => Bitrate::Medium,
+            Bitrate::Medium => Bitrate::High,
+            Bitrate::High => Bitrate::Auto,
+            Bitrate::Custom(_) => Bitrate::Auto,
+        };
+    }
+    
+    pub fn next_framerate(&mut self) {
+        self.video_settings.frame_rate = match self.video_settings.frame_rate {
+            FrameRate::Original => FrameRate::FPS10,
+            FrameRate::FPS10 => FrameRate::FPS15,
+            FrameRate::FPS15 => FrameRate::FPS24,
+            FrameRate::FPS24 => FrameRate::FPS30,
+            FrameRate::FPS30 => FrameRate::FPS60,
+            FrameRate::FPS60 => FrameRate::Original,
+        };
+    }
+    
+    pub fn next_frame_rate_mode(&mut self) {
+        self.video_settings.frame_rate_mode = match self.video_settings.frame_rate_mode {
+            FrameRateMode::Auto => FrameRateMode::Constant,
+            FrameRateMode::Constant => FrameRateMode::Variable,
+            FrameRateMode::Variable => FrameRateMode::Auto,
+        };
+    }
+    
+    pub fn next_image_quality(&mut self) {
+        self.video_settings.image_quality = match self.video_settings.image_quality {
+            ImageQuality::Low => ImageQuality::Medium,
+            ImageQuality::Medium => ImageQuality::High,
+            ImageQuality::High => ImageQuality::Lossless,
+            ImageQuality::Lossless => ImageQuality::Low,
+        };
+    }
+    
+    pub fn next_h264_profile(&mut self) {
+        self.video_settings.h264_profile = match self.video_settings.h264_profile {
+            H264Profile::Auto => H264Profile::Baseline,
+            H264Profile::Baseline => H264Profile::Main,
+            H264Profile::Main => H264Profile::High,
+            H264Profile::High => H264Profile::Auto,
+        };
+    }
+    
+    // Levels step through H264Level::ALL, wrapping at either end
+    pub fn step_h264_level(&mut self, increase: bool) {
+        let levels = H264Level::ALL;
+        let current = levels.iter().position(|level| *level == self.video_settings.h264_level).unwrap_or(0);
+        let next = if increase { (current + 1) % levels.len() } else { (current + levels.len() - 1) % levels.len() };
+        self.video_settings.h264_level = levels[next];
+    }
+    
+    pub fn next_tune(&mut self) {
+        self.video_settings.tune = match self.video_settings.tune {
+            EncoderTune::None => EncoderTune::Film,
+            EncoderTune::Film => EncoderTune::Animation,
+            EncoderTune::Animation => EncoderTune::Grain,
+            EncoderTune::Grain => EncoderTune::FastDecode,
+            EncoderTune::FastDecode => EncoderTune::ZeroLatency,
+            EncoderTune::ZeroLatency => EncoderTune::None,
+        };
+    }
+    
+    pub fn next_quality_target(&mut self) {
+        self.video_settings.quality_target = match self.video_settings.quality_target {
+            QualityTarget::Off => QualityTarget::Vmaf93,
+            QualityTarget::Vmaf93 => QualityTarget::Vmaf95,
+            QualityTarget::Vmaf95 => QualityTarget::Vmaf97,
+            QualityTarget::Vmaf97 => QualityTarget::Off,
+        };
+    }
+    
+    pub fn next_downmix(&mut self) {
+        self.video_settings.downmix = match self.video_settings.downmix {
+            Downmix::Off => Downmix::Stereo,
+            Downmix::Stereo => Downmix::DialogueBoost,
+            Downmix::DialogueBoost => Downmix::Off,
+        };
+    }
+    
+    pub fn next_pixel_format(&mut self) {
+        self.video_settings.pixel_format = match self.video_settings.pixel_format {
+            PixelFormat::Auto => PixelFormat::Yuv420p,
+            PixelFormat::Yuv420p => PixelFormat::Yuv420p10,
+            PixelFormat::Yuv420p10 => PixelFormat::Auto,
+        };
+    }
+    
+    pub fn next_keyframe_interval(&mut self) {
+        self.video_settings.keyframe_interval = match self.video_settings.keyframe_interval {
+            KeyframeInterval::Auto => KeyframeInterval::Sec1,
+            KeyframeInterval::Sec1 => KeyframeInterval::Sec2,
+            KeyframeInterval::Sec2 => KeyframeInterval::Sec4,
+            KeyframeInterval::Sec4 => KeyframeInterval::Sec10,
+            KeyframeInterval::Sec10 => KeyframeInterval::Auto,
+        };
+    }
+    
+    pub fn change_selected_setting(&mut self, increase: bool) {
+        match self.selected_setting {
+            AdvancedSetting::Backend => {
+                if increase {
+                    self.next_backend();
+                } else {
+                    self.previous_backend();
+                }
+            },
             AdvancedSetting::Resolution => {
                 if increase {
                     self.next_resolution();
@@ -239,12 +2089,22 @@ impl App {
                     // Previous resolution (cycle backwards)
                     self.video_settings.resolution = match self.video_settings.resolution {
                         Resolution::Original => Resolution::UHD4K,
-                        Resolution::HD720p => Resolution::Original,
+                        Resolution::SD360p => Resolution::Original,
+                        Resolution::SD480p => Resolution::SD360p,
+                        Resolution::HD720p => Resolution::SD480p,
                         Resolution::HD1080p => Resolution::HD720p,
                         Resolution::UHD4K => Resolution::HD1080p,
                     };
                 }
             },
+            AdvancedSetting::Crop => self.step_crop(increase),
+            AdvancedSetting::Ladder => {
+                self.video_settings.ladder = match (self.video_settings.ladder, increase) {
+                    (Ladder::Off, true) | (Ladder::Hls, false) => Ladder::Renditions,
+                    (Ladder::Renditions, true) | (Ladder::Off, false) => Ladder::Hls,
+                    (Ladder::Hls, true) | (Ladder::Renditions, false) => Ladder::Off,
+                };
+            },
             AdvancedSetting::Bitrate => {
                 if increase {
                     self.next_bitrate();
@@ -255,6 +2115,7 @@ impl App {
                         Bitrate::Low => Bitrate::Auto,
                         Bitrate::Medium => Bitrate::Low,
                         Bitrate::High => Bitrate::Medium,
+                        Bitrate::Custom(_) => Bitrate::High,
                     };
                 }
             },
@@ -265,12 +2126,452 @@ impl App {
                     // Previous framerate (cycle backwards)
                     self.video_settings.frame_rate = match self.video_settings.frame_rate {
                         FrameRate::Original => FrameRate::FPS60,
-                        FrameRate::FPS24 => FrameRate::Original,
+                        FrameRate::FPS10 => FrameRate::Original,
+                        FrameRate::FPS15 => FrameRate::FPS10,
+                        FrameRate::FPS24 => FrameRate::FPS15,
                         FrameRate::FPS30 => FrameRate::FPS24,
                         FrameRate::FPS60 => FrameRate::FPS30,
                     };
                 }
             },
+            AdvancedSetting::FrameRateMode => {
+                if increase {
+                    self.next_frame_rate_mode();
+                } else {
+                    // Previous mode (cycle backwards)
+                    self.video_settings.frame_rate_mode = match self.video_settings.frame_rate_mode {
+                        FrameRateMode::Auto => FrameRateMode::Variable,
+                        FrameRateMode::Constant => FrameRateMode::Auto,
+                        FrameRateMode::Variable => FrameRateMode::Constant,
+                    };
+                }
+            },
+            AdvancedSetting::ImageQuality => {
+                if increase {
+                    self.next_image_quality();
+                } else {
+                    // Previous quality (cycle backwards)
+                    self.video_settings.image_quality = match self.video_settings.image_quality {
+                        ImageQuality::Low => ImageQuality::Lossless,
+                        ImageQuality::Medium => ImageQuality::Low,
+                        ImageQuality::High => ImageQuality::Medium,
+                        ImageQuality::Lossless => ImageQuality::High,
+                    };
+                }
+            },
+            // → goes soft track, burned in, removed; ← goes burned in, soft track, removed
+            AdvancedSetting::Subtitles => {
+                let subtitles = &mut self.video_settings.subtitles;
+                match subtitles {
+                    Some(subs) if subs.burn_in == increase => *subtitles = None,
+                    Some(subs) => subs.burn_in = increase,
+                    None => {},
+                }
+            },
+            // → goes replace, mix, removed; ← goes mix, replace, removed
+            AdvancedSetting::ExternalAudio => {
+                let audio = &mut self.video_settings.external_audio;
+                match audio {
+                    Some(file) if file.mix == increase => *audio = None,
+                    Some(file) => file.mix = increase,
+                    None => {},
+                }
+            },
+            AdvancedSetting::H264Profile => {
+                if increase {
+                    self.next_h264_profile();
+                } else {
+                    // Previous profile (cycle backwards)
+                    self.video_settings.h264_profile = match self.video_settings.h264_profile {
+                        H264Profile::Auto => H264Profile::High,
+                        H264Profile::Baseline => H264Profile::Auto,
+                        H264Profile::Main => H264Profile::Baseline,
+                        H264Profile::High => H264Profile::Main,
+                    };
+                }
+            },
+            AdvancedSetting::H264Level => self.step_h264_level(increase),
+            AdvancedSetting::Tune => {
+                if increase {
+                    self.next_tune();
+                } else {
+                    // Previous tune (cycle backwards)
+                    self.video_settings.tune = match self.video_settings.tune {
+                        EncoderTune::None => EncoderTune::ZeroLatency,
+                        EncoderTune::Film => EncoderTune::None,
+                        EncoderTune::Animation => EncoderTune::Film,
+                        EncoderTune::Grain => EncoderTune::Animation,
+                        EncoderTune::FastDecode => EncoderTune::Grain,
+                        EncoderTune::ZeroLatency => EncoderTune::FastDecode,
+                    };
+                }
+            },
+            AdvancedSetting::PixelFormat => {
+                if increase {
+                    self.next_pixel_format();
+                } else {
+                    // Previous pixel format (cycle backwards)
+                    self.video_settings.pixel_format = match self.video_settings.pixel_format {
+                        PixelFormat::Auto => PixelFormat::Yuv420p10,
+                        PixelFormat::Yuv420p => PixelFormat::Auto,
+                        PixelFormat::Yuv420p10 => PixelFormat::Yuv420p,
+                    };
+                }
+            },
+            AdvancedSetting::KeyframeInterval => {
+                if increase {
+                    self.next_keyframe_interval();
+                } else {
+                    // Previous interval (cycle backwards)
+                    self.video_settings.keyframe_interval = match self.video_settings.keyframe_interval {
+                        KeyframeInterval::Auto => KeyframeInterval::Sec10,
+                        KeyframeInterval::Sec1 => KeyframeInterval::Auto,
+                        KeyframeInterval::Sec2 => KeyframeInterval::Sec1,
+                        KeyframeInterval::Sec4 => KeyframeInterval::Sec2,
+                        KeyframeInterval::Sec10 => KeyframeInterval::Sec4,
+                    };
+                }
+            },
+            AdvancedSetting::SceneCut => self.video_settings.scene_cut = !self.video_settings.scene_cut,
+            AdvancedSetting::FileTimes => self.video_settings.keep_file_times = !self.video_settings.keep_file_times,
+            AdvancedSetting::Checksum => self.video_settings.write_checksum = !self.video_settings.write_checksum,
+            AdvancedSetting::Metadata => self.video_settings.write_metadata = !self.video_settings.write_metadata,
+            AdvancedSetting::SourceCheck => self.video_settings.check_integrity = !self.video_settings.check_integrity,
+            AdvancedSetting::AudioPassthrough => self.video_settings.audio_passthrough = !self.video_settings.audio_passthrough,
+            AdvancedSetting::SmartQuality => {
+                if increase {
+                    self.next_quality_target();
+                } else {
+                    // Previous quality target (cycle backwards)
+                    self.video_settings.quality_target = match self.video_settings.quality_target {
+                        QualityTarget::Off => QualityTarget::Vmaf97,
+                        QualityTarget::Vmaf93 => QualityTarget::Off,
+                        QualityTarget::Vmaf95 => QualityTarget::Vmaf93,
+                        QualityTarget::Vmaf97 => QualityTarget::Vmaf95,
+                    };
+                }
+            },
+            AdvancedSetting::Downmix => {
+                if increase {
+                    self.next_downmix();
+                } else {
+                    // Previous downmix (cycle backwards)
+                    self.video_settings.downmix = match self.video_settings.downmix {
+                        Downmix::Off => Downmix::DialogueBoost,
+                        Downmix::Stereo => Downmix::Off,
+                        Downmix::DialogueBoost => Downmix::Stereo,
+                    };
+                }
+            },
+            AdvancedSetting::IoPriority => {
+                self.video_settings.io_priority = match (self.video_settings.io_priority, increase) {
+                    (IoPriority::Normal, true) | (IoPriority::Idle, false) => IoPriority::Low,
+                    (IoPriority::Low, true) | (IoPriority::Normal, false) => IoPriority::Idle,
+                    (IoPriority::Idle, true) | (IoPriority::Low, false) => IoPriority::Normal,
+                };
+            },
+            AdvancedSetting::EcoMode => {
+                self.video_settings.eco_mode = match (self.video_settings.eco_mode, increase) {
+                    (EcoMode::Off, true) | (EcoMode::Always, false) => EcoMode::Battery,
+                    (EcoMode::Battery, true) | (EcoMode::Off, false) => EcoMode::Always,
+                    (EcoMode::Always, true) | (EcoMode::Battery, false) => EcoMode::Off,
+                };
+            },
+            AdvancedSetting::LogLevel => {
+                self.video_settings.log_level = match (self.video_settings.log_level, increase) {
+                    (LogLevel::Normal, true) | (LogLevel::Debug, false) => LogLevel::Verbose,
+                    (LogLevel::Verbose, true) | (LogLevel::Trace, false) => LogLevel::Debug,
+                    (LogLevel::Debug, true) | (LogLevel::Normal, false) => LogLevel::Trace,
+                    (LogLevel::Trace, true) | (LogLevel::Verbose, false) => LogLevel::Normal,
+                };
+            },
+            // Paths are edited as text rather than cycled
+            AdvancedSetting::FFmpegPath
+            | AdvancedSetting::FFprobePath
+            | AdvancedSetting::UploadTo
+            | AdvancedSetting::FallbackOutput
+            | AdvancedSetting::ExtraExtensions => {},
+        }
+    }
+    
+    // Binary path setting methods
+    
+    pub fn is_path_setting_selected(&self) -> bool {
+        matches!(
+            self.selected_setting,
+            AdvancedSetting::FFmpegPath
+                | AdvancedSetting::FFprobePath
+                | AdvancedSetting::UploadTo
+                | AdvancedSetting::FallbackOutput
+                | AdvancedSetting::ExtraExtensions
+        )
+    }
+    
+    fn selected_path_config(&mut self) -> Option<&mut Option<PathBuf>> {
+        match self.selected_setting {
+            AdvancedSetting::FFmpegPath => Some(&mut self.config.ffmpeg_path),
+            AdvancedSetting::FFprobePath => Some(&mut self.config.ffprobe_path),
+            AdvancedSetting::FallbackOutput => Some(&mut self.config.fallback_output_dir),
+            _ => None,
+        }
+    }
+    
+    pub fn start_path_edit(&mut self) {
+        let current = if self.selected_setting == AdvancedSetting::UploadTo {
+            self.config.upload_to.clone()
+        } else if self.selected_setting == AdvancedSetting::ExtraExtensions {
+            Some(self.config.extra_extensions.join(", "))
+        } else {
+            self.selected_path_config()
+                .and_then(|path| path.as_ref().map(|p| p.to_string_lossy().into_owned()))
+        };
+        
+        if self.is_path_setting_selected() {
+            self.path_input = Some(current.unwrap_or_default());
+        }
+    }
+    
+    pub fn path_input_char(&mut self, c: char) {
+        if let Some(input) = &mut self.path_input {
+            input.push(c);
+        }
+    }
+    
+    pub fn path_input_backspace(&mut self) {
+        if let Some(input) = &mut self.path_input {
+            input.pop();
+        }
+    }
+    
+    pub fn cancel_path_edit(&mut self) {
+        self.path_input = None;
+    }
+    
+    // Store the edited path (empty clears it), persist the config and re-probe FFmpeg if it was a tool's
+    pub fn commit_path_edit(&mut self) {
+        if self.selected_setting == AdvancedSetting::UploadTo {
+            return self.commit_upload_edit();
+        }
+        if self.selected_setting == AdvancedSetting::ExtraExtensions {
+            return self.commit_extensions_edit();
+        }
+        
+        if let Some(input) = self.path_input.take() {
+            let trimmed = input.trim();
+            let new_path = if trimmed.is_empty() { None } else { Some(PathBuf::from(trimmed)) };
+            
+            if let Some(path) = self.selected_path_config() {
+                *path = new_path;
+            }
+            
+            let _ = self.config.save();
+            if self.selected_setting != AdvancedSetting::FallbackOutput {
+                self.config.apply();
+                self.ffmpeg_capabilities = FFmpegCapabilities::detect();
+            }
+        }
+    }
+    
+    // Extensions are separated by commas or spaces; a leading dot is dropped
+    fn commit_extensions_edit(&mut self) {
+        let Some(input) = self.path_input.take() else {
+            return;
+        };
+        let mut extensions: Vec<String> = Vec::new();
+        for extension in input.split([',', ' ']).map(|ext| ext.trim().trim_start_matches('.').to_lowercase()) {
+            if !extension.is_empty() && !extensions.contains(&extension) {
+                extensions.push(extension);
+            }
+        }
+        
+        self.file_browser.set_extra_extensions(&extensions);
+        self.config.extra_extensions = extensions;
+        let _ = self.config.save();
+    }
+    
+    // An unrecognised destination keeps the text open for correcting
+    fn commit_upload_edit(&mut self) {
+        let Some(input) = self.path_input.as_deref().map(str::trim) else {
+            return;
+        };
+        let target = UploadTarget::parse(input);
+        if target.is_none() && !input.is_empty() {
+            return;
+        }
+        
+        self.config.upload_to = target.as_ref().map(|target| target.as_str().to_string());
+        self.video_settings.upload_to = target;
+        self.path_input = None;
+        let _ = self.config.save();
+    }
+    
+    // Framing preview methods
+    
+    // Show a frame of the highlighted file with the crop and resolution settings applied
+    pub fn open_framing_preview(&mut self) {
+        let Some(source) = self.file_browser.get_selected_file().filter(|path| is_convertible(path)).cloned() else {
+            self.framing_preview = Some(FramingPreview::unavailable("Highlight a video in the File Browser to preview its framing"));
+            return;
+        };
+        
+        if self.ffmpeg_capabilities.is_none() {
+            self.framing_preview = Some(FramingPreview::unavailable("Previewing the framing needs FFmpeg"));
+            return;
+        }
+        
+        let filters = FFmpegConverter::picture_filters(self.get_current_format(), &self.video_settings);
+        self.framing_preview = Some(FramingPreview::open(source, filters, self.preview.notifier()));
+    }
+    
+    pub fn close_framing_preview(&mut self) {
+        self.framing_preview = None;
+    }
+    
+    // Change the crop or resolution from the preview and show the result
+    pub fn step_framing(&mut self, setting: AdvancedSetting, increase: bool) {
+        if self.framing_preview.as_ref().is_none_or(|preview| preview.source.is_none()) {
+            return;
+        }
+        self.selected_setting = setting;
+        self.change_selected_setting(increase);
+        
+        let filters = FFmpegConverter::picture_filters(self.get_current_format(), &self.video_settings);
+        if let Some(preview) = &mut self.framing_preview {
+            preview.update(filters);
+        }
+    }
+    
+    // Trim editor methods
+    
+    // Step through the selected file from its in point, if one is set
+    pub fn open_trim_editor(&mut self) {
+        let Some(source) = self.selected_source().filter(|path| path.is_file()).cloned() else {
+            return;
+        };
+        
+        if self.ffmpeg_capabilities.is_none() {
+            self.track_message = Some("Trimming needs FFmpeg".to_string());
+            return;
+        }
+        
+        self.trim_editor = Some(TrimEditor::open(source, self.video_settings.trim.start.unwrap_or(0.0), self.preview.notifier()));
+    }
+    
+    pub fn close_trim_editor(&mut self) {
+        self.trim_editor = None;
+    }
+    
+    pub fn step_trim(&mut self, frames: i64) {
+        if let Some(editor) = &mut self.trim_editor {
+            editor.step(frames);
+        }
+    }
+    
+    // Go to the in or out point, or the file's first or last frame when it isn't set
+    pub fn seek_trim_point(&mut self, end: bool) {
+        let Some(editor) = &mut self.trim_editor else {
+            return;
+        };
+        let trim = self.video_settings.trim;
+        let position = match (end, trim.start, trim.end) {
+            (false, Some(start), _) => (start * editor.frame_rate).round() as u64,
+            (false, None, _) => 0,
+            // The out point is where the last kept frame ends
+            (true, _, Some(end)) => ((end * editor.frame_rate).round() as u64).saturating_sub(1),
+            (true, _, None) => editor.frames,
+        };
+        editor.seek(position);
+    }
+    
+    // Start or end the output at the frame shown; a point on the wrong side of
+    // the other one clears that one
+    pub fn set_trim_point(&mut self, end: bool) {
+        let Some(editor) = &self.trim_editor else {
+            return;
+        };
+        let trim = &mut self.video_settings.trim;
+        if end {
+            let at = editor.end_time();
+            trim.end = (at < editor.duration()).then_some(at);
+            if trim.start.is_some_and(|start| start >= at) {
+                trim.start = None;
+            }
+        } else {
+            let at = editor.time();
+            trim.start = (at > 0.0).then_some(at);
+            if trim.end.is_some_and(|end| end <= at) {
+                trim.end = None;
+            }
+        }
+    }
+    
+    pub fn clear_trim(&mut self) {
+        self.video_settings.trim = Trim::default();
+    }
+    
+    // Bitrate calculator popup methods
+    
+    pub fn open_bitrate_calculator(&mut self) {
+        let mut calculator = BitrateCalculator::new();
+        
+        // Pre-fill the duration from the selected file once it has been probed
+        if let Some(source) = self.selected_source().filter(|path| path.is_file() || disc::is_video_ts(path) || is_url(path)).cloned() {
+            self.source_probe.start(source.clone(), ProbeRequest::Duration);
+            calculator.reading = Some(source);
+        }
+        
+        self.bitrate_calculator = Some(calculator);
+    }
+    
+    pub fn close_bitrate_calculator(&mut self) {
+        self.bitrate_calculator = None;
+    }
+    
+    pub fn calculator_input(&mut self, c: char) {
+        if let Some(calculator) = &mut self.bitrate_calculator {
+            let accepted = match calculator.focused {
+                CalculatorField::Duration => c.is_ascii_digit() || c == ':' || c == '.',
+                CalculatorField::TargetSize => c.is_ascii_digit() || c == '.',
+                CalculatorField::AudioBitrate => c.is_ascii_digit(),
+            };
+            if accepted {
+                calculator.focused_input().push(c);
+            }
+        }
+    }
+    
+    pub fn calculator_backspace(&mut self) {
+        if let Some(calculator) = &mut self.bitrate_calculator {
+            calculator.focused_input().pop();
+        }
+    }
+    
+    pub fn calculator_next_field(&mut self) {
+        if let Some(calculator) = &mut self.bitrate_calculator {
+            calculator.focused = match calculator.focused {
+                CalculatorField::Duration => CalculatorField::TargetSize,
+                CalculatorField::TargetSize => CalculatorField::AudioBitrate,
+                CalculatorField::AudioBitrate => CalculatorField::Duration,
+            };
+        }
+    }
+    
+    pub fn calculator_previous_field(&mut self) {
+        if let Some(calculator) = &mut self.bitrate_calculator {
+            calculator.focused = match calculator.focused {
+                CalculatorField::Duration => CalculatorField::AudioBitrate,
+                CalculatorField::TargetSize => CalculatorField::Duration,
+                CalculatorField::AudioBitrate => CalculatorField::TargetSize,
+            };
+        }
+    }
+    
+    // Use the calculated bitrate for the next conversion and close the popup
+    pub fn apply_bitrate_calculator(&mut self) {
+        if let Some(bitrate) = self.bitrate_calculator.as_ref().and_then(|c| c.result()) {
+            self.video_settings.bitrate = bitrate;
+            self.selected_setting = AdvancedSetting::Bitrate;
+            self.bitrate_calculator = None;
         }
     }
 }
\ No newline at end of file
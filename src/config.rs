@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use rust_tui_video_convert::converter::{
+    Bitrate, Crop, Downmix, EcoMode, EncoderTune, FrameRate, FrameRateMode, H264Level, H264Profile, ImageQuality, KeyframeInterval,
+    LogLevel, PixelFormat, QualityTarget, Resolution, VideoFormat, VideoSettings,
+};
+use rust_tui_video_convert::hooks::JobHooks;
+use rust_tui_video_convert::upload::UploadTarget;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to access config file: {0}")]
+    Io(#[from] io::Error),
+    
+    #[error("Invalid config file: {0}")]
+    Parse(#[from] toml::de::Error),
+    
+    #[error("Failed to write config: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    
+    #[error("No config directory available on this system")]
+    NoConfigDir,
+}
+
+// Environment variables that override the config file, for containers and
+// scripts where editing it is awkward. Empty ones are ignored.
+const ENV_OVERRIDES: [&str; 13] = [
+    "RTVC_FFMPEG_PATH",
+    "RTVC_FFPROBE_PATH",
+    "RTVC_TOOLS_DIR",
+    "RTVC_OUTPUT_DIR",
+    "RTVC_FALLBACK_OUTPUT_DIR",
+    "RTVC_UPLOAD_TO",
+    "RTVC_HOOK_URL",
+    "RTVC_HOOK_SCRIPT",
+    "RTVC_LOG_LEVEL",
+    "RTVC_ACCESSIBLE_UI",
+    "RTVC_REDUCED_FLICKER",
+    "RTVC_API_TOKEN",
+    "RTVC_PRESET",
+];
+
+// User configuration, stored as TOML in the platform config directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Explicit executables to use instead of looking up `ffmpeg`/`ffprobe` on PATH.
+    // Relative paths are resolved against the directory containing this application,
+    // so portable installs can ship the binaries alongside it.
+    pub ffmpeg_path: Option<PathBuf>,
+    pub ffprobe_path: Option<PathBuf>,
+    // Searched for ffmpeg/ffprobe when they aren't set above, before the folder this
+    // application is in and then PATH. Relative to the application's folder too.
+    pub tools_dir: Option<PathBuf>,
+    // Where outputs are written unless a destination folder is picked; unset
+    // writes each one beside its source
+    pub output_dir: Option<PathBuf>,
+    // Destination finished outputs are uploaded to: s3://bucket/prefix or
+    // sftp://user@host/dir. Unset keeps them local only.
+    pub upload_to: Option<String>,
+    // Told about every finished job: a URL the job is POSTed to as JSON, and a
+    // script run with VIDEO_CONVERT_* variables describing it
+    pub hook_url: Option<String>,
+    pub hook_script: Option<PathBuf>,
+    // Bearer token the --serve API asks every request for; it won't listen beyond
+    // this machine without one
+    pub api_token: Option<String>,
+    // Target format a session starts on, by extension (e.g. "webm"), bringing the
+    // settings last used with it from `formats`; MP4 when unset
+    pub preset: Option<String>,
+    // Offered as the output folder when the source's own can't be written to (a
+    // DVD, a read-only share); the system temp folder is offered when unset
+    pub fallback_output_dir: Option<PathBuf>,
+    // Extensions the file browser lists besides those it converts to, the disc
+    // and the subtitle ones, without the dot, e.g. ["mpg", "divx", "rmvb"]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_extensions: Vec<String>,
+    // Accessible display: brighter colors, text markers wherever color alone told
+    // what is selected or focused, and one column where panes would sit side by side
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub accessible_ui: bool,
+    // Redraw for job progress only when a whole percent or the step shown changes,
+    // at most once a second, for slow SSH links where every redraw costs
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reduced_flicker: bool,
+    // What stands in for green and red in the interface
+    #[serde(skip_serializing_if = "ColorPalette::is_standard")]
+    pub palette: ColorPalette,
+    // FFmpeg log level new jobs start with; above normal, each encode writes a
+    // report into the log directory
+    #[serde(skip_serializing_if = "LogLevel::is_normal")]
+    pub log_level: LogLevel,
+    // Whether new jobs encode gently: "battery" while unplugged, or "always"
+    #[serde(skip_serializing_if = "EcoMode::is_off")]
+    pub eco_mode: EcoMode,
+    // Settings last used with each target format, keyed by its extension, so
+    // picking a format again brings back what suited it
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub formats: BTreeMap<String, FormatDefaults>,
+    // The file as it was read, before the environment overrode it, so saving
+    // doesn't write the overrides into it
+    #[serde(skip)]
+    file: Option<Box<Config>>,
+}
+
+// Success and failure are green and red in the standard palette, which look
+// alike with the common red-green color blindness. The others swap in blue for
+// green, and a vermillion or orange for red that stays apart from it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorPalette {
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+}
+
+impl ColorPalette {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorPalette::Standard => "standard",
+            ColorPalette::Deuteranopia => "deuteranopia",
+            ColorPalette::Protanopia => "protanopia",
+        }
+    }
+    
+    fn is_standard(&self) -> bool {
+        *self == ColorPalette::Standard
+    }
+}
+
+// The settings that shape an encode; where the output goes and what happens to
+// it afterwards stay the same whichever format is picked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatDefaults {
+    pub resolution: Resolution,
+    pub crop: Crop,
+    pub bitrate: Bitrate,
+    pub quality_target: QualityTarget,
+    pub frame_rate: FrameRate,
+    pub frame_rate_mode: FrameRateMode,
+    pub image_quality: ImageQuality,
+    pub h264_profile: H264Profile,
+    pub h264_level: H264Level,
+    pub tune: EncoderTune,
+    pub pixel_format: PixelFormat,
+    pub keyframe_interval: KeyframeInterval,
+    pub scene_cut: bool,
+    pub audio_passthrough: bool,
+    pub downmix: Downmix,
+}
+
+impl FormatDefaults {
+    pub fn from_settings(settings: &VideoSettings) -> Self {
+        Self {
+            resolution: settings.resolution,
+            crop: settings.crop,
+            bitrate: settings.bitrate,
+            quality_target: settings.quality_target,
+            frame_rate: settings.frame_rate,
+            frame_rate_mode: settings.frame_rate_mode,
+            image_quality: settings.image_quality,
+            h264_profile: settings.h264_profile,
+            h264_level: settings.h264_level,
+            tune: settings.tune,
+            pixel_format: settings.pixel_format,
+            keyframe_interval: settings.keyframe_interval,
+            scene_cut: settings.scene_cut,
+            audio_passthrough: settings.audio_passthrough,
+            downmix: settings.downmix,
+        }
+    }
+    
+    pub fn apply(&self, settings: &mut VideoSettings) {
+        settings.resolution = self.resolution;
+        settings.crop = self.crop;
+        settings.bitrate = self.bitrate;
+        settings.quality_target = self.quality_target;
+        settings.frame_rate = self.frame_rate;
+        settings.frame_rate_mode = self.frame_rate_mode;
+        settings.image_quality = self.image_quality;
+        settings.h264_profile = self.h264_profile;
+        settings.h264_level = self.h264_level;
+        settings.tune = self.tune;
+        settings.pixel_format = self.pixel_format;
+        settings.keyframe_interval = self.keyframe_interval;
+        settings.scene_cut = self.scene_cut;
+        settings.audio_passthrough = self.audio_passthrough;
+        settings.downmix = self.downmix;
+    }
+}
+
+// Fields missing from an older config fall back to the usual defaults
+impl Default for FormatDefaults {
+    fn default() -> Self {
+        Self::from_settings(&VideoSettings::default())
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rust-tui-video-convert").join("config.toml"))
+    }
+    
+    // Plugin manifests (*.toml) live in a directory next to the config file
+    pub fn plugin_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rust-tui-video-convert").join("plugins"))
+    }
+    
+    // The conversion history shown on the Stats tab, one JSON line per finished job
+    pub fn history_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rust-tui-video-convert").join("history.jsonl"))
+    }
+    
+    // FFmpeg reports, one file per encode, for debugging codec problems
+    pub fn log_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rust-tui-video-convert").join("logs"))
+    }
+    
+    // A missing config file is not an error; defaults are used instead
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = Self::path().ok_or(ConfigError::NoConfigDir)?;
+        
+        let mut config: Self = match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(ConfigError::Io(e)),
+        };
+        config.file = Some(Box::new(config.clone()));
+        config.override_from_env(None);
+        Ok(config)
+    }
+    
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let path = Self::path().ok_or(ConfigError::NoConfigDir)?;
+        
+        // Overridden settings keep the file's value unless they were changed since
+        let mut saved = self.clone();
+        if let Some(file) = &self.file {
+            saved.override_from_env(Some(file));
+        }
+        
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, toml::to_string_pretty(&saved)?)?;
+        
+        Ok(())
+    }
+    
+    // The RTVC_* variables that are set, and so override the file
+    pub fn env_overrides() -> Vec<&'static str> {
+        ENV_OVERRIDES.into_iter().filter(|name| env_text(name).is_some()).collect()
+    }
+    
+    // Put each variable's value in place, or with `file`, put the file's back
+    fn override_from_env(&mut self, file: Option<&Config>) {
+        let path = |name| env_text(name).map(|value| Some(PathBuf::from(value)));
+        let text = |name| env_text(name).map(Some);
+        let flag = |name| env_text(name).and_then(|value| parse_flag(&value));
+        
+        override_field(&mut self.ffmpeg_path, path("RTVC_FFMPEG_PATH"), file.map(|file| &file.ffmpeg_path));
+        override_field(&mut self.ffprobe_path, path("RTVC_FFPROBE_PATH"), file.map(|file| &file.ffprobe_path));
+        override_field(&mut self.tools_dir, path("RTVC_TOOLS_DIR"), file.map(|file| &file.tools_dir));
+        override_field(&mut self.output_dir, path("RTVC_OUTPUT_DIR"), file.map(|file| &file.output_dir));
+        override_field(&mut self.fallback_output_dir, path("RTVC_FALLBACK_OUTPUT_DIR"), file.map(|file| &file.fallback_output_dir));
+        override_field(&mut self.upload_to, text("RTVC_UPLOAD_TO"), file.map(|file| &file.upload_to));
+        override_field(&mut self.hook_url, text("RTVC_HOOK_URL"), file.map(|file| &file.hook_url));
+        override_field(&mut self.hook_script, path("RTVC_HOOK_SCRIPT"), file.map(|file| &file.hook_script));
+        let log_level = env_text("RTVC_LOG_LEVEL").and_then(|value| toml::Value::String(value.to_lowercase()).try_into().ok());
+        override_field(&mut self.log_level, log_level, file.map(|file| &file.log_level));
+        override_field(&mut self.accessible_ui, flag("RTVC_ACCESSIBLE_UI"), file.map(|file| &file.accessible_ui));
+        override_field(&mut self.reduced_flicker, flag("RTVC_REDUCED_FLICKER"), file.map(|file| &file.reduced_flicker));
+        override_field(&mut self.api_token, text("RTVC_API_TOKEN"), file.map(|file| &file.api_token));
+        override_field(&mut self.preset, text("RTVC_PRESET"), file.map(|file| &file.preset));
+    }
+    
+    // The preset's format, if it names one
+    pub fn preset_format(&self) -> Option<VideoFormat> {
+        self.preset.as_deref().and_then(VideoFormat::from_extension)
+    }
+    
+    pub fn upload_target(&self) -> Option<UploadTarget> {
+        self.upload_to.as_deref().and_then(UploadTarget::parse)
+    }
+    
+    pub fn hooks(&self) -> JobHooks {
+        JobHooks { url: self.hook_url.clone(), script: self.hook_script.clone() }
+    }
+    
+    // Make the configured binaries the ones every FFmpeg/FFprobe call uses; a tool
+    // that isn't configured is looked for in the portable folders first
+    pub fn apply(&self) {
+        rust_tui_video_convert::ffmpeg::set_binary_paths(
+            self.ffmpeg_path.as_deref().map(resolve_tool_path).or_else(|| self.portable_tool("ffmpeg")),
+            self.ffprobe_path.as_deref().map(resolve_tool_path).or_else(|| self.portable_tool("ffprobe")),
+        );
+    }
+    
+    // Where a tool shipped with the application would be, in the order they're searched
+    pub fn portable_dirs(&self) -> Vec<PathBuf> {
+        let exe_dir = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+        let tools_dir = self.tools_dir.as_ref().map(|dir| match &exe_dir {
+            Some(exe_dir) if dir.is_relative() => exe_dir.join(dir),
+            _ => dir.clone(),
+        });
+        tools_dir.into_iter().chain(exe_dir).collect()
+    }
+    
+    fn portable_tool(&self, name: &str) -> Option<PathBuf> {
+        let file_name = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+        self.portable_dirs().into_iter().map(|dir| dir.join(&file_name)).find(|path| path.is_file())
+    }
+}
+
+fn env_text(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn override_field<T: Clone + PartialEq>(field: &mut T, value: Option<T>, file: Option<&T>) {
+    let Some(value) = value else {
+        return;
+    };
+    match file {
+        Some(file) if *field == value => *field = file.clone(),
+        Some(_) => {},
+        None => *field = value,
+    }
+}
+
+fn resolve_tool_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    
+    // Prefer a binary sitting next to our own executable; otherwise leave the
+    // path as given so bare names still go through the normal PATH lookup
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(path)))
+        .filter(|candidate| candidate.is_file())
+        .unwrap_or_else(|| path.to_path_buf())
+}
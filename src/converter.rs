@@ -1,13 +1,34 @@
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
-use std::thread;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use crate::disc;
+use crate::hls::{HlsPackager, Rendition};
+use crate::hooks::JobHooks;
+use crate::power;
+use crate::upload::{UploadTarget, Uploader};
+use crate::ytdlp::YtDlpDownloader;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Resolution {
     Original,
+    #[serde(rename = "360p")]
+    SD360p,
+    #[serde(rename = "480p")]
+    SD480p,
+    #[serde(rename = "720p")]
     HD720p,
+    #[serde(rename = "1080p")]
     HD1080p,
+    #[serde(rename = "4k")]
     UHD4K,
 }
 
@@ -15,28 +36,229 @@ impl Resolution {
     pub fn as_str(&self) -> &'static str {
         match self {
             Resolution::Original => "Original",
+            Resolution::SD360p => "360p",
+            Resolution::SD480p => "480p",
             Resolution::HD720p => "720p",
             Resolution::HD1080p => "1080p",
             Resolution::UHD4K => "4K",
         }
     }
     
+    pub fn description(&self) -> &'static str {
+        match self {
+            Resolution::Original => "Keeps the source's frame size; nothing is scaled",
+            Resolution::SD360p => "Scales to 640x360, for slow connections and small previews",
+            Resolution::SD480p => "Scales to 854x480, standard definition for phones and older players",
+            Resolution::HD720p => "Scales to 1280x720: small files that still look sharp on phones and laptops",
+            Resolution::HD1080p => "Scales to 1920x1080, the usual size for TVs and monitors",
+            Resolution::UHD4K => "Scales to 3840x2160; only worth it for 4K sources, and slow to encode",
+        }
+    }
+    
     pub fn dimensions(&self) -> Option<(u32, u32)> {
         match self {
             Resolution::Original => None,
+            Resolution::SD360p => Some((640, 360)),
+            Resolution::SD480p => Some((854, 480)),
             Resolution::HD720p => Some((1280, 720)),
             Resolution::HD1080p => Some((1920, 1080)),
             Resolution::UHD4K => Some((3840, 2160)),
         }
     }
+    
+    // Frame size after scaling a source into this resolution's box: the largest
+    // even size that keeps the source aspect ratio
+    pub fn output_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let Some((max_width, max_height)) = self.dimensions() else {
+            return (width, height);
+        };
+        if width == 0 || height == 0 {
+            return (max_width, max_height);
+        }
+        
+        let scale = (f64::from(max_width) / f64::from(width)).min(f64::from(max_height) / f64::from(height));
+        let even = |value: f64| ((value / 2.0).round() as u32 * 2).max(2);
+        (even(f64::from(width) * scale), even(f64::from(height) * scale))
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+// Cutting the picture to another shape before it's scaled, keeping the centre
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Crop {
+    Off,
+    #[serde(rename = "16:9")]
+    Widescreen,
+    #[serde(rename = "4:3")]
+    Standard,
+    #[serde(rename = "2.39:1")]
+    Scope,
+    #[serde(rename = "1:1")]
+    Square,
+    #[serde(rename = "9:16")]
+    Vertical,
+}
+
+impl Crop {
+    pub const ALL: [Crop; 6] = [Crop::Off, Crop::Widescreen, Crop::Standard, Crop::Scope, Crop::Square, Crop::Vertical];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Crop::Off => "Off",
+            Crop::Widescreen => "16:9",
+            Crop::Standard => "4:3",
+            Crop::Scope => "2.39:1",
+            Crop::Square => "1:1",
+            Crop::Vertical => "9:16",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            Crop::Off => "Keeps the whole picture",
+            Crop::Widescreen => "Trims a 4:3 or taller picture to widescreen, e.g. a letterboxed TV recording",
+            Crop::Standard => "Trims the sides of a widescreen picture to 4:3 for old TVs and frames",
+            Crop::Scope => "Cuts the black bars off a cinema-scope film that was letterboxed into 16:9",
+            Crop::Square => "A centred square for social feeds and avatars",
+            Crop::Vertical => "A centred vertical strip for phone-first feeds (Shorts, Reels, TikTok)",
+        }
+    }
+    
+    // Width and height of the shape
+    fn aspect(&self) -> Option<(u32, u32)> {
+        match self {
+            Crop::Off => None,
+            Crop::Widescreen => Some((16, 9)),
+            Crop::Standard => Some((4, 3)),
+            Crop::Scope => Some((239, 100)),
+            Crop::Square => Some((1, 1)),
+            Crop::Vertical => Some((9, 16)),
+        }
+    }
+    
+    // FFmpeg crop filter for the largest even-sized centred area of this shape.
+    // Measured in displayed pixels (sar), so anamorphic DVD frames crop right.
+    pub fn filter(&self) -> Option<String> {
+        let (w, h) = self.aspect()?;
+        Some(format!(
+            "crop=trunc(min(iw\\,ih*{w}/{h}/sar)/2)*2:trunc(min(ih\\,iw*sar*{h}/{w})/2)*2",
+            w = w,
+            h = h
+        ))
+    }
+    
+    // Frame size the crop leaves of a width x height source with the given
+    // sample aspect ratio; matches `filter`
+    pub fn cropped_size(&self, width: u32, height: u32, sar: f64) -> (u32, u32) {
+        let Some((w, h)) = self.aspect() else {
+            return (width, height);
+        };
+        let sar = if sar > 0.0 { sar } else { 1.0 };
+        let (w, h) = (f64::from(w), f64::from(h));
+        let even = |value: f64| ((value / 2.0).trunc() as u32 * 2).max(2);
+        let cropped_width = f64::from(width).min(f64::from(height) * w / h / sar);
+        let cropped_height = f64::from(height).min(f64::from(width) * sar * h / w);
+        (even(cropped_width), even(cropped_height))
+    }
+}
+
+// A ladder of renditions for adaptive streaming: the source at falling sizes and
+// bitrates, for a player to switch between as the connection allows. Each is
+// named after its size (`talk.720p.mp4`); with HLS they're also cut into segments
+// with playlists that a plain web server can stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ladder {
+    #[default]
+    Off,
+    Renditions,
+    Hls,
+}
+
+impl Ladder {
+    // The rungs, tallest first, with the video bitrate each is encoded at in kbps
+    pub const RUNGS: [(Resolution, u32); 4] = [
+        (Resolution::HD1080p, 5000),
+        (Resolution::HD720p, 2800),
+        (Resolution::SD480p, 1400),
+        (Resolution::SD360p, 800),
+    ];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ladder::Off => "Off",
+            Ladder::Renditions => "1080p to 360p",
+            Ladder::Hls => "1080p to 360p + HLS",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            Ladder::Off => "One output at the chosen resolution",
+            Ladder::Renditions => "1080p, 720p, 480p and 360p renditions at fixed bitrates in one job, named after their size. Sizes above the source's are left out",
+            Ladder::Hls => "The renditions, plus HLS segments and a master playlist in a name.hls folder for streaming from any web server (MP4, M4V or TS)",
+        }
+    }
+    
+    pub fn is_off(&self) -> bool {
+        *self == Ladder::Off
+    }
+    
+    // Video formats with a bitrate to vary and no fixed frame size
+    pub fn fits(format: VideoFormat) -> bool {
+        !format.is_audio() && !format.is_animated_image() && !format.has_fixed_bitrate()
+    }
+    
+    // HLS segments are MPEG-TS, which takes these formats' H.264 and AAC without re-encoding
+    pub fn packages(format: VideoFormat) -> bool {
+        matches!(format, VideoFormat::MP4 | VideoFormat::M4V | VideoFormat::TS)
+    }
+    
+    // What's left of the ladder in a format that can't take all of it
+    pub fn for_format(&self, format: VideoFormat) -> Ladder {
+        match self {
+            _ if !Self::fits(format) => Ladder::Off,
+            Ladder::Hls if !Self::packages(format) => Ladder::Renditions,
+            ladder => *ladder,
+        }
+    }
+    
+    // The rungs for a source of the given size: none that would upscale it, but
+    // always at least the smallest. Every rung when the size isn't known.
+    pub fn rungs_for(source_size: Option<(u32, u32)>) -> Vec<(Resolution, u32)> {
+        let Some((width, height)) = source_size.filter(|&(width, height)| width > 0 && height > 0) else {
+            return Self::RUNGS.to_vec();
+        };
+        let rungs: Vec<_> = Self::RUNGS.into_iter().filter(|(resolution, _)| resolution.output_size(width, height).0 <= width).collect();
+        if rungs.is_empty() {
+            Self::RUNGS[Self::RUNGS.len() - 1..].to_vec()
+        } else {
+            rungs
+        }
+    }
+    
+    // A rung's settings: its size and bitrate, and keyframes every 2 seconds without
+    // scene cuts, so every rendition's segments start at the same moments
+    pub fn rung_settings(settings: &VideoSettings, resolution: Resolution, kbps: u32) -> VideoSettings {
+        VideoSettings {
+            resolution,
+            bitrate: Bitrate::Custom(kbps),
+            keyframe_interval: KeyframeInterval::Sec2,
+            scene_cut: false,
+            extra_outputs: Vec::new(),
+            ..settings.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Bitrate {
     Auto,
     Low,
     Medium,
     High,
+    Custom(u32), // Exact video bitrate in kbps
 }
 
 impl Bitrate {
@@ -46,12 +268,54 @@ impl Bitrate {
             Bitrate::Low => "Low",
             Bitrate::Medium => "Medium",
             Bitrate::High => "High",
+            Bitrate::Custom(_) => "Custom",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            Bitrate::Auto => "The encoder picks the rate for a constant quality, so file size follows the content",
+            Bitrate::Low => "A fixed, low rate for the resolution: small files, visible blocking in busy scenes",
+            Bitrate::Medium => "A fixed rate that suits most content at the chosen resolution",
+            Bitrate::High => "A fixed, generous rate: close to the source, at over twice the size of Low",
+            Bitrate::Custom(_) => "Exactly this many kbps of video, e.g. from the calculator to hit a file size",
+        }
+    }
+    
+    pub fn label(&self) -> String {
+        match self {
+            Bitrate::Custom(kbps) => format!("Custom ({} kbps)", kbps),
+            _ => self.as_str().to_string(),
+        }
+    }
+    
+    // Video bitrate needed to hit a target file size, after reserving room for audio.
+    // Sizes use decimal megabytes to match what file managers report.
+    pub fn for_target_size(duration_secs: f64, target_size_mb: f64, audio_kbps: u32) -> Option<Bitrate> {
+        if duration_secs <= 0.0 || target_size_mb <= 0.0 {
+            return None;
+        }
+        
+        let total_kbps = target_size_mb * 8_000.0 / duration_secs;
+        let video_kbps = total_kbps - f64::from(audio_kbps);
+        
+        if video_kbps < 1.0 {
+            None
+        } else {
+            Some(Bitrate::Custom(video_kbps.floor() as u32))
         }
     }
     
     pub fn value_kbps(&self, resolution: &Resolution) -> u32 {
         match (self, resolution) {
             (Bitrate::Auto, _) => 0, // Let the converter decide
+            (Bitrate::Custom(kbps), _) => *kbps,
+            (Bitrate::Low, Resolution::SD360p) => 500,
+            (Bitrate::Medium, Resolution::SD360p) => 800,
+            (Bitrate::High, Resolution::SD360p) => 1200,
+            (Bitrate::Low, Resolution::SD480p) => 800,
+            (Bitrate::Medium, Resolution::SD480p) => 1400,
+            (Bitrate::High, Resolution::SD480p) => 2000,
             (Bitrate::Low, Resolution::HD720p) => 1500,
             (Bitrate::Medium, Resolution::HD720p) => 2500,
             (Bitrate::High, Resolution::HD720p) => 4000,
@@ -66,11 +330,19 @@ impl Bitrate {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FrameRate {
     Original,
+    #[serde(rename = "10")]
+    FPS10,
+    #[serde(rename = "15")]
+    FPS15,
+    #[serde(rename = "24")]
     FPS24,
+    #[serde(rename = "30")]
     FPS30,
+    #[serde(rename = "60")]
     FPS60,
 }
 
@@ -78,15 +350,30 @@ impl FrameRate {
     pub fn as_str(&self) -> &'static str {
         match self {
             FrameRate::Original => "Original",
+            FrameRate::FPS10 => "10 fps",
+            FrameRate::FPS15 => "15 fps",
             FrameRate::FPS24 => "24 fps",
             FrameRate::FPS30 => "30 fps",
             FrameRate::FPS60 => "60 fps",
         }
     }
     
+    pub fn description(&self) -> &'static str {
+        match self {
+            FrameRate::Original => "Keeps the source's frame rate",
+            FrameRate::FPS10 => "10 fps: choppy, only for screen recordings and animated images",
+            FrameRate::FPS15 => "15 fps: smooth enough for animated images and slides",
+            FrameRate::FPS24 => "24 fps, the cinema rate",
+            FrameRate::FPS30 => "30 fps, the usual rate for phone and web video",
+            FrameRate::FPS60 => "60 fps: fluid motion for games and sports, at a larger size",
+        }
+    }
+    
     pub fn value(&self) -> Option<u32> {
         match self {
             FrameRate::Original => None,
+            FrameRate::FPS10 => Some(10),
+            FrameRate::FPS15 => Some(15),
             FrameRate::FPS24 => Some(24),
             FrameRate::FPS30 => Some(30),
             FrameRate::FPS60 => Some(60),
@@ -94,340 +381,2394 @@ impl FrameRate {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct VideoSettings {
-    pub resolution: Resolution,
-    pub bitrate: Bitrate,
-    pub frame_rate: FrameRate,
+// Frame timing of the output. Phones record variable frame rate, which makes some
+// editors drift audio out of sync; Constant duplicates or drops frames to a fixed rate.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameRateMode {
+    Auto,
+    #[serde(rename = "cfr")]
+    Constant,
+    #[serde(rename = "vfr")]
+    Variable,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum VideoFormat {
-    MP4,
-    MKV,
-    AVI,
-    MOV,
-    WEBM,
+impl FrameRateMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrameRateMode::Auto => "Auto",
+            FrameRateMode::Constant => "Constant (CFR)",
+            FrameRateMode::Variable => "Variable (VFR)",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            FrameRateMode::Auto => "Keeps the source's frame timing, whatever it is",
+            FrameRateMode::Constant => "Duplicates or drops frames to a fixed rate so editors keep phone audio in sync",
+            FrameRateMode::Variable => "Keeps each frame's own timestamp and never duplicates frames",
+        }
+    }
+    
+    // Value for -fps_mode
+    pub fn ffmpeg_name(&self) -> Option<&'static str> {
+        match self {
+            FrameRateMode::Auto => None,
+            FrameRateMode::Constant => Some("cfr"),
+            FrameRateMode::Variable => Some("vfr"),
+        }
+    }
 }
 
-impl VideoFormat {
+// Quality of animated image output, where a bitrate has no meaning
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageQuality {
+    Low,
+    Medium,
+    High,
+    Lossless,
+}
+
+impl ImageQuality {
     pub fn as_str(&self) -> &'static str {
         match self {
-            VideoFormat::MP4 => "MP4",
-            VideoFormat::MKV => "MKV",
-            VideoFormat::AVI => "AVI",
-            VideoFormat::MOV => "MOV",
-            VideoFormat::WEBM => "WEBM",
+            ImageQuality::Low => "Low",
+            ImageQuality::Medium => "Medium",
+            ImageQuality::High => "High",
+            ImageQuality::Lossless => "Lossless",
         }
     }
     
-    pub fn extension(&self) -> &'static str {
+    pub fn description(&self) -> &'static str {
         match self {
-            VideoFormat::MP4 => "mp4",
-            VideoFormat::MKV => "mkv",
-            VideoFormat::AVI => "avi",
-            VideoFormat::MOV => "mov",
-            VideoFormat::WEBM => "webm",
+            ImageQuality::Low => "Smallest animated images, with visible banding",
+            ImageQuality::Medium => "A balance of size and detail for animated images",
+            ImageQuality::High => "Close to the source, at a much larger size",
+            ImageQuality::Lossless => "Every pixel kept exactly; the largest files by far",
         }
     }
     
-    pub fn description(&self) -> &'static str {
+    // libwebp quality factor, 0-100
+    pub fn webp_quality(&self) -> u8 {
         match self {
-            VideoFormat::MP4 => "MPEG-4 Part 14 - Widely supported format with good compression",
-            VideoFormat::MKV => "Matroska Video - Container format that can hold many codecs",
-            VideoFormat::AVI => "Audio Video Interleave - Microsoft's container format",
-            VideoFormat::MOV => "QuickTime File Format - Apple's container format",
-            VideoFormat::WEBM => "WebM - Open, royalty-free format designed for the web",
+            ImageQuality::Low => 50,
+            ImageQuality::Medium => 75,
+            ImageQuality::High => 90,
+            ImageQuality::Lossless => 100,
         }
     }
     
-    pub fn from_extension(ext: &str) -> Option<Self> {
-        match ext.to_lowercase().as_str() {
-            "mp4" => Some(VideoFormat::MP4),
-            "mkv" => Some(VideoFormat::MKV),
-            "avi" => Some(VideoFormat::AVI),
-            "mov" => Some(VideoFormat::MOV),
-            "webm" => Some(VideoFormat::WEBM),
-            _ => None,
+    // APNG is always lossless, so lower qualities shrink it with a reduced palette
+    pub fn palette_colors(&self) -> Option<u32> {
+        match self {
+            ImageQuality::Low => Some(64),
+            ImageQuality::Medium => Some(256),
+            ImageQuality::High | ImageQuality::Lossless => None,
         }
     }
 }
 
-#[derive(Clone)]
-pub struct ConversionProgress {
-    pub percent: u8,
-    pub current_step: String,
-    pub source_file: PathBuf,
-    pub target_format: VideoFormat,
-    pub output_file: PathBuf,
-    pub is_complete: bool,
-    pub has_error: bool,
-    pub error_message: Option<String>,
-    pub video_settings: Option<VideoSettings>,
+// H.264 profile; Auto leaves the choice to x264 (High for ordinary 8-bit video)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum H264Profile {
+    Auto,
+    Baseline,
+    Main,
+    High,
 }
 
-pub enum ConversionMode {
-    Simulation,
-    FFmpeg,
-    NativeFFmpeg,
+impl H264Profile {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            H264Profile::Auto => "Auto",
+            H264Profile::Baseline => "Baseline",
+            H264Profile::Main => "Main",
+            H264Profile::High => "High",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            H264Profile::Auto => "Lets x264 choose (High for ordinary 8-bit video)",
+            H264Profile::Baseline => "Plays on the oldest phones and decoders, at the cost of larger files",
+            H264Profile::Main => "For older TVs and set-top boxes that lack High",
+            H264Profile::High => "Best compression; plays on anything from the last decade",
+        }
+    }
+    
+    // Value for -profile:v
+    pub fn ffmpeg_name(&self) -> Option<&'static str> {
+        match self {
+            H264Profile::Auto => None,
+            H264Profile::Baseline => Some("baseline"),
+            H264Profile::Main => Some("main"),
+            H264Profile::High => Some("high"),
+        }
+    }
 }
 
-pub struct VideoConverter {
-    progress_tx: mpsc::Sender<ConversionProgress>,
-    mode: ConversionMode,
+// H.264 level: the frame size, macroblock rate and bitrate a decoder must handle.
+// Older TVs and hardware decoders refuse streams above the level they support.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum H264Level {
+    Auto,
+    #[serde(rename = "3.0")]
+    L3_0,
+    #[serde(rename = "3.1")]
+    L3_1,
+    #[serde(rename = "4.0")]
+    L4_0,
+    #[serde(rename = "4.1")]
+    L4_1,
+    #[serde(rename = "4.2")]
+    L4_2,
+    #[serde(rename = "5.0")]
+    L5_0,
+    #[serde(rename = "5.1")]
+    L5_1,
+    #[serde(rename = "5.2")]
+    L5_2,
 }
 
-impl VideoConverter {
-    pub fn new(mode: ConversionMode) -> (Self, mpsc::Receiver<ConversionProgress>) {
-        let (progress_tx, progress_rx) = mpsc::channel();
-        (Self { progress_tx, mode }, progress_rx)
+impl H264Level {
+    pub const ALL: [H264Level; 9] = [
+        H264Level::Auto,
+        H264Level::L3_0,
+        H264Level::L3_1,
+        H264Level::L4_0,
+        H264Level::L4_1,
+        H264Level::L4_2,
+        H264Level::L5_0,
+        H264Level::L5_1,
+        H264Level::L5_2,
+    ];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            H264Level::Auto => "Auto",
+            H264Level::L3_0 => "3.0",
+            H264Level::L3_1 => "3.1",
+            H264Level::L4_0 => "4.0",
+            H264Level::L4_1 => "4.1",
+            H264Level::L4_2 => "4.2",
+            H264Level::L5_0 => "5.0",
+            H264Level::L5_1 => "5.1",
+            H264Level::L5_2 => "5.2",
+        }
     }
-
-    pub fn convert(&self, source_file: PathBuf, target_format: VideoFormat) {
-        let progress_tx = self.progress_tx.clone();
-        
-        // Create output file path
-        let output_file = Self::generate_output_path(&source_file, target_format);
-        
-        // Default video settings
-        let default_settings = VideoSettings {
-            resolution: Resolution::Original,
-            bitrate: Bitrate::Auto,
-            frame_rate: FrameRate::Original,
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            H264Level::Auto => "Lets x264 pick the lowest level the stream fits",
+            H264Level::L3_0 => "Up to SD resolution; for very old phones and players",
+            H264Level::L3_1 => "Up to 720p at 30 fps",
+            H264Level::L4_0 => "Up to 1080p at 30 fps; a common limit of hardware decoders and TVs",
+            H264Level::L4_1 => "Up to 1080p at 30 fps at Blu-ray bitrates",
+            H264Level::L4_2 => "Up to 1080p at 60 fps",
+            H264Level::L5_0 => "Up to 1080p at 72 fps or 4K at low frame rates",
+            H264Level::L5_1 => "Up to 4K at 30 fps",
+            H264Level::L5_2 => "Up to 4K at 60 fps",
+        }
+    }
+    
+    // Value for -level
+    pub fn ffmpeg_name(&self) -> Option<&'static str> {
+        match self {
+            H264Level::Auto => None,
+            level => Some(level.as_str()),
+        }
+    }
+    
+    // (macroblocks per frame, macroblocks per second, kbps for Baseline/Main) from
+    // Table A-1 of the H.264 spec; High profile allows 25% more bitrate
+    fn limits(&self) -> Option<(u64, u64, u64)> {
+        match self {
+            H264Level::Auto => None,
+            H264Level::L3_0 => Some((1_620, 40_500, 10_000)),
+            H264Level::L3_1 => Some((3_600, 108_000, 14_000)),
+            H264Level::L4_0 => Some((8_192, 245_760, 20_000)),
+            H264Level::L4_1 => Some((8_192, 245_760, 50_000)),
+            H264Level::L4_2 => Some((8_704, 522_240, 50_000)),
+            H264Level::L5_0 => Some((22_080, 589_824, 135_000)),
+            H264Level::L5_1 => Some((36_864, 983_040, 240_000)),
+            H264Level::L5_2 => Some((36_864, 2_073_600, 240_000)),
+        }
+    }
+    
+    // Whether a stream of this size, rate and bitrate (0 = unknown) fits the level
+    pub fn allows(&self, profile: H264Profile, width: u32, height: u32, fps: f64, kbps: u32) -> bool {
+        let Some((max_frame, max_rate, max_kbps)) = self.limits() else {
+            return true;
         };
         
-        // Send initial progress notification
-        Self::send_progress(
-            &progress_tx, 
-            0, 
-            "Initializing conversion...".to_string(),
-            &source_file,
-            target_format,
-            &output_file,
-            false,
-            false,
-            None,
-            Some(default_settings)
-        );
-        
-        match self.mode {
-            ConversionMode::Simulation => {
-                self.simulate_conversion(source_file, target_format, output_file)
-            },
-            
-            ConversionMode::NativeFFmpeg => {
-                // Use native FFmpeg library
-                let native = crate::native_converter::NativeConverter::new(self.progress_tx.clone());
-                if let Err(e) = native.convert(source_file.clone(), target_format, output_file.clone()) {
-                    // Handle error
-                    Self::send_progress(
-                        &progress_tx, 
-                        0, 
-                        format!("Native FFmpeg error: {}, falling back to simulation", e),
-                        &source_file,
-                        target_format,
-                        &output_file,
-                        false,
-                        true,
-                        Some(format!("Native FFmpeg error: {}", e)),
-                        None
-                    );
-                    // Fall back to simulation
-                    self.simulate_conversion(source_file, target_format, output_file);
-                }
-            },
-            
-            ConversionMode::FFmpeg => {
-                // Check if FFmpeg is available
-                if let Ok(available) = crate::ffmpeg::FFmpegConverter::check_ffmpeg_available() {
-                    if available {
-                        // Use FFmpeg for conversion
-                        let ffmpeg = crate::ffmpeg::FFmpegConverter::new(self.progress_tx.clone());
-                        if let Err(e) = ffmpeg.convert(source_file.clone(), target_format, output_file.clone()) {
-                            // Handle error
-                            Self::send_progress(
-                                &progress_tx, 
-                                0, 
-                                format!("FFmpeg error: {}, falling back to simulation", e),
-                                &source_file,
-                                target_format,
-                                &output_file,
-                                false,
-                                true,
-                                Some(format!("FFmpeg error: {}", e)),
-                                None
-                            );
-                            // Fall back to simulation
-                            self.simulate_conversion(source_file, target_format, output_file);
-                        }
-                    } else {
-                        // FFmpeg not available, fall back to simulation
-                        Self::send_progress(
-                            &progress_tx, 
-                            0, 
-                            "FFmpeg not found, using simulation mode".to_string(),
-                            &source_file,
-                            target_format,
-                            &output_file,
-                            false,
-                            false,
-                            None,
-                            None
-                        );
-                        self.simulate_conversion(source_file, target_format, output_file);
-                    }
-                } else {
-                    // Error checking FFmpeg, fall back to simulation
-                    Self::send_progress(
-                        &progress_tx, 
-                        0,
-                        "Error checking FFmpeg availability, using simulation mode".to_string(), 
-                        &source_file,
-                        target_format,
-                        &output_file,
-                        false,
-                        false,
-                        None,
-                        Some(default_settings)
-                    );
-                    self.simulate_conversion(source_file, target_format, output_file);
-                }
-            }
+        let macroblocks = u64::from(width.div_ceil(16)) * u64::from(height.div_ceil(16));
+        let max_kbps = if profile == H264Profile::High { max_kbps * 5 / 4 } else { max_kbps };
+        macroblocks <= max_frame
+            && macroblocks as f64 * fps <= max_rate as f64
+            && u64::from(kbps) <= max_kbps
+    }
+}
+
+// x264 tuning for the kind of content being encoded
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncoderTune {
+    None,
+    Film,
+    Animation,
+    Grain,
+    FastDecode,
+    ZeroLatency,
+}
+
+impl EncoderTune {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EncoderTune::None => "None",
+            EncoderTune::Film => "Film",
+            EncoderTune::Animation => "Animation",
+            EncoderTune::Grain => "Grain",
+            EncoderTune::FastDecode => "Fast decode",
+            EncoderTune::ZeroLatency => "Zero latency",
         }
     }
     
-    fn simulate_conversion(&self, source_file: PathBuf, target_format: VideoFormat, output_file: PathBuf) {
-        let progress_tx = self.progress_tx.clone();
-        
-        // Spawn a thread to handle the conversion simulation
-        thread::spawn(move || {
-            // This is a simulation of video conversion
-            
-            // Step 1: Analyzing video
-            Self::send_progress(
-                &progress_tx, 
-                0, 
-                "Analyzing video file...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None,
-                None
-            );
-            thread::sleep(Duration::from_millis(500));
-            
-            // Step 2: Extracting audio
-            Self::send_progress(
-                &progress_tx, 
-                10, 
-                "Extracting audio stream...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None,
-                None
-            );
-            thread::sleep(Duration::from_millis(1000));
-            
-            // Step 3: Processing video
-            for i in 20..=80 {
-                Self::send_progress(
-                    &progress_tx, 
-                    i, 
-                    format!("Converting video frame {}/100...", i),
-                    &source_file,
-                    target_format,
-                    &output_file,
-                    false,
-                    false,
-                    None,
-                    None
-                );
-                thread::sleep(Duration::from_millis(100));
-            }
-            
-            // Step 4: Muxing streams
-            Self::send_progress(
-                &progress_tx, 
-                90, 
-                "Muxing audio and video streams...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None,
-                None
-            );
-            thread::sleep(Duration::from_millis(500));
-            
-            // Step 5: Finalizing
-            Self::send_progress(
-                &progress_tx, 
-                100, 
-                "Finalizing output file...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None,
-                None
-            );
-            thread::sleep(Duration::from_millis(300));
-            
-            // Complete
-            Self::send_progress(
-                &progress_tx, 
-                100, 
-                "Conversion complete!".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                true,
-                false,
-                None,
-                None
-            );
-        });
+    pub fn description(&self) -> &'static str {
+        match self {
+            EncoderTune::None => "No content-specific tuning",
+            EncoderTune::Film => "For live-action footage: keeps fine detail",
+            EncoderTune::Animation => "For cartoons: flat areas and sharp edges, with more reference frames",
+            EncoderTune::Grain => "Preserves film grain instead of smoothing it away, at a much larger size",
+            EncoderTune::FastDecode => "Skips features that are slow to decode, for weak players",
+            EncoderTune::ZeroLatency => "No frame buffering, for live streaming; compresses worse",
+        }
     }
     
-    fn send_progress(
-        tx: &mpsc::Sender<ConversionProgress>,
-        percent: u8,
-        step: String,
-        source_file: &PathBuf,
-        target_format: VideoFormat,
-        output_file: &PathBuf,
-        is_complete: bool,
-        has_error: bool,
-        error_message: Option<String>,
-        video_settings: Option<VideoSettings>,
-    ) {
-        let _ = tx.send(ConversionProgress {
-            percent,
-            current_step: step,
-            source_file: source_file.clone(),
-            target_format,
-            output_file: output_file.clone(),
-            is_complete,
-            has_error,
-            error_message,
-            video_settings,
-        });
+    // Value for -tune
+    pub fn ffmpeg_name(&self) -> Option<&'static str> {
+        match self {
+            EncoderTune::None => None,
+            EncoderTune::Film => Some("film"),
+            EncoderTune::Animation => Some("animation"),
+            EncoderTune::Grain => Some("grain"),
+            EncoderTune::FastDecode => Some("fastdecode"),
+            EncoderTune::ZeroLatency => Some("zerolatency"),
+        }
+    }
+}
+
+// Smart quality: search sample encodes for the highest CRF (smallest file) that
+// still reaches this VMAF score, instead of using the format's fixed CRF
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QualityTarget {
+    Off,
+    #[serde(rename = "93")]
+    Vmaf93,
+    #[serde(rename = "95")]
+    Vmaf95,
+    #[serde(rename = "97")]
+    Vmaf97,
+}
+
+impl QualityTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QualityTarget::Off => "Off",
+            QualityTarget::Vmaf93 => "VMAF 93",
+            QualityTarget::Vmaf95 => "VMAF 95",
+            QualityTarget::Vmaf97 => "VMAF 97",
+        }
     }
     
-    fn generate_output_path(source_file: &PathBuf, target_format: VideoFormat) -> PathBuf {
-        let parent = source_file.parent().unwrap_or_else(|| Path::new(""));
-        let stem = source_file.file_stem().unwrap_or_default();
-        
-        let mut output_path = parent.to_path_buf();
-        output_path.push(format!("{}.{}", stem.to_string_lossy(), target_format.extension()));
-        
-        output_path
+    pub fn description(&self) -> &'static str {
+        match self {
+            QualityTarget::Off => "Uses the format's fixed quality setting (CRF)",
+            QualityTarget::Vmaf93 => "Searches sample encodes for the smallest file scoring VMAF 93: good, with artifacts on close viewing",
+            QualityTarget::Vmaf95 => "Searches sample encodes for the smallest file scoring VMAF 95: hard to tell from the source",
+            QualityTarget::Vmaf97 => "Searches sample encodes for the smallest file scoring VMAF 97: visually lossless, larger files",
+        }
+    }
+    
+    pub fn vmaf(&self) -> Option<f64> {
+        match self {
+            QualityTarget::Off => None,
+            QualityTarget::Vmaf93 => Some(93.0),
+            QualityTarget::Vmaf95 => Some(95.0),
+            QualityTarget::Vmaf97 => Some(97.0),
+        }
+    }
+}
+
+// Folding surround audio down to stereo for laptop and phone speakers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Downmix {
+    Off,
+    Stereo,
+    #[serde(rename = "dialogue")]
+    DialogueBoost,
+}
+
+impl Downmix {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Downmix::Off => "Off",
+            Downmix::Stereo => "Stereo",
+            Downmix::DialogueBoost => "Stereo + dialogue boost",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            Downmix::Off => "Keeps surround tracks' channel layout",
+            Downmix::Stereo => "Folds 5.1 and wider audio to stereo for laptop and phone speakers",
+            Downmix::DialogueBoost => "Stereo with the centre channel lifted, so speech stays clear over music and effects",
+        }
+    }
+    
+    // Audio filter for a source track with `channels` channels, or None when it's
+    // already stereo or mono. Dialogue boost lifts the centre channel, where speech
+    // lives, over the others; it needs 5.1 or wider, whose first channels are
+    // always FL FR FC LFE and the left/right surrounds.
+    pub fn audio_filter(&self, channels: u32) -> Option<&'static str> {
+        match self {
+            _ if channels <= 2 => None,
+            Downmix::Off => None,
+            Downmix::DialogueBoost if channels >= 6 => Some("pan=stereo|FL<c2+0.30*c0+0.30*c4|FR<c2+0.30*c1+0.30*c5"),
+            Downmix::Stereo | Downmix::DialogueBoost => Some("aformat=channel_layouts=stereo"),
+        }
+    }
+}
+
+// Disk priority of the encoder, so a conversion sharing a disk with other work
+// doesn't starve it. Applied with `ionice`, so Linux and the FFmpeg backend only.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IoPriority {
+    Normal,
+    // Lowest best-effort priority: still progresses steadily under contention
+    Low,
+    // Only touches the disk when nothing else wants it
+    Idle,
+}
+
+impl IoPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IoPriority::Normal => "Normal",
+            IoPriority::Low => "Low",
+            IoPriority::Idle => "Idle",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            IoPriority::Normal => "The encoder reads and writes like any other program",
+            IoPriority::Low => "Lowest best-effort priority: other programs' disk access goes first, and the encode still progresses",
+            IoPriority::Idle => "Only touches the disk when nothing else wants it; can stall behind a busy disk",
+        }
+    }
+    
+    // Arguments for `ionice` ahead of the command it runs
+    pub fn ionice_args(&self) -> Option<&'static [&'static str]> {
+        match self {
+            IoPriority::Normal => None,
+            IoPriority::Low => Some(&["-c", "2", "-n", "7"]),
+            IoPriority::Idle => Some(&["-c", "3"]),
+        }
+    }
+}
+
+// Gentler encoding for laptops: fewer encoder threads and faster presets, so a
+// long batch doesn't run the machine hot until it throttles. Decided as each job
+// starts, so a batch follows the power cable being pulled or plugged in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EcoMode {
+    #[default]
+    Off,
+    Battery,
+    Always,
+}
+
+// x264 preset eco mode swaps in for medium and slow
+pub const ECO_X264_PRESET: &str = "faster";
+// libvpx-vp9 speed eco mode asks for; the default 0 is its slowest
+pub const ECO_VP9_CPU_USED: &str = "4";
+
+impl EcoMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EcoMode::Off => "Off",
+            EcoMode::Battery => "On battery",
+            EcoMode::Always => "Always",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            EcoMode::Off => "Encodes with every core and the format's usual presets",
+            EcoMode::Battery => "While the laptop is unplugged, half the cores and faster presets: cooler and longer-lasting, slightly larger files",
+            EcoMode::Always => "Half the cores and faster presets on every job, e.g. to keep a machine quiet and responsive",
+        }
+    }
+    
+    // Whether a job starting now runs in eco mode
+    pub fn engaged(&self) -> bool {
+        match self {
+            EcoMode::Off => false,
+            EcoMode::Battery => power::on_battery(),
+            EcoMode::Always => true,
+        }
+    }
+    
+    pub fn is_off(&self) -> bool {
+        *self == EcoMode::Off
+    }
+}
+
+// How much FFmpeg logs about a job. Above Normal, the encode also writes a
+// `-report` style log (through FFREPORT) for digging into codec problems.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[default]
+    Normal,
+    Verbose,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Normal => "Normal",
+            LogLevel::Verbose => "Verbose",
+            LogLevel::Debug => "Debug",
+            LogLevel::Trace => "Trace",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            LogLevel::Normal => "No report is written",
+            LogLevel::Verbose => "Writes an FFmpeg report with stream and filter setup details",
+            LogLevel::Debug => "Writes an FFmpeg report with codec internals; reports can reach many megabytes",
+            LogLevel::Trace => "Writes an FFmpeg report of everything it logs; expect gigabytes on a long encode",
+        }
+    }
+    
+    pub fn is_normal(&self) -> bool {
+        *self == LogLevel::Normal
+    }
+    
+    // FFmpeg's numeric -loglevel for the report; None writes no report
+    pub fn report_level(&self) -> Option<u8> {
+        match self {
+            LogLevel::Normal => None,
+            LogLevel::Verbose => Some(40),
+            LogLevel::Debug => Some(48),
+            LogLevel::Trace => Some(56),
+        }
+    }
+}
+
+// Output pixel format; Auto keeps the source's where the encoder supports it.
+// 10-bit avoids banding and keeps HDR gradients, but many TVs and phones can't decode it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PixelFormat {
+    Auto,
+    Yuv420p,
+    #[serde(rename = "yuv420p10le")]
+    Yuv420p10,
+}
+
+impl PixelFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PixelFormat::Auto => "Auto",
+            PixelFormat::Yuv420p => "yuv420p (8-bit)",
+            PixelFormat::Yuv420p10 => "yuv420p10le (10-bit)",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            PixelFormat::Auto => "Keeps what the encoder chooses for the source",
+            PixelFormat::Yuv420p => "8-bit 4:2:0, which every player supports",
+            PixelFormat::Yuv420p10 => "10-bit: less banding in gradients and HDR-ready, but some players can't decode it",
+        }
+    }
+    
+    // Value for -pix_fmt
+    pub fn ffmpeg_name(&self) -> Option<&'static str> {
+        match self {
+            PixelFormat::Auto => None,
+            PixelFormat::Yuv420p => Some("yuv420p"),
+            PixelFormat::Yuv420p10 => Some("yuv420p10le"),
+        }
+    }
+}
+
+// Time between forced keyframes. Streaming servers cut segments on keyframes,
+// so they usually want one every 2 seconds exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyframeInterval {
+    Auto,
+    #[serde(rename = "1")]
+    Sec1,
+    #[serde(rename = "2")]
+    Sec2,
+    #[serde(rename = "4")]
+    Sec4,
+    #[serde(rename = "10")]
+    Sec10,
+}
+
+impl KeyframeInterval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyframeInterval::Auto => "Auto",
+            KeyframeInterval::Sec1 => "1 second",
+            KeyframeInterval::Sec2 => "2 seconds",
+            KeyframeInterval::Sec4 => "4 seconds",
+            KeyframeInterval::Sec10 => "10 seconds",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            KeyframeInterval::Auto => "The encoder's default spacing, usually several seconds",
+            KeyframeInterval::Sec1 => "A keyframe every second: precise seeking and streaming segments, larger files",
+            KeyframeInterval::Sec2 => "A keyframe every 2 seconds, what most streaming services ask for",
+            KeyframeInterval::Sec4 => "A keyframe every 4 seconds: a balance of seeking and size",
+            KeyframeInterval::Sec10 => "A keyframe every 10 seconds: smallest files, coarse seeking",
+        }
+    }
+    
+    pub fn seconds(&self) -> Option<u32> {
+        match self {
+            KeyframeInterval::Auto => None,
+            KeyframeInterval::Sec1 => Some(1),
+            KeyframeInterval::Sec2 => Some(2),
+            KeyframeInterval::Sec4 => Some(4),
+            KeyframeInterval::Sec10 => Some(10),
+        }
+    }
+}
+
+// Frame rate used for animated images when the setting is left at Original;
+// full video rates make them far larger than the GIFs they replace
+pub const ANIMATION_DEFAULT_FPS: u32 = 15;
+
+// A sidecar subtitle file to add while converting
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalSubtitles {
+    pub path: PathBuf,
+    // Render into the picture instead of muxing a selectable track
+    pub burn_in: bool,
+}
+
+impl ExternalSubtitles {
+    // Language code from names like "movie.eng.srt" or "movie.pt-BR.srt", if present
+    pub fn language(&self) -> Option<String> {
+        let stem = self.path.file_stem()?.to_str()?;
+        let (_, tag) = stem.rsplit_once('.')?;
+        let primary = tag.split('-').next()?;
+        let looks_like_language = (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic());
+        looks_like_language.then(|| tag.to_lowercase())
+    }
+}
+
+// An audio file to use instead of the source's audio, or to mix over its main
+// track, e.g. a voiceover or licensed music for a screen capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalAudio {
+    pub path: PathBuf,
+    // Mix with the source audio instead of replacing it
+    pub mix: bool,
+}
+
+// The part of the source to convert, as times into it in seconds; None runs
+// from the start or to the end
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Trim {
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+}
+
+impl Trim {
+    pub fn is_off(&self) -> bool {
+        self.start.is_none() && self.end.is_none()
+    }
+    
+    // Where the output starts in the source and how long it runs; the length is
+    // None when there's no out point and the source's isn't known
+    pub fn window(&self, source_duration: Option<f64>) -> (f64, Option<f64>) {
+        let start = self.start.unwrap_or(0.0);
+        let length = self.end.or(source_duration).map(|end| (end - start).max(0.0));
+        (start, length)
+    }
+    
+    // e.g. "0:00:05.240 to end"
+    pub fn label(&self) -> String {
+        if self.is_off() {
+            return "Off".to_string();
+        }
+        format!(
+            "{} to {}",
+            self.start.map_or("start".to_string(), format_position),
+            self.end.map_or("end".to_string(), format_position)
+        )
+    }
+}
+
+// A time into a file to the millisecond, e.g. "0:01:05.240"
+pub fn format_position(secs: f64) -> String {
+    let millis = (secs.max(0.0) * 1000.0).round() as u64;
+    format!("{}:{:02}:{:02}.{:03}", millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60, millis % 1000)
+}
+
+// Accepts plain seconds, "mm:ss" or "hh:mm:ss", with fractions of a second
+pub fn parse_position(input: &str) -> Option<f64> {
+    let parts: Vec<&str> = input.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    let mut total = 0.0;
+    for (index, part) in parts.into_iter().enumerate() {
+        // f64's parser takes "nan" and "inf" too
+        let value = part.parse::<f64>().ok().filter(|value| value.is_finite() && *value >= 0.0)?;
+        // Minutes and seconds after the first part stay under an hour or a minute
+        if index > 0 && value >= 60.0 {
+            return None;
+        }
+        total = total * 60.0 + value;
+    }
+    Some(total)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrackKind {
+    Audio,
+    Subtitle,
+}
+
+impl TrackKind {
+    // Stream specifier letter, as in -map 0:a:1
+    pub fn specifier(&self) -> &'static str {
+        match self {
+            TrackKind::Audio => "a",
+            TrackKind::Subtitle => "s",
+        }
+    }
+}
+
+// A source track to keep, and the disposition flags it gets in the output
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackSelection {
+    pub kind: TrackKind,
+    pub number: usize,
+    pub default: bool,
+    pub forced: bool,
+}
+
+impl TrackSelection {
+    // Value for -disposition; "0" clears flags the muxer would otherwise copy
+    pub fn disposition(&self) -> &'static str {
+        match (self.default, self.forced) {
+            (true, true) => "default+forced",
+            (true, false) => "default",
+            (false, true) => "forced",
+            (false, false) => "0",
+        }
+    }
+}
+
+// Another file a job writes from the same source, in its own format and at its
+// own resolution (None keeps the job's). Every other setting is the job's.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtraOutput {
+    pub format: VideoFormat,
+    pub resolution: Option<Resolution>,
+}
+
+impl ExtraOutput {
+    // "WEBM", or "WEBM 720p" with a resolution of its own
+    pub fn label(&self) -> String {
+        match self.resolution {
+            Some(resolution) => format!("{} {}", self.format.as_str(), resolution.as_str()),
+            None => self.format.as_str().to_string(),
+        }
+    }
+    
+    // The job's settings as this output is encoded with them
+    pub fn settings(&self, settings: &VideoSettings) -> VideoSettings {
+        VideoSettings {
+            resolution: self.resolution.unwrap_or(settings.resolution),
+            ladder: Ladder::Off,
+            extra_outputs: Vec::new(),
+            ..settings.clone()
+        }
+    }
+    
+    // Named like the job's output, with the resolution added when it has its own
+    // so two sizes of one format don't overwrite each other
+    pub fn output_path(&self, source_file: &Path, settings: &VideoSettings, sample: bool) -> PathBuf {
+        let name = self.resolution.map(|resolution| {
+            let mut name = settings.output_name.clone().unwrap_or_else(|| source_stem(source_file));
+            name.push(format!(".{}", resolution.as_str().to_lowercase()));
+            name
+        });
+        let name = name.as_deref().or(settings.output_name.as_deref());
+        VideoConverter::generate_output_path(source_file, name, settings.output_dir.as_deref(), self.format, sample)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VideoSettings {
+    pub resolution: Resolution,
+    // Applied before scaling
+    pub crop: Crop,
+    pub trim: Trim,
+    pub bitrate: Bitrate,
+    // Ignored when an explicit bitrate is set
+    pub quality_target: QualityTarget,
+    pub frame_rate: FrameRate,
+    pub frame_rate_mode: FrameRateMode,
+    pub image_quality: ImageQuality,
+    pub subtitles: Option<ExternalSubtitles>,
+    pub external_audio: Option<ExternalAudio>,
+    // Audio and subtitle tracks to keep with their flags; empty leaves the
+    // choice to the backend, which keeps the main audio track
+    pub tracks: Vec<TrackSelection>,
+    pub h264_profile: H264Profile,
+    pub h264_level: H264Level,
+    pub tune: EncoderTune,
+    pub pixel_format: PixelFormat,
+    pub keyframe_interval: KeyframeInterval,
+    // Let the encoder add keyframes at scene changes; off gives a fixed GOP
+    pub scene_cut: bool,
+    // Keep surround bitstreams (AC3, DTS, TrueHD) as they are where the container allows
+    pub audio_passthrough: bool,
+    // Applies to surround tracks, and wins over passthrough
+    pub downmix: Downmix,
+    pub io_priority: IoPriority,
+    pub eco_mode: EcoMode,
+    // FFmpeg reports are written to `log_dir` when the level asks for one;
+    // without a folder none is written
+    pub log_level: LogLevel,
+    pub log_dir: Option<PathBuf>,
+    // Output file name without extension; None keeps the source's name
+    pub output_name: Option<OsString>,
+    // Directory for the output; None puts it beside the source (in the working
+    // directory for a URL)
+    pub output_dir: Option<PathBuf>,
+    // Give the output the source's access/modification times (and Unix permissions)
+    pub keep_file_times: bool,
+    // Download a URL source with yt-dlp before converting it, for pages rather
+    // than media files
+    pub fetch_with_ytdlp: bool,
+    // Write a sha256sum-style `<output>.sha256` beside each finished output
+    pub write_checksum: bool,
+    // Write `<output>.json` with the output's ffprobe info, these settings and the command line
+    pub write_metadata: bool,
+    // Decode the start and end of the source before encoding, failing fast on damaged files
+    pub check_integrity: bool,
+    // Copy finished outputs here as the job's last stage
+    pub upload_to: Option<UploadTarget>,
+    // Notified once the job has finished, however it ended
+    pub hooks: JobHooks,
+    // Copy every stream into the new container untouched (FFmpeg backend only);
+    // the picture, audio and track settings are then ignored
+    pub remux: bool,
+    // More files from the same source, written by the same job; FFmpeg encodes
+    // them all from one decode of the source where it can
+    pub extra_outputs: Vec<ExtraOutput>,
+    // Writes the source as a ladder of renditions in place of the one output
+    pub ladder: Ladder,
+}
+
+impl VideoSettings {
+    // Pixel format to request from the encoder, if any. Formats that only take
+    // 8-bit video get yuv420p when 10-bit was asked for.
+    pub fn output_pixel_format(&self, format: VideoFormat) -> Option<&'static str> {
+        if format.has_fixed_pixel_format() {
+            return None;
+        }
+        match self.pixel_format {
+            PixelFormat::Yuv420p10 if !format.supports_10bit() => Some("yuv420p"),
+            pixel_format => pixel_format.ffmpeg_name(),
+        }
+    }
+    
+    // H.264 profile for -profile:v. 10-bit needs High 10 whatever was chosen,
+    // and 3GP defaults to the baseline profile phones decode.
+    pub fn h264_profile_name(&self, format: VideoFormat) -> Option<&'static str> {
+        if self.output_pixel_format(format) == Some("yuv420p10le") {
+            return Some("high10");
+        }
+        self.h264_profile.ffmpeg_name().or((format == VideoFormat::ThreeGP).then_some("baseline"))
+    }
+    
+    // Settings the target container can't honour, each with how to put it right.
+    // The FFmpeg backend would adjust these itself (8-bit, High 10, burned-in or
+    // dropped subtitles); this says so before the job starts instead of mid-run.
+    pub fn container_conflicts(&self, format: VideoFormat) -> Vec<String> {
+        let formats_where = |keep: fn(&VideoFormat) -> bool| {
+            VideoFormat::ALL.into_iter().filter(keep).map(|format| format.as_str()).collect::<Vec<_>>().join(", ")
+        };
+        let mut conflicts = Vec::new();
+        
+        match self.ladder {
+            Ladder::Off => {},
+            _ if !Ladder::fits(format) => conflicts.push(format!(
+                "{} can't be encoded as a rendition ladder, so only the one output would be written. Set ABR Ladder to Off, or choose one of {}",
+                format.as_str(), formats_where(|format| Ladder::fits(*format))
+            )),
+            Ladder::Hls if !Ladder::packages(format) => conflicts.push(format!(
+                "HLS segments can't carry {} video, so the renditions wouldn't be packaged. Set ABR Ladder to 1080p to 360p, or choose one of {}",
+                format.as_str(), formats_where(|format| Ladder::packages(*format))
+            )),
+            _ => {},
+        }
+        
+        // Audio targets drop the picture and every subtitle, so that's all there is to say
+        if format.is_audio() {
+            if self.subtitles.is_some() || self.tracks.iter().any(|track| track.kind == TrackKind::Subtitle) {
+                conflicts.push(format!(
+                    "{} is audio only, so the subtitles would be dropped. Choose a video format to keep them",
+                    format.as_str()
+                ));
+            }
+            if self.external_audio.is_some() {
+                conflicts.push(format!(
+                    "{} is made from the source's audio, so the added audio file would be ignored. Convert the audio file itself, or choose a video format",
+                    format.as_str()
+                ));
+            }
+            return conflicts;
+        }
+        
+        if format.is_animated_image() {
+            if let Some(audio) = &self.external_audio {
+                conflicts.push(format!(
+                    "{} has no sound, so {} would be dropped. Choose a video format to keep it",
+                    format.as_str(), crate::paths::escape(audio.path.file_name().unwrap_or_default())
+                ));
+            }
+        }
+        
+        if self.pixel_format == PixelFormat::Yuv420p10 && !format.has_fixed_pixel_format() && !format.supports_10bit() {
+            conflicts.push(format!(
+                "{} is 8-bit only, so 10-bit would be encoded as yuv420p. Set Pixel Format to Auto, or choose one of {}",
+                format.as_str(), formats_where(VideoFormat::supports_10bit)
+            ));
+        }
+        
+        let h264 = crate::ffmpeg::FFmpegConverter::video_encoder(format) == Some("libx264");
+        let limited_profile = matches!(self.h264_profile, H264Profile::Baseline | H264Profile::Main);
+        if h264 && limited_profile && self.output_pixel_format(format) == Some("yuv420p10le") {
+            conflicts.push(format!(
+                "10-bit H.264 needs the High 10 profile, so {} would be ignored. Set H.264 Profile to Auto, or Pixel Format to yuv420p",
+                self.h264_profile.as_str()
+            ));
+        }
+        
+        let carries_subtitles = |format: &VideoFormat| format.text_subtitle_codec(false).is_some();
+        if !carries_subtitles(&format) {
+            if let Some(subs) = self.subtitles.as_ref().filter(|subs| !subs.burn_in) {
+                conflicts.push(format!(
+                    "{} can't carry subtitle tracks, so {} would be burned in. Set the subtitles to burn in, or choose one of {}",
+                    format.as_str(), crate::paths::escape(subs.path.file_name().unwrap_or_default()), formats_where(carries_subtitles)
+                ));
+            }
+            let chosen = self.tracks.iter().filter(|track| track.kind == TrackKind::Subtitle).count();
+            if chosen > 0 {
+                conflicts.push(format!(
+                    "{} can't carry subtitle tracks, so the {} chosen would be dropped. Choose one of {} to keep them",
+                    format.as_str(), chosen, formats_where(carries_subtitles)
+                ));
+            }
+        }
+        
+        conflicts
+    }
+    
+    pub fn h264_level_name(&self, format: VideoFormat) -> Option<&'static str> {
+        self.h264_level.ffmpeg_name().or((format == VideoFormat::ThreeGP).then_some("3.0"))
+    }
+    
+    // Whether an audio track in `codec` is copied for a receiver to decode
+    pub fn passes_through(&self, format: VideoFormat, codec: &str) -> bool {
+        self.audio_passthrough && format.passthrough_codecs().contains(&codec)
+    }
+    
+    // Encoder options (name, value) for the keyframe settings, shared by both backends.
+    // The interval becomes a GOP length in frames at the output frame rate; without
+    // one, keyframes are forced by timestamp instead.
+    pub fn keyframe_options(&self, format: VideoFormat, fps: Option<f64>) -> Vec<(&'static str, String)> {
+        let mut options = Vec::new();
+        if format.has_fixed_gop() {
+            return options;
+        }
+        
+        let fps = self.frame_rate.value().map(f64::from).or(fps).filter(|fps| *fps > 0.0);
+        match (self.keyframe_interval.seconds(), fps) {
+            (Some(seconds), Some(fps)) => {
+                let frames = (f64::from(seconds) * fps).round().max(1.0).to_string();
+                if !self.scene_cut {
+                    options.push(("keyint_min", frames.clone()));
+                }
+                options.push(("g", frames));
+            },
+            (Some(seconds), None) => options.push(("force_key_frames", format!("expr:gte(t,n_forced*{})", seconds))),
+            (None, _) => {},
+        }
+        if !self.scene_cut {
+            options.push(("sc_threshold", "0".to_string()));
+        }
+        options
+    }
+    
+    // Refuse a level the output would exceed, since players that honour it reject
+    // the file. Takes the source's size and frame rate; the output ones follow
+    // from the resolution and frame rate settings.
+    pub fn check_h264_level(&self, width: u32, height: u32, fps: f64) -> Result<(), String> {
+        let (width, height) = self.crop.cropped_size(width, height, 1.0);
+        let (width, height) = self.resolution.output_size(width, height);
+        let fps = self.frame_rate.value().map_or(fps, f64::from);
+        let kbps = self.bitrate.value_kbps(&self.resolution);
+        if self.h264_level.allows(self.h264_profile, width, height, fps, kbps) {
+            return Ok(());
+        }
+        
+        let needed = H264Level::ALL
+            .into_iter()
+            .find(|level| *level != H264Level::Auto && level.allows(self.h264_profile, width, height, fps, kbps));
+        let stream = if kbps > 0 {
+            format!("{}x{} at {:.0} fps and {} kbps", width, height, fps, kbps)
+        } else {
+            format!("{}x{} at {:.0} fps", width, height, fps)
+        };
+        Err(match needed {
+            Some(level) => format!("H.264 level {} is too low for {}; it needs level {} or higher", self.h264_level.as_str(), stream, level.as_str()),
+            None => format!("{} is beyond every H.264 level", stream),
+        })
+    }
+    
+    // True when nothing asks for a different picture, so a source already in the
+    // target's video codec can be copied instead of re-encoded. Formats with a
+    // fixed profile or delivery spec always encode.
+    pub fn keeps_picture(&self, format: VideoFormat) -> bool {
+        !format.has_fixed_gop()
+            && format != VideoFormat::ThreeGP
+            && self.resolution.dimensions().is_none()
+            && self.crop == Crop::Off
+            // Copied video could only be cut at keyframes
+            && self.trim.is_off()
+            && self.bitrate.value_kbps(&self.resolution) == 0
+            && self.quality_target == QualityTarget::Off
+            && self.frame_rate.value().is_none()
+            && self.frame_rate_mode == FrameRateMode::Auto
+            && self.pixel_format == PixelFormat::Auto
+            && self.h264_profile == H264Profile::Auto
+            && self.h264_level == H264Level::Auto
+            && self.tune == EncoderTune::None
+            && self.keyframe_interval == KeyframeInterval::Auto
+            && self.scene_cut
+            && !self.subtitles.as_ref().is_some_and(|subs| subs.burn_in)
+    }
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            resolution: Resolution::Original,
+            crop: Crop::Off,
+            trim: Trim::default(),
+            bitrate: Bitrate::Auto,
+            quality_target: QualityTarget::Off,
+            frame_rate: FrameRate::Original,
+            frame_rate_mode: FrameRateMode::Auto,
+            image_quality: ImageQuality::Medium,
+            subtitles: None,
+            external_audio: None,
+            tracks: Vec::new(),
+            h264_profile: H264Profile::Auto,
+            h264_level: H264Level::Auto,
+            tune: EncoderTune::None,
+            pixel_format: PixelFormat::Auto,
+            keyframe_interval: KeyframeInterval::Auto,
+            scene_cut: true,
+            audio_passthrough: false,
+            downmix: Downmix::Off,
+            io_priority: IoPriority::Normal,
+            eco_mode: EcoMode::Off,
+            log_level: LogLevel::Normal,
+            log_dir: None,
+            output_name: None,
+            output_dir: None,
+            keep_file_times: false,
+            fetch_with_ytdlp: false,
+            write_checksum: false,
+            write_metadata: false,
+            check_integrity: false,
+            upload_to: None,
+            hooks: JobHooks::default(),
+            remux: false,
+            extra_outputs: Vec::new(),
+            ladder: Ladder::Off,
+        }
+    }
+}
+
+// Network sources FFmpeg reads directly, e.g. a file on a LAN web server
+pub fn is_url(source_file: &Path) -> bool {
+    source_file.to_str().is_some_and(|source| {
+        let scheme = source.split("://").next().unwrap_or_default();
+        source.contains("://") && (scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https"))
+    })
+}
+
+// Name of the source without its extension; for a URL, that of the decoded last
+// path segment with any query string left out, and for a DVD folder the disc's
+pub fn source_stem(source_file: &Path) -> OsString {
+    if is_url(source_file) {
+        let url = source_file.to_string_lossy();
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let after_host = path.splitn(4, '/').nth(3).unwrap_or_default();
+        let name = percent_decode(after_host.rsplit('/').next().unwrap_or_default()).replace(['/', '\\'], "_");
+        let stem = Path::new(&name).file_stem().unwrap_or_default();
+        return if stem.is_empty() { OsString::from("download") } else { stem.to_os_string() };
+    }
+    if disc::is_video_ts(source_file) {
+        return disc::title_name(source_file);
+    }
+    // Kept as the OS gave it, so names that aren't UTF-8 survive into the output's
+    source_file.file_stem().unwrap_or_default().to_os_string()
+}
+
+// "Holiday%20Trip.mp4" -> "Holiday Trip.mp4"; malformed escapes are kept as they are
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Copy the source's access/modification times to the output, and its permissions
+// on Unix, so converted files keep their place when sorted by date
+pub(crate) fn copy_file_times(source_file: &Path, output_file: &Path) -> std::io::Result<()> {
+    // A DVD folder goes by its title's first piece
+    let first_segment = disc::title_segments(source_file).into_iter().next().filter(|_| disc::is_video_ts(source_file));
+    let metadata = fs::metadata(first_segment.as_deref().unwrap_or(source_file))?;
+    let times = fs::FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?);
+    fs::File::options().write(true).open(output_file)?.set_times(times)?;
+    
+    // Last, since a read-only source makes the output read-only too
+    #[cfg(unix)]
+    fs::set_permissions(output_file, metadata.permissions())?;
+    Ok(())
+}
+
+// `<output>.sha256` beside the output
+pub fn checksum_path(output_file: &Path) -> PathBuf {
+    let mut sidecar = output_file.as_os_str().to_owned();
+    sidecar.push(".sha256");
+    PathBuf::from(sidecar)
+}
+
+// Hash the output and write `<output>.sha256` in the format `sha256sum -c` checks
+pub fn write_checksum(output_file: &Path) -> std::io::Result<PathBuf> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(output_file)?, &mut hasher)?;
+    let digest = hasher.finalize();
+    
+    let name = output_file.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let sidecar = checksum_path(output_file);
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    fs::write(&sidecar, format!("{}  {}\n", hex, name))?;
+    Ok(sidecar)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NamePart {
+    Text(String),
+    Name,
+    Number { width: usize },
+    Date,
+}
+
+// Output naming for a batch, e.g. "holiday_{n:03}" gives holiday_001, holiday_002, ...
+// {name} is the source's name, {n} its 1-based place in the batch (zero-padded
+// with {n:03}) and {date} the day of the conversion as YYYY-MM-DD.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTemplate {
+    parts: Vec<NamePart>,
+}
+
+impl NameTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        if template.contains(['/', '\\']) {
+            return Err("The name template can't contain a directory".to_string());
+        }
+        
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(NamePart::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..].find('}').ok_or("Unclosed { in the name template")? + start;
+            parts.push(match &rest[start + 1..end] {
+                "name" => NamePart::Name,
+                "n" => NamePart::Number { width: 0 },
+                "date" => NamePart::Date,
+                variable => match variable.strip_prefix("n:").and_then(|width| width.parse().ok()) {
+                    Some(width) => NamePart::Number { width },
+                    None => return Err(format!("Unknown name variable: {{{}}}", variable)),
+                },
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(NamePart::Text(rest.to_string()));
+        }
+        
+        if parts.is_empty() {
+            return Err("The name template is empty".to_string());
+        }
+        Ok(Self { parts })
+    }
+    
+    // Whether every file of a batch gets its own name
+    pub fn is_unique(&self) -> bool {
+        self.parts.iter().any(|part| matches!(part, NamePart::Name | NamePart::Number { .. }))
+    }
+    
+    pub fn render(&self, source_file: &Path, number: usize, date: &str) -> OsString {
+        let mut name = OsString::new();
+        for part in &self.parts {
+            match part {
+                NamePart::Text(text) => name.push(text),
+                NamePart::Name => name.push(source_stem(source_file)),
+                NamePart::Number { width } => name.push(format!("{:0width$}", number, width = *width)),
+                NamePart::Date => name.push(date),
+            }
+        }
+        name
+    }
+}
+
+// Today's date (UTC) as YYYY-MM-DD
+pub fn today() -> String {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+    civil_date(days)
+}
+
+// A moment as "YYYY-MM-DD HH:MM:SS UTC", for job start and finish times
+pub fn timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let of_day = secs % 86_400;
+    format!(
+        "{} {:02}:{:02}:{:02} UTC",
+        civil_date((secs / 86_400) as i64),
+        of_day / 3600,
+        of_day % 3600 / 60,
+        of_day % 60,
+    )
+}
+
+// Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+fn civil_date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Length of the clip produced by a test encode, taken from the middle of the source
+pub const SAMPLE_DURATION_SECS: f64 = 10.0;
+
+// Home-theater audio codecs that audio passthrough keeps as they are
+pub const SURROUND_CODECS: [&str; 4] = ["ac3", "eac3", "dts", "truehd"];
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VideoFormat {
+    MP4,
+    MKV,
+    AVI,
+    MOV,
+    WEBM,
+    TS,
+    FLV,
+    OGV,
+    WMV,
+    ThreeGP,
+    M4V,
+    MXF,
+    WEBP,
+    APNG,
+    MP3,
+    FLAC,
+    WAV,
+    M4A,
+}
+
+impl VideoFormat {
+    // Every target, in the order they are offered
+    pub const ALL: [VideoFormat; 18] = [
+        VideoFormat::MP4,
+        VideoFormat::MKV,
+        VideoFormat::AVI,
+        VideoFormat::MOV,
+        VideoFormat::WEBM,
+        VideoFormat::TS,
+        VideoFormat::FLV,
+        VideoFormat::OGV,
+        VideoFormat::WMV,
+        VideoFormat::ThreeGP,
+        VideoFormat::M4V,
+        VideoFormat::MXF,
+        VideoFormat::WEBP,
+        VideoFormat::APNG,
+        VideoFormat::MP3,
+        VideoFormat::FLAC,
+        VideoFormat::WAV,
+        VideoFormat::M4A,
+    ];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VideoFormat::MP4 => "MP4",
+            VideoFormat::MKV => "MKV",
+            VideoFormat::AVI => "AVI",
+            VideoFormat::MOV => "MOV",
+            VideoFormat::WEBM => "WEBM",
+            VideoFormat::TS => "TS",
+            VideoFormat::FLV => "FLV",
+            VideoFormat::OGV => "OGV",
+            VideoFormat::WMV => "WMV",
+            VideoFormat::ThreeGP => "3GP",
+            VideoFormat::M4V => "M4V",
+            VideoFormat::MXF => "MXF",
+            VideoFormat::WEBP => "WEBP",
+            VideoFormat::APNG => "APNG",
+            VideoFormat::MP3 => "MP3",
+            VideoFormat::FLAC => "FLAC",
+            VideoFormat::WAV => "WAV",
+            VideoFormat::M4A => "M4A",
+        }
+    }
+    
+    pub fn extension(&self) -> &'static str {
+        match self {
+            VideoFormat::MP4 => "mp4",
+            VideoFormat::MKV => "mkv",
+            VideoFormat::AVI => "avi",
+            VideoFormat::MOV => "mov",
+            VideoFormat::WEBM => "webm",
+            VideoFormat::TS => "ts",
+            VideoFormat::FLV => "flv",
+            VideoFormat::OGV => "ogv",
+            VideoFormat::WMV => "wmv",
+            VideoFormat::ThreeGP => "3gp",
+            VideoFormat::M4V => "m4v",
+            VideoFormat::MXF => "mxf",
+            VideoFormat::WEBP => "webp",
+            VideoFormat::APNG => "apng",
+            VideoFormat::MP3 => "mp3",
+            VideoFormat::FLAC => "flac",
+            VideoFormat::WAV => "wav",
+            VideoFormat::M4A => "m4a",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            VideoFormat::MP4 => "MPEG-4 Part 14 - Widely supported format with good compression",
+            VideoFormat::MKV => "Matroska Video - Container format that can hold many codecs",
+            VideoFormat::AVI => "Audio Video Interleave - Microsoft's container format",
+            VideoFormat::MOV => "QuickTime File Format - Apple's container format",
+            VideoFormat::WEBM => "WebM - Open, royalty-free format designed for the web",
+            VideoFormat::TS => "MPEG Transport Stream - Broadcast and IPTV container that tolerates cut-off streams",
+            VideoFormat::FLV => "Flash Video - Legacy streaming container still used for RTMP ingest",
+            VideoFormat::OGV => "Ogg Video - Open Theora/Vorbis format for free-software platforms",
+            VideoFormat::WMV => "Windows Media Video - ASF container for older Windows players",
+            VideoFormat::ThreeGP => "3GPP - Lightweight format for feature phones and low-bandwidth devices",
+            VideoFormat::M4V => "Apple M4V - MPEG-4 variant for iTunes, iPod and Apple TV",
+            VideoFormat::MXF => "Material Exchange Format - XDCAM HD422 for broadcast ingest systems",
+            VideoFormat::WEBP => "Animated WebP - Looping image with much smaller files than GIF",
+            VideoFormat::APNG => "Animated PNG - Lossless looping image with full colour and transparency",
+            VideoFormat::MP3 => "MP3 Audio - Lossy audio that plays on practically everything",
+            VideoFormat::FLAC => "FLAC Audio - Lossless compressed audio for archiving music",
+            VideoFormat::WAV => "WAV Audio - Uncompressed PCM for editors and audio tools",
+            VideoFormat::M4A => "M4A Audio - AAC in an MPEG-4 container for Apple devices and phones",
+        }
+    }
+    
+    // Broadcast ingest only accepts standard rasters, so MXF is always scaled and
+    // padded to 1080 lines (or 720 when that was asked for) regardless of the source
+    pub fn fixed_frame_size(&self, resolution: &Resolution) -> Option<(u32, u32)> {
+        match (self, resolution) {
+            (VideoFormat::MXF, Resolution::HD720p) => Some((1280, 720)),
+            (VideoFormat::MXF, _) => Some((1920, 1080)),
+            _ => None,
+        }
+    }
+    
+    // ffprobe's name for the demuxer that reads this format. Formats sharing one
+    // (the MP4 family, Matroska and WebM) can't be told apart by their contents
+    pub fn demuxer(&self) -> &'static str {
+        match self {
+            VideoFormat::MP4 | VideoFormat::MOV | VideoFormat::ThreeGP | VideoFormat::M4V | VideoFormat::M4A => "mov,mp4,m4a,3gp,3g2,mj2",
+            VideoFormat::MKV | VideoFormat::WEBM => "matroska,webm",
+            VideoFormat::AVI => "avi",
+            VideoFormat::TS => "mpegts",
+            VideoFormat::FLV => "flv",
+            VideoFormat::OGV => "ogg",
+            VideoFormat::WMV => "asf",
+            VideoFormat::MXF => "mxf",
+            VideoFormat::WEBP => "webp_pipe",
+            VideoFormat::APNG => "apng",
+            VideoFormat::MP3 => "mp3",
+            VideoFormat::FLAC => "flac",
+            VideoFormat::WAV => "wav",
+        }
+    }
+    
+    // Audio-only targets: the video stream is dropped and only the first audio track is kept
+    pub fn is_audio(&self) -> bool {
+        matches!(self, VideoFormat::MP3 | VideoFormat::FLAC | VideoFormat::WAV | VideoFormat::M4A)
+    }
+    
+    // Looping image targets: silent, and sized by quality rather than bitrate
+    pub fn is_animated_image(&self) -> bool {
+        matches!(self, VideoFormat::WEBP | VideoFormat::APNG)
+    }
+    
+    // Codec for a soft subtitle track in this container, or None if it can't
+    // carry text subtitles (those get burned in instead)
+    pub fn text_subtitle_codec(&self, styled: bool) -> Option<&'static str> {
+        match self {
+            VideoFormat::MKV if styled => Some("ass"),
+            VideoFormat::MKV => Some("srt"),
+            VideoFormat::MP4 | VideoFormat::MOV | VideoFormat::M4V | VideoFormat::ThreeGP => Some("mov_text"),
+            VideoFormat::WEBM => Some("webvtt"),
+            _ => None,
+        }
+    }
+    
+    // ProRes, XDCAM and animated images pick their own pixel format
+    pub fn has_fixed_pixel_format(&self) -> bool {
+        matches!(self, VideoFormat::MOV | VideoFormat::MXF | VideoFormat::WEBP | VideoFormat::APNG)
+    }
+    
+    // Containers whose players are expected to handle 10-bit H.264/VP9; Flash,
+    // 3GPP phones and the older codecs are 8-bit only
+    pub fn supports_10bit(&self) -> bool {
+        matches!(self, VideoFormat::MP4 | VideoFormat::MKV | VideoFormat::TS | VideoFormat::M4V | VideoFormat::WEBM)
+    }
+    
+    // Intra-only (ProRes, image frames) or fixed by the delivery spec (XDCAM's 12-frame GOP)
+    pub fn has_fixed_gop(&self) -> bool {
+        matches!(self, VideoFormat::MOV | VideoFormat::MXF | VideoFormat::WEBP | VideoFormat::APNG)
+    }
+    
+    // Surround codecs the container can hold untouched; MP4 only takes TrueHD as
+    // experimental, and the web and legacy formats take none
+    pub fn passthrough_codecs(&self) -> &'static [&'static str] {
+        match self {
+            VideoFormat::MKV | VideoFormat::TS => &SURROUND_CODECS,
+            VideoFormat::MP4 => &["ac3", "eac3", "dts"],
+            VideoFormat::M4V | VideoFormat::MOV => &["ac3", "eac3"],
+            VideoFormat::AVI => &["ac3", "dts"],
+            _ => &[],
+        }
+    }
+    
+    // Formats whose bitrate is dictated by the codec profile rather than the settings
+    pub fn has_fixed_bitrate(&self) -> bool {
+        matches!(self, VideoFormat::MOV | VideoFormat::MXF | VideoFormat::WEBP | VideoFormat::APNG)
+    }
+    
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        let ext = ext.to_lowercase();
+        Self::ALL.into_iter().find(|format| format.extension() == ext)
+    }
+}
+
+#[derive(Clone)]
+pub struct ConversionProgress {
+    pub percent: u8,
+    pub current_step: String,
+    pub source_file: PathBuf,
+    pub target_format: VideoFormat,
+    pub output_file: PathBuf,
+    pub is_complete: bool,
+    pub has_error: bool,
+    pub error_message: Option<String>,
+    pub video_settings: Option<VideoSettings>,
+    // How the encode is going, as far as the backend reports it
+    pub stats: EncodeStats,
+    // Which stage the job is in; ProgressSender fills it in as the backend moves on
+    pub stage: ConversionStage,
+    // Streams passed through without re-encoding, e.g. "audio #1 (aac)"; set on completion
+    pub copied_streams: Vec<String>,
+    // Stand-ins for encoders the FFmpeg build lacks, e.g. "mpeg4 for libx264"; set on completion
+    pub substituted_encoders: Vec<String>,
+    // The job's other outputs, besides output_file; set on completion
+    pub extra_output_files: Vec<PathBuf>,
+    // Master playlist of a ladder packaged for HLS; set on completion
+    pub hls_playlist: Option<PathBuf>,
+    // Length of the source, and of the part of it encoded (a trim or a test encode's
+    // window), as the backend measured them; set on completion
+    pub source_duration_secs: Option<f64>,
+    pub encoded_secs: Option<f64>,
+    // Stopped by the user rather than failing; has_error is set as well
+    pub cancelled: bool,
+}
+
+impl ConversionProgress {
+    // Every file the job wrote, its main output first
+    pub fn output_files(&self) -> Vec<PathBuf> {
+        std::iter::once(self.output_file.clone()).chain(self.extra_output_files.iter().cloned()).collect()
+    }
+}
+
+// Figures FFmpeg reports while encoding (its -progress keys); each stays None
+// until reported, and other backends leave them all unset
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EncodeStats {
+    // Frames encoded so far
+    pub frame: Option<u64>,
+    // Encoding speed in frames per second
+    pub fps: Option<f64>,
+    // Bitrate of the output so far, in kbit/s
+    pub bitrate_kbps: Option<f64>,
+    // Bytes of output written so far
+    pub total_size: Option<u64>,
+    // Encoding speed as a multiple of playback, e.g. 2.5 for "2.5x"
+    pub speed: Option<f64>,
+}
+
+impl EncodeStats {
+    // Take in one `key=value` line of FFmpeg's -progress output; false for keys
+    // that aren't statistics. "N/A" values clear the figure.
+    pub fn update(&mut self, line: &str) -> bool {
+        let Some((key, value)) = line.split_once('=') else {
+            return false;
+        };
+        let value = value.trim();
+        match key {
+            "frame" => self.frame = value.parse().ok(),
+            "fps" => self.fps = value.parse::<f64>().ok().filter(|fps| *fps > 0.0),
+            "bitrate" => self.bitrate_kbps = value.strip_suffix("kbits/s").and_then(|rate| rate.trim().parse().ok()),
+            "total_size" => self.total_size = value.parse().ok(),
+            "speed" => self.speed = value.strip_suffix('x').and_then(|speed| speed.trim().parse().ok()),
+            _ => return false,
+        }
+        true
+    }
+}
+
+// The stages a conversion goes through, in order. Which ones a job has depends on
+// its backend and settings; `percent` belongs to the stage the job is in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConversionStage {
+    // Fetching a web page's video with yt-dlp
+    Download,
+    // Reading the source's duration, container and streams
+    #[default]
+    Probe,
+    // Decoding the start and end of the source to catch damage
+    SourceCheck,
+    // Test encodes for smart quality
+    QualitySearch,
+    Encode,
+    // Checksum, upload and completion hooks
+    Finish,
+}
+
+impl ConversionStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConversionStage::Download => "Download",
+            ConversionStage::Probe => "Probe",
+            ConversionStage::SourceCheck => "Source check",
+            ConversionStage::QualitySearch => "Quality search",
+            ConversionStage::Encode => "Encode",
+            ConversionStage::Finish => "Finish",
+        }
+    }
+    
+    // Whether the stage reports how far along it is; the others only start and end
+    pub fn reports_percent(&self) -> bool {
+        matches!(self, ConversionStage::Download | ConversionStage::Encode | ConversionStage::Finish)
+    }
+    
+    // The stages a job will go through. Plugins run their tool in one go; only the
+    // FFmpeg backend checks sources and searches for a quality.
+    pub fn planned(mode: ConversionMode, source_file: &Path, settings: &VideoSettings, sample: bool) -> Vec<ConversionStage> {
+        if mode == ConversionMode::Plugin {
+            return vec![ConversionStage::Encode];
+        }
+        
+        let ffmpeg = mode == ConversionMode::FFmpeg;
+        let finishing = !sample && !mode.is_simulation()
+            && (settings.write_checksum || settings.upload_to.is_some() || !settings.hooks.is_empty());
+        [
+            (ConversionStage::Download, settings.fetch_with_ytdlp && is_url(source_file)),
+            (ConversionStage::Probe, true),
+            (ConversionStage::SourceCheck, ffmpeg && settings.check_integrity && !sample && !is_url(source_file)),
+            (ConversionStage::QualitySearch, ffmpeg && !settings.remux && settings.quality_target.vmaf().is_some()
+                && settings.bitrate.value_kbps(&settings.resolution) == 0),
+            (ConversionStage::Encode, true),
+            (ConversionStage::Finish, finishing),
+        ]
+        .into_iter()
+        .filter_map(|(stage, planned)| planned.then_some(stage))
+        .collect()
+    }
+}
+
+// Before/after figures for a finished conversion, shown on the Complete screen
+#[derive(Debug, Clone)]
+pub struct ConversionStats {
+    pub source_size: Option<u64>,
+    pub output_size: Option<u64>,
+    pub elapsed: Duration,
+    // Wall-clock time the job finished; it started `elapsed` before
+    pub finished_at: SystemTime,
+    pub source_duration_secs: Option<f64>,
+    pub encoded_secs: Option<f64>,
+    pub copied_streams: Vec<String>,
+    pub substituted_encoders: Vec<String>,
+    pub extra_output_files: Vec<PathBuf>,
+    pub hls_playlist: Option<PathBuf>,
+}
+
+impl ConversionStats {
+    pub fn collect(progress: &ConversionProgress, elapsed: Duration) -> Self {
+        Self {
+            source_size: disc::source_size(&progress.source_file),
+            output_size: fs::metadata(&progress.output_file).ok().map(|m| m.len()),
+            elapsed,
+            finished_at: SystemTime::now(),
+            source_duration_secs: progress.source_duration_secs,
+            encoded_secs: progress.encoded_secs,
+            copied_streams: progress.copied_streams.clone(),
+            substituted_encoders: progress.substituted_encoders.clone(),
+            extra_output_files: progress.extra_output_files.clone(),
+            hls_playlist: progress.hls_playlist.clone(),
+        }
+    }
+    
+    pub fn started_at(&self) -> SystemTime {
+        self.finished_at.checked_sub(self.elapsed).unwrap_or(self.finished_at)
+    }
+    
+    // Bytes of the source the output was made from: all of it, or for a trim or a
+    // test encode the share of its length encoded. Unknown when only the encoded
+    // length is known.
+    pub fn encoded_source_size(&self) -> Option<u64> {
+        let share = match (self.encoded_secs, self.source_duration_secs) {
+            (Some(encoded), Some(source)) if source > 0.0 => (encoded / source).min(1.0),
+            (Some(_), _) => return None,
+            (None, _) => 1.0,
+        };
+        self.source_size.map(|size| (size as f64 * share) as u64)
+    }
+    
+    // Positive when the output is smaller than the part of the source it was made from
+    pub fn percent_saved(&self) -> Option<f64> {
+        match (self.encoded_source_size(), self.output_size) {
+            (Some(source), Some(output)) if source > 0 => {
+                Some((1.0 - output as f64 / source as f64) * 100.0)
+            },
+            _ => None,
+        }
+    }
+    
+    // Source bytes processed per second, in MB/s
+    pub fn throughput_mb_per_sec(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        match self.source_size {
+            Some(source) if secs > 0.0 => Some(source as f64 / 1_000_000.0 / secs),
+            _ => None,
+        }
+    }
+    
+    // Seconds of video encoded per second of wall-clock time
+    pub fn realtime_factor(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        match self.encoded_secs {
+            Some(duration) if secs > 0.0 => Some(duration / secs),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConversionMode {
+    Simulation,
+    FFmpeg,
+    // In-process libav backend, only in builds with the `native` feature
+    Native,
+    // External tool described by a plugin manifest
+    Plugin,
+}
+
+impl ConversionMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConversionMode::Simulation => "Simulation Mode",
+            ConversionMode::FFmpeg => "External FFmpeg",
+            ConversionMode::Native => "Native libav",
+            ConversionMode::Plugin => "External Plugin",
+        }
+    }
+    
+    pub fn native_available() -> bool {
+        #[cfg(feature = "native")]
+        {
+            crate::native_converter::NativeConverter::is_available()
+        }
+        #[cfg(not(feature = "native"))]
+        {
+            false
+        }
+    }
+    
+    pub fn is_simulation(&self) -> bool {
+        *self == ConversionMode::Simulation
+    }
+}
+
+pub type ProgressNotifier = Arc<dyn Fn() + Send + Sync>;
+
+// Delivers progress to the UI and wakes its event loop. The channel is bounded,
+// so a converter waits for the UI to catch up instead of queueing without limit.
+// Clones share the stage, so whichever part of a backend moves the job on
+// stamps every update sent after it.
+#[derive(Clone)]
+pub struct ProgressSender {
+    tx: mpsc::Sender<ConversionProgress>,
+    notify: Option<ProgressNotifier>,
+    // Unset passes on the stage updates came with, as when forwarding a backend's
+    stage: Arc<Mutex<Option<ConversionStage>>>,
+}
+
+impl ProgressSender {
+    pub fn new(tx: mpsc::Sender<ConversionProgress>, notify: Option<ProgressNotifier>) -> Self {
+        Self { tx, notify, stage: Arc::default() }
+    }
+    
+    // Sends into another channel but shares this sender's stage, for a job that
+    // forwards a backend's updates after looking at them
+    pub fn redirect(&self, tx: mpsc::Sender<ConversionProgress>) -> Self {
+        Self { tx, notify: None, stage: self.stage.clone() }
+    }
+    
+    pub fn set_stage(&self, stage: ConversionStage) {
+        *self.stage.lock().unwrap_or_else(|e| e.into_inner()) = Some(stage);
+    }
+    
+    // Fails once the job's receiver is gone; the undelivered update is dropped
+    pub async fn send(&self, progress: ConversionProgress) -> Result<(), mpsc::error::SendError<()>> {
+        let progress = self.stamp(progress);
+        self.tx.send(progress).await.map_err(|_| mpsc::error::SendError(()))?;
+        self.wake();
+        Ok(())
+    }
+    
+    // For converters running on a blocking thread
+    pub fn blocking_send(&self, progress: ConversionProgress) -> Result<(), mpsc::error::SendError<()>> {
+        let progress = self.stamp(progress);
+        self.tx.blocking_send(progress).map_err(|_| mpsc::error::SendError(()))?;
+        self.wake();
+        Ok(())
+    }
+    
+    fn stamp(&self, progress: ConversionProgress) -> ConversionProgress {
+        match *self.stage.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(stage) => ConversionProgress { stage, ..progress },
+            None => progress,
+        }
+    }
+    
+    fn wake(&self) {
+        if let Some(notify) = &self.notify {
+            notify();
+        }
+    }
+}
+
+// What a backend reports about its job. ProgressReporter fills in the files the
+// job is about and turns each event into the ConversionProgress the UI reads,
+// so a new figure only has to be added there.
+pub enum ProgressEvent {
+    // The job is under way, with the settings it runs with when there are any
+    Started { step: String, settings: Option<Box<VideoSettings>> },
+    // A step or notice, `percent` of the way through the current stage
+    Stage { percent: u8, step: String },
+    // The encoder's figures as it writes frames
+    Frame { percent: u8, step: String, stats: EncodeStats },
+    // Finished, with the streams copied, the encoders stood in for, and the lengths
+    // of the source and of what was encoded where the backend knows them
+    Completed {
+        step: String,
+        copied_streams: Vec<String>,
+        substituted_encoders: Vec<String>,
+        source_duration_secs: Option<f64>,
+        encoded_secs: Option<f64>,
+    },
+    Failed { step: String, error: String },
+    // Stopped by the user, with any partial output removed
+    Cancelled,
+}
+
+impl ProgressEvent {
+    pub fn completed(step: String) -> Self {
+        ProgressEvent::Completed {
+            step,
+            copied_streams: Vec::new(),
+            substituted_encoders: Vec::new(),
+            source_duration_secs: None,
+            encoded_secs: None,
+        }
+    }
+}
+
+// Sends one job's events, each about the same source and output
+#[derive(Clone)]
+pub struct ProgressReporter {
+    tx: ProgressSender,
+    source_file: PathBuf,
+    target_format: VideoFormat,
+    output_file: PathBuf,
+}
+
+impl ProgressReporter {
+    pub fn new(tx: ProgressSender, source_file: &Path, target_format: VideoFormat, output_file: &Path) -> Self {
+        Self { tx, source_file: source_file.to_path_buf(), target_format, output_file: output_file.to_path_buf() }
+    }
+    
+    pub fn set_stage(&self, stage: ConversionStage) {
+        self.tx.set_stage(stage);
+    }
+    
+    // Undelivered events are dropped; the job's receiver is gone by then
+    pub async fn report(&self, event: ProgressEvent) {
+        let _ = self.tx.send(self.progress(event)).await;
+    }
+    
+    // For backends running on a blocking thread
+    pub fn blocking_report(&self, event: ProgressEvent) {
+        let _ = self.tx.blocking_send(self.progress(event));
+    }
+    
+    fn progress(&self, event: ProgressEvent) -> ConversionProgress {
+        let progress = ConversionProgress {
+            percent: 0,
+            current_step: String::new(),
+            source_file: self.source_file.clone(),
+            target_format: self.target_format,
+            output_file: self.output_file.clone(),
+            is_complete: false,
+            has_error: false,
+            error_message: None,
+            video_settings: None,
+            stats: EncodeStats::default(),
+            stage: ConversionStage::default(),
+            copied_streams: Vec::new(),
+            substituted_encoders: Vec::new(),
+            extra_output_files: Vec::new(),
+            hls_playlist: None,
+            source_duration_secs: None,
+            encoded_secs: None,
+            cancelled: false,
+        };
+        match event {
+            ProgressEvent::Started { step, settings } => ConversionProgress {
+                current_step: step,
+                video_settings: settings.map(|settings| *settings),
+                ..progress
+            },
+            ProgressEvent::Stage { percent, step } => ConversionProgress { percent, current_step: step, ..progress },
+            ProgressEvent::Frame { percent, step, stats } => ConversionProgress { percent, current_step: step, stats, ..progress },
+            ProgressEvent::Completed { step, copied_streams, substituted_encoders, source_duration_secs, encoded_secs } => ConversionProgress {
+                percent: 100,
+                current_step: step,
+                is_complete: true,
+                copied_streams,
+                substituted_encoders,
+                source_duration_secs,
+                encoded_secs,
+                ..progress
+            },
+            ProgressEvent::Failed { step, error } => ConversionProgress {
+                current_step: step,
+                is_complete: true,
+                has_error: true,
+                error_message: Some(error),
+                ..progress
+            },
+            ProgressEvent::Cancelled => ConversionProgress {
+                current_step: "Conversion cancelled".to_string(),
+                is_complete: true,
+                has_error: true,
+                error_message: Some(CANCELLED_MESSAGE.to_string()),
+                cancelled: true,
+                ..progress
+            },
+        }
+    }
+}
+
+// Error shown for a job the user stopped; ConversionProgress::cancelled tells them apart
+pub const CANCELLED_MESSAGE: &str = "Cancelled by user";
+
+// Backend progress held while the checksum and upload stages run
+const FINISHING_PROGRESS_BUFFER: usize = 64;
+
+// Progress of one pass of a multi-output job, held while it's rescaled for the whole job
+const OUTPUTS_PROGRESS_BUFFER: usize = 64;
+
+// One file a job writes: its format, where it goes and the settings it's encoded with
+pub struct JobOutput {
+    pub format: VideoFormat,
+    pub file: PathBuf,
+    pub settings: VideoSettings,
+}
+
+// How an output is told apart in a job's notes: by format, and by size as well
+// where the job writes that format more than once (a ladder's renditions)
+pub fn output_label(format: VideoFormat, resolution: Resolution, formats: &[VideoFormat]) -> String {
+    if formats.iter().filter(|other| **other == format).count() > 1 {
+        format!("{} {}", format.as_str(), resolution.as_str())
+    } else {
+        format.as_str().to_string()
+    }
+}
+
+pub struct VideoConverter {
+    progress_tx: ProgressSender,
+    mode: ConversionMode,
+    cancel: CancellationToken,
+}
+
+impl VideoConverter {
+    pub fn new(mode: ConversionMode, progress_tx: ProgressSender, cancel: CancellationToken) -> Self {
+        Self { progress_tx, mode, cancel }
+    }
+    
+    pub async fn convert(&self, source_file: PathBuf, target_format: VideoFormat, settings: VideoSettings, sample: bool) {
+        // Test encodes and simulated runs get no checksum and stay local
+        let finishing = !sample && !self.mode.is_simulation();
+        let checksum = settings.write_checksum && finishing;
+        let upload = settings.upload_to.clone().filter(|_| finishing);
+        let hooks = Some(settings.hooks.clone()).filter(|hooks| finishing && !hooks.is_empty());
+        if !checksum && upload.is_none() && hooks.is_none() {
+            return self.run(source_file, target_format, settings, sample).await;
+        }
+        let started = Instant::now();
+        
+        // The backend reports to us instead, so its completion can wait for these stages
+        let (tx, mut rx) = mpsc::channel(FINISHING_PROGRESS_BUFFER);
+        let backend = VideoConverter::new(self.mode, ProgressSender::new(tx, None), self.cancel.clone());
+        let run = async move { backend.run(source_file, target_format, settings, sample).await };
+        let forward = async {
+            while let Some(progress) = rx.recv().await {
+                let finished = progress.is_complete;
+                if finished && !progress.has_error {
+                    self.progress_tx.set_stage(ConversionStage::Finish);
+                }
+                let progress = if finished && !progress.has_error {
+                    self.finish(checksum, upload.as_ref(), progress).await
+                } else {
+                    progress
+                };
+                // Before the job is reported finished, so a headless run can't exit under the hooks
+                if let Some(hooks) = hooks.as_ref().filter(|_| finished) {
+                    for e in hooks.run(&progress, started.elapsed()).await {
+                        let _ = self.progress_tx.send(ConversionProgress {
+                            current_step: format!("Completion hook failed: {}", e),
+                            is_complete: false,
+                            has_error: false,
+                            error_message: None,
+                            ..progress.clone()
+                        }).await;
+                    }
+                }
+                let _ = self.progress_tx.send(progress).await;
+                if finished {
+                    break;
+                }
+            }
+        };
+        tokio::join!(run, forward);
+    }
+    
+    // The stages after a successful conversion, each passing on the completion
+    async fn finish(&self, checksum: bool, upload: Option<&UploadTarget>, complete: ConversionProgress) -> ConversionProgress {
+        let complete = if checksum { self.checksum(complete).await } else { complete };
+        match upload {
+            Some(target) if !complete.has_error => self.upload(target, complete).await,
+            _ => complete,
+        }
+    }
+    
+    async fn checksum(&self, complete: ConversionProgress) -> ConversionProgress {
+        let _ = self.progress_tx.send(ConversionProgress {
+            current_step: "Computing SHA-256 checksum...".to_string(),
+            is_complete: false,
+            ..complete.clone()
+        }).await;
+        
+        let output_files = complete.output_files();
+        let written = tokio::task::spawn_blocking(move || output_files.iter().map(|file| write_checksum(file)).collect::<io::Result<Vec<_>>>()).await
+            .unwrap_or_else(|e| Err(io::Error::other(e)));
+        match written {
+            Ok(sidecars) if sidecars.len() > 1 => ConversionProgress {
+                current_step: format!("Conversion complete! Checksums written beside all {} outputs", sidecars.len()),
+                ..complete
+            },
+            Ok(sidecars) => ConversionProgress {
+                current_step: format!("Conversion complete! Checksum written to {}", sidecars[0].display()),
+                ..complete
+            },
+            Err(e) => ConversionProgress {
+                current_step: format!("Checksum error: {}", e),
+                has_error: true,
+                error_message: Some(format!("Converted, but the checksum couldn't be written: {}", e)),
+                ..complete
+            },
+        }
+    }
+    
+    // Ship the converted files, then turn the backend's completion into the job's
+    async fn upload(&self, target: &UploadTarget, complete: ConversionProgress) -> ConversionProgress {
+        let uploader = Uploader::new(&self.progress_tx, &self.cancel);
+        // One after another, stopping at the first that doesn't make it
+        let mut remotes = Vec::new();
+        let mut uploaded = Ok(None);
+        for output_file in complete.output_files() {
+            uploaded = uploader.upload(target, &complete.source_file, complete.target_format, &output_file).await;
+            match &uploaded {
+                Ok(Some(remote)) => remotes.push(remote.clone()),
+                _ => break,
+            }
+        }
+        match uploaded {
+            Ok(Some(_)) => ConversionProgress { current_step: format!("Conversion complete! Uploaded to {}", remotes.join(", ")), ..complete },
+            Ok(None) => ConversionProgress {
+                percent: 0,
+                current_step: "Upload cancelled".to_string(),
+                has_error: true,
+                error_message: Some(CANCELLED_MESSAGE.to_string()),
+                cancelled: true,
+                ..complete
+            },
+            Err(e) => ConversionProgress {
+                current_step: format!("Upload error: {}", e),
+                has_error: true,
+                error_message: Some(format!("Converted, but the upload failed: {}", e)),
+                ..complete
+            },
+        }
+    }
+    
+    async fn run(&self, source_file: PathBuf, target_format: VideoFormat, settings: VideoSettings, sample: bool) {
+        // Create output file path
+        let output_file = Self::generate_output_path(&source_file, settings.output_name.as_deref(), settings.output_dir.as_deref(), target_format, sample);
+        let reporter = ProgressReporter::new(self.progress_tx.clone(), &source_file, target_format, &output_file);
+        
+        // A web page is fetched with yt-dlp first, and the download converted like a local file
+        let download = if settings.fetch_with_ytdlp && is_url(&source_file) {
+            reporter.set_stage(ConversionStage::Download);
+            match YtDlpDownloader::new(&self.progress_tx, &self.cancel).download(&source_file, target_format, &output_file).await {
+                Ok(Some(download)) => Some(download),
+                Ok(None) => return reporter.report(ProgressEvent::Cancelled).await,
+                Err(e) => {
+                    return reporter.report(ProgressEvent::Failed { step: format!("Download error: {}", e), error: format!("Download error: {}", e) }).await;
+                },
+            }
+        } else {
+            None
+        };
+        let (source_file, settings, output_file) = match &download {
+            Some(download) => {
+                // The download sits in a scratch folder, so the output goes where the URL's would
+                let settings = VideoSettings { output_dir: Some(settings.output_dir.clone().unwrap_or_default()), ..settings };
+                let output_file = Self::generate_output_path(&download.path, settings.output_name.as_deref(), settings.output_dir.as_deref(), target_format, sample);
+                (download.path.clone(), settings, output_file)
+            },
+            None => (source_file, settings, output_file),
+        };
+        
+        // A ladder writes a rendition per rung instead, each named after its size. Sizes
+        // above the source's are left out, and the tallest kept stands for the job.
+        let ladder = if sample || settings.remux { Ladder::Off } else { settings.ladder.for_format(target_format) };
+        let rungs = if ladder.is_off() { Vec::new() } else { Ladder::rungs_for(self.source_size(&source_file).await) };
+        let rendition = |resolution| ExtraOutput { format: target_format, resolution: Some(resolution) }.output_path(&source_file, &settings, sample);
+        let output_file = rungs.first().map_or(output_file, |&(resolution, _)| rendition(resolution));
+        let reporter = ProgressReporter::new(self.progress_tx.clone(), &source_file, target_format, &output_file);
+        
+        // A refused read or write is reported as such, not as whatever the tool exits with
+        if !self.mode.is_simulation() {
+            if let Err(problem) = crate::paths::check_access(&source_file, &output_file) {
+                return reporter.report(ProgressEvent::Failed { step: problem.to_string(), error: format!("{}. {}", problem, problem.suggestion()) }).await;
+            }
+        }
+        
+        // Send initial progress notification
+        reporter.set_stage(ConversionStage::Probe);
+        reporter.report(ProgressEvent::Started {
+            step: if download.is_some() {
+                "Download finished; converting (step 2 of 2)...".to_string()
+            } else if sample {
+                format!("Initializing {}-second test encode...", SAMPLE_DURATION_SECS)
+            } else {
+                "Initializing conversion...".to_string()
+            },
+            settings: Some(Box::new(settings.clone())),
+        }).await;
+        
+        // Extra outputs are named only now, so they follow a download like the main one
+        let mut outputs = if rungs.is_empty() {
+            vec![JobOutput {
+                format: target_format,
+                file: output_file,
+                settings: VideoSettings { extra_outputs: Vec::new(), ladder: Ladder::Off, ..settings.clone() },
+            }]
+        } else {
+            rungs.iter().map(|&(resolution, kbps)| JobOutput {
+                format: target_format,
+                file: rendition(resolution),
+                settings: VideoSettings { ladder, ..Ladder::rung_settings(&settings, resolution, kbps) },
+            }).collect()
+        };
+        outputs.extend(settings.extra_outputs.iter().map(|extra| JobOutput {
+            format: extra.format,
+            file: extra.output_path(&source_file, &settings, sample),
+            settings: extra.settings(&settings),
+        }));
+        if outputs.len() > 1 || !ladder.is_off() {
+            self.run_outputs(&source_file, outputs, sample).await
+        } else {
+            self.run_backend(&reporter, &source_file, outputs, sample).await
+        }
+    }
+    
+    // A job with several outputs. FFmpeg writes them all in one pass over the source
+    // when it can; otherwise the backend converts them one after another, each with
+    // an equal share of the progress. The job finishes once, naming every output.
+    async fn run_outputs(&self, source_file: &Path, outputs: Vec<JobOutput>, sample: bool) {
+        let (target_format, output_file) = (outputs[0].format, outputs[0].file.clone());
+        let extra_output_files: Vec<PathBuf> = outputs[1..].iter().map(|output| output.file.clone()).collect();
+        // A ladder's renditions, to package for HLS once they're all written
+        let renditions: Vec<Rendition> = outputs.iter()
+            .filter(|output| output.settings.ladder == Ladder::Hls)
+            .map(|output| Rendition {
+                file: output.file.clone(),
+                resolution: output.settings.resolution,
+                kbps: output.settings.bitrate.value_kbps(&output.settings.resolution),
+            })
+            .collect();
+        let passes: Vec<Vec<JobOutput>> = if self.mode == ConversionMode::FFmpeg && crate::ffmpeg::FFmpegConverter::shares_decode(&outputs[0].settings) {
+            vec![outputs]
+        } else {
+            outputs.into_iter().map(|output| vec![output]).collect()
+        };
+        
+        let count = passes.len();
+        let formats: Vec<VideoFormat> = passes.iter().flatten().map(|output| output.format).collect();
+        let mut copied_streams = Vec::new();
+        let mut substituted_encoders = Vec::new();
+        for (index, pass) in passes.into_iter().enumerate() {
+            let (tx, mut rx) = mpsc::channel(OUTPUTS_PROGRESS_BUFFER);
+            let backend = VideoConverter::new(self.mode, self.progress_tx.redirect(tx), self.cancel.clone());
+            let reporter = ProgressReporter::new(backend.progress_tx.clone(), source_file, pass[0].format, &pass[0].file);
+            // In a pass of its own, what was copied or stood in for is told apart by output
+            let label = (count > 1).then(|| output_label(pass[0].format, pass[0].settings.resolution, &formats));
+            let run = backend.run_backend(&reporter, source_file, pass, sample);
+            let forward = async {
+                while let Some(progress) = rx.recv().await {
+                    if progress.is_complete {
+                        return Some(progress);
+                    }
+                    let percent = ((index * 100 + usize::from(progress.percent)) / count) as u8;
+                    let _ = self.progress_tx.send(ConversionProgress { percent, ..progress }).await;
+                }
+                None
+            };
+            let (_, finished) = tokio::join!(run, forward);
+            let Some(finished) = finished else {
+                return;
+            };
+            if finished.has_error {
+                let _ = self.progress_tx.send(finished).await;
+                return;
+            }
+            
+            let labelled = |streams: &[String]| -> Vec<String> {
+                streams.iter().map(|stream| label.as_ref().map_or_else(|| stream.clone(), |label| format!("{}: {}", label, stream))).collect()
+            };
+            copied_streams.extend(labelled(&finished.copied_streams));
+            substituted_encoders.extend(labelled(&finished.substituted_encoders));
+            if index + 1 < count {
+                let written = finished.output_file.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                let _ = self.progress_tx.send(ConversionProgress {
+                    percent: (((index + 1) * 100) / count) as u8,
+                    current_step: format!("Wrote {} ({} of {})", written, index + 1, count),
+                    is_complete: false,
+                    ..finished
+                }).await;
+            } else {
+                let finished = ConversionProgress {
+                    target_format,
+                    output_file: output_file.clone(),
+                    copied_streams: std::mem::take(&mut copied_streams),
+                    substituted_encoders: std::mem::take(&mut substituted_encoders),
+                    extra_output_files: extra_output_files.clone(),
+                    ..finished
+                };
+                let finished = if renditions.is_empty() { finished } else { self.package_hls(&renditions, finished).await };
+                let _ = self.progress_tx.send(finished).await;
+            }
+        }
+    }
+    
+    // Cut a ladder's renditions into HLS segments in a folder beside them, named
+    // like the job's output would be (`talk.hls` for `talk.1080p.mp4`)
+    async fn package_hls(&self, renditions: &[Rendition], complete: ConversionProgress) -> ConversionProgress {
+        let dir = complete.output_file.with_extension("").with_extension("hls");
+        let packager = HlsPackager::new(&self.progress_tx, &self.cancel);
+        match packager.package(&complete.source_file, complete.target_format, renditions, &dir).await {
+            Ok(Some(master)) => ConversionProgress {
+                current_step: format!("Conversion complete! HLS playlist written to {}", master.display()),
+                hls_playlist: Some(master),
+                ..complete
+            },
+            Ok(None) => ConversionProgress {
+                percent: 0,
+                current_step: "HLS packaging cancelled".to_string(),
+                has_error: true,
+                error_message: Some(CANCELLED_MESSAGE.to_string()),
+                cancelled: true,
+                ..complete
+            },
+            Err(e) => ConversionProgress {
+                current_step: format!("HLS error: {}", e),
+                has_error: true,
+                error_message: Some(format!("Converted, but the HLS packaging failed: {}", e)),
+                ..complete
+            },
+        }
+    }
+    
+    // Width and height of the source's picture, where FFmpeg can read it
+    async fn source_size(&self, source_file: &Path) -> Option<(u32, u32)> {
+        if self.mode != ConversionMode::FFmpeg {
+            return None;
+        }
+        let source_file = source_file.to_path_buf();
+        tokio::task::spawn_blocking(move || crate::ffmpeg::FFmpegConverter::video_stream_info(&source_file))
+            .await
+            .ok()?
+            .ok()
+            .map(|info| (info.width, info.height))
+    }
+    
+    // Hand the outputs to the backend; only FFmpeg is given more than one at a time
+    async fn run_backend(&self, reporter: &ProgressReporter, source_file: &Path, mut outputs: Vec<JobOutput>, sample: bool) {
+        match self.mode {
+            ConversionMode::Simulation => {
+                self.simulate_conversion(reporter).await
+            },
+            
+            #[cfg(feature = "native")]
+            ConversionMode::Native => {
+                let output = outputs.remove(0);
+                let native = crate::native_converter::NativeConverter::new(self.progress_tx.clone(), self.cancel.clone());
+                if let Err(e) = native.convert(source_file.to_path_buf(), output.format, output.file, output.settings, sample).await {
+                    reporter.report(ProgressEvent::Failed {
+                        step: format!("Native conversion error: {}", e),
+                        error: format!("Native conversion error: {}", e),
+                    }).await;
+                }
+            },
+            
+            #[cfg(not(feature = "native"))]
+            ConversionMode::Native => {
+                reporter.report(ProgressEvent::Failed {
+                    step: "Native backend not available".to_string(),
+                    error: "This build does not include the native backend (build with --features native)".to_string(),
+                }).await;
+            },
+            
+            // Plugin jobs run through JobManager::submit_plugin, which knows the plugin
+            ConversionMode::Plugin => {
+                reporter.report(ProgressEvent::Failed {
+                    step: "No plugin selected".to_string(),
+                    error: "Plugin conversions need a plugin to run".to_string(),
+                }).await;
+            },
+            
+            ConversionMode::FFmpeg => {
+                let ffmpeg = crate::ffmpeg::FFmpegConverter::new(self.progress_tx.clone(), self.cancel.clone());
+                let converted = if outputs.len() > 1 {
+                    ffmpeg.convert_outputs(source_file.to_path_buf(), outputs, sample).await
+                } else {
+                    let output = outputs.remove(0);
+                    ffmpeg.convert(source_file.to_path_buf(), output.format, output.file, output.settings, sample).await
+                };
+                if let Err(e) = converted {
+                    // Report the failure rather than pretending it worked with a simulation
+                    reporter.report(ProgressEvent::Failed { step: format!("FFmpeg error: {}", e), error: format!("FFmpeg error: {}", e) }).await;
+                }
+            }
+        }
+    }
+    
+    async fn simulate_conversion(&self, reporter: &ProgressReporter) {
+        // Purely visual: walks through the usual steps without touching the disk,
+        // so nothing that looks like a converted video is ever left behind
+        
+        // Step 1: Analyzing video
+        reporter.report(ProgressEvent::Stage { percent: 0, step: "[Simulation] Analyzing video file...".to_string() }).await;
+        if !self.wait(500).await {
+            return reporter.report(ProgressEvent::Cancelled).await;
+        }
+        
+        // Step 2: Extracting audio
+        reporter.report(ProgressEvent::Stage { percent: 10, step: "[Simulation] Extracting audio stream...".to_string() }).await;
+        if !self.wait(1000).await {
+            return reporter.report(ProgressEvent::Cancelled).await;
+        }
+        
+        // Step 3: Processing video
+        reporter.set_stage(ConversionStage::Encode);
+        for i in 20..=80 {
+            reporter.report(ProgressEvent::Stage { percent: i, step: format!("[Simulation] Converting video frame {}/100...", i) }).await;
+            if !self.wait(100).await {
+                return reporter.report(ProgressEvent::Cancelled).await;
+            }
+        }
+        
+        // Step 4: Muxing streams
+        reporter.report(ProgressEvent::Stage { percent: 90, step: "[Simulation] Muxing audio and video streams...".to_string() }).await;
+        if !self.wait(500).await {
+            return reporter.report(ProgressEvent::Cancelled).await;
+        }
+        
+        // Step 5: Finalizing
+        reporter.report(ProgressEvent::Stage { percent: 100, step: "[Simulation] Finalizing...".to_string() }).await;
+        if !self.wait(300).await {
+            return reporter.report(ProgressEvent::Cancelled).await;
+        }
+        
+        // Complete
+        reporter.report(ProgressEvent::completed("Simulation complete - no output file was written".to_string())).await;
+    }
+    
+    // Sleep for the given time, returning false if the job was cancelled meanwhile
+    async fn wait(&self, millis: u64) -> bool {
+        tokio::select! {
+            _ = self.cancel.cancelled() => false,
+            _ = tokio::time::sleep(Duration::from_millis(millis)) => true,
+        }
+    }
+    
+    pub fn generate_output_path(source_file: &Path, name: Option<&OsStr>, dir: Option<&Path>, target_format: VideoFormat, sample: bool) -> PathBuf {
+        let parent = match dir {
+            Some(dir) => dir,
+            None if is_url(source_file) => Path::new(""),
+            None if disc::is_video_ts(source_file) => disc::output_dir(source_file),
+            None => source_file.parent().unwrap_or_else(|| Path::new("")),
+        };
+        let stem = name.map_or_else(|| source_stem(source_file), OsStr::to_os_string);
+        let file_name = |suffix: &str| {
+            let mut file_name = stem.clone();
+            file_name.push(format!("{}.{}", suffix, target_format.extension()));
+            file_name
+        };
+        
+        // Test encodes get their own name so they never overwrite a full conversion
+        let mut output_path = parent.join(file_name(if sample { ".sample" } else { "" }));
+        
+        // Converting to the source's own format would otherwise write over the source
+        if output_path == source_file {
+            output_path.set_file_name(file_name(".converted"));
+        }
+        
+        output_path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parse_position_reads_seconds_and_clock_times() {
+        assert_eq!(parse_position("90"), Some(90.0));
+        assert_eq!(parse_position(" 5.25 "), Some(5.25));
+        assert_eq!(parse_position("1:05.24"), Some(65.24));
+        assert_eq!(parse_position("1:02:03"), Some(3723.0));
+        assert_eq!(parse_position("120:00"), Some(7200.0));
+    }
+    
+    #[test]
+    fn parse_position_rejects_non_finite_values() {
+        assert_eq!(parse_position("nan"), None);
+        assert_eq!(parse_position("NaN"), None);
+        assert_eq!(parse_position("inf"), None);
+        assert_eq!(parse_position("-inf"), None);
+        assert_eq!(parse_position("1:nan"), None);
+        assert_eq!(parse_position("infinity:00"), None);
+    }
+    
+    #[test]
+    fn parse_position_rejects_malformed_times() {
+        assert_eq!(parse_position(""), None);
+        assert_eq!(parse_position("-5"), None);
+        assert_eq!(parse_position("1:2:3:4"), None);
+        assert_eq!(parse_position("1:60"), None);
+        assert_eq!(parse_position("1:60:00"), None);
+        assert_eq!(parse_position("1:00:60"), None);
+        assert_eq!(parse_position("1::30"), None);
     }
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossterm::{
+    cursor::Show,
+    event::DisableMouseCapture,
+    execute,
+    terminal::{disable_raw_mode, LeaveAlternateScreen},
+};
+use rust_tui_video_convert::converter;
+
+use crate::app::App;
+use crate::config::Config;
+
+// What the app was doing as of the last handled event, for the crash report
+static STATE: Mutex<Option<String>> = Mutex::new(None);
+
+// Put the terminal back and write a crash report when the app panics.
+// Only the main thread owns the screen; a panicking worker is reported quietly
+// so its message doesn't scribble over the interface.
+pub fn install() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let main_thread = thread::current().name() == Some("main");
+        if main_thread {
+            let _ = disable_raw_mode();
+            let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show);
+            default_hook(info);
+        }
+        match write_report(info) {
+            Ok(path) if main_thread => {
+                eprintln!("\nA crash report was written to {}", path.display());
+                eprintln!("Please attach it when reporting this issue.");
+            },
+            Err(e) if main_thread => eprintln!("\nCouldn't write a crash report: {}", e),
+            _ => {},
+        }
+    }));
+}
+
+// Back to the standard hook once the terminal has been restored
+pub fn uninstall() {
+    drop(panic::take_hook());
+}
+
+// Remember a summary of the app's state in case the next event panics
+pub fn record_state(app: &App) {
+    let summary = summary(app);
+    *STATE.lock().unwrap_or_else(|e| e.into_inner()) = Some(summary);
+}
+
+fn summary(app: &App) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "Tab: {:?}", app.current_tab);
+    match app.selected_source() {
+        Some(source) => {
+            let _ = writeln!(text, "Selected: {}", source.display());
+        },
+        None => text.push_str("Selected: nothing\n"),
+    }
+    if let Some(format) = app.selected_format {
+        let _ = writeln!(text, "Format: {}", format.extension());
+    }
+    let job = app.current_conversion();
+    if let Some(job) = job {
+        let sample = if job.sample { ", test encode" } else { "" };
+        let _ = writeln!(text, "Conversion: {}{} ({})", job.backend, sample, job.state().label());
+    }
+    match job.and_then(|job| job.progress.as_ref()) {
+        Some(progress) => {
+            let _ = writeln!(
+                text,
+                "Last progress: {}% [{}] {}",
+                progress.percent,
+                progress.stage.label(),
+                progress.current_step,
+            );
+            if let Some(error) = &progress.error_message {
+                let _ = writeln!(text, "Error: {}", error);
+            }
+        },
+        None => text.push_str("Last progress: none\n"),
+    }
+    for job in app.remote_jobs() {
+        let step = job.progress.as_ref().map_or("", |p| p.current_step.as_str());
+        let _ = writeln!(text, "Remote job {}: {} {}", job.id, job.state().label(), step);
+    }
+    text
+}
+
+// Write the report into the log directory and return where it went
+fn write_report(info: &PanicHookInfo) -> io::Result<PathBuf> {
+    let dir = Config::log_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory on this system"))?;
+    let now = SystemTime::now();
+    let secs = now.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let path = dir.join(format!("crash-{}.txt", secs));
+    
+    let state = STATE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let mut report = String::new();
+    let _ = writeln!(report, "## Crash");
+    let _ = writeln!(report, "Version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "System: {} ({})", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(report, "Time: {}", converter::timestamp(now));
+    let _ = writeln!(report, "Thread: {}", thread::current().name().unwrap_or("unnamed"));
+    let _ = writeln!(report, "Panic: {}", info);
+    let _ = writeln!(report, "\n## App state");
+    report.push_str(state.as_deref().unwrap_or("not recorded yet\n"));
+    let _ = writeln!(report, "\n## Backtrace\n{}", Backtrace::force_capture());
+    
+    fs::create_dir_all(&dir)?;
+    fs::write(&path, report)?;
+    Ok(path)
+}
\ No newline at end of file
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rust_tui_video_convert::converter::{ConversionMode, VideoFormat};
+use rust_tui_video_convert::ffmpeg::{self, FFmpegConverter};
+
+use crate::app::App;
+use crate::config::{ColorPalette, Config};
+use crate::launch;
+
+// Rows of the diagnostics screen, under a heading
+pub struct DiagnosticsSection {
+    pub title: &'static str,
+    pub rows: Vec<(&'static str, String)>,
+}
+
+// Everything a bug report needs about this install, gathered when the screen opens
+pub struct Diagnostics {
+    pub sections: Vec<DiagnosticsSection>,
+    pub scroll: u16,
+    // Where the report was last saved, or why it couldn't be
+    pub saved: Option<Result<PathBuf, String>>,
+}
+
+impl Diagnostics {
+    pub fn collect(app: &App) -> Self {
+        let sections = vec![
+            DiagnosticsSection {
+                title: "Application",
+                rows: vec![
+                    ("Version", env!("CARGO_PKG_VERSION").to_string()),
+                    ("System", format!("{} ({})", env::consts::OS, env::consts::ARCH)),
+                    ("Conversion tool", app.mode_label(app.available_mode())),
+                    ("Native backend", if ConversionMode::native_available() { "built in" } else { "not in this build" }.to_string()),
+                ],
+            },
+            DiagnosticsSection {
+                title: "FFmpeg",
+                rows: vec![
+                    ("FFmpeg", binary_location("ffmpeg", ffmpeg::ffmpeg_binary(), app.config.ffmpeg_path.is_some())),
+                    ("FFmpeg version", version_text(&ffmpeg::ffmpeg_binary())),
+                    ("Version check", version_check_text(app)),
+                    ("FFprobe", binary_location("ffprobe", ffmpeg::ffprobe_binary(), app.config.ffprobe_path.is_some())),
+                    ("FFprobe version", version_text(&ffmpeg::ffprobe_binary())),
+                    ("Encoders", encoders_text(app)),
+                    ("Last command", ffmpeg::last_command().map_or_else(|| "none this session".to_string(), |args| command_text(&args))),
+                ],
+            },
+            DiagnosticsSection {
+                title: "Configuration",
+                rows: vec![
+                    ("Config file", match Config::path() {
+                        Some(path) if path.is_file() => path.display().to_string(),
+                        Some(path) => format!("{} (not saved yet)", path.display()),
+                        None => "no config directory on this system".to_string(),
+                    }),
+                    ("History", location_text(Config::history_path())),
+                    ("Plugins", location_text(Config::plugin_dir())),
+                    ("Logs", location_text(Config::log_dir())),
+                    ("FFmpeg log level", app.video_settings.log_level.as_str().to_string()),
+                    ("Environment overrides", match Config::env_overrides().as_slice() {
+                        [] => "none".to_string(),
+                        names => names.join(", "),
+                    }),
+                ],
+            },
+            DiagnosticsSection {
+                title: "Terminal",
+                rows: vec![
+                    ("TERM", env_text("TERM")),
+                    ("COLORTERM", env_text("COLORTERM")),
+                    ("Size", crossterm::terminal::size().map_or_else(|e| e.to_string(), |(cols, rows)| format!("{} columns x {} rows", cols, rows))),
+                    ("Colors", color_support().to_string()),
+                    ("UTF-8 locale", if utf8_locale() { "yes" } else { "no (symbols may not show)" }.to_string()),
+                    ("Display", display_text(app)),
+                ],
+            },
+        ];
+        Self { sections, scroll: 0, saved: None }
+    }
+    
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+    
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_add(1).min(self.line_count());
+    }
+    
+    fn line_count(&self) -> u16 {
+        self.sections.iter().map(|section| section.rows.len() as u16 + 2).sum()
+    }
+    
+    // The report as plain text, ready to paste into an issue
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for section in &self.sections {
+            text.push_str(&format!("## {}\n", section.title));
+            for (label, value) in &section.rows {
+                text.push_str(&format!("{}: {}\n", label, value));
+            }
+            text.push('\n');
+        }
+        text
+    }
+    
+    // Write the report into the log directory, replacing the previous one
+    pub fn save(&mut self) {
+        self.saved = Some(match Config::log_dir() {
+            Some(dir) => {
+                let path = dir.join("diagnostics.txt");
+                fs::create_dir_all(&dir)
+                    .and_then(|()| fs::write(&path, self.text()))
+                    .map(|()| path)
+                    .map_err(|e| e.to_string())
+            },
+            None => Err("no config directory on this system".to_string()),
+        });
+    }
+}
+
+// Where a tool will be run from: the configured path, or the one PATH finds
+fn binary_location(name: &str, binary: PathBuf, configured: bool) -> String {
+    if configured {
+        let state = if binary.is_file() { "configured" } else { "configured, missing" };
+        return format!("{} ({})", binary.display(), state);
+    }
+    // Found in the tools folder or beside the application rather than on PATH
+    if binary.parent().is_some_and(|dir| !dir.as_os_str().is_empty()) {
+        return format!("{} (portable)", binary.display());
+    }
+    match launch::find_on_path(name) {
+        Some(path) => format!("{} (from PATH)", path.display()),
+        None => "not found on PATH".to_string(),
+    }
+}
+
+fn version_text(binary: &Path) -> String {
+    ffmpeg::tool_version(binary).unwrap_or_else(|| "couldn't be run".to_string())
+}
+
+// The encoders the output formats use, and which of them this FFmpeg lacks
+// along with the stand-in used instead
+fn encoders_text(app: &App) -> String {
+    let Some(capabilities) = &app.ffmpeg_capabilities else {
+        return "unknown, FFmpeg couldn't be run".to_string();
+    };
+    let used: BTreeMap<&str, VideoFormat> = VideoFormat::ALL
+        .into_iter()
+        .flat_map(|format| {
+            FFmpegConverter::video_encoder(format).into_iter()
+                .chain(FFmpegConverter::audio_encoder(format))
+                .map(move |encoder| (encoder, format))
+        })
+        .collect();
+    let (found, missing): (Vec<_>, Vec<_>) = used.into_iter().partition(|(encoder, _)| capabilities.encoders.contains(*encoder));
+    let found: Vec<&str> = found.into_iter().map(|(encoder, _)| encoder).collect();
+    if missing.is_empty() {
+        return format!("{} (all present)", found.join(", "));
+    }
+    let missing: Vec<String> = missing
+        .into_iter()
+        .map(|(encoder, format)| match FFmpegConverter::pick_encoder(&capabilities.encoders, format, encoder) {
+            Some(stand_in) => format!("{} (using {})", encoder, stand_in),
+            None => encoder.to_string(),
+        })
+        .collect();
+    format!("{}; missing {}", found.join(", "), missing.join(", "))
+}
+
+// Whether this FFmpeg is new enough for the current format and settings
+fn version_check_text(app: &App) -> String {
+    let warnings = app.ffmpeg_warnings();
+    match &app.ffmpeg_capabilities {
+        None => "unknown, FFmpeg couldn't be run".to_string(),
+        Some(_) if !warnings.is_empty() => warnings.join("; "),
+        Some(capabilities) if capabilities.version.is_none() => "development build, not checked".to_string(),
+        Some(_) => "new enough for the current settings".to_string(),
+    }
+}
+
+// Arguments quoted where a shell would split them, so the line can be rerun
+fn command_text(args: &[OsString]) -> String {
+    args.iter()
+        .map(|arg| arg.to_string_lossy())
+        .map(|arg| {
+            if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "'\"\\$`;&|<>()*?".contains(c)) {
+                arg.into_owned()
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn location_text(path: Option<PathBuf>) -> String {
+    path.map_or_else(|| "no config directory on this system".to_string(), |path| path.display().to_string())
+}
+
+fn env_text(name: &str) -> String {
+    env::var_os(name).map_or_else(|| "(unset)".to_string(), |value| value.to_string_lossy().into_owned())
+}
+
+// What the terminal says it can show, going by the usual variables
+fn color_support() -> &'static str {
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    let term = env::var("TERM").unwrap_or_default();
+    if env::var_os("NO_COLOR").is_some() {
+        "NO_COLOR is set"
+    } else if colorterm == "truecolor" || colorterm == "24bit" {
+        "24-bit"
+    } else if term.contains("256color") {
+        "256"
+    } else {
+        "16 (or unknown)"
+    }
+}
+
+fn utf8_locale() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(env::var_os)
+        .find(|value| !value.is_empty())
+        .is_some_and(|value| {
+            let value = value.to_string_lossy().to_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+        // Windows terminals take Unicode without a locale variable
+        || cfg!(windows)
+}
+
+fn display_text(app: &App) -> String {
+    let mut options = Vec::new();
+    if app.config.accessible_ui {
+        options.push("accessible".to_string());
+    }
+    if app.config.reduced_flicker {
+        options.push("reduced flicker".to_string());
+    }
+    if app.config.palette != ColorPalette::Standard {
+        options.push(format!("{} palette", app.config.palette.as_str()));
+    }
+    if options.is_empty() {
+        "standard".to_string()
+    } else {
+        options.join(", ")
+    }
+}
\ No newline at end of file
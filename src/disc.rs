@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::paths::tool_arg;
+
+// Disc files the browser lists as sources: DVD program streams, and the transport
+// streams Blu-ray and AVCHD cameras write
+pub const DISC_EXTENSIONS: [&str; 3] = ["vob", "m2ts", "mts"];
+
+// Whether `path` is a DVD's VIDEO_TS folder with a title in it. Such a folder is
+// converted as a whole instead of being browsed into.
+pub fn is_video_ts(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.eq_ignore_ascii_case("VIDEO_TS"))
+        && !title_segments(path).is_empty()
+}
+
+// The main title's VOB files in playing order. A DVD splits each title set into
+// 1 GB pieces, VTS_01_1.VOB, VTS_01_2.VOB and so on (VTS_01_0.VOB is its menu);
+// the largest set is taken to be the feature.
+pub fn title_segments(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut title_sets: BTreeMap<u32, Vec<(u32, PathBuf, u64)>> = BTreeMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some((title_set, part)) = path.file_name().and_then(OsStr::to_str).and_then(segment_number) else {
+            continue;
+        };
+        let size = entry.metadata().map_or(0, |m| m.len());
+        title_sets.entry(title_set).or_default().push((part, path, size));
+    }
+    
+    let Some(mut segments) = title_sets.into_values().max_by_key(|segments| segments.iter().map(|(_, _, size)| size).sum::<u64>()) else {
+        return Vec::new();
+    };
+    segments.sort_by_key(|(part, _, _)| *part);
+    segments.into_iter().map(|(_, path, _)| path).collect()
+}
+
+// "VTS_01_2.VOB" -> (1, 2); menus and VIDEO_TS.VOB don't count
+fn segment_number(name: &str) -> Option<(u32, u32)> {
+    let name = name.to_ascii_uppercase();
+    let (title_set, part) = name.strip_prefix("VTS_")?.strip_suffix(".VOB")?.split_once('_')?;
+    let part: u32 = part.parse().ok()?;
+    (part > 0).then_some((title_set.parse().ok()?, part))
+}
+
+// What FFmpeg reads for a DVD folder: the main title's pieces joined end to end
+// with the concat protocol, "concat:.../VTS_01_1.VOB|.../VTS_01_2.VOB"
+pub fn concat_input(dir: &Path) -> OsString {
+    let mut input = OsString::from("concat:");
+    for (index, segment) in title_segments(dir).iter().enumerate() {
+        if index > 0 {
+            input.push("|");
+        }
+        input.push(tool_arg(segment));
+    }
+    input
+}
+
+// The disc's name for its output, from the folder VIDEO_TS was copied into
+// (MOVIE/VIDEO_TS gives MOVIE)
+pub fn title_name(dir: &Path) -> OsString {
+    dir.parent()
+        .and_then(Path::file_name)
+        .map_or_else(|| OsString::from("DVD"), OsStr::to_os_string)
+}
+
+// Outputs go beside the disc's folder rather than into it
+pub fn output_dir(dir: &Path) -> &Path {
+    dir.parent().and_then(Path::parent).unwrap_or_else(|| Path::new(""))
+}
+
+// How much a source takes up; for a DVD folder, its main title
+pub fn source_size(path: &Path) -> Option<u64> {
+    if is_video_ts(path) {
+        return Some(title_segments(path).iter().filter_map(|segment| fs::metadata(segment).ok()).map(|m| m.len()).sum());
+    }
+    fs::metadata(path).ok().map(|m| m.len())
+}
\ No newline at end of file
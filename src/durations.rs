@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::SystemTime;
+
+use rust_tui_video_convert::ffmpeg::FFmpegConverter;
+use rust_tui_video_convert::notifier::Notifier;
+
+// Files either side of the selection whose lengths are looked up; more than a
+// terminal shows, so scrolling finds them ready
+pub const PROBE_WINDOW: usize = 60;
+
+// Looks up video lengths for the file list on a worker thread, a few files at a
+// time as they come into view. Only the latest batch is worked on: moving on
+// drops whatever of the previous one hadn't been probed yet.
+pub struct DurationProbe {
+    requests: mpsc::Sender<Vec<PathBuf>>,
+    results: mpsc::Receiver<(PathBuf, Option<f64>)>,
+    notify: Notifier,
+    // Keyed by path, with the modification time the length was read at, so a file
+    // that was still being written is probed again; None when ffprobe couldn't tell
+    cache: HashMap<PathBuf, (Option<SystemTime>, Option<f64>)>,
+    requested: Vec<PathBuf>,
+}
+
+impl DurationProbe {
+    pub fn new() -> Self {
+        let (requests, request_rx) = mpsc::channel::<Vec<PathBuf>>();
+        let (result_tx, results) = mpsc::channel();
+        let notify = Notifier::default();
+        
+        let worker_notify = notify.clone();
+        thread::spawn(move || {
+            let mut next = None;
+            while let Some(mut batch) = next.take().or_else(|| request_rx.recv().ok()) {
+                while let Ok(newer) = request_rx.try_recv() {
+                    batch = newer;
+                }
+                
+                for path in batch {
+                    let duration = FFmpegConverter::get_video_duration(&path).ok();
+                    if result_tx.send((path, duration)).is_err() {
+                        return;
+                    }
+                    worker_notify.notify();
+                    
+                    if let Ok(newer) = request_rx.try_recv() {
+                        next = Some(newer);
+                        break;
+                    }
+                }
+            }
+        });
+        
+        Self {
+            requests,
+            results,
+            notify,
+            cache: HashMap::new(),
+            requested: Vec::new(),
+        }
+    }
+    
+    // Called from the worker whenever a length is ready to collect with poll
+    pub fn set_notifier(&self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify.set(notify);
+    }
+    
+    // Probe whichever of `paths` aren't known yet, or changed since
+    pub fn request(&mut self, paths: &[PathBuf]) {
+        let missing: Vec<PathBuf> = paths.iter()
+            .filter(|path| match self.cache.get(*path) {
+                Some((modified, _)) => *modified != modified_time(path),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        // The same batch again is already being worked on
+        if missing.is_empty() || missing == self.requested {
+            return;
+        }
+        
+        self.requested = missing.clone();
+        let _ = self.requests.send(missing);
+    }
+    
+    // Collect finished lookups
+    pub fn poll(&mut self) {
+        for (path, duration) in self.results.try_iter() {
+            let modified = modified_time(&path);
+            self.requested.retain(|requested| *requested != path);
+            self.cache.insert(path, (modified, duration));
+        }
+    }
+    
+    pub fn get(&self, path: &Path) -> Option<f64> {
+        self.cache.get(path).and_then(|(_, duration)| *duration)
+    }
+}
+
+impl Default for DurationProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// A length as it is shown in the list, "01:32:10", so the column lines up
+pub fn format_length(duration_secs: f64) -> String {
+    let secs = duration_secs.round() as u64;
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
\ No newline at end of file
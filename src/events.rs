@@ -0,0 +1,42 @@
+use std::sync::mpsc;
+use std::thread;
+use crossterm::event::{self, Event};
+
+use crate::remote::RemoteRequest;
+
+// Everything that can wake the main loop
+pub enum AppEvent {
+    Input(Event),
+    // A job queued new progress; drain it with JobManager::poll
+    JobProgress,
+    // A file preview or popup frame finished extracting; collect it with App::poll_previews
+    PreviewReady,
+    // A subtitle extraction finished; collect it with App::poll_subtitles
+    SubtitlesExtracted,
+    // A still frame was saved or failed; collect it with App::poll_stills
+    StillSaved,
+    // Tracks or a length for a popup were read; collect them with App::poll_source_probe
+    SourceProbed,
+    // Files appeared in or left the browser's folder; list it again with App::reload_browser
+    DirectoryChanged,
+    // A file browser read more of its folder; collect it with App::poll_browsers
+    ListingProgress,
+    // Video lengths for the file list were found; collect them with DurationProbe::poll
+    DurationsReady,
+    // A control API client wants something done; answer with RemoteRequest::respond
+    Remote(RemoteRequest),
+}
+
+pub type EventSender = mpsc::Sender<AppEvent>;
+
+// Forward terminal input onto the event channel from a dedicated thread,
+// so the main loop can block on a single receiver
+pub fn spawn_input_reader(tx: EventSender) {
+    thread::spawn(move || {
+        while let Ok(event) = event::read() {
+            if tx.send(AppEvent::Input(event)).is_err() {
+                break;
+            }
+        }
+    });
+}
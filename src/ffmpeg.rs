@@ -1,11 +1,21 @@
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use std::sync::mpsc;
-use std::thread;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Mutex, OnceLock, RwLock};
+use serde::Deserialize;
 use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio_util::sync::CancellationToken;
 
-use crate::converter::{ConversionProgress, VideoFormat};
+use crate::converter::{
+    ConversionStage, EncodeStats, FrameRateMode, H264Level, ImageQuality, IoPriority, JobOutput, KeyframeInterval, PixelFormat, ProgressEvent, ProgressReporter, ProgressSender, TrackKind, Trim, VideoFormat, VideoSettings, ANIMATION_DEFAULT_FPS,
+    ECO_VP9_CPU_USED, ECO_X264_PRESET, SAMPLE_DURATION_SECS, SURROUND_CODECS, is_url, output_label,
+};
+use crate::metadata;
+use crate::paths::{self, tool_arg};
+use crate::power;
 
 #[derive(Error, Debug)]
 pub enum FFmpegError {
@@ -23,40 +33,1041 @@ pub enum FFmpegError {
     
     #[error("Invalid input file")]
     InvalidInput,
+    
+    // ffprobe ran but couldn't make sense of the file
+    #[error("{0}")]
+    Unreadable(String),
 }
 
-pub struct FFmpegConverter {
-    progress_tx: mpsc::Sender<ConversionProgress>,
+// Smart quality scores windows this long, centred at these fractions of the source
+const QUALITY_SAMPLE_SECS: f64 = 4.0;
+const QUALITY_SAMPLE_POSITIONS: [f64; 3] = [0.25, 0.5, 0.75];
+
+// The source integrity check decodes this much from the start and from the end
+const INTEGRITY_WINDOW_SECS: f64 = 5.0;
+
+// Executables configured by the user; None means look up `ffmpeg`/`ffprobe` on PATH
+struct BinaryPaths {
+    ffmpeg: Option<PathBuf>,
+    ffprobe: Option<PathBuf>,
 }
 
-impl FFmpegConverter {
-    pub fn new(progress_tx: mpsc::Sender<ConversionProgress>) -> Self {
-        Self { progress_tx }
+static BINARY_PATHS: RwLock<BinaryPaths> = RwLock::new(BinaryPaths { ffmpeg: None, ffprobe: None });
+
+pub fn set_binary_paths(ffmpeg: Option<PathBuf>, ffprobe: Option<PathBuf>) {
+    if let Ok(mut paths) = BINARY_PATHS.write() {
+        *paths = BinaryPaths { ffmpeg, ffprobe };
+    }
+}
+
+pub fn ffmpeg_binary() -> PathBuf {
+    BINARY_PATHS.read().ok()
+        .and_then(|paths| paths.ffmpeg.clone())
+        .unwrap_or_else(|| PathBuf::from("ffmpeg"))
+}
+
+pub fn ffprobe_binary() -> PathBuf {
+    BINARY_PATHS.read().ok()
+        .and_then(|paths| paths.ffprobe.clone())
+        .unwrap_or_else(|| PathBuf::from("ffprobe"))
+}
+
+// The first line of `<binary> -version`, e.g. "ffmpeg version 6.1.1 Copyright ...";
+// None when it can't be run
+pub fn tool_version(binary: &Path) -> Option<String> {
+    let output = Command::new(binary).arg("-version").output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|line| line.trim().to_string()).filter(|line| output.status.success() && !line.is_empty())
+}
+
+// An FFmpeg release number, as far as options and filters go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FFmpegVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl FFmpegVersion {
+    // Releases that introduced what the encodes rely on
+    const SCALE_DIVISIBLE_BY: Self = Self::new(4, 3);
+    const FPS_MODE: Self = Self::new(5, 1);
+    
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
     }
     
-    pub fn check_ffmpeg_available() -> Result<bool, FFmpegError> {
-        match Command::new("ffmpeg").arg("-version").output() {
-            Ok(_) => Ok(true),
-            Err(e) => {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    Ok(false)
+    // From the first line of `ffmpeg -version`: "ffmpeg version 6.1.1-0ubuntu1",
+    // "ffmpeg version n7.0". Git snapshots ("N-112345-g...") and dated builds
+    // carry no release number and give None.
+    pub fn parse(version_line: &str) -> Option<Self> {
+        let number = version_line.split_whitespace().skip_while(|word| *word != "version").nth(1)?;
+        let number = number.strip_prefix('n').unwrap_or(number);
+        let mut parts = number.split(|c: char| !c.is_ascii_digit()).map(str::parse::<u32>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().and_then(Result::ok).unwrap_or(0);
+        // A year, from builds named after their date
+        (major < 100).then_some(Self { major, minor })
+    }
+}
+
+impl std::fmt::Display for FFmpegVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+// The command line of the latest encode started, for bug reports
+static LAST_COMMAND: Mutex<Option<Vec<OsString>>> = Mutex::new(None);
+
+pub fn last_command() -> Option<Vec<OsString>> {
+    LAST_COMMAND.lock().ok()?.clone()
+}
+
+// `ionice` exists on Linux only, and even there isn't always installed
+fn ionice_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        cfg!(target_os = "linux")
+            && Command::new("ionice")
+                .arg("--version")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+    })
+}
+
+// FFmpeg for an encode, started under ionice when a lower disk priority is asked for
+fn encoder_command(priority: IoPriority) -> tokio::process::Command {
+    match priority.ionice_args().filter(|_| ionice_available()) {
+        Some(args) => {
+            let mut command = tokio::process::Command::new("ionice");
+            command.args(args).arg(ffmpeg_binary());
+            command
+        },
+        None => tokio::process::Command::new(ffmpeg_binary()),
+    }
+}
+
+// FFREPORT value writing FFmpeg's report into `dir` under its own name (program,
+// date and time). Its option syntax needs ':' and '\' escaped, as in a Windows
+// path, and '%' doubled so the folder's name isn't expanded.
+fn report_env(dir: &Path, level: u8) -> String {
+    let dir = dir.to_string_lossy().replace('\\', "\\\\").replace(':', "\\:").replace('%', "%%");
+    format!("file={}/%p-%t.log:level={}", dir, level)
+}
+
+// A still image as packed 8-bit RGB, row by row
+#[derive(Debug, Clone)]
+pub struct RgbFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbFrame {
+    pub fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
+        let idx = ((y * self.width + x) * 3) as usize;
+        (self.pixels[idx], self.pixels[idx + 1], self.pixels[idx + 2])
+    }
+}
+
+// An embedded audio or subtitle stream; `number` counts streams of its kind
+// only (the N in -map 0:a:N or 0:s:N). `default`/`forced` are the source's flags.
+#[derive(Debug, Clone)]
+pub struct MediaTrack {
+    pub kind: TrackKind,
+    pub number: usize,
+    pub codec: String,
+    // Audio channel count; 0 for subtitles
+    pub channels: u32,
+    pub language: Option<String>,
+    pub title: Option<String>,
+    pub default: bool,
+    pub forced: bool,
+}
+
+impl MediaTrack {
+    // Sidecar format a track can be written as. ASS/SSA keep their styling; other
+    // text formats become SRT. Image-based tracks (PGS, VobSub, DVB) have no text
+    // to extract without OCR, so they give None.
+    pub fn sidecar_extension(&self) -> Option<&'static str> {
+        match self.codec.as_str() {
+            "ass" | "ssa" => Some("ass"),
+            "subrip" | "srt" | "mov_text" | "webvtt" | "text" | "microdvd" | "subviewer" | "mpl2" | "jacosub" => Some("srt"),
+            _ => None,
+        }
+    }
+    
+    // e.g. "#2 eng - Commentary (subrip)"
+    pub fn label(&self) -> String {
+        let mut label = format!("#{}", self.number + 1);
+        if let Some(language) = &self.language {
+            label.push_str(&format!(" {}", language));
+        }
+        if let Some(title) = &self.title {
+            label.push_str(&format!(" - {}", title));
+        }
+        label.push_str(&format!(" ({})", self.codec));
+        label
+    }
+}
+
+// A file embedded in the container: usually a font for styled subtitles, or cover art
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub filename: Option<String>,
+    pub mimetype: Option<String>,
+}
+
+impl Attachment {
+    pub fn is_font(&self) -> bool {
+        let font_extension = self.filename.as_deref().is_some_and(|name| {
+            let name = name.to_lowercase();
+            name.ends_with(".ttf") || name.ends_with(".otf") || name.ends_with(".ttc")
+        });
+        font_extension || self.mimetype.as_deref().is_some_and(|mime| mime.contains("font"))
+    }
+}
+
+// e.g. "3 fonts and 1 other file"
+pub fn describe_attachments(attachments: &[Attachment]) -> String {
+    let fonts = attachments.iter().filter(|attachment| attachment.is_font()).count();
+    let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+    match (fonts, attachments.len() - fonts) {
+        (fonts, 0) => plural(fonts, "font"),
+        (0, other) => plural(other, "attached file"),
+        (fonts, other) => format!("{} and {}", plural(fonts, "font"), plural(other, "other file")),
+    }
+}
+
+// The container ffprobe found in a file, whatever its extension says
+#[derive(Debug, Clone)]
+pub struct ContainerInfo {
+    // Demuxer names, e.g. "mov,mp4,m4a,3gp,3g2,mj2"
+    pub demuxer: String,
+    // e.g. "QuickTime / MOV"
+    pub long_name: String,
+    // Cover art doesn't count
+    pub has_video: bool,
+    pub has_audio: bool,
+}
+
+impl ContainerInfo {
+    // The format the contents are in, preferring `hint` (usually the one the
+    // extension names) among formats that share a demuxer
+    pub fn format(&self, hint: Option<VideoFormat>) -> Option<VideoFormat> {
+        hint.filter(|format| format.demuxer() == self.demuxer)
+            .or_else(|| VideoFormat::ALL.into_iter().find(|format| format.demuxer() == self.demuxer))
+    }
+    
+    // The format the file's extension claims, when the contents are something else
+    pub fn extension_mismatch(&self, path: &Path) -> Option<VideoFormat> {
+        let claimed = VideoFormat::from_extension(path.extension()?.to_str()?)?;
+        (claimed.demuxer() != self.demuxer).then_some(claimed)
+    }
+    
+    // e.g. "clip.avi is really QuickTime / MOV, not AVI"
+    pub fn mismatch_message(&self, path: &Path) -> Option<String> {
+        let claimed = self.extension_mismatch(path)?;
+        let name = paths::escape(path.file_name().unwrap_or_default());
+        Some(format!("{} is really {}, not {}", name, self.long_name, claimed.as_str()))
+    }
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    format: ProbeFormatEntries,
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormatEntries {
+    #[serde(default)]
+    format_name: String,
+    #[serde(default)]
+    format_long_name: String,
+}
+
+// Codec, size and frame rate of a file's first video stream
+#[derive(Debug, Clone)]
+pub struct VideoStreamInfo {
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+}
+
+#[derive(Deserialize)]
+struct ProbeStreams {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    #[serde(default)]
+    codec_name: String,
+    #[serde(default)]
+    codec_type: String,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    #[serde(default)]
+    disposition: HashMap<String, u8>,
+    #[serde(default)]
+    channels: u32,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    // A fraction such as "30000/1001"
+    #[serde(default)]
+    avg_frame_rate: String,
+}
+
+pub struct FFmpegConverter {
+    progress_tx: ProgressSender,
+    cancel: CancellationToken,
+    // Set while convert_outputs plans a shared decode: encodes are kept here
+    // instead of being run
+    planned: Option<Mutex<Vec<Encode>>>,
+}
+
+// A built FFmpeg command and what its run needs to know about the files it writes
+struct Encode {
+    cmd: tokio::process::Command,
+    outputs: Vec<EncodedOutput>,
+    // How long the output will run, which the progress is measured against
+    duration_secs: Option<f64>,
+    // The whole source's length, for the job's figures
+    source_duration_secs: Option<f64>,
+    copied_streams: Vec<String>,
+    substituted_encoders: Vec<String>,
+}
+
+// One file an encode writes, and what it gets once FFmpeg has succeeded
+struct EncodedOutput {
+    target_format: VideoFormat,
+    file: PathBuf,
+    settings: VideoSettings,
+    keep_file_times: bool,
+    write_metadata: bool,
+}
+
+impl Encode {
+    // One FFmpeg run writing every output: the input options once, as the outputs
+    // read the same source the same way, then each output's options and file.
+    // What was copied or stood in for is told apart by format.
+    fn merge(encodes: Vec<Encode>) -> Encode {
+        let args = |encode: &Encode| -> Vec<OsString> { encode.cmd.as_std().get_args().map(ToOwned::to_owned).collect() };
+        let output_start = |args: &[OsString]| args.iter().position(|arg| arg == "-y").map_or(0, |at| at + 1);
+        let first = args(&encodes[0]);
+        let mut cmd = tokio::process::Command::new(encodes[0].cmd.as_std().get_program());
+        cmd.args(&first[..output_start(&first)]).arg("-progress").arg("pipe:1");
+        
+        let mut outputs = Vec::new();
+        let mut duration_secs = None;
+        let mut source_duration_secs = None;
+        let mut copied_streams = Vec::new();
+        let mut substituted_encoders = Vec::new();
+        let formats: Vec<VideoFormat> = encodes.iter().map(|encode| encode.outputs[0].target_format).collect();
+        for encode in encodes {
+            // Each command asked for progress on its own; the merged one does it once
+            let args = args(&encode);
+            let mut options = args[output_start(&args)..].iter();
+            while let Some(arg) = options.next() {
+                if arg == "-progress" {
+                    options.next();
                 } else {
-                    Err(FFmpegError::ExecutionError(e))
+                    cmd.arg(arg);
                 }
             }
+            
+            let label = output_label(encode.outputs[0].target_format, encode.outputs[0].settings.resolution, &formats);
+            copied_streams.extend(encode.copied_streams.iter().map(|stream| format!("{}: {}", label, stream)));
+            substituted_encoders.extend(encode.substituted_encoders.iter().map(|encoder| format!("{}: {}", label, encoder)));
+            // The outputs cover the same stretch of the source, so any one's length will do
+            duration_secs = duration_secs.or(encode.duration_secs);
+            source_duration_secs = source_duration_secs.or(encode.source_duration_secs);
+            outputs.extend(encode.outputs);
         }
+        Encode { cmd, outputs, duration_secs, source_duration_secs, copied_streams, substituted_encoders }
+    }
+}
+
+// Encoders and muxers compiled into the local FFmpeg build
+#[derive(Debug, Clone, Default)]
+pub struct FFmpegCapabilities {
+    pub encoders: HashSet<String>,
+    pub muxers: HashSet<String>,
+    // None for development builds, which are assumed current
+    pub version: Option<FFmpegVersion>,
+    // Pixel formats of the encoders that can write 10-bit, as this build has them
+    pub pixel_formats: HashMap<String, Vec<String>>,
+}
+
+impl FFmpegCapabilities {
+    // Returns None when FFmpeg can't be run at all
+    pub fn detect() -> Option<Self> {
+        let encoders = Self::query(&["-encoders"])?;
+        let muxers = Self::query(&["-muxers"])?;
+        let encoders = Self::parse_encoders(&encoders);
+        
+        // libx264 and libvpx only encode 10-bit when their library was built for it
+        let pixel_formats = ["libx264", "libvpx-vp9"]
+            .into_iter()
+            .filter(|encoder| encoders.contains(*encoder))
+            .filter_map(|encoder| {
+                let help = Self::query(&["-h", &format!("encoder={}", encoder)])?;
+                let formats = help.lines().find_map(|line| line.trim().strip_prefix("Supported pixel formats:"))?;
+                Some((encoder.to_string(), formats.split_whitespace().map(String::from).collect()))
+            })
+            .collect();
+        
+        Some(Self {
+            encoders,
+            muxers: Self::parse_listing(&muxers, 'E'),
+            version: tool_version(&ffmpeg_binary()).as_deref().and_then(FFmpegVersion::parse),
+            pixel_formats,
+        })
     }
     
-    fn get_video_duration(source_file: &PathBuf) -> Result<f64, FFmpegError> {
-        // Use FFprobe to get video duration
-        let output = Command::new("ffprobe")
-            .arg("-v").arg("error")
-            .arg("-show_entries").arg("format=duration")
-            .arg("-of").arg("default=noprint_wrappers=1:nokey=1")
-            .arg(source_file)
+    fn query(args: &[&str]) -> Option<String> {
+        let output = Command::new(ffmpeg_binary())
+            .arg("-hide_banner")
+            .args(args)
             .output()
-            .map_err(FFmpegError::ExecutionError)?;
+            .ok()?;
+        
+        if !output.status.success() {
+            return None;
+        }
+        
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+    
+    // Video and audio encoders alike from `ffmpeg -encoders`
+    fn parse_encoders(output: &str) -> HashSet<String> {
+        Self::parse_listing(output, 'V').union(&Self::parse_listing(output, 'A')).cloned().collect()
+    }
+    
+    // Both listings are a legend, a "--"/"------" separator, then one
+    // "<flags> <name>[,<name>] <description>" row per entry
+    fn parse_listing(output: &str, flag: char) -> HashSet<String> {
+        output
+            .lines()
+            .skip_while(|line| !line.trim_start().starts_with("--"))
+            .skip(1)
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let flags = parts.next()?;
+                let names = parts.next()?;
+                flags.contains(flag).then_some(names)
+            })
+            .flat_map(|names| names.split(','))
+            .map(String::from)
+            .collect()
+    }
+    
+    // Everything the FFmpeg backend needs for this format that the build lacks
+    pub fn missing_for(&self, format: VideoFormat) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        
+        let muxer = FFmpegConverter::muxer(format);
+        if !self.muxers.contains(muxer) {
+            missing.push(muxer);
+        }
+        
+        // A stand-in encoder makes up for a missing one
+        let encoders = FFmpegConverter::video_encoder(format).into_iter()
+            .chain(FFmpegConverter::audio_encoder(format));
+        for encoder in encoders {
+            if FFmpegConverter::pick_encoder(&self.encoders, format, encoder).is_none() {
+                missing.push(encoder);
+            }
+        }
+        
+        missing
+    }
+    
+    // Encoders this build lacks for the format that a stand-in will replace, as (wanted, used)
+    pub fn substitutions(&self, format: VideoFormat) -> Vec<(&'static str, &'static str)> {
+        FFmpegConverter::video_encoder(format).into_iter()
+            .chain(FFmpegConverter::audio_encoder(format))
+            .filter_map(|encoder| {
+                let used = FFmpegConverter::pick_encoder(&self.encoders, format, encoder)?;
+                (used != encoder).then_some((encoder, used))
+            })
+            .collect()
+    }
+    
+    // What in an encode to `format` with these settings this FFmpeg is too old
+    // for, named as the Settings tab does, so it's caught before the encode fails
+    pub fn compatibility_warnings(&self, format: VideoFormat, settings: &VideoSettings) -> Vec<String> {
+        let mut warnings = Vec::new();
+        
+        // Audio targets skip the video options these are about
+        if let Some(version) = self.version.filter(|_| !format.is_audio()) {
+            let scales = format.fixed_frame_size(&settings.resolution).is_none() && settings.resolution.dimensions().is_some();
+            if scales && version < FFmpegVersion::SCALE_DIVISIBLE_BY {
+                warnings.push(format!(
+                    "Resolution {} needs FFmpeg {} or newer (scale's force_divisible_by); this is {}",
+                    settings.resolution.as_str(), FFmpegVersion::SCALE_DIVISIBLE_BY, version
+                ));
+            }
+            
+            let fps_mode = settings.frame_rate_mode.ffmpeg_name().is_some() && !format.is_animated_image();
+            if fps_mode && version < FFmpegVersion::FPS_MODE {
+                warnings.push(format!(
+                    "Frame Rate Mode {} needs FFmpeg {} or newer (-fps_mode); this is {}",
+                    settings.frame_rate_mode.as_str(), FFmpegVersion::FPS_MODE, version
+                ));
+            }
+        }
+        
+        let Some(encoder) = FFmpegConverter::video_encoder(format) else {
+            return warnings;
+        };
+        let ten_bit = settings.output_pixel_format(format) == Some("yuv420p10le");
+        if let Some(formats) = self.pixel_formats.get(encoder).filter(|formats| ten_bit && !formats.iter().any(|f| f == "yuv420p10le")) {
+            let library = if encoder == "libvpx-vp9" { "libvpx 1.4+ built with --enable-vp9-highbitdepth" } else { "an x264 from 2017 or later" };
+            warnings.push(format!(
+                "{} here only encodes {}, so 10-bit output will fail; it needs {}",
+                encoder, formats.join(", "), library
+            ));
+        }
+        
+        warnings
+    }
+    
+    // How to get a build that includes the given encoder or muxer
+    pub fn install_hint(component: &str) -> String {
+        let configure_flag = match component {
+            "libx264" => Some("--enable-gpl --enable-libx264"),
+            "libvpx-vp9" => Some("--enable-libvpx"),
+            "libopus" => Some("--enable-libopus"),
+            "libmp3lame" => Some("--enable-libmp3lame"),
+            "libtheora" => Some("--enable-libtheora"),
+            "libvorbis" => Some("--enable-libvorbis"),
+            _ => None,
+        };
+        
+        match configure_flag {
+            Some(flag) => format!(
+                "Install a full FFmpeg build (e.g. from ffmpeg.org) or rebuild with {}", flag
+            ),
+            None => "Install a full FFmpeg build (e.g. from ffmpeg.org or your package manager)".to_string(),
+        }
+    }
+}
+
+impl FFmpegConverter {
+    pub fn new(progress_tx: ProgressSender, cancel: CancellationToken) -> Self {
+        Self { progress_tx, cancel, planned: None }
+    }
+    
+    // Container muxer FFmpeg uses for each target format
+    pub fn muxer(format: VideoFormat) -> &'static str {
+        match format {
+            VideoFormat::MP4 => "mp4",
+            VideoFormat::MKV => "matroska",
+            VideoFormat::AVI => "avi",
+            VideoFormat::MOV => "mov",
+            VideoFormat::WEBM => "webm",
+            VideoFormat::TS => "mpegts",
+            VideoFormat::FLV => "flv",
+            VideoFormat::OGV => "ogg",
+            VideoFormat::WMV => "asf",
+            VideoFormat::ThreeGP => "3gp",
+            // Apple's MP4 flavour; FFmpeg's own "m4v" muxer writes raw MPEG-4 video
+            VideoFormat::M4V => "ipod",
+            VideoFormat::MXF => "mxf",
+            VideoFormat::WEBP => "webp",
+            VideoFormat::APNG => "apng",
+            VideoFormat::MP3 => "mp3",
+            VideoFormat::FLAC => "flac",
+            VideoFormat::WAV => "wav",
+            VideoFormat::M4A => "ipod",
+        }
+    }
+    
+    // None for the audio-only formats, which drop the video
+    pub fn video_encoder(format: VideoFormat) -> Option<&'static str> {
+        match format {
+            VideoFormat::MP4 | VideoFormat::MKV | VideoFormat::TS | VideoFormat::FLV => Some("libx264"),
+            VideoFormat::ThreeGP | VideoFormat::M4V => Some("libx264"),
+            VideoFormat::AVI => Some("mpeg4"),
+            VideoFormat::MOV => Some("prores_ks"),
+            VideoFormat::WEBM => Some("libvpx-vp9"),
+            VideoFormat::OGV => Some("libtheora"),
+            VideoFormat::WMV => Some("wmv2"),
+            VideoFormat::MXF => Some("mpeg2video"),
+            VideoFormat::WEBP => Some("libwebp_anim"),
+            VideoFormat::APNG => Some("apng"),
+            VideoFormat::MP3 | VideoFormat::FLAC | VideoFormat::WAV | VideoFormat::M4A => None,
+        }
+    }
+    
+    // Encoders to use when a build lacks the format's own, best first. Each fits the
+    // format's container; FFmpeg's built-in ones are the last resort as they're always there.
+    pub fn encoder_fallbacks(format: VideoFormat, encoder: &str) -> &'static [&'static str] {
+        match (encoder, format) {
+            // FLV only carries H.264 or Sorenson Spark
+            ("libx264", VideoFormat::FLV) => &["libopenh264", "flv"],
+            ("libx264", _) => &["libopenh264", "mpeg4"],
+            // WebM takes AV1 too, and libaom uses the same CRF options as VP9
+            ("libvpx-vp9", _) => &["libaom-av1"],
+            // Ogg carries VP8 besides Theora
+            ("libtheora", _) => &["libvpx"],
+            ("libmp3lame", _) => &["mp2", "ac3"],
+            ("libopus", _) => &["libvorbis", "opus"],
+            ("libvorbis", _) => &["libopus", "vorbis"],
+            _ => &[],
+        }
+    }
+    
+    // The encoder an encode to `format` uses for `encoder`: itself when the build has
+    // it, otherwise the first fallback it has. None when it has neither.
+    pub fn pick_encoder(available: &HashSet<String>, format: VideoFormat, encoder: &'static str) -> Option<&'static str> {
+        std::iter::once(encoder)
+            .chain(Self::encoder_fallbacks(format, encoder).iter().copied())
+            .find(|candidate| available.contains(*candidate))
+    }
+    
+    // FFmpeg's own Opus and Vorbis encoders are still marked experimental
+    fn is_experimental(encoder: &str) -> bool {
+        matches!(encoder, "opus" | "vorbis")
+    }
+    
+    // What a stand-in video encoder needs besides the format's own options: its
+    // constant-quality setting unless a bitrate is set, and for libaom a usable speed.
+    // (libaom takes WebM's CRF as it is.)
+    fn fallback_options(encoder: &str, constant_quality: bool) -> Vec<&'static str> {
+        let mut options = match encoder {
+            "libaom-av1" => vec!["-cpu-used", "6", "-row-mt", "1"],
+            _ => Vec::new(),
+        };
+        if constant_quality {
+            options.extend_from_slice(match encoder {
+                "libopenh264" => &["-rc_mode", "quality"][..],
+                "mpeg4" | "flv" => &["-q:v", "4"],
+                "libvpx" => &["-crf", "10", "-b:v", "4M"],
+                _ => &[],
+            });
+        }
+        options
+    }
+    
+    // None means the source audio is copied as-is, except for animated images
+    // which have no audio at all
+    pub fn audio_encoder(format: VideoFormat) -> Option<&'static str> {
+        match format {
+            VideoFormat::MP4 | VideoFormat::TS | VideoFormat::FLV | VideoFormat::ThreeGP | VideoFormat::M4V => Some("aac"),
+            VideoFormat::MKV | VideoFormat::WEBP | VideoFormat::APNG => None,
+            VideoFormat::AVI | VideoFormat::MP3 => Some("libmp3lame"),
+            VideoFormat::MOV | VideoFormat::WAV => Some("pcm_s16le"),
+            VideoFormat::M4A => Some("aac"),
+            VideoFormat::FLAC => Some("flac"),
+            VideoFormat::WEBM => Some("libopus"),
+            VideoFormat::OGV => Some("libvorbis"),
+            VideoFormat::WMV => Some("wmav2"),
+            VideoFormat::MXF => Some("pcm_s24le"),
+        }
+    }
+    
+    // Codec a source video stream must already be in to be copied instead of
+    // re-encoded; None where the target always encodes
+    pub fn copyable_video_codec(format: VideoFormat) -> Option<&'static str> {
+        match Self::video_encoder(format)? {
+            "libx264" => Some("h264"),
+            "libvpx-vp9" => Some("vp9"),
+            "libtheora" => Some("theora"),
+            "mpeg4" => Some("mpeg4"),
+            "wmv2" => Some("wmv2"),
+            _ => None,
+        }
+    }
+    
+    // CRFs the smart quality search may pick from, for the CRF-based encoders
+    pub fn crf_search_range(format: VideoFormat) -> Option<RangeInclusive<u8>> {
+        match Self::video_encoder(format)? {
+            "libx264" => Some(16..=32),
+            "libvpx-vp9" => Some(20..=50),
+            _ => None,
+        }
+    }
+    
+    // The same for audio. XDCAM also fixes the sample rate, so its PCM is always converted.
+    pub fn copyable_audio_codec(format: VideoFormat) -> Option<&'static str> {
+        match Self::audio_encoder(format)? {
+            "aac" => Some("aac"),
+            "libmp3lame" => Some("mp3"),
+            "libopus" => Some("opus"),
+            "libvorbis" => Some("vorbis"),
+            "pcm_s16le" => Some("pcm_s16le"),
+            "wmav2" => Some("wmav2"),
+            "flac" => Some("flac"),
+            _ => None,
+        }
+    }
+    
+    pub fn get_video_duration(source_file: &Path) -> Result<f64, FFmpegError> {
+        let output = Self::duration_command(source_file).output()?;
+        Self::parse_duration_output(output)
+    }
+    
+    // Grab the frame at `at_secs` as RGB, scaled and letterboxed to exactly width x height
+    pub fn extract_frame(source_file: &Path, at_secs: f64, width: u32, height: u32) -> Result<RgbFrame, FFmpegError> {
+        Self::extract_filtered_frame(source_file, at_secs, &[], width, height, "black")
+    }
+    
+    // Same, with `filters` applied to the frame first, to show what an output will
+    // look like. The letterbox is drawn in `pad_color` so it can be told apart
+    // from black bars in the picture itself.
+    pub fn extract_filtered_frame(
+        source_file: &Path,
+        at_secs: f64,
+        filters: &[String],
+        width: u32,
+        height: u32,
+        pad_color: &str,
+    ) -> Result<RgbFrame, FFmpegError> {
+        // Square pixels first, so an anamorphic source shows at its display shape
+        let mut chain = vec!["scale=trunc(iw*sar/2)*2:ih,setsar=1".to_string()];
+        chain.extend(filters.iter().cloned());
+        chain.push(format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2:{color}",
+            w = width,
+            h = height,
+            color = pad_color
+        ));
+        let output = Command::new(ffmpeg_binary())
+            .arg("-v").arg("error")
+            .arg("-ss").arg(format!("{:.3}", at_secs))
+            .arg("-i").arg(tool_arg(source_file))
+            .arg("-frames:v").arg("1")
+            .arg("-vf").arg(chain.join(","))
+            .arg("-f").arg("rawvideo")
+            .arg("-pix_fmt").arg("rgb24")
+            .arg("pipe:1")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        
+        if !output.status.success() {
+            return Err(FFmpegError::ProcessError(output.status.code().unwrap_or(-1)));
+        }
+        
+        // Nothing decoded (e.g. an audio-only file) leaves the output short
+        let mut pixels = output.stdout;
+        let expected = (width * height * 3) as usize;
+        if pixels.len() < expected {
+            return Err(FFmpegError::InvalidInput);
+        }
+        pixels.truncate(expected);
+        
+        Ok(RgbFrame { width, height, pixels })
+    }
+    
+    // Save the frame at `at_secs` at full size; the image type follows the output's extension
+    pub fn export_still(source_file: &Path, at_secs: f64, output_file: &Path) -> Result<(), FFmpegError> {
+        let mut command = Command::new(ffmpeg_binary());
+        command
+            .arg("-v").arg("error")
+            .arg("-ss").arg(format!("{:.3}", at_secs))
+            .arg("-i").arg(tool_arg(source_file))
+            .arg("-frames:v").arg("1")
+            .arg("-update").arg("1");
+        
+        // FFmpeg's default JPEG quality is visibly blocky
+        let jpeg = output_file.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case("jpg"));
+        if jpeg {
+            command.arg("-q:v").arg("2");
+        }
+        
+        let status = command
+            .arg("-y")
+            .arg(tool_arg(output_file))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        
+        if !status.success() {
+            return Err(FFmpegError::ProcessError(status.code().unwrap_or(-1)));
+        }
         
+        // A timestamp past the last frame "succeeds" without writing anything
+        if std::fs::metadata(output_file).map_or(true, |meta| meta.len() == 0) {
+            let _ = std::fs::remove_file(output_file);
+            return Err(FFmpegError::InvalidInput);
+        }
+        
+        Ok(())
+    }
+    
+    // Peak level (0.0-1.0) of the first audio track across `columns` equal slices
+    // of the file, read back from FFmpeg's showwavespic rendering
+    pub fn extract_waveform(source_file: &Path, columns: u32) -> Result<Vec<f32>, FFmpegError> {
+        const HEIGHT: u32 = 64;
+        
+        let output = Command::new(ffmpeg_binary())
+            .arg("-v").arg("error")
+            .arg("-i").arg(tool_arg(source_file))
+            .arg("-filter_complex").arg(format!(
+                "[0:a:0]aformat=channel_layouts=mono,showwavespic=s={}x{}:colors=white",
+                columns,
+                HEIGHT
+            ))
+            .arg("-frames:v").arg("1")
+            .arg("-f").arg("rawvideo")
+            .arg("-pix_fmt").arg("gray")
+            .arg("pipe:1")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        
+        // No audio track makes the filter graph invalid, so FFmpeg fails
+        if !output.status.success() {
+            return Err(FFmpegError::ProcessError(output.status.code().unwrap_or(-1)));
+        }
+        
+        let pixels = output.stdout;
+        if pixels.len() < (columns * HEIGHT) as usize {
+            return Err(FFmpegError::InvalidInput);
+        }
+        
+        // The wave is drawn centred, so a column's lit height is its peak
+        let peaks = (0..columns)
+            .map(|x| {
+                let lit = (0..HEIGHT).filter(|y| pixels[(y * columns + x) as usize] > 0).count();
+                lit as f32 / HEIGHT as f32
+            })
+            .collect();
+        
+        Ok(peaks)
+    }
+    
+    pub fn subtitle_tracks(source_file: &Path) -> Result<Vec<MediaTrack>, FFmpegError> {
+        Self::probe_tracks(source_file, TrackKind::Subtitle)
+    }
+    
+    pub fn audio_tracks(source_file: &Path) -> Result<Vec<MediaTrack>, FFmpegError> {
+        Self::probe_tracks(source_file, TrackKind::Audio)
+    }
+    
+    fn probe_tracks(source_file: &Path, kind: TrackKind) -> Result<Vec<MediaTrack>, FFmpegError> {
+        let output = Command::new(ffprobe_binary())
+            .arg("-v").arg("error")
+            .arg("-select_streams").arg(kind.specifier())
+            .arg("-show_entries").arg("stream=codec_name,channels:stream_tags=language,title:stream_disposition=default,forced")
+            .arg("-of").arg("json")
+            .arg(tool_arg(source_file))
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        
+        if !output.status.success() {
+            return Err(FFmpegError::ProcessError(output.status.code().unwrap_or(-1)));
+        }
+        
+        let probe: ProbeStreams = serde_json::from_slice(&output.stdout).map_err(|_| FFmpegError::InvalidInput)?;
+        let tracks = probe.streams
+            .into_iter()
+            .enumerate()
+            .map(|(number, mut stream)| MediaTrack {
+                kind,
+                number,
+                codec: stream.codec_name,
+                channels: stream.channels,
+                // "und" is what muxers write when the language was never set
+                language: stream.tags.remove("language").filter(|language| language != "und"),
+                title: stream.tags.remove("title").filter(|title| !title.is_empty()),
+                default: stream.disposition.get("default") == Some(&1),
+                forced: stream.disposition.get("forced") == Some(&1),
+            })
+            .collect();
+        
+        Ok(tracks)
+    }
+    
+    pub fn video_stream_info(source_file: &Path) -> Result<VideoStreamInfo, FFmpegError> {
+        let output = Command::new(ffprobe_binary())
+            .arg("-v").arg("error")
+            .arg("-select_streams").arg("v:0")
+            .arg("-show_entries").arg("stream=codec_name,width,height,avg_frame_rate")
+            .arg("-of").arg("json")
+            .arg(tool_arg(source_file))
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        
+        if !output.status.success() {
+            return Err(FFmpegError::ProcessError(output.status.code().unwrap_or(-1)));
+        }
+        
+        let probe: ProbeStreams = serde_json::from_slice(&output.stdout).map_err(|_| FFmpegError::InvalidInput)?;
+        let stream = probe.streams.into_iter().next().ok_or(FFmpegError::InvalidInput)?;
+        let frame_rate = match stream.avg_frame_rate.split_once('/') {
+            Some((num, den)) => match (num.parse::<f64>(), den.parse::<f64>()) {
+                (Ok(num), Ok(den)) if den > 0.0 => num / den,
+                _ => 0.0,
+            },
+            None => stream.avg_frame_rate.parse().unwrap_or(0.0),
+        };
+        
+        Ok(VideoStreamInfo { codec: stream.codec_name, width: stream.width, height: stream.height, frame_rate })
+    }
+    
+    // Which demuxer reads the file, found from its contents rather than its name
+    pub fn probe_container(source_file: &Path) -> Result<ContainerInfo, FFmpegError> {
+        let output = Command::new(ffprobe_binary())
+            .arg("-v").arg("error")
+            .arg("-show_entries").arg("format=format_name,format_long_name:stream=codec_type:stream_disposition=attached_pic")
+            .arg("-of").arg("json")
+            .arg(tool_arg(source_file))
+            .stdin(Stdio::null())
+            .output()?;
+        
+        // ffprobe's complaint, e.g. "clip.txt: Invalid data found when processing input"
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or_default();
+            let message = message.rsplit_once(": ").map_or(message, |(_, reason)| reason).trim();
+            return Err(match message {
+                "" => FFmpegError::ProcessError(output.status.code().unwrap_or(-1)),
+                message => FFmpegError::Unreadable(message.to_string()),
+            });
+        }
+        
+        let probe: ProbeFormat = serde_json::from_slice(&output.stdout).map_err(|_| FFmpegError::InvalidInput)?;
+        if probe.format.format_name.is_empty() {
+            return Err(FFmpegError::InvalidInput);
+        }
+        let has_video = probe.streams.iter()
+            .any(|stream| stream.codec_type == "video" && stream.disposition.get("attached_pic") != Some(&1));
+        let has_audio = probe.streams.iter().any(|stream| stream.codec_type == "audio");
+        Ok(ContainerInfo { demuxer: probe.format.format_name, long_name: probe.format.format_long_name, has_video, has_audio })
+    }
+    
+    // Why a probed file can't be converted to `target`; None when it can, or when the
+    // probe failed for a reason that says nothing about the file (ffprobe missing, say).
+    // Audio targets only need an audio stream.
+    pub fn unplayable_reason(probe: &Result<ContainerInfo, FFmpegError>, target: VideoFormat) -> Option<String> {
+        match probe {
+            Ok(container) if target.is_audio() && !container.has_audio => Some(format!("{} with no audio stream", container.long_name)),
+            Ok(container) if target.is_audio() => None,
+            Ok(container) if !container.has_video && container.has_audio => Some(format!(
+                "{} with no video stream; choose an audio format to convert it", container.long_name
+            )),
+            Ok(container) if !container.has_video => Some(format!("{} with no video stream", container.long_name)),
+            Err(FFmpegError::Unreadable(message)) => Some(message.clone()),
+            _ => None,
+        }
+    }
+    
+    pub fn attachments(source_file: &Path) -> Result<Vec<Attachment>, FFmpegError> {
+        let output = Command::new(ffprobe_binary())
+            .arg("-v").arg("error")
+            .arg("-select_streams").arg("t")
+            .arg("-show_entries").arg("stream_tags=filename,mimetype")
+            .arg("-of").arg("json")
+            .arg(tool_arg(source_file))
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        
+        if !output.status.success() {
+            return Err(FFmpegError::ProcessError(output.status.code().unwrap_or(-1)));
+        }
+        
+        let probe: ProbeStreams = serde_json::from_slice(&output.stdout).map_err(|_| FFmpegError::InvalidInput)?;
+        let attachments = probe.streams
+            .into_iter()
+            .map(|mut stream| Attachment {
+                filename: stream.tags.remove("filename"),
+                mimetype: stream.tags.remove("mimetype"),
+            })
+            .collect();
+        
+        Ok(attachments)
+    }
+    
+    // Where to start reading the source, how much of it to convert, and so how long
+    // the output will run, which the progress is measured against. That's the trim's
+    // window, or for a test encode a sample's length from the middle of it.
+    fn input_window(trim: &Trim, source_duration: Option<f64>, sample: bool) -> (Option<f64>, Option<f64>, Option<f64>) {
+        let (start, length) = trim.window(source_duration);
+        if sample {
+            let offset = length.map_or(0.0, |length| ((length - SAMPLE_DURATION_SECS) / 2.0).max(0.0));
+            let output = length.map_or(SAMPLE_DURATION_SECS, |length| length.min(SAMPLE_DURATION_SECS));
+            (Some(start + offset), Some(SAMPLE_DURATION_SECS), Some(output))
+        } else {
+            (trim.start.filter(|start| *start > 0.0), length.filter(|_| trim.end.is_some()), length)
+        }
+    }
+    
+    // Run one of the blocking ffprobe queries without stalling the async runtime;
+    // a failed probe counts as nothing found
+    async fn probe_blocking<T: Default + Send + 'static>(
+        source_file: &Path,
+        query: fn(&Path) -> Result<T, FFmpegError>,
+    ) -> T {
+        let source_file = source_file.to_path_buf();
+        tokio::task::spawn_blocking(move || query(&source_file))
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .unwrap_or_default()
+    }
+    
+    // Write one subtitle track to a sidecar file; its extension picks SRT or ASS
+    pub fn extract_subtitle(source_file: &Path, track: &MediaTrack, output_file: &Path) -> Result<(), FFmpegError> {
+        let codec = match output_file.extension().and_then(|ext| ext.to_str()) {
+            Some("ass") => "ass",
+            _ => "srt",
+        };
+        
+        let status = Command::new(ffmpeg_binary())
+            .arg("-v").arg("error")
+            .arg("-y")
+            .arg("-i").arg(tool_arg(source_file))
+            .arg("-map").arg(format!("0:s:{}", track.number))
+            .arg("-c:s").arg(codec)
+            .arg(tool_arg(output_file))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        
+        match status.code() {
+            Some(0) => Ok(()),
+            Some(code) => {
+                let _ = std::fs::remove_file(output_file);
+                Err(FFmpegError::ProcessError(code))
+            },
+            None => Err(FFmpegError::ProcessTerminated),
+        }
+    }
+    
+    // Same as get_video_duration, without blocking a runtime worker thread
+    async fn probe_duration(source_file: &Path) -> Result<f64, FFmpegError> {
+        let output = tokio::process::Command::from(Self::duration_command(source_file)).output().await?;
+        Self::parse_duration_output(output)
+    }
+    
+    fn duration_command(source_file: &Path) -> Command {
+        // Use FFprobe to get video duration
+        let mut cmd = Command::new(ffprobe_binary());
+        cmd.arg("-v").arg("error")
+           .arg("-show_entries").arg("format=duration")
+           .arg("-of").arg("default=noprint_wrappers=1:nokey=1")
+           .arg(tool_arg(source_file));
+        cmd
+    }
+    
+    fn parse_duration_output(output: Output) -> Result<f64, FFmpegError> {
         if !output.status.success() {
             return Err(FFmpegError::ProcessError(output.status.code().unwrap_or(-1)));
         }
@@ -66,247 +1077,1275 @@ impl FFmpegConverter {
         duration_str.parse::<f64>().map_err(|_| FFmpegError::InvalidInput)
     }
     
-    pub fn convert(&self, source_file: PathBuf, target_format: VideoFormat, output_file: PathBuf) -> Result<(), FFmpegError> {
+    // Cropping, scaling, plus the frame rate and palette handling animated images
+    // need. Keep in step with the native backend's filter graph.
+    pub fn video_filters(format: VideoFormat, settings: &VideoSettings) -> Vec<String> {
+        let mut filters = Vec::new();
+        
+        if format.is_animated_image() {
+            let fps = settings.frame_rate.value().unwrap_or(ANIMATION_DEFAULT_FPS);
+            filters.push(format!("fps={}", fps));
+        }
+        
+        filters.extend(Self::picture_filters(format, settings));
+        
+        // Build the palette from the clip itself so the reduced colours still fit it
+        if let (VideoFormat::APNG, Some(colors)) = (format, settings.image_quality.palette_colors()) {
+            filters.push(format!("split[a][b];[a]palettegen=max_colors={}[p];[b][p]paletteuse", colors));
+        }
+        
+        filters
+    }
+    
+    // The part of the chain that decides the framing: crop, then scale (and pad
+    // for formats with a fixed frame size)
+    pub fn picture_filters(format: VideoFormat, settings: &VideoSettings) -> Vec<String> {
+        let mut filters: Vec<String> = settings.crop.filter().into_iter().collect();
+        
+        if let Some((width, height)) = format.fixed_frame_size(&settings.resolution) {
+            filters.push(format!(
+                "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1",
+                w = width,
+                h = height
+            ));
+        } else if let Some((width, height)) = settings.resolution.dimensions() {
+            filters.push(format!(
+                "scale={}:{}:force_original_aspect_ratio=decrease:force_divisible_by=2",
+                width, height
+            ));
+        }
+        
+        filters
+    }
+    
+    // Whether a job's outputs can all be written by one FFmpeg run that decodes the
+    // source once. A subtitle file kept as a track, or an audio file, is a second
+    // input that each output would take differently, so then they're converted one at a time.
+    pub fn shares_decode(settings: &VideoSettings) -> bool {
+        settings.subtitles.as_ref().is_none_or(|subs| subs.burn_in) && settings.external_audio.is_none()
+    }
+    
+    // Several outputs from one decode of the source: each output's command is
+    // planned as convert would build it, then they run as one. Planning stops at
+    // the first output convert would refuse, which has reported why.
+    pub async fn convert_outputs(&self, source_file: PathBuf, outputs: Vec<JobOutput>, sample: bool) -> Result<(), FFmpegError> {
+        let planner = Self { progress_tx: self.progress_tx.clone(), cancel: self.cancel.clone(), planned: Some(Mutex::default()) };
+        for (index, output) in outputs.into_iter().enumerate() {
+            // The source only needs checking for damage once
+            let settings = VideoSettings { check_integrity: output.settings.check_integrity && index == 0, ..output.settings };
+            planner.convert(source_file.clone(), output.format, output.file, settings, sample).await?;
+            let planned = planner.planned.as_ref().map_or(0, |planned| planned.lock().unwrap_or_else(|e| e.into_inner()).len());
+            if planned <= index {
+                return Ok(());
+            }
+        }
+        
+        let encodes = planner.planned.map(|planned| planned.into_inner().unwrap_or_else(|e| e.into_inner())).unwrap_or_default();
+        self.run_ffmpeg(&source_file, Encode::merge(encodes)).await;
+        Ok(())
+    }
+    
+    pub async fn convert(
+        &self,
+        source_file: PathBuf,
+        target_format: VideoFormat,
+        output_file: PathBuf,
+        settings: VideoSettings,
+        sample: bool,
+    ) -> Result<(), FFmpegError> {
         // Verify source file exists
-        if !source_file.exists() {
+        if !is_url(&source_file) && !source_file.exists() {
             return Err(FFmpegError::InvalidInput);
         }
         
-        // Start conversion in a separate thread
-        let progress_tx = self.progress_tx.clone();
+        let reporter = ProgressReporter::new(self.progress_tx.clone(), &source_file, target_format, &output_file);
+        
+        // Send initial progress
+        reporter.report(ProgressEvent::Stage { percent: 0, step: "Starting FFmpeg conversion...".to_string() }).await;
+        
+        // First, get video duration
+        let duration_seconds = Self::probe_duration(&source_file).await;
+        
+        // Send analyzing progress
+        reporter.report(ProgressEvent::Stage {
+            percent: 0,
+            step: format!("Analyzing video file... Duration: {} seconds", 
+                duration_seconds.as_ref().copied().unwrap_or(0.0)),
+        }).await;
+        
+        // Fail now rather than partway through on a file that isn't a video FFmpeg can read
+        let container = if is_url(&source_file) {
+            None
+        } else {
+            Self::probe_blocking(&source_file, |path| Ok(Some(Self::probe_container(path)))).await
+        };
+        if let Some(reason) = container.as_ref().and_then(|probe| Self::unplayable_reason(probe, target_format)) {
+            let message = format!("Not a playable video: {}", reason);
+            reporter.report(ProgressEvent::Failed { step: message.clone(), error: message }).await;
+            return Ok(());
+        }
+        
+        // FFmpeg reads the file by its contents anyway; say so when the name is misleading
+        if let Some(Ok(container)) = &container {
+            if let Some(message) = container.mismatch_message(&source_file) {
+                reporter.report(ProgressEvent::Stage { percent: 0, step: format!("{}; reading it as such", message) }).await;
+            }
+        }
+        
+        // A truncated or damaged source should fail here, not hours into the encode
+        if settings.check_integrity && !sample && !is_url(&source_file) {
+            reporter.set_stage(ConversionStage::SourceCheck);
+            reporter.report(ProgressEvent::Stage { percent: 0, step: "Checking the source for damage...".to_string() }).await;
+            let checked = tokio::select! {
+                checked = Self::check_integrity(&source_file, duration_seconds.as_ref().ok().copied()) => Some(checked),
+                _ = self.cancel.cancelled() => None,
+            };
+            match checked {
+                None => {
+                    reporter.report(ProgressEvent::Cancelled).await;
+                    return Ok(());
+                },
+                Some(Err(reason)) => {
+                    let message = format!("The source looks damaged: {}", reason);
+                    reporter.report(ProgressEvent::Failed { step: message.clone(), error: message }).await;
+                    return Ok(());
+                },
+                Some(Ok(())) => {},
+            }
+        }
         
-        thread::spawn(move || {
-            // Send initial progress
-            Self::send_progress(
-                &progress_tx,
-                0,
-                "Starting FFmpeg conversion...".to_string(),
-                &source_file,
+        // A remux copies every stream as it is, so none of the other settings apply
+        if settings.remux {
+            let copied_streams = Self::probe_blocking(&source_file, |path| Self::video_stream_info(path).map(Some))
+                .await
+                .map(|info| format!("video ({})", info.codec))
+                .into_iter()
+                .chain(std::iter::once("every other stream".to_string()))
+                .collect();
+            // Copied streams can only be cut at keyframes, so a trim lands on the nearest ones
+            let (seek, limit, duration_secs) = Self::input_window(&settings.trim, duration_seconds.as_ref().ok().copied(), false);
+            if !settings.trim.is_off() {
+                reporter.report(ProgressEvent::Stage { percent: 0, step: "A remux cuts the trim at the nearest keyframes".to_string() }).await;
+            }
+            let mut cmd = encoder_command(settings.io_priority);
+            if let Some(start) = seek {
+                cmd.arg("-ss").arg(format!("{:.3}", start));
+            }
+            cmd.arg("-i")
+               .arg(tool_arg(&source_file))
+               .arg("-y")
+               .arg("-map").arg("0")
+               .arg("-c").arg("copy");
+            if let Some(length) = limit {
+                cmd.arg("-t").arg(format!("{:.3}", length));
+            }
+            cmd.arg("-progress").arg("pipe:1")
+               .arg(tool_arg(&output_file));
+            let output = EncodedOutput {
                 target_format,
-                &output_file,
-                false,
-                false,
+                file: output_file,
+                keep_file_times: settings.keep_file_times && !is_url(&source_file),
+                write_metadata: settings.write_metadata,
+                settings,
+            };
+            let source_duration_secs = duration_seconds.as_ref().ok().copied();
+            self.encode(&source_file, Encode { cmd, outputs: vec![output], duration_secs, source_duration_secs, copied_streams, substituted_encoders: Vec::new() }).await;
+            return Ok(());
+        }
+        
+        // Stand in for encoders this build lacks rather than failing the encode.
+        // If the listing can't be read, the format's own encoders are tried.
+        let available = Self::available_encoders().await;
+        let resolve = |encoder: &'static str| match &available {
+            Some(available) => Self::pick_encoder(available, target_format, encoder).unwrap_or(encoder),
+            None => encoder,
+        };
+        
+        // Audio targets drop the picture, so none of the video settings apply
+        let Some(wanted_video) = Self::video_encoder(target_format) else {
+            let duration = duration_seconds.as_ref().ok().copied();
+            self.convert_audio(&reporter, &source_file, target_format, &output_file, &settings, sample, duration, resolve).await;
+            return Ok(());
+        };
+        let video_encoder = resolve(wanted_video);
+        let audio_encoder = Self::audio_encoder(target_format).map(resolve);
+        let video_substituted = video_encoder != wanted_video;
+        
+        if settings.pixel_format == PixelFormat::Yuv420p10 && !target_format.supports_10bit() {
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("{} is 8-bit only; encoding yuv420p instead of 10-bit", target_format.as_str()),
+            }).await;
+        }
+        
+        // Don't encode a stream above the chosen H.264 level; players that honour it would reject it
+        let x264 = video_encoder == "libx264";
+        if x264 && settings.h264_level != H264Level::Auto {
+            let check = Self::probe_blocking(&source_file, |path| Self::video_stream_info(path).map(Some))
+                .await
+                .map(|info| settings.check_h264_level(info.width, info.height, info.frame_rate));
+            if let Some(Err(message)) = check {
+                reporter.report(ProgressEvent::Failed { step: message.clone(), error: message }).await;
+                return Ok(());
+            }
+        }
+        
+        // External subtitles go in as their own track where the container allows it
+        let subtitles = settings.subtitles.as_ref();
+        let styled = subtitles.is_some_and(|subs| subs.path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ass")));
+        let soft_codec = subtitles
+            .filter(|subs| !subs.burn_in)
+            .and_then(|_| target_format.text_subtitle_codec(styled));
+        if subtitles.is_some_and(|subs| !subs.burn_in) && soft_codec.is_none() {
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("{} can't carry subtitle tracks; burning them in instead", target_format.as_str()),
+            }).await;
+        }
+        
+        // An audio file given alongside takes the place of the source's audio
+        let external_audio = settings.external_audio.as_ref().filter(|_| !target_format.is_animated_image());
+        let external_tracks = match external_audio {
+            Some(audio) => Self::probe_blocking(&audio.path, Self::audio_tracks).await,
+            None => Vec::new(),
+        };
+        if let Some(audio) = external_audio.filter(|_| external_tracks.is_empty()) {
+            let message = format!("{} has no audio track to use", paths::escape(audio.path.file_name().unwrap_or_default()));
+            reporter.report(ProgressEvent::Failed { step: message.clone(), error: message }).await;
+            return Ok(());
+        }
+        
+        // Chosen audio/subtitle tracks, limited to what the target container can hold
+        let mut tracks = settings.tracks.clone();
+        if target_format.is_animated_image() {
+            tracks.clear();
+        } else if external_audio.is_some() && tracks.iter().any(|track| track.kind == TrackKind::Audio) {
+            tracks.retain(|track| track.kind != TrackKind::Audio);
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: "The added audio file takes the place of the chosen audio tracks".to_string(),
+            }).await;
+        } else if tracks.iter().any(|track| track.kind == TrackKind::Subtitle) && target_format != VideoFormat::MKV {
+            // Outside Matroska subtitles are converted to text, so image-based tracks can't go along
+            let text_tracks: Vec<usize> = Self::probe_blocking(&source_file, Self::subtitle_tracks)
+                .await
+                .iter()
+                .filter(|track| track.sidecar_extension().is_some())
+                .map(|track| track.number)
+                .collect();
+            let carries_subtitles = target_format.text_subtitle_codec(false).is_some();
+            let before = tracks.len();
+            tracks.retain(|track| {
+                track.kind == TrackKind::Audio || (carries_subtitles && text_tracks.contains(&track.number))
+            });
+            if tracks.len() < before {
+                reporter.report(ProgressEvent::Stage {
+                    percent: 0,
+                    step: format!("Dropped {} subtitle track(s) {} can't carry", before - tracks.len(), target_format.as_str()),
+                }).await;
+            }
+        }
+        
+        // Fonts and cover art only survive in Matroska; elsewhere say they're being dropped
+        let attachments = if target_format.is_animated_image() {
+            Vec::new()
+        } else {
+            Self::probe_blocking(&source_file, Self::attachments).await
+        };
+        let keep_attachments = !attachments.is_empty() && target_format == VideoFormat::MKV;
+        if !attachments.is_empty() && !keep_attachments {
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("{} can't carry attachments; dropping {}", target_format.as_str(), describe_attachments(&attachments)),
+            }).await;
+        }
+        
+        // Settled as the job starts, so a batch follows the power cable
+        let eco = settings.eco_mode.engaged();
+        
+        // Smart quality replaces the format's fixed CRF with one found by sample encodes
+        let searched_crf = match settings.quality_target.vmaf() {
+            Some(_) if video_substituted => {
+                reporter.report(ProgressEvent::Stage {
+                    percent: 0,
+                    step: format!("Smart quality tunes {}, which this FFmpeg lacks; using {}'s usual quality", wanted_video, video_encoder),
+                }).await;
                 None
-            );
-            
-            // First, get video duration
-            let duration_seconds = Self::get_video_duration(&source_file);
-            
-            // Send analyzing progress
-            Self::send_progress(
-                &progress_tx,
-                0,
-                format!("Analyzing video file... Duration: {} seconds", 
-                    duration_seconds.unwrap_or_else(|_| 0.0)),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
+            },
+            Some(_) if settings.bitrate.value_kbps(&settings.resolution) > 0 => {
+                reporter.report(ProgressEvent::Stage { percent: 0, step: "A target bitrate is set, so smart quality is skipped".to_string() }).await;
                 None
-            );
-            
-            // Build FFmpeg command with appropriate options based on format
-            let mut cmd = Command::new("ffmpeg");
-            
-            // Add input file
-            cmd.arg("-i")
-               .arg(&source_file)
-               .arg("-y"); // Overwrite output files without asking
-               
-            // Add format-specific options
-            match target_format {
-                VideoFormat::MP4 => {
-                    // H.264 video with AAC audio - good compatibility
-                    cmd.arg("-c:v").arg("libx264")
-                       .arg("-preset").arg("medium")
-                       .arg("-crf").arg("23")
-                       .arg("-c:a").arg("aac")
-                       .arg("-b:a").arg("128k");
-                },
-                VideoFormat::MKV => {
-                    // H.264 video with high quality
-                    cmd.arg("-c:v").arg("libx264")
-                       .arg("-preset").arg("slow")
-                       .arg("-crf").arg("18")
-                       .arg("-c:a").arg("copy");
-                },
-                VideoFormat::AVI => {
-                    // MPEG-4 video for compatibility
-                    cmd.arg("-c:v").arg("mpeg4")
-                       .arg("-q:v").arg("6")
-                       .arg("-c:a").arg("libmp3lame")
-                       .arg("-q:a").arg("4");
-                },
-                VideoFormat::MOV => {
-                    // ProRes for high quality
-                    cmd.arg("-c:v").arg("prores_ks")
-                       .arg("-profile:v").arg("3")
-                       .arg("-c:a").arg("pcm_s16le");
-                },
-                VideoFormat::WEBM => {
-                    // VP9 video with Opus audio - good for web
-                    cmd.arg("-c:v").arg("libvpx-vp9")
-                       .arg("-crf").arg("30")
-                       .arg("-b:v").arg("0")
-                       .arg("-c:a").arg("libopus")
-                       .arg("-b:a").arg("96k");
-                },
+            },
+            Some(target) => {
+                reporter.set_stage(ConversionStage::QualitySearch);
+                let duration = duration_seconds.as_ref().copied().unwrap_or(0.0);
+                self.search_crf(&source_file, target_format, &output_file, &settings, duration, target, eco).await
+            },
+            None => None,
+        };
+        if self.cancel.is_cancelled() {
+            reporter.report(ProgressEvent::Cancelled).await;
+            return Ok(());
+        }
+        let crf = |default: u8| searched_crf.unwrap_or(default).to_string();
+        
+        // Streams already in the target's codecs are copied rather than re-encoded,
+        // as long as no setting asks for a different picture
+        let burns_in = subtitles.is_some() && soft_codec.is_none();
+        let source_video = match Self::copyable_video_codec(target_format) {
+            Some(_) if settings.keeps_picture(target_format) && !burns_in => {
+                Self::probe_blocking(&source_file, |path| Self::video_stream_info(path).map(Some)).await
+            },
+            _ => None,
+        };
+        let copy_video = source_video
+            .as_ref()
+            .is_some_and(|info| Some(info.codec.as_str()) == Self::copyable_video_codec(target_format));
+        if eco && !copy_video {
+            reporter.report(ProgressEvent::Stage { percent: 0, step: power::eco_notice() }).await;
+        }
+        
+        // Output audio is the chosen tracks in order, or else the main track
+        let source_audio = if target_format.is_animated_image() {
+            Vec::new()
+        } else {
+            Self::probe_blocking(&source_file, Self::audio_tracks).await
+        };
+        // Mixing needs something to mix with; a silent source just takes the file's audio
+        let mixes = external_audio.is_some_and(|audio| audio.mix) && !source_audio.is_empty();
+        if let Some(audio) = external_audio {
+            let name = paths::escape(audio.path.file_name().unwrap_or_default());
+            let step = if mixes {
+                format!("Mixing {} over the source's audio", name)
+            } else if audio.mix {
+                format!("The source has no audio to mix with; using {} as its audio", name)
+            } else {
+                format!("Replacing the source's audio with {}", name)
+            };
+            reporter.report(ProgressEvent::Stage { percent: 0, step }).await;
+        }
+        let output_audio: Vec<&MediaTrack> = if external_audio.is_some() {
+            external_tracks.first().into_iter().collect()
+        } else if tracks.is_empty() {
+            source_audio.first().into_iter().collect()
+        } else {
+            tracks
+                .iter()
+                .filter(|track| track.kind == TrackKind::Audio)
+                .filter_map(|track| source_audio.get(track.number))
+                .collect()
+        };
+        // Matroska copies every audio track as it is, unless it's being downmixed
+        let downmix_filters: Vec<Option<&str>> = output_audio
+            .iter()
+            .map(|track| settings.downmix.audio_filter(track.channels))
+            .collect();
+        let copy_audio: Vec<bool> = output_audio
+            .iter()
+            .zip(&downmix_filters)
+            .map(|(track, downmix)| {
+                // The added audio is padded or mixed, which means encoding it
+                external_audio.is_none()
+                    && downmix.is_none()
+                    && (target_format == VideoFormat::MKV
+                        || Some(track.codec.as_str()) == Self::copyable_audio_codec(target_format)
+                        || settings.passes_through(target_format, &track.codec))
+            })
+            .collect();
+        if settings.audio_passthrough {
+            let transcoded: Vec<String> = output_audio
+                .iter()
+                .zip(&copy_audio)
+                .zip(&downmix_filters)
+                .filter(|((track, copy), downmix)| !**copy && downmix.is_none() && SURROUND_CODECS.contains(&track.codec.as_str()))
+                .map(|((track, _), _)| format!("audio #{} ({})", track.number + 1, track.codec))
+                .collect();
+            if !transcoded.is_empty() {
+                reporter.report(ProgressEvent::Stage {
+                    percent: 0,
+                    step: format!("{} can't carry {} untouched; transcoding it", target_format.as_str(), transcoded.join(", ")),
+                }).await;
+            }
+        }
+        
+        // Explicit maps are only needed for chosen tracks, an external subtitle file, attachments,
+        // or a main audio track that's copied or downmixed, which has to be the one probed.
+        // Matroska copies audio anyway, so there FFmpeg otherwise keeps choosing the main track.
+        let soft_subtitles = subtitles.zip(soft_codec);
+        let picks_main_audio = tracks.is_empty()
+            && (downmix_filters.first().is_some_and(Option::is_some)
+                || (target_format != VideoFormat::MKV && copy_audio.first() == Some(&true)));
+        let explicit_maps = !tracks.is_empty() || soft_subtitles.is_some() || external_audio.is_some() || keep_attachments || picks_main_audio;
+        
+        let keep_file_times = settings.keep_file_times && !sample && !is_url(&source_file);
+        let write_metadata = settings.write_metadata && !sample;
+        let mut copied_streams = Vec::new();
+        if let Some(info) = source_video.as_ref().filter(|_| copy_video) {
+            copied_streams.push(format!("video ({})", info.codec));
+        }
+        if !explicit_maps && source_audio.len() > 1 && copy_audio.first() == Some(&true) {
+            copied_streams.push("audio".to_string());
+        } else {
+            for (track, _) in output_audio.iter().zip(&copy_audio).filter(|(_, copy)| **copy) {
+                copied_streams.push(format!("audio #{} ({})", track.number + 1, track.codec));
+            }
+        }
+        if !copied_streams.is_empty() {
+            reporter.report(ProgressEvent::Stage { percent: 0, step: format!("Copying {} without re-encoding", copied_streams.join(", ")) }).await;
+        }
+        
+        // Only streams that are actually encoded need the stand-ins
+        let encodes_audio = copy_audio.iter().any(|copy| !copy);
+        let substitutions: Vec<(&str, &str)> = std::iter::once((wanted_video, video_encoder)).filter(|_| !copy_video)
+            .chain(Self::audio_encoder(target_format).zip(audio_encoder).filter(|_| encodes_audio))
+            .filter(|(wanted, used)| wanted != used)
+            .collect();
+        for (wanted, used) in &substitutions {
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("This FFmpeg has no {}; encoding with {} instead", wanted, used),
+            }).await;
+        }
+        let substituted_encoders = substitutions.iter().map(|(wanted, used)| format!("{} for {}", used, wanted)).collect();
+        
+        if settings.io_priority != IoPriority::Normal && !ionice_available() {
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: "I/O priority needs ionice (Linux); converting at normal priority".to_string(),
+            }).await;
+        }
+        
+        // Build FFmpeg command with appropriate options based on format
+        let mut cmd = encoder_command(settings.io_priority);
+        
+        // Seek to the trim's in point, or for a test encode to the sample window
+        let (seek, limit, output_duration) = Self::input_window(&settings.trim, duration_seconds.as_ref().ok().copied(), sample);
+        if let Some(start) = seek {
+            cmd.arg("-ss").arg(format!("{:.3}", start));
+        }
+        
+        // Add input file
+        cmd.arg("-i")
+           .arg(tool_arg(&source_file))
+           .arg("-y"); // Overwrite output files without asking
+        
+        if let Some((subs, _)) = soft_subtitles {
+            // Seek the subtitles along with the video so a trim or sample stays in sync
+            if let Some(start) = seek {
+                cmd.arg("-ss").arg(format!("{:.3}", start));
+            }
+            cmd.arg("-i").arg(tool_arg(&subs.path));
+        }
+        
+        // The audio file comes after them, seeked the same way
+        let audio_input = 1 + usize::from(soft_subtitles.is_some());
+        if let Some(audio) = external_audio {
+            if let Some(start) = seek {
+                cmd.arg("-ss").arg(format!("{:.3}", start));
+            }
+            cmd.arg("-i").arg(tool_arg(&audio.path));
+        }
+        
+        // Fonts are there for the styled subtitles, so keeping them keeps every subtitle track too
+        let all_subtitles = tracks.is_empty() && keep_attachments;
+        if explicit_maps {
+            cmd.arg("-map").arg("0:v:0");
+            if mixes {
+                cmd.arg("-map").arg("[mixed]");
+            } else if external_audio.is_some() {
+                cmd.arg("-map").arg(format!("{}:a:0", audio_input));
+            } else if tracks.is_empty() {
+                cmd.arg("-map").arg("0:a:0?");
+            }
+            if all_subtitles {
+                cmd.arg("-map").arg("0:s?");
+            }
+            for track in &tracks {
+                cmd.arg("-map").arg(format!("0:{}:{}", track.kind.specifier(), track.number));
+            }
+            if soft_subtitles.is_some() {
+                cmd.arg("-map").arg("1:0");
+            }
+            if keep_attachments {
+                cmd.arg("-map").arg("0:t").arg("-c:t").arg("copy");
             }
             
-            // Add progress reporting
-            cmd.arg("-progress")
-               .arg("pipe:1") // Output progress information to stdout
-               .arg(&output_file);
-            
-            // Configure stdio
-            cmd.stdout(Stdio::piped())
-               .stderr(Stdio::piped());
+            // Matroska keeps embedded subtitles as they are, other containers need their text codec
+            let kept_subtitles = if all_subtitles && soft_subtitles.is_some() {
+                Self::probe_blocking(&source_file, Self::subtitle_tracks).await.len()
+            } else {
+                tracks.iter().filter(|track| track.kind == TrackKind::Subtitle).count()
+            };
+            let subtitle_codec = match soft_subtitles {
+                _ if (kept_subtitles > 0 || all_subtitles) && target_format == VideoFormat::MKV => Some("copy"),
+                _ if kept_subtitles > 0 => target_format.text_subtitle_codec(false),
+                Some((_, codec)) => Some(codec),
+                None => None,
+            };
+            if let Some(codec) = subtitle_codec {
+                cmd.arg("-c:s").arg(codec);
+            }
             
-            // Execute command
-            match cmd.spawn() {
-                Ok(mut child) => {
-                    // Get stdout for progress tracking
-                    let stdout = child.stdout.take().unwrap();
-                    let reader = BufReader::new(stdout);
-                    
-                    // Track progress
-                    let mut duration_ms: f64 = 0.0;
-                    let mut time_ms: f64 = 0.0;
-                    
-                    // Parse FFmpeg progress output
-                    for line in reader.lines() {
-                        if let Ok(line) = line {
-                            // Parse progress information
-                            if line.starts_with("out_time_ms=") {
-                                if let Ok(time) = line[12..].parse::<f64>() {
-                                    time_ms = time;
-                                    
-                                    // Calculate progress percentage if we have duration
-                                    if duration_ms > 0.0 {
-                                        let percent = ((time_ms / duration_ms) * 100.0).min(100.0) as u8;
-                                        
-                                        Self::send_progress(
-                                            &progress_tx,
-                                            percent,
-                                            format!("Converting video... {}%", percent),
-                                            &source_file,
-                                            target_format,
-                                            &output_file,
-                                            false,
-                                            false,
-                                            None
-                                        );
-                                    }
-                                }
-                            } else if line.starts_with("duration=") {
-                                if let Ok(time) = line[9..].parse::<f64>() {
-                                    duration_ms = time * 1000.0;
-                                }
-                            } else if line == "progress=end" {
-                                // Conversion complete
-                                Self::send_progress(
-                                    &progress_tx,
-                                    100,
-                                    "Conversion complete!".to_string(),
-                                    &source_file,
-                                    target_format,
-                                    &output_file,
-                                    true,
-                                    false,
-                                    None
-                                );
-                                break;
+            // Disposition flags are indexed by the track's position within its kind in the output
+            let mut audio_index = 0;
+            let mut subtitle_index = 0;
+            for track in &tracks {
+                let index = match track.kind {
+                    TrackKind::Audio => &mut audio_index,
+                    TrackKind::Subtitle => &mut subtitle_index,
+                };
+                cmd.arg(format!("-disposition:{}:{}", track.kind.specifier(), index))
+                   .arg(track.disposition());
+                *index += 1;
+            }
+            if let Some(language) = soft_subtitles.and_then(|(subs, _)| subs.language()) {
+                cmd.arg(format!("-metadata:s:s:{}", kept_subtitles)).arg(format!("language={}", language));
+            }
+        }
+        
+        if let Some(length) = limit {
+            cmd.arg("-t").arg(format!("{:.3}", length));
+        }
+        
+        // An explicit bitrate replaces the format's default constant-quality mode
+        let target_kbps = settings.bitrate.value_kbps(&settings.resolution);
+        
+        // Add codecs, then format-specific options
+        cmd.arg("-c:v").arg(if copy_video { "copy" } else { video_encoder });
+        if eco && !copy_video {
+            cmd.arg("-threads").arg(power::eco_threads().to_string());
+        }
+        if target_format.is_animated_image() {
+            cmd.arg("-an");
+        } else if let Some(encoder) = audio_encoder {
+            cmd.arg("-c:a").arg(encoder);
+            if Self::is_experimental(encoder) {
+                cmd.arg("-strict").arg("experimental");
+            }
+            for (index, _) in copy_audio.iter().enumerate().filter(|(_, copy)| **copy) {
+                cmd.arg(format!("-c:a:{}", index)).arg("copy");
+            }
+        } else {
+            // Matroska otherwise copies audio, so downmixed tracks need an encoder of their own
+            cmd.arg("-c:a").arg("copy");
+            for (index, _) in copy_audio.iter().enumerate().filter(|(_, copy)| !**copy) {
+                cmd.arg(format!("-c:a:{}", index)).arg("aac")
+                   .arg(format!("-b:a:{}", index)).arg("192k");
+            }
+        }
+        if let Some(downmix) = external_audio.and(downmix_filters.first().copied()) {
+            // Mixed audio lasts as long as the source's; a replacement is padded with
+            // silence and cut where the video ends
+            let downmix = downmix.map(|filter| format!(",{}", filter)).unwrap_or_default();
+            if mixes {
+                cmd.arg("-filter_complex").arg(format!(
+                    "[0:a:0][{}:a:0]amix=inputs=2:duration=first:dropout_transition=0{}[mixed]",
+                    audio_input, downmix
+                ));
+            } else {
+                cmd.arg("-filter:a:0").arg(format!("apad{}", downmix))
+                   .arg("-shortest");
+            }
+        } else {
+            for (index, filter) in downmix_filters.iter().enumerate() {
+                if let Some(filter) = filter {
+                    cmd.arg(format!("-filter:a:{}", index)).arg(filter);
+                }
+            }
+        }
+        
+        // The x264 options only apply when it's libx264 doing the encoding
+        let encodes_x264 = x264 && !copy_video;
+        let preset = |usual: &'static str| if eco { ECO_X264_PRESET } else { usual };
+        match target_format {
+            VideoFormat::MP4 | VideoFormat::TS | VideoFormat::FLV | VideoFormat::M4V => {
+                // H.264 video with AAC audio - good compatibility
+                if encodes_x264 {
+                    cmd.arg("-preset").arg(preset("medium"));
+                }
+                if target_kbps == 0 && encodes_x264 {
+                    cmd.arg("-crf").arg(crf(23));
+                }
+                cmd.arg("-b:a").arg("128k");
+            },
+            VideoFormat::MKV => {
+                // H.264 video with high quality, original audio
+                if encodes_x264 {
+                    cmd.arg("-preset").arg(preset("slow"));
+                }
+                if target_kbps == 0 && encodes_x264 {
+                    cmd.arg("-crf").arg(crf(18));
+                }
+            },
+            VideoFormat::AVI => {
+                // MPEG-4 video with MP3 audio for compatibility
+                if target_kbps == 0 && !copy_video {
+                    cmd.arg("-q:v").arg("6");
+                }
+                cmd.arg("-q:a").arg("4");
+            },
+            VideoFormat::MOV => {
+                // ProRes with uncompressed audio for high quality
+                cmd.arg("-profile:v").arg("3");
+            },
+            VideoFormat::WEBM => {
+                // VP9 video with Opus audio - good for web
+                if target_kbps == 0 && !copy_video {
+                    cmd.arg("-crf").arg(crf(30))
+                       .arg("-b:v").arg("0");
+                }
+                if eco && !copy_video && video_encoder == "libvpx-vp9" {
+                    cmd.arg("-cpu-used").arg(ECO_VP9_CPU_USED);
+                }
+                cmd.arg("-b:a").arg("96k");
+            },
+            VideoFormat::OGV => {
+                // Theora video with Vorbis audio, both on quality scales
+                if target_kbps == 0 && !copy_video {
+                    cmd.arg("-q:v").arg("7");
+                }
+                cmd.arg("-q:a").arg("5");
+            },
+            VideoFormat::WMV => {
+                // WMV2 video with WMA audio for Windows Media Player
+                if target_kbps == 0 && !copy_video {
+                    cmd.arg("-q:v").arg("5");
+                }
+                cmd.arg("-b:a").arg("160k");
+            },
+            VideoFormat::ThreeGP => {
+                // Low-rate H.264 and AAC, which phones can decode (baseline 3.0 below)
+                if encodes_x264 {
+                    cmd.arg("-preset").arg(preset("medium"));
+                }
+                if target_kbps == 0 && encodes_x264 {
+                    cmd.arg("-crf").arg(crf(26));
+                }
+                cmd.arg("-b:a").arg("64k");
+            },
+            VideoFormat::MXF => {
+                // XDCAM HD422: 4:2:2 MPEG-2 at a constant 50 Mbit/s with 24-bit 48 kHz PCM
+                cmd.arg("-pix_fmt").arg("yuv422p")
+                   .arg("-b:v").arg("50M")
+                   .arg("-minrate").arg("50M")
+                   .arg("-maxrate").arg("50M")
+                   .arg("-bufsize").arg("17825792")
+                   .arg("-g").arg("12")
+                   .arg("-bf").arg("2")
+                   .arg("-intra_vlc").arg("1")
+                   .arg("-non_linear_quant").arg("1")
+                   .arg("-dc").arg("10")
+                   .arg("-ar").arg("48000");
+            },
+            VideoFormat::WEBP => {
+                // Lossy or lossless WebP frames, looping forever like a GIF
+                let quality = settings.image_quality;
+                if quality == ImageQuality::Lossless {
+                    cmd.arg("-lossless").arg("1");
+                } else {
+                    cmd.arg("-quality").arg(quality.webp_quality().to_string());
+                }
+                cmd.arg("-compression_level").arg("4")
+                   .arg("-loop").arg("0");
+            },
+            VideoFormat::APNG => {
+                // Lossless PNG frames; the palette filter below trades colours for size
+                let pixel_format = match settings.image_quality {
+                    ImageQuality::Lossless => "rgba",
+                    ImageQuality::High => "rgb24",
+                    _ => "pal8",
+                };
+                cmd.arg("-pix_fmt").arg(pixel_format)
+                   .arg("-pred").arg("mixed")
+                   .arg("-plays").arg("0");
+            },
+            // Extracted by convert_audio before getting here
+            VideoFormat::MP3 | VideoFormat::FLAC | VideoFormat::WAV | VideoFormat::M4A => {},
+        }
+        
+        // A stand-in doesn't take the format's CRF and preset, so it gets its own settings
+        if video_substituted && !copy_video {
+            cmd.args(Self::fallback_options(video_encoder, target_kbps == 0));
+        }
+        
+        // Of the stand-ins only libaom encodes 10-bit
+        let pixel_format = settings.output_pixel_format(target_format)
+            .map(|format| if format == "yuv420p10le" && video_substituted && video_encoder != "libaom-av1" { "yuv420p" } else { format });
+        if let Some(pixel_format) = pixel_format {
+            cmd.arg("-pix_fmt").arg(pixel_format);
+        }
+        
+        // H.264 profile, level and tuning; 3GP falls back to what phones decode
+        if x264 {
+            if let Some(profile) = settings.h264_profile_name(target_format) {
+                cmd.arg("-profile:v").arg(profile);
+            }
+            if let Some(level) = settings.h264_level_name(target_format) {
+                cmd.arg("-level").arg(level);
+            }
+            if let Some(tune) = settings.tune.ffmpeg_name() {
+                cmd.arg("-tune").arg(tune);
+            }
+        }
+        
+        // Keyframe spacing needs the output frame rate, so probe the source's if it's kept
+        if settings.keyframe_interval != KeyframeInterval::Auto || !settings.scene_cut {
+            let source_fps = match settings.frame_rate.value() {
+                Some(_) => None,
+                None => Self::probe_blocking(&source_file, |path| Self::video_stream_info(path).map(Some))
+                    .await
+                    .map(|info| info.frame_rate),
+            };
+            for (name, value) in settings.keyframe_options(target_format, source_fps) {
+                cmd.arg(format!("-{}", name)).arg(value);
+            }
+        }
+        
+        // Add advanced video settings
+        // (ProRes, XDCAM and animated images are profile- or quality-based, so a target bitrate doesn't apply)
+        if target_kbps > 0 && !target_format.has_fixed_bitrate() {
+            cmd.arg("-b:v").arg(format!("{}k", target_kbps));
+        }
+        let mut filters = Vec::new();
+        if let Some(subs) = subtitles.filter(|_| soft_codec.is_none()) {
+            filters.push(burn_in_filter(&subs.path, seek.unwrap_or(0.0)));
+        }
+        filters.extend(Self::video_filters(target_format, &settings));
+        if !filters.is_empty() {
+            cmd.arg("-vf").arg(filters.join(","));
+        }
+        if let Some(fps) = settings.frame_rate.value().filter(|_| !target_format.is_animated_image()) {
+            cmd.arg("-r").arg(fps.to_string());
+        }
+        
+        // Animated images already run through the fps filter, so their timing is fixed
+        if let Some(mode) = settings.frame_rate_mode.ffmpeg_name().filter(|_| !target_format.is_animated_image()) {
+            // Constant frame rate at the source's average rate unless one was chosen
+            if settings.frame_rate_mode == FrameRateMode::Constant && settings.frame_rate.value().is_none() {
+                let source_fps = Self::probe_blocking(&source_file, |path| Self::video_stream_info(path).map(Some))
+                    .await
+                    .map(|info| info.frame_rate)
+                    .filter(|fps| *fps > 0.0);
+                if let Some(fps) = source_fps {
+                    cmd.arg("-r").arg(format!("{:.3}", fps));
+                }
+            }
+            cmd.arg("-fps_mode").arg(mode);
+        }
+        
+        // Add progress reporting
+        cmd.arg("-progress")
+           .arg("pipe:1") // Output progress information to stdout
+           .arg(tool_arg(&output_file));
+        
+        let output = EncodedOutput { target_format, file: output_file, settings, keep_file_times, write_metadata };
+        let encode = Encode {
+            cmd,
+            outputs: vec![output],
+            duration_secs: output_duration,
+            source_duration_secs: duration_seconds.as_ref().ok().copied(),
+            copied_streams,
+            substituted_encoders,
+        };
+        self.encode(&source_file, encode).await;
+        
+        Ok(())
+    }
+    
+    // Extract one audio track: the first chosen one, or else the main track. It's copied
+    // when it's already in the target's codec and isn't being downmixed.
+    #[allow(clippy::too_many_arguments)]
+    async fn convert_audio(
+        &self,
+        reporter: &ProgressReporter,
+        source_file: &Path,
+        target_format: VideoFormat,
+        output_file: &Path,
+        settings: &VideoSettings,
+        sample: bool,
+        duration_seconds: Option<f64>,
+        resolve: impl Fn(&'static str) -> &'static str,
+    ) {
+        let number = settings.tracks.iter()
+            .find(|track| track.kind == TrackKind::Audio)
+            .map_or(0, |track| track.number);
+        let source_audio = Self::probe_blocking(source_file, Self::audio_tracks).await;
+        let track = source_audio.get(number);
+        let downmix = track.and_then(|track| settings.downmix.audio_filter(track.channels));
+        let copy = downmix.is_none() && track.is_some_and(|track| Some(track.codec.as_str()) == Self::copyable_audio_codec(target_format));
+        
+        let mut copied_streams = Vec::new();
+        if let Some(track) = track.filter(|_| copy) {
+            copied_streams.push(format!("audio #{} ({})", number + 1, track.codec));
+            reporter.report(ProgressEvent::Stage { percent: 0, step: format!("Copying {} without re-encoding", copied_streams[0]) }).await;
+        }
+        let wanted = Self::audio_encoder(target_format).filter(|_| !copy);
+        let encoder = wanted.map(&resolve);
+        let mut substituted_encoders = Vec::new();
+        if let Some((wanted, used)) = wanted.zip(encoder).filter(|(wanted, used)| wanted != used) {
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("This FFmpeg has no {}; encoding with {} instead", wanted, used),
+            }).await;
+            substituted_encoders.push(format!("{} for {}", used, wanted));
+        }
+        
+        let mut cmd = encoder_command(settings.io_priority);
+        let (seek, limit, output_duration) = Self::input_window(&settings.trim, duration_seconds, sample);
+        if let Some(start) = seek {
+            cmd.arg("-ss").arg(format!("{:.3}", start));
+        }
+        cmd.arg("-i")
+           .arg(tool_arg(source_file))
+           .arg("-y")
+           .arg("-map").arg(format!("0:a:{}", number))
+           .arg("-c:a").arg(encoder.unwrap_or("copy"));
+        if let Some(length) = limit {
+            cmd.arg("-t").arg(format!("{:.3}", length));
+        }
+        if let Some(filter) = downmix {
+            cmd.arg("-filter:a").arg(filter);
+        }
+        // The lossy formats at a bitrate that's transparent for most music
+        if encoder.is_some() && matches!(target_format, VideoFormat::MP3 | VideoFormat::M4A) {
+            cmd.arg("-b:a").arg("192k");
+        }
+        cmd.arg("-progress")
+           .arg("pipe:1")
+           .arg(tool_arg(output_file));
+        
+        let keep_file_times = settings.keep_file_times && !sample && !is_url(source_file);
+        let write_metadata = settings.write_metadata && !sample;
+        let output = EncodedOutput {
+            target_format,
+            file: output_file.to_path_buf(),
+            settings: settings.clone(),
+            keep_file_times,
+            write_metadata,
+        };
+        let encode = Encode {
+            cmd,
+            outputs: vec![output],
+            duration_secs: output_duration,
+            source_duration_secs: duration_seconds,
+            copied_streams,
+            substituted_encoders,
+        };
+        self.encode(source_file, encode).await;
+    }
+    
+    // Run an encode, or keep it while convert_outputs plans a shared decode
+    async fn encode(&self, source_file: &Path, encode: Encode) {
+        match &self.planned {
+            Some(planned) => planned.lock().unwrap_or_else(|e| e.into_inner()).push(encode),
+            None => self.run_ffmpeg(source_file, encode).await,
+        }
+    }
+    
+    // Run a built FFmpeg command to the end, turning its progress output into
+    // updates; each output gets its file times and metadata sidecar once it succeeds.
+    // FFmpeg's progress only says how far into the output it is, so the percentage
+    // comes from the output's expected duration, probed beforehand.
+    async fn run_ffmpeg(&self, source_file: &Path, encode: Encode) {
+        let Encode { mut cmd, outputs, duration_secs, source_duration_secs, copied_streams, substituted_encoders } = encode;
+        let main = &outputs[0];
+        let settings = &main.settings;
+        let reporter = ProgressReporter::new(self.progress_tx.clone(), source_file, main.target_format, &main.file);
+        reporter.set_stage(ConversionStage::Encode);
+        
+        // A debug report for this job, when its log level asks for one
+        if let (Some(level), Some(dir)) = (settings.log_level.report_level(), settings.log_dir.as_deref()) {
+            let step = match std::fs::create_dir_all(dir) {
+                Ok(()) => {
+                    cmd.env("FFREPORT", report_env(dir, level));
+                    format!("Writing a {} FFmpeg report to {}", settings.log_level.as_str().to_lowercase(), dir.display())
+                },
+                Err(e) => format!("No FFmpeg report: couldn't create {}: {}", dir.display(), e),
+            };
+            reporter.report(ProgressEvent::Stage { percent: 0, step }).await;
+        }
+        
+        // Configure stdio; stderr is never read, so don't let it fill a pipe and stall FFmpeg
+        cmd.stdout(Stdio::piped())
+           .stderr(Stdio::null())
+           .kill_on_drop(true);
+        
+        // Kept for the metadata sidecar
+        let command_line: Vec<OsString> = std::iter::once(cmd.as_std().get_program())
+            .chain(cmd.as_std().get_args())
+            .map(ToOwned::to_owned)
+            .collect();
+        if let Ok(mut last) = LAST_COMMAND.lock() {
+            *last = Some(command_line.clone());
+        }
+        
+        // Execute command
+        match cmd.spawn() {
+            Ok(mut child) => {
+                // Get stdout for progress tracking
+                let stdout = child.stdout.take().unwrap();
+                let mut lines = BufReader::new(stdout).lines();
+                
+                // Track progress
+                let duration_us = duration_secs.filter(|secs| *secs > 0.0).map(|secs| secs * 1_000_000.0);
+                let mut time_us = None;
+                let mut stats = EncodeStats::default();
+                let mut finished = false;
+                
+                // Parse FFmpeg progress output
+                loop {
+                    let line = tokio::select! {
+                        _ = self.cancel.cancelled() => {
+                            // Stop FFmpeg and remove the partial outputs
+                            let _ = child.kill().await;
+                            for output in &outputs {
+                                let _ = std::fs::remove_file(&output.file);
                             }
+                            reporter.report(ProgressEvent::Cancelled).await;
+                            return;
+                        },
+                        line = lines.next_line() => match line {
+                            Ok(Some(line)) => line,
+                            _ => break,
+                        },
+                    };
+                    
+                    // Parse progress information; each block of keys ends in
+                    // progress=continue, and is sent then with all its figures
+                    if stats.update(&line) {
+                        continue;
+                    }
+                    // Despite its name, out_time_ms is in microseconds too; it's
+                    // read when a build doesn't print out_time_us
+                    if let Some(value) = line.strip_prefix("out_time_us=").or_else(|| line.strip_prefix("out_time_ms=")) {
+                        if let Ok(value) = value.parse::<f64>() {
+                            time_us = Some(value);
                         }
+                    } else if line == "progress=continue" {
+                        // Without a duration there's no percentage, but the figures still show
+                        let percent = match (time_us, duration_us) {
+                            (Some(time_us), Some(duration_us)) => Some(((time_us / duration_us) * 100.0).clamp(0.0, 100.0) as u8),
+                            _ => None,
+                        };
+                        if percent.is_some() || stats != EncodeStats::default() {
+                            reporter.report(ProgressEvent::Frame {
+                                percent: percent.unwrap_or(0),
+                                step: match percent {
+                                    Some(percent) => format!("Converting video... {}%", percent),
+                                    None => "Converting video... (length unknown)".to_string(),
+                                },
+                                stats,
+                            }).await;
+                        }
+                    } else if line == "progress=end" {
+                        // FFmpeg still has to close the output, so completion waits for it to exit
+                        finished = true;
+                        break;
                     }
-                    
-                    // Wait for process to complete
-                    match child.wait() {
-                        Ok(status) => {
-                            if !status.success() {
-                                if let Some(code) = status.code() {
-                                    Self::send_progress(
-                                        &progress_tx,
-                                        0,
-                                        format!("FFmpeg failed with exit code: {}", code),
-                                        &source_file,
-                                        target_format,
-                                        &output_file,
-                                        true,
-                                        true,
-                                        Some(format!("FFmpeg process failed with status: {}", code))
-                                    );
-                                } else {
-                                    Self::send_progress(
-                                        &progress_tx,
-                                        0,
-                                        "FFmpeg process terminated by signal".to_string(),
-                                        &source_file,
-                                        target_format,
-                                        &output_file,
-                                        true,
-                                        true,
-                                        Some("FFmpeg process terminated by signal".to_string())
-                                    );
+                }
+                
+                // Wait for process to complete
+                match child.wait().await {
+                    Ok(status) => {
+                        if status.success() && finished {
+                            for output in &outputs {
+                                if output.keep_file_times {
+                                    if let Err(e) = crate::converter::copy_file_times(source_file, &output.file) {
+                                        reporter.report(ProgressEvent::Stage {
+                                            percent: 100,
+                                            step: format!("Couldn't copy the source's file times: {}", e),
+                                        }).await;
+                                    }
+                                }
+                                if output.write_metadata {
+                                    let written = metadata::write_sidecar(source_file, &output.file, output.target_format, &output.settings, Some(&command_line));
+                                    if let Err(e) = written {
+                                        reporter.report(ProgressEvent::Stage {
+                                            percent: 100,
+                                            step: format!("Couldn't write the metadata sidecar: {}", e),
+                                        }).await;
+                                    }
                                 }
                             }
-                        },
-                        Err(e) => {
-                            Self::send_progress(
-                                &progress_tx,
-                                0,
-                                format!("Error waiting for FFmpeg: {}", e),
-                                &source_file,
-                                target_format,
-                                &output_file,
-                                true,
-                                true,
-                                Some(format!("Error waiting for FFmpeg: {}", e))
-                            );
+                            
+                            // Conversion complete, with the copied streams and lengths for the job report
+                            reporter.report(ProgressEvent::Completed {
+                                step: "Conversion complete!".to_string(),
+                                copied_streams,
+                                substituted_encoders,
+                                source_duration_secs,
+                                encoded_secs: duration_secs,
+                            }).await;
+                        } else if status.success() {
+                            // Exiting cleanly before saying it was done may leave an output cut short
+                            reporter.report(ProgressEvent::Failed {
+                                step: "FFmpeg exited before finishing the output".to_string(),
+                                error: "FFmpeg exited without reporting the end of the encode".to_string(),
+                            }).await;
+                        } else if let Some(code) = status.code() {
+                            reporter.report(ProgressEvent::Failed {
+                                step: format!("FFmpeg failed with exit code: {}", code),
+                                error: format!("FFmpeg process failed with status: {}", code),
+                            }).await;
+                        } else {
+                            reporter.report(ProgressEvent::Failed {
+                                step: "FFmpeg process terminated by signal".to_string(),
+                                error: "FFmpeg process terminated by signal".to_string(),
+                            }).await;
                         }
+                    },
+                    Err(e) => {
+                        reporter.report(ProgressEvent::Failed {
+                            step: format!("Error waiting for FFmpeg: {}", e),
+                            error: format!("Error waiting for FFmpeg: {}", e),
+                        }).await;
                     }
-                },
-                Err(e) => {
-                    Self::send_progress(
-                        &progress_tx,
-                        0,
-                        format!("Failed to start FFmpeg: {}", e),
-                        &source_file,
-                        target_format,
-                        &output_file,
-                        true,
-                        true,
-                        Some(format!("Failed to start FFmpeg: {}", e))
-                    );
                 }
+            },
+            Err(e) => {
+                reporter.report(ProgressEvent::Failed {
+                    step: format!("Failed to start FFmpeg: {}", e),
+                    error: format!("Failed to start FFmpeg: {}", e),
+                }).await;
             }
-        });
+        }
+    }
+    
+    // Binary search for the highest CRF whose sample encodes all still reach the target
+    // VMAF, scoring a few short windows spread through the source. Falls back to the
+    // best CRF in range if none does; None leaves the format's own CRF.
+    #[allow(clippy::too_many_arguments)]
+    async fn search_crf(
+        &self,
+        source_file: &Path,
+        target_format: VideoFormat,
+        output_file: &Path,
+        settings: &VideoSettings,
+        duration: f64,
+        target: f64,
+        eco: bool,
+    ) -> Option<u8> {
+        let range = Self::crf_search_range(target_format)?;
+        let reporter = ProgressReporter::new(self.progress_tx.clone(), source_file, target_format, output_file);
+        if !Self::has_filter("libvmaf").await {
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: "Smart quality needs an FFmpeg build with libvmaf; using the default quality".to_string(),
+            }).await;
+            return None;
+        }
+        
+        let windows: Vec<f64> = if duration > QUALITY_SAMPLE_SECS * 2.0 {
+            QUALITY_SAMPLE_POSITIONS
+                .iter()
+                .map(|position| (duration * position - QUALITY_SAMPLE_SECS / 2.0).max(0.0))
+                .collect()
+        } else {
+            vec![0.0]
+        };
+        let sample_file = std::env::temp_dir().join(format!("rust-tui-video-convert-quality-{}.mkv", std::process::id()));
+        
+        let (mut low, mut high) = (*range.start(), *range.end());
+        let mut best = None;
+        while low <= high {
+            let crf = (low + high) / 2;
+            let mut lowest_score = f64::INFINITY;
+            for start in &windows {
+                if self.cancel.is_cancelled() {
+                    let _ = std::fs::remove_file(&sample_file);
+                    return None;
+                }
+                let Some(score) = Self::sample_vmaf(source_file, target_format, settings, crf, *start, &sample_file, eco).await else {
+                    let _ = std::fs::remove_file(&sample_file);
+                    return None;
+                };
+                lowest_score = lowest_score.min(score);
+            }
+            
+            reporter.report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("Quality search: CRF {} scores VMAF {:.1} (target {:.0})", crf, lowest_score, target),
+            }).await;
+            if lowest_score >= target {
+                best = Some(crf);
+                low = crf + 1;
+            } else if crf == 0 {
+                break;
+            } else {
+                high = crf - 1;
+            }
+        }
+        let _ = std::fs::remove_file(&sample_file);
         
+        let crf = best.unwrap_or(*range.start());
+        reporter.report(ProgressEvent::Stage { percent: 0, step: format!("Smart quality: encoding at CRF {}", crf) }).await;
+        Some(crf)
+    }
+    
+    // Decode the first and last few seconds of the source, where truncated downloads
+    // and broken copies show up; the first error FFmpeg reports, if any
+    async fn check_integrity(source_file: &Path, duration: Option<f64>) -> Result<(), String> {
+        let mut starts = vec![0.0];
+        if let Some(duration) = duration.filter(|duration| *duration > INTEGRITY_WINDOW_SECS * 2.0) {
+            starts.push(duration - INTEGRITY_WINDOW_SECS);
+        }
+        
+        for start in starts {
+            let output = tokio::process::Command::new(ffmpeg_binary())
+                .arg("-v").arg("error")
+                .arg("-ss").arg(format!("{:.3}", start))
+                .arg("-t").arg(format!("{:.3}", INTEGRITY_WINDOW_SECS))
+                .arg("-i").arg(tool_arg(source_file))
+                .arg("-f").arg("null")
+                .arg("-")
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| e.to_string())?;
+            
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if let Some(error) = stderr.lines().map(str::trim).find(|line| !line.is_empty()) {
+                return Err(error.to_string());
+            }
+            if !output.status.success() {
+                return Err(format!("FFmpeg exited with status {}", output.status.code().unwrap_or(-1)));
+            }
+        }
         Ok(())
     }
     
-    fn send_progress(
-        tx: &mpsc::Sender<ConversionProgress>,
-        percent: u8,
-        step: String,
-        source_file: &PathBuf,
+    // Encode one window of the source at `crf` and score it against the original
+    async fn sample_vmaf(
+        source_file: &Path,
         target_format: VideoFormat,
-        output_file: &PathBuf,
-        is_complete: bool,
-        has_error: bool,
-        error_message: Option<String>,
-    ) {
-        let _ = tx.send(ConversionProgress {
-            percent,
-            current_step: step,
-            source_file: source_file.clone(),
-            target_format,
-            output_file: output_file.clone(),
-            is_complete,
-            has_error,
-            error_message,
-            video_settings: None,
-        });
+        settings: &VideoSettings,
+        crf: u8,
+        start: f64,
+        sample_file: &Path,
+        eco: bool,
+    ) -> Option<f64> {
+        let encoder = Self::video_encoder(target_format)?;
+        let mut encode = encoder_command(settings.io_priority);
+        encode.arg("-v").arg("error")
+              .arg("-ss").arg(format!("{:.3}", start))
+              .arg("-t").arg(format!("{:.3}", QUALITY_SAMPLE_SECS))
+              .arg("-i").arg(tool_arg(source_file))
+              .arg("-map").arg("0:v:0")
+              .arg("-c:v").arg(encoder)
+              .arg("-crf").arg(crf.to_string());
+        if encoder == "libvpx-vp9" {
+            encode.arg("-b:v").arg("0");
+        }
+        // Sampled the way the full encode will run, so the CRF found suits it
+        if eco {
+            encode.arg("-threads").arg(power::eco_threads().to_string());
+            match encoder {
+                "libx264" => encode.arg("-preset").arg(ECO_X264_PRESET),
+                "libvpx-vp9" => encode.arg("-cpu-used").arg(ECO_VP9_CPU_USED),
+                _ => &mut encode,
+            };
+        }
+        let filters = Self::video_filters(target_format, settings);
+        if !filters.is_empty() {
+            encode.arg("-vf").arg(filters.join(","));
+        }
+        let encoded = encode
+            .arg("-y")
+            .arg(tool_arg(sample_file))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .ok()?;
+        if !encoded.success() {
+            return None;
+        }
+        
+        // libvmaf wants both at the same size, so the reference is scaled to the sample's
+        let output = tokio::process::Command::new(ffmpeg_binary())
+            .arg("-hide_banner")
+            .arg("-i").arg(tool_arg(sample_file))
+            .arg("-ss").arg(format!("{:.3}", start))
+            .arg("-t").arg(format!("{:.3}", QUALITY_SAMPLE_SECS))
+            .arg("-i").arg(tool_arg(source_file))
+            .arg("-lavfi").arg(
+                "[0:v]setpts=PTS-STARTPTS[dist0];[1:v]setpts=PTS-STARTPTS[ref0];\
+                 [ref0][dist0]scale2ref=flags=bicubic[ref][dist];[dist][ref]libvmaf"
+            )
+            .arg("-f").arg("null")
+            .arg("-")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .output()
+            .await
+            .ok()?;
+        
+        // e.g. "[libvmaf @ 0x...] VMAF score: 95.123456"
+        String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .find_map(|line| line.split("VMAF score:").nth(1))
+            .and_then(|score| score.trim().parse().ok())
+    }
+    
+    // The encoders the FFmpeg build includes; None if it couldn't list them
+    async fn available_encoders() -> Option<HashSet<String>> {
+        let output = tokio::process::Command::new(ffmpeg_binary())
+            .arg("-hide_banner")
+            .arg("-encoders")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .await
+            .ok()
+            .filter(|output| output.status.success())?;
+        Some(FFmpegCapabilities::parse_encoders(&String::from_utf8_lossy(&output.stdout)))
     }
+    
+    // Whether the FFmpeg build includes a filter, e.g. libvmaf
+    async fn has_filter(name: &str) -> bool {
+        let output = tokio::process::Command::new(ffmpeg_binary())
+            .arg("-hide_banner")
+            .arg("-filters")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .await;
+        
+        // One "<flags> <name> <in>-><out> <description>" row per filter
+        output.is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|line| line.split_whitespace().nth(1) == Some(name))
+        })
+    }
+}
+
+// Render a subtitle file into the picture. Input seeking for a sample restarts
+// timestamps at zero, so shift them back while the subtitles are drawn.
+pub fn burn_in_filter(path: &Path, offset_secs: f64) -> String {
+    let subtitles = format!("subtitles=filename={}", escape_filter_value(&path.to_string_lossy()));
+    if offset_secs > 0.0 {
+        format!("setpts=PTS+{:.3}/TB,{},setpts=PTS-STARTPTS", offset_secs, subtitles)
+    } else {
+        subtitles
+    }
+}
+
+// A filter option value needs escaping twice: once for the option parser and
+// once more for the filter graph parser
+fn escape_filter_value(value: &str) -> String {
+    let escape = |input: &str, special: &[char]| {
+        input.chars().fold(String::new(), |mut out, c| {
+            if special.contains(&c) {
+                out.push('\\');
+            }
+            out.push(c);
+            out
+        })
+    };
+    let option = escape(value, &['\\', '\'', ':']);
+    escape(&option, &['\\', '\'', '[', ']', ',', ';'])
 }
\ No newline at end of file
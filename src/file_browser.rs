@@ -1,63 +1,409 @@
+use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use crate::converter::VideoFormat;
+use crate::disc::{self, DISC_EXTENSIONS};
+use crate::notifier::Notifier;
+use crate::paths;
+
+// Sidecar subtitle files, listed so they can be picked to go into a conversion
+pub const SUBTITLE_EXTENSIONS: [&str; 3] = ["srt", "ass", "vtt"];
+
+pub fn is_subtitle_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SUBTITLE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+// A listed file that converts, or a DVD folder (which converts as a whole)
+pub fn is_convertible(path: &Path) -> bool {
+    (path.is_file() && !is_subtitle_file(path)) || disc::is_video_ts(path)
+}
+
+// How long the listing thread gathers entries before handing them over, so a
+// slow network share fills in as it's read without a redraw per file
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+// Folders whose listings are kept, so going back up with ".." is instant
+const LISTING_CACHE_SIZE: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Folder,
+    // A DVD's VIDEO_TS folder
+    Disc,
+    File,
+}
+
+// A listed entry with what the listing thread read about it, so drawing the list
+// never has to go to the disk
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    // The name as the list shows it, after an icon for its kind
+    pub label: String,
+    // For videos, their size in bytes, counted in the folder's summary
+    size: Option<u64>,
+}
+
+impl Entry {
+    // ".." for going up a level
+    fn parent(path: &Path) -> Self {
+        Self { path: path.to_path_buf(), kind: EntryKind::Folder, label: "..".to_string(), size: None }
+    }
+    
+    pub fn is_dir(&self) -> bool {
+        self.kind != EntryKind::File
+    }
+    
+    // As is_convertible tells, but from what the listing read
+    pub fn is_convertible(&self) -> bool {
+        match self.kind {
+            EntryKind::Folder => false,
+            EntryKind::Disc => true,
+            EntryKind::File => !is_subtitle_file(&self.path),
+        }
+    }
+}
+
+// Entries read by the listing thread
+type Batch = Vec<Entry>;
+
+enum ListingUpdate {
+    Entries(Batch),
+    // Read to the end; the folder's modification time when reading started
+    Complete(Option<SystemTime>),
+    // The folder hasn't changed since it was cached
+    Unchanged,
+}
+
+// A folder being read in the background
+struct Listing {
+    updates: mpsc::Receiver<ListingUpdate>,
+    read: Batch,
+    // Re-reading a folder already on screen replaces the list once done,
+    // rather than emptying it and filling it up again
+    replace_when_done: bool,
+}
+
+struct CachedListing {
+    modified: Option<SystemTime>,
+    entries: Batch,
+    used: Instant,
+}
 
 pub struct FileBrowser {
     current_dir: PathBuf,
-    files: Vec<PathBuf>,
+    // Everything listed in the directory, and the part the type-ahead filter lets through
+    all_files: Vec<Entry>,
+    files: Vec<Entry>,
     selected_idx: usize,
+    // Text the list is narrowed by while the filter box is open
+    query: Option<String>,
     filter: Vec<String>,
+    // Videos listed in the current directory and their combined size
+    video_count: usize,
+    video_bytes: u64,
+    listing: Option<Listing>,
+    cache: HashMap<PathBuf, CachedListing>,
+    notify: Notifier,
 }
 
 impl FileBrowser {
     pub fn new(starting_dir: PathBuf) -> Self {
+        Self::with_filter(starting_dir, Self::listed_extensions(&[]), Notifier::default())
+    }
+    
+    // A browser that lists only folders, starting where this one is, for picking
+    // where outputs go; it wakes the same notifier
+    pub fn folder_picker(&self) -> Self {
+        Self::with_filter(self.current_dir.clone(), Vec::new(), self.notify.clone())
+    }
+    
+    fn with_filter(starting_dir: PathBuf, filter: Vec<String>, notify: Notifier) -> Self {
         let mut browser = Self {
             current_dir: starting_dir,
+            all_files: Vec::new(),
             files: Vec::new(),
             selected_idx: 0,
-            filter: vec!["mp4", "mkv", "avi", "mov", "webm"].into_iter().map(String::from).collect(),
+            query: None,
+            filter,
+            video_count: 0,
+            video_bytes: 0,
+            listing: None,
+            cache: HashMap::new(),
+            notify,
         };
         browser.refresh_files();
         browser
     }
-
+    
+    // Called from the listing thread whenever entries are ready to collect with poll
+    pub fn set_notifier(&self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify.set(notify);
+    }
+    
+    // What the browser converts to, disc files, the subtitle sidecars, and whatever
+    // else the user asked for (lowercase, without the dot)
+    fn listed_extensions(extra: &[String]) -> Vec<String> {
+        VideoFormat::ALL
+            .iter()
+            .map(|format| format.extension())
+            .chain(DISC_EXTENSIONS)
+            .chain(SUBTITLE_EXTENSIONS)
+            .map(String::from)
+            .chain(extra.iter().map(|ext| ext.trim_start_matches('.').to_lowercase()))
+            .collect()
+    }
+    
+    // List files with these extensions too, e.g. sources it can read but not write
+    pub fn set_extra_extensions(&mut self, extra: &[String]) {
+        self.filter = Self::listed_extensions(extra);
+        // What was cached was picked by the old extensions
+        self.cache.clear();
+        self.reload();
+    }
+    
+    // Start listing the current directory afresh; entries show up as they're read.
+    // A folder visited lately shows at once as it was, and is only read again if
+    // it changed since.
     pub fn refresh_files(&mut self) {
-        self.files.clear();
+        self.clear_listing();
+        let cached = self.cache.get_mut(&self.current_dir).map(|cached| {
+            cached.used = Instant::now();
+            (cached.modified, cached.entries.clone())
+        });
+        let known = match cached {
+            Some((modified, entries)) => {
+                self.add_entries(entries);
+                modified
+            },
+            None => None,
+        };
+        self.apply_query();
+        self.selected_idx = 0;
+        self.start_listing(known.is_some(), known);
+    }
+    
+    // Read the folder again after it changed. The old list stays up until the new
+    // one is complete, then the selected entry stays selected; if that went away,
+    // whatever now sits at its position is
+    pub fn reload(&mut self) {
+        self.cache.remove(&self.current_dir);
+        self.start_listing(true, None);
+    }
+    
+    // Whether the list is still filling in (not when a complete one is being checked)
+    pub fn is_loading(&self) -> bool {
+        self.listing.as_ref().is_some_and(|listing| !listing.replace_when_done)
+    }
+    
+    fn clear_listing(&mut self) {
+        self.all_files.clear();
+        self.video_count = 0;
+        self.video_bytes = 0;
         
         // Add parent directory option if not at root
         if let Some(parent) = self.current_dir.parent() {
-            self.files.push(parent.to_path_buf());
+            self.all_files.push(Entry::parent(parent));
         }
-        
-        // Add directories and filtered files
-        for entry in WalkDir::new(&self.current_dir)
-            .max_depth(1)
-            .sort_by_file_name()
-            .into_iter()
-            .filter_map(Result::ok)
-        {
-            let path = entry.path().to_path_buf();
-            
-            // Skip the current directory (it's already represented)
-            if path == self.current_dir {
-                continue;
+    }
+    
+    // Read the directory on its own thread, which can take seconds on a network
+    // share with thousands of entries. A listing that's replaced before it's done
+    // is dropped, and its thread stops at its next batch. When the folder's
+    // modification time is still `known` it isn't read at all.
+    fn start_listing(&mut self, replace_when_done: bool, known: Option<SystemTime>) {
+        let (tx, updates) = mpsc::channel();
+        let dir = self.current_dir.clone();
+        let filter = self.filter.clone();
+        let notify = self.notify.clone();
+        thread::spawn(move || {
+            let modified = fs::metadata(&dir).and_then(|m| m.modified()).ok();
+            if known.is_some() && modified == known {
+                let _ = tx.send(ListingUpdate::Unchanged);
+                notify.notify();
+                return;
             }
             
-            // Always include directories
-            if path.is_dir() {
-                self.files.push(path);
-            } 
-            // Only include files that match our filter
-            else if let Some(extension) = path.extension() {
-                if let Some(ext_str) = extension.to_str() {
-                    if self.filter.contains(&ext_str.to_lowercase()) {
-                        self.files.push(path);
+            // A folder that can't be read ends without Complete, and isn't cached
+            if let Ok(entries) = fs::read_dir(&dir) {
+                let mut batch = Batch::new();
+                let mut last_sent = Instant::now();
+                for entry in entries.filter_map(Result::ok) {
+                    if let Some(listed) = list_entry(entry.path(), &filter) {
+                        batch.push(listed);
+                    }
+                    
+                    if !batch.is_empty() && last_sent.elapsed() >= BATCH_INTERVAL {
+                        if tx.send(ListingUpdate::Entries(std::mem::take(&mut batch))).is_err() {
+                            return;
+                        }
+                        notify.notify();
+                        last_sent = Instant::now();
                     }
                 }
+                let _ = tx.send(ListingUpdate::Entries(batch));
+                let _ = tx.send(ListingUpdate::Complete(modified));
+            }
+            drop(tx);
+            notify.notify();
+        });
+        
+        self.listing = Some(Listing {
+            updates,
+            read: Batch::new(),
+            replace_when_done,
+        });
+    }
+    
+    // Collect entries the listing thread has read, keeping the selection on the
+    // same entry; true when the list changed
+    pub fn poll(&mut self) -> bool {
+        let selected = self.get_selected_file().cloned();
+        let Some(listing) = &mut self.listing else {
+            return false;
+        };
+        
+        let mut arrived = Batch::new();
+        let mut complete = None;
+        let finished = loop {
+            match listing.updates.try_recv() {
+                Ok(ListingUpdate::Entries(batch)) => arrived.extend(batch),
+                Ok(ListingUpdate::Complete(modified)) => complete = Some(modified),
+                Ok(ListingUpdate::Unchanged) => {},
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        listing.read.extend(arrived.iter().cloned());
+        
+        if !listing.replace_when_done {
+            if arrived.is_empty() && !finished {
+                return false;
             }
+            self.add_entries(arrived);
+            if finished {
+                let read = self.listing.take().map(|listing| listing.read).unwrap_or_default();
+                if let Some(modified) = complete {
+                    self.remember(modified, read);
+                }
+            }
+        } else {
+            if !finished {
+                return false;
+            }
+            let read = self.listing.take().map(|listing| listing.read).unwrap_or_default();
+            // Unchanged, or no longer readable: the list on screen stays
+            let Some(modified) = complete else {
+                return false;
+            };
+            self.remember(modified, read.clone());
+            self.clear_listing();
+            self.add_entries(read);
         }
         
-        // Reset selection
-        self.selected_idx = 0;
+        self.apply_query();
+        self.selected_idx = selected
+            .and_then(|selected| self.files.iter().position(|entry| entry.path == selected))
+            .unwrap_or(self.selected_idx)
+            .min(self.files.len().saturating_sub(1));
+        true
+    }
+    
+    // Keep a complete listing of the current directory for coming back to it,
+    // forgetting the folder visited longest ago when the cache is full
+    fn remember(&mut self, modified: Option<SystemTime>, entries: Batch) {
+        if self.cache.len() >= LISTING_CACHE_SIZE && !self.cache.contains_key(&self.current_dir) {
+            let oldest = self.cache.iter()
+                .min_by_key(|(_, cached)| cached.used)
+                .map(|(dir, _)| dir.clone());
+            if let Some(oldest) = oldest {
+                self.cache.remove(&oldest);
+            }
+        }
+        self.cache.insert(self.current_dir.clone(), CachedListing {
+            modified,
+            entries,
+            used: Instant::now(),
+        });
+    }
+    
+    // Merge read entries into the list, in name order after ".."
+    fn add_entries(&mut self, entries: Batch) {
+        for entry in entries {
+            if let Some(size) = entry.size {
+                self.video_count += 1;
+                self.video_bytes += size;
+            }
+            self.all_files.push(entry);
+        }
+        
+        let first = usize::from(self.current_dir.parent().is_some());
+        self.all_files[first..].sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name()));
+    }
+    
+    // Show the entries whose names contain the query, ignoring case; ".." stays
+    // so the filter never traps you in a folder
+    fn apply_query(&mut self) {
+        let query = self.query.as_deref().unwrap_or_default().to_lowercase();
+        let parent = self.current_dir.parent();
+        self.files = self.all_files.iter()
+            .filter(|entry| {
+                query.is_empty()
+                    || Some(entry.path.as_path()) == parent
+                    || entry.path.file_name().is_some_and(|name| name.to_string_lossy().to_lowercase().contains(&query))
+            })
+            .cloned()
+            .collect();
+    }
+    
+    // Re-filter after the query changed, staying on the selected entry while it
+    // still matches and otherwise moving to the first match rather than ".."
+    fn requery(&mut self) {
+        let selected = self.get_selected_file().cloned();
+        self.apply_query();
+        let filtering = self.query.as_deref().is_some_and(|query| !query.is_empty());
+        let first = usize::from(filtering && self.current_dir.parent().is_some() && self.files.len() > 1);
+        self.selected_idx = selected
+            .and_then(|selected| self.files.iter().position(|entry| entry.path == selected))
+            .filter(|&idx| idx >= first)
+            .unwrap_or(first);
+    }
+    
+    // Type-ahead filter box
+    
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+    
+    pub fn open_query(&mut self) {
+        self.query.get_or_insert_with(String::new);
+    }
+    
+    pub fn push_query(&mut self, c: char) {
+        if let Some(query) = &mut self.query {
+            query.push(c);
+            self.requery();
+        }
+    }
+    
+    pub fn pop_query(&mut self) {
+        if let Some(query) = &mut self.query {
+            query.pop();
+            self.requery();
+        }
+    }
+    
+    // Close the box and list everything again
+    pub fn clear_query(&mut self) {
+        if self.query.take().is_some() {
+            self.requery();
+        }
     }
     
     pub fn next(&mut self) {
@@ -81,25 +427,32 @@ impl FileBrowser {
             return false;
         }
         
-        let selected = &self.files[self.selected_idx];
-        if selected.is_dir() {
-            self.current_dir = selected.clone();
-            self.refresh_files();
-            true
-        } else {
-            false
+        let selected = self.files[self.selected_idx].path.clone();
+        self.go_to(selected)
+    }
+    
+    // Show `dir` instead, if it's a folder
+    pub fn go_to(&mut self, dir: PathBuf) -> bool {
+        if !dir.is_dir() {
+            return false;
         }
+        
+        self.current_dir = dir;
+        // A filter typed for one folder means nothing in the next
+        self.query = None;
+        self.refresh_files();
+        true
     }
     
     pub fn get_selected_file(&self) -> Option<&PathBuf> {
-        if self.files.is_empty() {
-            None
-        } else {
-            Some(&self.files[self.selected_idx])
-        }
+        self.get_selected_entry().map(|entry| &entry.path)
+    }
+    
+    pub fn get_selected_entry(&self) -> Option<&Entry> {
+        self.files.get(self.selected_idx)
     }
     
-    pub fn get_files(&self) -> &Vec<PathBuf> {
+    pub fn get_files(&self) -> &[Entry] {
         &self.files
     }
     
@@ -111,13 +464,18 @@ impl FileBrowser {
         &self.current_dir
     }
     
+    // How many videos the current directory holds and their total size in bytes
+    pub fn video_summary(&self) -> (usize, u64) {
+        (self.video_count, self.video_bytes)
+    }
+    
     pub fn is_selected_parent_dir(&self) -> bool {
         if self.files.is_empty() {
             return false;
         }
         
         if let Some(parent) = self.current_dir.parent() {
-            return self.files[self.selected_idx] == parent.to_path_buf();
+            return self.files[self.selected_idx].path == parent;
         }
         
         false
@@ -128,30 +486,36 @@ impl FileBrowser {
             return false;
         }
         
-        self.files[self.selected_idx].is_file()
+        self.files[self.selected_idx].path.is_file()
+    }
+}
+
+// Whether the listing shows this entry: folders always, files when their
+// extension is listed. Videos come with their size for the folder's summary.
+fn list_entry(path: PathBuf, filter: &[String]) -> Option<Entry> {
+    let metadata = fs::metadata(&path).ok();
+    if metadata.as_ref().is_some_and(|m| m.is_dir()) {
+        let kind = if disc::is_video_ts(&path) { EntryKind::Disc } else { EntryKind::Folder };
+        return Some(Entry { label: label(&path, kind), path, kind, size: None });
     }
     
-    pub fn format_path_for_display(&self, path: &Path) -> String {
-        if let Some(parent) = self.current_dir.parent() {
-            if path == parent {
-                return "..".to_string();
-            }
-        }
-        
-        if path.is_dir() {
-            if let Some(name) = path.file_name() {
-                if let Some(name_str) = name.to_str() {
-                    return format!("📁 {}", name_str);
-                }
-            }
-            return "📁 <unknown>".to_string();
-        } else {
-            if let Some(name) = path.file_name() {
-                if let Some(name_str) = name.to_str() {
-                    return format!("🎬 {}", name_str);
-                }
-            }
-            return "🎬 <unknown>".to_string();
-        }
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    if !filter.contains(&extension) {
+        return None;
+    }
+    let size = (!is_subtitle_file(&path)).then(|| metadata.map(|m| m.len()).unwrap_or(0));
+    Some(Entry { label: label(&path, EntryKind::File), path, kind: EntryKind::File, size })
+}
+
+// Names that aren't UTF-8 are shown escaped, and can still be picked
+fn label(path: &Path, kind: EntryKind) -> String {
+    let icon = match kind {
+        EntryKind::Folder => "📁",
+        EntryKind::Disc => "💿",
+        EntryKind::File => "🎬",
+    };
+    match path.file_name() {
+        Some(name) => format!("{} {}", icon, paths::escape(name)),
+        None => format!("{} <unknown>", icon),
     }
 }
\ No newline at end of file
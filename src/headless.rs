@@ -0,0 +1,981 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use serde_json::Value;
+use walkdir::WalkDir;
+
+use rust_tui_video_convert::{
+    Bitrate, ConversionMode, ConversionProgress, Crop, Downmix, EcoMode, EncoderTune, ExternalAudio, ExternalSubtitles, ExtraOutput, FFmpegCapabilities, FrameRate, IoPriority,
+    FrameRateMode, H264Level, H264Profile, ImageQuality, JobId, JobManager, KeyframeInterval, Ladder, LogLevel, NameTemplate, PixelFormat,
+    QualityTarget, Resolution, VideoFormat, VideoSettings,
+};
+use rust_tui_video_convert::converter::{is_url, parse_position, today, ConversionStats, VideoConverter};
+use rust_tui_video_convert::disc;
+use rust_tui_video_convert::hooks::JobHooks;
+use rust_tui_video_convert::metadata;
+use rust_tui_video_convert::paths;
+use rust_tui_video_convert::upload::UploadTarget;
+
+use crate::config::Config;
+use crate::history::{self, HistoryEntry};
+
+pub const USAGE: &str = "\
+Usage: rust-tui-video-convert [--serve <addr>]
+       rust-tui-video-convert --format <fmt> [options] <file>...
+       rust-tui-video-convert convert <file>... --to <fmt>
+
+Without files the interactive interface starts. Given files, each one is
+converted in turn and progress is written to stdout as newline-delimited JSON.
+With `convert`, the files are queued in an interface that is already running
+(using its settings) and its replies are printed; if none is running they are
+converted here as usual. A DVD's VIDEO_TS folder counts as one file: its main
+title, with the VOB pieces joined.
+
+Options:
+  -f, --format <fmt>        Target format: mp4, mkv, avi, mov, webm, ts, flv,
+                            ogv, wmv, 3gp, m4v, mxf, webp, apng, mp3, flac,
+                            wav or m4a (or --to); defaults to the preset
+                            in the config file or RTVC_PRESET
+  -r, --resolution <res>    original, 360p, 480p, 720p, 1080p or 4k (default:
+                            original)
+      --also <fmt[:res]>    Write another output in the same job, in its own
+                            format and optionally resolution (repeatable, e.g.
+                            --also webm:720p --also mp3); FFmpeg decodes the
+                            source once for all of them
+      --ladder <kind>       Write 1080p, 720p, 480p and 360p renditions for
+                            adaptive streaming instead of one output: off,
+                            renditions or hls (also packages them as HLS
+                            segments and playlists; mp4, m4v or ts)
+      --crop <shape>        Cut the centre of the picture to off, 16:9, 4:3,
+                            2.39:1, 1:1 or 9:16 before scaling (default: off)
+  -b, --bitrate <rate>      auto, low, medium, high or a bitrate in kbps (default: auto)
+      --vmaf <score>        Smart quality: search sample encodes for the smallest
+                            CRF-based encode reaching VMAF 93, 95 or 97 (needs
+                            libvmaf; default: off)
+      --fps <rate>          original, 10, 15, 24, 30 or 60 (default: original;
+                            animated images use 15)
+      --fps-mode <mode>     auto, cfr or vfr; cfr fixes variable-rate phone video
+                            at its average rate (or --fps) so editors keep audio
+                            in sync
+  -q, --quality <q>         WEBP/APNG quality: low, medium, high or lossless
+                            (default: medium)
+      --profile <p>         H.264 profile: auto, baseline, main or high
+      --level <l>           H.264 level: auto, 3.0, 3.1, 4.0, 4.1, 4.2, 5.0, 5.1
+                            or 5.2; a file above the level is refused
+      --tune <tune>         H.264 tuning: none, film, animation, grain,
+                            fastdecode or zerolatency (default: none)
+      --pix-fmt <fmt>       auto, yuv420p or yuv420p10le (10-bit H.264/VP9 for
+                            MP4, MKV, TS, M4V and WEBM)
+      --keyint <secs>       Keyframe every 1, 2, 4 or 10 seconds (default: auto)
+      --no-scenecut         No extra keyframes at scene changes, for a fixed GOP
+      --audio-passthrough   Keep AC3/E-AC3/DTS/TrueHD audio untouched where the
+                            format can carry it
+      --downmix <mode>      Fold surround audio to stereo: off, stereo or dialogue
+                            (stereo with the centre channel lifted; default: off)
+      --io-priority <p>     Disk priority of the encoder (Linux, with ionice):
+                            normal, low or idle (default: normal)
+      --eco <when>          Encode on half the cores with faster presets, so
+                            laptops don't throttle: off, battery (while
+                            unplugged) or always (default: off)
+      --log-level <level>   normal, verbose, debug or trace; above normal, each
+                            encode writes an FFmpeg report to the log directory
+      --subtitles <file>    Add an .srt/.ass/.vtt file as a subtitle track (burned
+                            in if the format can't carry one); one input only
+      --burn-subtitles <file>
+                            Render an .srt/.ass/.vtt file into the picture
+      --start <time>        Convert from this point of the source: seconds, mm:ss
+                            or hh:mm:ss, e.g. 1:05.24
+      --end <time>          Convert up to this point of the source
+      --audio <file>        Use this file's audio instead of the source's, e.g. a
+                            voiceover or music track; one input only
+      --mix-audio <file>    Mix this file's audio over the source's
+      --name <template>     Output name without extension: {name} is the source's
+                            name, {n} its place in the batch ({n:03} pads it to
+                            001) and {date} today's date, e.g. holiday_{n:03}
+      --yt-dlp              Download URL inputs with yt-dlp first, for video pages
+                            rather than direct links to media files
+      --keep-times          Give outputs the source's access/modification times
+                            (and permissions on Unix)
+      --checksum            Write a SHA-256 sidecar (<output>.sha256) for each
+                            output, checkable with `sha256sum -c`
+      --metadata            Write <output>.json with the output's ffprobe streams,
+                            the settings used and the FFmpeg command line
+      --check-source        Decode the first and last 5 seconds of each input before
+                            converting it, failing damaged files straight away
+      --upload <dest>       Upload each output to s3://bucket/prefix (AWS CLI) or
+                            sftp://user@host/dir (curl) after converting it
+      --hook-url <url>      POST each finished job to <url> as JSON (with curl)
+      --hook-script <file>  Run <file> after each job, described by VIDEO_CONVERT_*
+                            environment variables (both default to the config's)
+      --mirror <dir>        Treat the inputs as directories and recreate their trees
+                            under <dir>, converting each video in them; with the
+                            up-to-date check below, reruns convert only new or
+                            changed files
+      --force               Re-encode files whose output already exists and is
+                            newer than the source (skipped by default)
+      --report <file>       Write a summary of every job to <file>: CSV if it ends
+                            in .csv, JSON otherwise
+      --serve <addr>        With the interface, also accept jobs over HTTP on
+                            <addr> (e.g. 127.0.0.1:8377). Addresses beyond this
+                            machine need an API token (api_token in the config,
+                            or RTVC_API_TOKEN), which every request must send as
+                            an Authorization: Bearer <token> header
+  -h, --help                Show this help
+
+Exit status:
+  0  every file converted (or skipped as already converted)
+  1  a conversion failed
+  2  invalid command line
+  3  an input file is missing or unreadable
+  4  no backend, or the backend lacks an encoder the format needs
+If files fail for different reasons, the first failure decides the status.";
+
+// Process exit status for a command line the parser rejects
+pub const USAGE_ERROR: i32 = 2;
+
+// A conversion requested on the command line instead of through the interface
+#[derive(Debug)]
+pub struct HeadlessArgs {
+    pub inputs: Vec<PathBuf>,
+    pub format: VideoFormat,
+    pub settings: VideoSettings,
+    pub name: Option<NameTemplate>,
+    pub report: Option<PathBuf>,
+    // Convert files whose output is already there and up to date too
+    pub force: bool,
+    // Inputs are directories whose trees are recreated here with converted files
+    pub mirror: Option<PathBuf>,
+    // Hand the files to a running instance if there is one
+    pub enqueue: bool,
+}
+
+pub enum Command {
+    // Optionally serving the HTTP control API on the given address
+    Interactive { serve: Option<String> },
+    Headless(Box<HeadlessArgs>),
+    Help,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = OsString>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    let enqueue = args.next_if(|arg| arg == "convert").is_some();
+    let mut inputs = Vec::new();
+    let mut format = None;
+    let mut settings = VideoSettings::default();
+    let mut name = None;
+    let mut report = None;
+    let mut serve = None;
+    let mut force = false;
+    let mut mirror = None;
+    
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.to_str().filter(|arg| arg.starts_with('-')) else {
+            inputs.push(PathBuf::from(arg));
+            continue;
+        };
+        
+        if flag == "-h" || flag == "--help" {
+            return Ok(Command::Help);
+        }
+        if flag == "--force" {
+            force = true;
+            continue;
+        }
+        if flag == "--no-scenecut" {
+            settings.scene_cut = false;
+            continue;
+        }
+        if flag == "--audio-passthrough" {
+            settings.audio_passthrough = true;
+            continue;
+        }
+        if flag == "--yt-dlp" {
+            settings.fetch_with_ytdlp = true;
+            continue;
+        }
+        if flag == "--keep-times" {
+            settings.keep_file_times = true;
+            continue;
+        }
+        if flag == "--checksum" {
+            settings.write_checksum = true;
+            continue;
+        }
+        if flag == "--metadata" {
+            settings.write_metadata = true;
+            continue;
+        }
+        if flag == "--check-source" {
+            settings.check_integrity = true;
+            continue;
+        }
+        
+        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag {
+            "--report" => {
+                report = Some(PathBuf::from(value));
+                continue;
+            },
+            "--mirror" => {
+                mirror = Some(PathBuf::from(value));
+                continue;
+            },
+            "--hook-script" => {
+                settings.hooks.script = Some(PathBuf::from(value));
+                continue;
+            },
+            "--subtitles" | "--burn-subtitles" => {
+                let burn_in = flag == "--burn-subtitles";
+                settings.subtitles = Some(ExternalSubtitles { path: PathBuf::from(value), burn_in });
+                continue;
+            },
+            "--audio" | "--mix-audio" => {
+                let mix = flag == "--mix-audio";
+                settings.external_audio = Some(ExternalAudio { path: PathBuf::from(value), mix });
+                continue;
+            },
+            _ => {},
+        }
+        let value = value.into_string().map_err(|_| format!("Invalid value for {}", flag))?;
+        
+        match flag {
+            "-f" | "--format" | "--to" => {
+                format = Some(VideoFormat::from_extension(&value).ok_or_else(|| format!("Unknown format: {}", value))?);
+            },
+            "-r" | "--resolution" => {
+                settings.resolution = parse_resolution(&value).ok_or_else(|| format!("Unknown resolution: {}", value))?;
+            },
+            "--also" => {
+                let (format, resolution) = match value.split_once(':') {
+                    Some((format, resolution)) => {
+                        (format, Some(parse_resolution(resolution).ok_or_else(|| format!("Unknown resolution: {}", resolution))?))
+                    },
+                    None => (value.as_str(), None),
+                };
+                let format = VideoFormat::from_extension(format).ok_or_else(|| format!("Unknown format: {}", format))?;
+                settings.extra_outputs.push(ExtraOutput { format, resolution });
+            },
+            "--ladder" => {
+                settings.ladder = match value.to_lowercase().as_str() {
+                    "off" => Ladder::Off,
+                    "renditions" => Ladder::Renditions,
+                    "hls" => Ladder::Hls,
+                    _ => return Err(format!("Unknown ladder: {}", value)),
+                };
+            },
+            "--crop" => {
+                settings.crop = Crop::ALL
+                    .into_iter()
+                    .find(|crop| crop.as_str().eq_ignore_ascii_case(&value))
+                    .ok_or_else(|| format!("Unknown crop: {}", value))?;
+            },
+            "--start" | "--end" => {
+                let at = parse_position(&value).ok_or_else(|| format!("Invalid time for {}: {}", flag, value))?;
+                if flag == "--start" {
+                    settings.trim.start = Some(at);
+                } else {
+                    settings.trim.end = Some(at);
+                }
+            },
+            "-b" | "--bitrate" => {
+                settings.bitrate = parse_bitrate(&value).ok_or_else(|| format!("Unknown bitrate: {}", value))?;
+            },
+            "--serve" => serve = Some(value),
+            "--name" => name = Some(NameTemplate::parse(&value)?),
+            "--hook-url" => settings.hooks.url = Some(value),
+            "--upload" => {
+                settings.upload_to = Some(UploadTarget::parse(&value).ok_or_else(|| format!("Unknown upload destination: {}", value))?);
+            },
+            "--fps" => {
+                settings.frame_rate = parse_frame_rate(&value).ok_or_else(|| format!("Unknown frame rate: {}", value))?;
+            },
+            "-q" | "--quality" => {
+                settings.image_quality = parse_image_quality(&value).ok_or_else(|| format!("Unknown quality: {}", value))?;
+            },
+            "--profile" => {
+                settings.h264_profile = parse_h264_profile(&value).ok_or_else(|| format!("Unknown H.264 profile: {}", value))?;
+            },
+            "--fps-mode" => {
+                settings.frame_rate_mode = match value.to_lowercase().as_str() {
+                    "auto" => FrameRateMode::Auto,
+                    "cfr" => FrameRateMode::Constant,
+                    "vfr" => FrameRateMode::Variable,
+                    _ => return Err(format!("Unknown frame rate mode: {}", value)),
+                };
+            },
+            "--vmaf" => {
+                settings.quality_target = match value.to_lowercase().as_str() {
+                    "off" => QualityTarget::Off,
+                    "93" => QualityTarget::Vmaf93,
+                    "95" => QualityTarget::Vmaf95,
+                    "97" => QualityTarget::Vmaf97,
+                    _ => return Err(format!("Unknown VMAF target: {}", value)),
+                };
+            },
+            "--downmix" => {
+                settings.downmix = match value.to_lowercase().as_str() {
+                    "off" => Downmix::Off,
+                    "stereo" => Downmix::Stereo,
+                    "dialogue" => Downmix::DialogueBoost,
+                    _ => return Err(format!("Unknown downmix: {}", value)),
+                };
+            },
+            "--log-level" => {
+                settings.log_level = match value.to_lowercase().as_str() {
+                    "normal" => LogLevel::Normal,
+                    "verbose" => LogLevel::Verbose,
+                    "debug" => LogLevel::Debug,
+                    "trace" => LogLevel::Trace,
+                    _ => return Err(format!("Unknown log level: {}", value)),
+                };
+            },
+            "--io-priority" => {
+                settings.io_priority = match value.to_lowercase().as_str() {
+                    "normal" => IoPriority::Normal,
+                    "low" => IoPriority::Low,
+                    "idle" => IoPriority::Idle,
+                    _ => return Err(format!("Unknown I/O priority: {}", value)),
+                };
+            },
+            "--eco" => {
+                settings.eco_mode = match value.to_lowercase().as_str() {
+                    "off" => EcoMode::Off,
+                    "battery" => EcoMode::Battery,
+                    "always" => EcoMode::Always,
+                    _ => return Err(format!("Unknown eco mode: {}", value)),
+                };
+            },
+            "--tune" => {
+                settings.tune = parse_tune(&value).ok_or_else(|| format!("Unknown tune: {}", value))?;
+            },
+            "--pix-fmt" => {
+                settings.pixel_format = parse_pixel_format(&value).ok_or_else(|| format!("Unknown pixel format: {}", value))?;
+            },
+            "--keyint" => {
+                settings.keyframe_interval = parse_keyframe_interval(&value).ok_or_else(|| format!("Unknown keyframe interval: {}", value))?;
+            },
+            "--level" => {
+                settings.h264_level = H264Level::ALL
+                    .into_iter()
+                    .find(|level| level.as_str().eq_ignore_ascii_case(&value))
+                    .ok_or_else(|| format!("Unknown H.264 level: {}", value))?;
+            },
+            _ => return Err(format!("Unknown option: {}", flag)),
+        }
+    }
+    
+    if inputs.is_empty() {
+        if enqueue {
+            return Err("convert needs at least one file".to_string());
+        }
+        return match format {
+            Some(_) => Err("No input files given".to_string()),
+            None => Ok(Command::Interactive { serve }),
+        };
+    }
+    if serve.is_some() {
+        return Err("--serve runs with the interface and can't be combined with files".to_string());
+    }
+    if settings.subtitles.is_some() {
+        if inputs.len() > 1 {
+            return Err("Subtitles can only be added when converting a single file".to_string());
+        }
+        // Queued jobs use the running instance's settings, which have no subtitles
+        if enqueue {
+            return Err("Subtitles can't be added to jobs queued with convert".to_string());
+        }
+    }
+    if settings.external_audio.is_some() {
+        if inputs.len() > 1 {
+            return Err("An audio file can only be added when converting a single file".to_string());
+        }
+        if enqueue {
+            return Err("An audio file can't be added to jobs queued with convert".to_string());
+        }
+    }
+    if let (Some(start), Some(end)) = (settings.trim.start, settings.trim.end) {
+        if start >= end {
+            return Err("--end must come after --start".to_string());
+        }
+    }
+    if enqueue && !settings.trim.is_off() {
+        return Err("Jobs queued with convert can't be trimmed".to_string());
+    }
+    
+    if let Some(name) = &name {
+        if inputs.len() > 1 && !name.is_unique() {
+            return Err("--name needs {name} or {n} to keep a batch's outputs apart".to_string());
+        }
+        if enqueue {
+            return Err("--name can't be used with jobs queued with convert".to_string());
+        }
+    }
+    
+    if mirror.is_some() && enqueue {
+        return Err("--mirror can't be used with jobs queued with convert".to_string());
+    }
+    if !settings.extra_outputs.is_empty() && enqueue {
+        return Err("--also can't be used with jobs queued with convert".to_string());
+    }
+    if !settings.ladder.is_off() && enqueue {
+        return Err("--ladder can't be used with jobs queued with convert".to_string());
+    }
+    
+    let format = format
+        .or_else(|| Config::load().ok()?.preset_format())
+        .ok_or("--format is required when converting files, unless a preset is set (RTVC_PRESET)")?;
+    Ok(Command::Headless(Box::new(HeadlessArgs { inputs, format, settings, name, report, force, mirror, enqueue })))
+}
+
+fn parse_resolution(value: &str) -> Option<Resolution> {
+    match value.to_lowercase().as_str() {
+        "original" => Some(Resolution::Original),
+        "360p" => Some(Resolution::SD360p),
+        "480p" => Some(Resolution::SD480p),
+        "720p" => Some(Resolution::HD720p),
+        "1080p" => Some(Resolution::HD1080p),
+        "4k" | "2160p" => Some(Resolution::UHD4K),
+        _ => None,
+    }
+}
+
+fn parse_bitrate(value: &str) -> Option<Bitrate> {
+    match value.to_lowercase().as_str() {
+        "auto" => Some(Bitrate::Auto),
+        "low" => Some(Bitrate::Low),
+        "medium" => Some(Bitrate::Medium),
+        "high" => Some(Bitrate::High),
+        kbps => kbps.trim_end_matches('k').parse().ok().filter(|kbps| *kbps > 0).map(Bitrate::Custom),
+    }
+}
+
+fn parse_frame_rate(value: &str) -> Option<FrameRate> {
+    match value.to_lowercase().as_str() {
+        "original" => Some(FrameRate::Original),
+        "10" => Some(FrameRate::FPS10),
+        "15" => Some(FrameRate::FPS15),
+        "24" => Some(FrameRate::FPS24),
+        "30" => Some(FrameRate::FPS30),
+        "60" => Some(FrameRate::FPS60),
+        _ => None,
+    }
+}
+
+fn parse_image_quality(value: &str) -> Option<ImageQuality> {
+    match value.to_lowercase().as_str() {
+        "low" => Some(ImageQuality::Low),
+        "medium" => Some(ImageQuality::Medium),
+        "high" => Some(ImageQuality::High),
+        "lossless" => Some(ImageQuality::Lossless),
+        _ => None,
+    }
+}
+
+fn parse_tune(value: &str) -> Option<EncoderTune> {
+    match value.to_lowercase().as_str() {
+        "none" => Some(EncoderTune::None),
+        "film" => Some(EncoderTune::Film),
+        "animation" => Some(EncoderTune::Animation),
+        "grain" => Some(EncoderTune::Grain),
+        "fastdecode" => Some(EncoderTune::FastDecode),
+        "zerolatency" => Some(EncoderTune::ZeroLatency),
+        _ => None,
+    }
+}
+
+fn parse_pixel_format(value: &str) -> Option<PixelFormat> {
+    match value.to_lowercase().as_str() {
+        "auto" => Some(PixelFormat::Auto),
+        "yuv420p" | "8bit" => Some(PixelFormat::Yuv420p),
+        "yuv420p10le" | "yuv420p10" | "10bit" => Some(PixelFormat::Yuv420p10),
+        _ => None,
+    }
+}
+
+fn parse_keyframe_interval(value: &str) -> Option<KeyframeInterval> {
+    match value.to_lowercase().trim_end_matches('s') {
+        "auto" => Some(KeyframeInterval::Auto),
+        "1" => Some(KeyframeInterval::Sec1),
+        "2" => Some(KeyframeInterval::Sec2),
+        "4" => Some(KeyframeInterval::Sec4),
+        "10" => Some(KeyframeInterval::Sec10),
+        _ => None,
+    }
+}
+
+fn parse_h264_profile(value: &str) -> Option<H264Profile> {
+    match value.to_lowercase().as_str() {
+        "auto" => Some(H264Profile::Auto),
+        "baseline" => Some(H264Profile::Baseline),
+        "main" => Some(H264Profile::Main),
+        "high" => Some(H264Profile::High),
+        _ => None,
+    }
+}
+
+// One line of the JSON progress stream
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum ProgressEvent<'a> {
+    Progress {
+        job: usize,
+        #[serde(serialize_with = "paths::serialize_escaped")]
+        input: &'a Path,
+        percent: u8,
+        fps: Option<f64>,
+        // Estimated seconds remaining, once there is enough progress to judge
+        eta: Option<f64>,
+        step: &'a str,
+    },
+    Complete {
+        job: usize,
+        #[serde(serialize_with = "paths::serialize_escaped")]
+        input: &'a Path,
+        #[serde(serialize_with = "paths::serialize_escaped")]
+        output: &'a Path,
+        elapsed: f64,
+        // Streams passed through without re-encoding
+        copied_streams: &'a [String],
+        // Stand-ins for encoders this FFmpeg lacks
+        substituted_encoders: &'a [String],
+        // Written by the same job with --also, or the other rungs of a --ladder
+        #[serde(serialize_with = "paths::serialize_escaped_all")]
+        extra_outputs: &'a [PathBuf],
+        // Master playlist of --ladder hls
+        #[serde(serialize_with = "paths::serialize_escaped_option")]
+        hls_playlist: Option<&'a Path>,
+    },
+    // The output exists and is newer than the input
+    Skipped {
+        job: usize,
+        #[serde(serialize_with = "paths::serialize_escaped")]
+        input: &'a Path,
+        #[serde(serialize_with = "paths::serialize_escaped")]
+        output: &'a Path,
+    },
+    Error {
+        job: usize,
+        #[serde(serialize_with = "paths::serialize_escaped")]
+        input: &'a Path,
+        message: &'a str,
+    },
+}
+
+fn emit(event: &ProgressEvent) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer(&mut stdout, event)?;
+    writeln!(stdout)?;
+    stdout.flush()
+}
+
+// How a single file's conversion ended
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Succeeded,
+    Skipped,
+    InputError,
+    EncoderMissing,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Skipped => "skipped",
+            JobStatus::InputError => "input_error",
+            JobStatus::EncoderMissing => "encoder_missing",
+            JobStatus::Failed => "failed",
+        }
+    }
+    
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            JobStatus::Succeeded | JobStatus::Skipped => 0,
+            JobStatus::Failed => 1,
+            JobStatus::InputError => 3,
+            JobStatus::EncoderMissing => 4,
+        }
+    }
+}
+
+// One entry of the --report summary
+#[derive(Serialize)]
+struct JobReport {
+    job: usize,
+    #[serde(serialize_with = "paths::serialize_escaped")]
+    input: PathBuf,
+    #[serde(serialize_with = "paths::serialize_escaped_option")]
+    output: Option<PathBuf>,
+    status: JobStatus,
+    error: Option<String>,
+    elapsed: Option<f64>,
+    input_size: Option<u64>,
+    output_size: Option<u64>,
+    copied_streams: Vec<String>,
+    substituted_encoders: Vec<String>,
+    #[serde(serialize_with = "paths::serialize_escaped_all")]
+    extra_outputs: Vec<PathBuf>,
+    #[serde(serialize_with = "paths::serialize_escaped_option")]
+    hls_playlist: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    format: &'a str,
+    backend: Option<&'a str>,
+    exit_code: i32,
+    // The same for every job
+    settings: Value,
+    jobs: &'a [JobReport],
+}
+
+impl Report<'_> {
+    // One row per job, the shared format and settings repeated on each
+    fn to_csv(&self) -> String {
+        let settings = match &self.settings {
+            Value::Object(map) => map
+                .iter()
+                .filter(|(_, value)| !value.is_null() && **value != Value::Array(Vec::new()))
+                .map(|(key, value)| match value {
+                    Value::String(text) => format!("{}={}", key, text),
+                    _ => format!("{}={}", key, value),
+                })
+                .collect::<Vec<_>>()
+                .join("; "),
+            _ => String::new(),
+        };
+        let size = |size: Option<u64>| size.map(|size| size.to_string()).unwrap_or_default();
+        
+        let mut csv = String::from("job,input,output,status,error,elapsed_secs,input_bytes,output_bytes,copied_streams,substituted_encoders,extra_outputs,hls_playlist,format,settings\n");
+        for job in self.jobs {
+            let fields = [
+                job.job.to_string(),
+                paths::escape(job.input.as_os_str()).into_owned(),
+                job.output.as_ref().map(|output| paths::escape(output.as_os_str()).into_owned()).unwrap_or_default(),
+                job.status.as_str().to_string(),
+                job.error.clone().unwrap_or_default(),
+                job.elapsed.map(|secs| format!("{:.3}", secs)).unwrap_or_default(),
+                size(job.input_size),
+                size(job.output_size),
+                job.copied_streams.join("; "),
+                job.substituted_encoders.join("; "),
+                job.extra_outputs.iter().map(|output| paths::escape(output.as_os_str())).collect::<Vec<_>>().join("; "),
+                job.hls_playlist.as_ref().map(|playlist| paths::escape(playlist.as_os_str()).into_owned()).unwrap_or_default(),
+                self.format.to_string(),
+                settings.clone(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+// Quote a field holding a separator, quote or line break, doubling its quotes
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Convert every input in turn, returning the process exit status
+pub fn run(args: HeadlessArgs) -> io::Result<i32> {
+    let config = Config::load().unwrap_or_default();
+    config.apply();
+    
+    // Hooks given on the command line replace the configured ones
+    let hooks = JobHooks {
+        url: args.settings.hooks.url.clone().or(config.hook_url),
+        script: args.settings.hooks.script.clone().or(config.hook_script),
+    };
+    
+    // Simulated output would only mislead a script, so a real backend is required
+    let mode = if ConversionMode::native_available() {
+        Ok(ConversionMode::Native)
+    } else {
+        match FFmpegCapabilities::detect() {
+            Some(capabilities) => match missing_for_outputs(&capabilities, &args) {
+                None => {
+                    // Worth saying before the run rather than after a failed encode
+                    for warning in capabilities.compatibility_warnings(args.format, &args.settings) {
+                        eprintln!("Warning: {}", warning);
+                    }
+                    for conflict in args.settings.container_conflicts(args.format) {
+                        eprintln!("Warning: {}", conflict);
+                    }
+                    Ok(ConversionMode::FFmpeg)
+                },
+                Some((format, missing)) => Err(format!("FFmpeg is missing {} needed for {}", missing.join(", "), format.as_str())),
+            },
+            None => Err("No conversion backend available; install FFmpeg".to_string()),
+        }
+    };
+    
+    let (wake_tx, wake_rx) = mpsc::channel();
+    let mut job_manager = JobManager::new();
+    job_manager.set_progress_notifier(move || {
+        let _ = wake_tx.send(());
+    });
+    
+    let sources = match &args.mirror {
+        Some(dest) => mirror_sources(&args.inputs, dest, args.format),
+        None => args.inputs.iter().map(|input| (input.clone(), None)).collect(),
+    };
+    
+    let date = today();
+    let mut reports = Vec::new();
+    for (job, (input, output_dir)) in sources.iter().enumerate() {
+        let job = job + 1;
+        let mut report = JobReport {
+            job,
+            input: input.clone(),
+            output: None,
+            status: JobStatus::Succeeded,
+            error: None,
+            elapsed: None,
+            input_size: disc::source_size(input),
+            output_size: None,
+            copied_streams: Vec::new(),
+            substituted_encoders: Vec::new(),
+            extra_outputs: Vec::new(),
+            hls_playlist: None,
+        };
+        
+        let job_mode = match &mode {
+            Err(message) => Err((JobStatus::EncoderMissing, message.clone())),
+            Ok(_) if !is_url(input) && !input.is_file() && !disc::is_video_ts(input) => Err((JobStatus::InputError, "Input file not found".to_string())),
+            Ok(mode) => Ok(*mode),
+        };
+        let job_mode = match job_mode {
+            Ok(job_mode) => job_mode,
+            Err((status, message)) => {
+                emit(&ProgressEvent::Error { job, input, message: &message })?;
+                report.status = status;
+                report.error = Some(message);
+                reports.push(report);
+                continue;
+            },
+        };
+        
+        // A mirrored tree takes precedence over the configured output folder
+        let output_dir = output_dir.as_ref().or(config.output_dir.as_ref());
+        let settings = VideoSettings {
+            output_name: args.name.as_ref().map(|name| name.render(input, job, &date)),
+            output_dir: output_dir.cloned(),
+            hooks: hooks.clone(),
+            log_dir: Config::log_dir(),
+            ..args.settings.clone()
+        };
+        
+        // Lets an interrupted library conversion pick up where it stopped. A ladder's
+        // smallest rung is written whatever the source's size, so that's the one looked for.
+        let output = if settings.ladder.for_format(args.format).is_off() {
+            VideoConverter::generate_output_path(input, settings.output_name.as_deref(), settings.output_dir.as_deref(), args.format, false)
+        } else {
+            let (smallest, _) = Ladder::RUNGS[Ladder::RUNGS.len() - 1];
+            ExtraOutput { format: args.format, resolution: Some(smallest) }.output_path(input, &settings, false)
+        };
+        if !args.force && already_converted(input, &output) {
+            emit(&ProgressEvent::Skipped { job, input, output: &output })?;
+            report.status = JobStatus::Skipped;
+            report.output_size = fs::metadata(&output).ok().map(|m| m.len());
+            report.output = Some(output);
+            reports.push(report);
+            continue;
+        }
+        
+        if let Some(dir) = output_dir {
+            if let Err(e) = fs::create_dir_all(dir) {
+                let message = format!("Couldn't create {}: {}", dir.display(), e);
+                emit(&ProgressEvent::Error { job, input, message: &message })?;
+                report.status = JobStatus::Failed;
+                report.error = Some(message);
+                reports.push(report);
+                continue;
+            }
+        }
+        
+        let started = Instant::now();
+        let id = job_manager.submit_conversion(job_mode, input.clone(), args.format, settings, false);
+        
+        let finished = wait_for_job(&mut job_manager, id, &wake_rx, |progress| {
+            let event = if progress.has_error {
+                ProgressEvent::Error {
+                    job,
+                    input,
+                    message: progress.error_message.as_deref().unwrap_or(&progress.current_step),
+                }
+            } else if progress.is_complete {
+                ProgressEvent::Complete {
+                    job,
+                    input,
+                    output: &progress.output_file,
+                    elapsed: started.elapsed().as_secs_f64(),
+                    copied_streams: &progress.copied_streams,
+                    substituted_encoders: &progress.substituted_encoders,
+                    extra_outputs: &progress.extra_output_files,
+                    hls_playlist: progress.hls_playlist.as_deref(),
+                }
+            } else {
+                ProgressEvent::Progress {
+                    job,
+                    input,
+                    percent: progress.percent,
+                    fps: progress.stats.fps,
+                    eta: estimate_remaining(started.elapsed(), progress.percent),
+                    step: &progress.current_step,
+                }
+            };
+            emit(&event)
+        })?;
+        
+        report.elapsed = Some(started.elapsed().as_secs_f64());
+        match finished {
+            Some(progress) if !progress.has_error => {
+                // The history only feeds the interface's Stats tab, so failing to write it isn't fatal
+                let stats = ConversionStats::collect(&progress, started.elapsed());
+                if let Err(e) = history::record(&HistoryEntry::new(&progress, &stats, None)) {
+                    eprintln!("Couldn't update the conversion history: {}", e);
+                }
+                report.output_size = fs::metadata(&progress.output_file).ok().map(|m| m.len());
+                report.output = Some(progress.output_file);
+                report.copied_streams = progress.copied_streams;
+                report.substituted_encoders = progress.substituted_encoders;
+                report.extra_outputs = progress.extra_output_files;
+                report.hls_playlist = progress.hls_playlist;
+            },
+            Some(progress) => {
+                report.status = JobStatus::Failed;
+                report.error = Some(progress.error_message.unwrap_or(progress.current_step));
+            },
+            None => {
+                let message = "Conversion stopped without reporting a result";
+                emit(&ProgressEvent::Error { job, input, message })?;
+                report.status = JobStatus::Failed;
+                report.error = Some(message.to_string());
+            },
+        }
+        reports.push(report);
+    }
+    
+    let exit_code = reports
+        .iter()
+        .map(|report| report.status.exit_code())
+        .find(|code| *code != 0)
+        .unwrap_or(0);
+    
+    if let Some(path) = &args.report {
+        let report = Report {
+            format: args.format.extension(),
+            backend: mode.as_ref().ok().map(|mode| mode.label()),
+            exit_code,
+            settings: metadata::settings_json(&args.settings),
+            jobs: &reports,
+        };
+        let is_csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let text = if is_csv { report.to_csv() } else { serde_json::to_string_pretty(&report)? };
+        fs::write(path, text)?;
+    }
+    
+    Ok(exit_code)
+}
+
+// The first format the job writes that FFmpeg lacks encoders for, with what's missing
+fn missing_for_outputs(capabilities: &FFmpegCapabilities, args: &HeadlessArgs) -> Option<(VideoFormat, Vec<&'static str>)> {
+    std::iter::once(args.format)
+        .chain(args.settings.extra_outputs.iter().map(|extra| extra.format))
+        .map(|format| (format, capabilities.missing_for(format)))
+        .find(|(_, missing)| !missing.is_empty())
+}
+
+// Every video under the input directories (and audio file, for an audio target),
+// with the directory under `dest` its output goes to. An input that isn't a directory,
+// or is a DVD folder, is kept as it is, so it is reported as missing or converted
+// straight into `dest`.
+fn mirror_sources(inputs: &[PathBuf], dest: &Path, target: VideoFormat) -> Vec<(PathBuf, Option<PathBuf>)> {
+    let mut sources = Vec::new();
+    for input in inputs {
+        if !input.is_dir() || disc::is_video_ts(input) {
+            sources.push((input.clone(), Some(dest.to_path_buf())));
+            continue;
+        }
+        
+        let videos = WalkDir::new(input)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            // A destination inside the source would otherwise be mirrored again
+            .filter(|path| !path.starts_with(dest))
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| VideoFormat::from_extension(&ext.to_string_lossy()))
+                    .is_some_and(|format| !format.is_animated_image() && (!format.is_audio() || target.is_audio()))
+            });
+        for video in videos {
+            let relative = video.parent().and_then(|parent| parent.strip_prefix(input).ok()).unwrap_or(Path::new(""));
+            let output_dir = dest.join(relative);
+            sources.push((video, Some(output_dir)));
+        }
+    }
+    sources
+}
+
+// An output at least as new as its local source, and not the source itself
+fn already_converted(input: &Path, output: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    if is_url(input) || output == input {
+        return false;
+    }
+    match (modified(input), modified(output)) {
+        (Some(source), Some(converted)) => converted >= source,
+        _ => false,
+    }
+}
+
+// Pass each progress update for a job to `on_progress` until it finishes,
+// returning its final update (None if the job vanished without one)
+fn wait_for_job(
+    job_manager: &mut JobManager,
+    id: JobId,
+    wake_rx: &mpsc::Receiver<()>,
+    mut on_progress: impl FnMut(&ConversionProgress) -> io::Result<()>,
+) -> io::Result<Option<ConversionProgress>> {
+    loop {
+        // The timeout is a fallback in case a job ends without waking us
+        let _ = wake_rx.recv_timeout(Duration::from_millis(500));
+        
+        for (job_id, progress) in job_manager.poll() {
+            if job_id != id {
+                continue;
+            }
+            on_progress(&progress)?;
+            if progress.is_complete {
+                return Ok(Some(progress));
+            }
+        }
+        
+        if !job_manager.is_running(id) {
+            return Ok(None);
+        }
+    }
+}
+
+// Linear estimate from the time taken so far
+fn estimate_remaining(elapsed: Duration, percent: u8) -> Option<f64> {
+    if percent == 0 || percent >= 100 {
+        return None;
+    }
+    let elapsed = elapsed.as_secs_f64();
+    Some(elapsed * f64::from(100 - percent) / f64::from(percent))
+}
\ No newline at end of file
@@ -0,0 +1,181 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+use rust_tui_video_convert::converter::{ConversionProgress, ConversionStats};
+use rust_tui_video_convert::ffmpeg::FFmpegConverter;
+use rust_tui_video_convert::paths;
+
+use crate::config::Config;
+
+// One finished conversion, stored as a line of JSON in the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    // Seconds since the Unix epoch; entries from older versions have no start time
+    #[serde(default)]
+    pub started_at: Option<u64>,
+    pub finished_at: u64,
+    #[serde(serialize_with = "paths::serialize_escaped")]
+    pub source: PathBuf,
+    #[serde(serialize_with = "paths::serialize_escaped")]
+    pub output: PathBuf,
+    // Other files the job wrote besides `output`
+    #[serde(default, skip_serializing_if = "Vec::is_empty", serialize_with = "paths::serialize_escaped_all")]
+    pub extra_outputs: Vec<PathBuf>,
+    pub format: String,
+    // Video encoder used, "<codec> (copy)" when the stream was copied, or the plugin's name
+    pub codec: String,
+    // Of a trimmed job, only the share of the source encoded counts, size and length
+    pub source_size: Option<u64>,
+    pub output_size: Option<u64>,
+    pub duration_secs: Option<f64>,
+    pub elapsed_secs: f64,
+}
+
+impl HistoryEntry {
+    pub fn new(progress: &ConversionProgress, stats: &ConversionStats, plugin: Option<&str>) -> Self {
+        let epoch_secs = |time: SystemTime| time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Self {
+            started_at: Some(epoch_secs(stats.started_at())),
+            finished_at: epoch_secs(stats.finished_at),
+            source: progress.source_file.clone(),
+            output: progress.output_file.clone(),
+            extra_outputs: progress.extra_output_files.clone(),
+            format: progress.target_format.extension().to_string(),
+            codec: codec_label(progress, plugin),
+            source_size: stats.encoded_source_size(),
+            output_size: stats.output_size,
+            duration_secs: stats.encoded_secs,
+            elapsed_secs: stats.elapsed.as_secs_f64(),
+        }
+    }
+    
+    pub fn finished(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.finished_at)
+    }
+    
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64(self.elapsed_secs.max(0.0))
+    }
+}
+
+fn codec_label(progress: &ConversionProgress, plugin: Option<&str>) -> String {
+    if let Some(plugin) = plugin {
+        return plugin.to_string();
+    }
+    // Copied video is listed as "video (h264)", a stand-in encoder as "mpeg4 for libx264".
+    // Audio targets go by their audio codec instead.
+    let format = progress.target_format;
+    let encoder = FFmpegConverter::video_encoder(format).or(FFmpegConverter::audio_encoder(format)).unwrap_or_default();
+    let kind = if format.is_audio() { "audio" } else { "video" };
+    let copied = progress.copied_streams.iter()
+        .filter(|stream| stream.starts_with(kind))
+        .find_map(|stream| stream.split_once(" (")?.1.strip_suffix(')'));
+    let substitute = progress.substituted_encoders.iter()
+        .find_map(|substitution| substitution.strip_suffix(encoder)?.strip_suffix(" for "));
+    match (copied, substitute) {
+        (Some(codec), _) => format!("{} (copy)", codec),
+        (None, Some(substitute)) => substitute.to_string(),
+        (None, None) => encoder.to_string(),
+    }
+}
+
+// Add a finished conversion to the history; without a config directory nothing is kept
+pub fn record(entry: &HistoryEntry) -> io::Result<()> {
+    let Some(path) = Config::history_path() else {
+        return Ok(());
+    };
+    
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", line)
+}
+
+// Every recorded conversion; lines that don't parse (say, a write cut short) are skipped
+pub fn load() -> io::Result<Vec<HistoryEntry>> {
+    let Some(path) = Config::history_path() else {
+        return Ok(Vec::new());
+    };
+    
+    match fs::read_to_string(&path) {
+        Ok(contents) => Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+// How many of the latest conversions the Stats tab lists
+const RECENT_ENTRIES: usize = 5;
+
+// Lifetime totals for the Stats tab
+#[derive(Debug, Clone, Default)]
+pub struct HistoryStats {
+    pub files: usize,
+    pub video_secs: f64,
+    // Source bytes minus output bytes; negative when outputs came out larger
+    pub bytes_saved: i64,
+    pub codecs: Vec<CodecStats>,
+    // Newest first
+    pub recent: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CodecStats {
+    pub codec: String,
+    pub files: usize,
+    // Only conversions whose source duration is known count towards the speed
+    pub video_secs: f64,
+    pub encode_secs: f64,
+}
+
+impl CodecStats {
+    // Seconds of video encoded per second of wall-clock time
+    pub fn realtime_factor(&self) -> Option<f64> {
+        (self.encode_secs > 0.0).then(|| self.video_secs / self.encode_secs)
+    }
+}
+
+impl HistoryStats {
+    pub fn from_entries(entries: &[HistoryEntry]) -> Self {
+        let mut stats = Self { files: entries.len(), ..Self::default() };
+        
+        for entry in entries {
+            stats.video_secs += entry.duration_secs.unwrap_or_default();
+            if let (Some(source), Some(output)) = (entry.source_size, entry.output_size) {
+                stats.bytes_saved += source as i64 - output as i64;
+            }
+            
+            let idx = match stats.codecs.iter().position(|codec| codec.codec == entry.codec) {
+                Some(idx) => idx,
+                None => {
+                    stats.codecs.push(CodecStats { codec: entry.codec.clone(), files: 0, video_secs: 0.0, encode_secs: 0.0 });
+                    stats.codecs.len() - 1
+                },
+            };
+            let codec = &mut stats.codecs[idx];
+            codec.files += 1;
+            if let Some(duration) = entry.duration_secs {
+                codec.video_secs += duration;
+                codec.encode_secs += entry.elapsed_secs;
+            }
+        }
+        
+        // Most used first
+        stats.codecs.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.codec.cmp(&b.codec)));
+        // Entries are appended as jobs finish, so the file ends with the newest
+        stats.recent = entries.iter().rev().take(RECENT_ENTRIES).cloned().collect();
+        stats
+    }
+    
+    pub fn video_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.video_secs.max(0.0))
+    }
+}
\ No newline at end of file
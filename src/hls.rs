@@ -0,0 +1,159 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+
+use crate::converter::{ProgressEvent, ProgressReporter, ProgressSender, Resolution, VideoFormat};
+use crate::ffmpeg::{ffmpeg_binary, FFmpegConverter};
+use crate::paths::tool_arg;
+
+#[derive(Error, Debug)]
+pub enum HlsError {
+    #[error("Failed to package HLS: {0}")]
+    Io(#[from] io::Error),
+    
+    #[error("FFmpeg exited with status {1} segmenting {0}")]
+    ProcessError(String, i32),
+}
+
+// Length FFmpeg aims for; a segment ends at the first keyframe after it, and the
+// ladder's rungs have one every 2 seconds
+const SEGMENT_SECS: u32 = 6;
+
+pub const MASTER_PLAYLIST: &str = "master.m3u8";
+
+// One rung of a ladder, as the playlists describe it
+pub struct Rendition {
+    pub file: PathBuf,
+    pub resolution: Resolution,
+    // Video bitrate it was encoded at, in kbps; used when the segments can't be measured
+    pub kbps: u32,
+}
+
+impl Rendition {
+    // "720p", naming its playlist and segments
+    fn name(&self) -> String {
+        self.resolution.as_str().to_lowercase()
+    }
+}
+
+// Last stage of a ladder job: cut each rendition into MPEG-TS segments with a
+// playlist of its own, without re-encoding, and write a master playlist listing
+// them by bandwidth for the player to choose from
+pub struct HlsPackager<'a> {
+    progress_tx: &'a ProgressSender,
+    cancel: &'a CancellationToken,
+}
+
+impl<'a> HlsPackager<'a> {
+    pub fn new(progress_tx: &'a ProgressSender, cancel: &'a CancellationToken) -> Self {
+        Self { progress_tx, cancel }
+    }
+    
+    // The master playlist, or None if the job was cancelled meanwhile
+    pub async fn package(
+        &self,
+        source_file: &Path,
+        target_format: VideoFormat,
+        renditions: &[Rendition],
+        dir: &Path,
+    ) -> Result<Option<PathBuf>, HlsError> {
+        // A folder left by an earlier run is reused; one made here goes if the job is cancelled
+        let created = !dir.exists();
+        fs::create_dir_all(dir)?;
+        
+        let mut master = String::from("#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-INDEPENDENT-SEGMENTS\n");
+        for (index, rendition) in renditions.iter().enumerate() {
+            let reporter = ProgressReporter::new(self.progress_tx.clone(), source_file, target_format, &rendition.file);
+            reporter.report(ProgressEvent::Stage {
+                percent: (index * 100 / renditions.len()) as u8,
+                step: format!("Packaging HLS: {} ({} of {})...", rendition.resolution.as_str(), index + 1, renditions.len()),
+            }).await;
+            
+            let name = rendition.name();
+            let playlist = dir.join(format!("{}.m3u8", name));
+            let mut child = tokio::process::Command::new(ffmpeg_binary())
+                .arg("-hide_banner")
+                .arg("-v").arg("error")
+                .arg("-y")
+                .arg("-i").arg(tool_arg(&rendition.file))
+                .arg("-map").arg("0:v:0")
+                .arg("-map").arg("0:a:0?")
+                .arg("-c").arg("copy")
+                .arg("-f").arg("hls")
+                .arg("-hls_time").arg(SEGMENT_SECS.to_string())
+                .arg("-hls_playlist_type").arg("vod")
+                .arg("-hls_flags").arg("independent_segments")
+                .arg("-hls_segment_filename").arg(tool_arg(&dir.join(format!("{}_%05d.ts", name))))
+                .arg(tool_arg(&playlist))
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn()?;
+            
+            let status = tokio::select! {
+                _ = self.cancel.cancelled() => {
+                    let _ = child.kill().await;
+                    if created {
+                        let _ = fs::remove_dir_all(dir);
+                    }
+                    return Ok(None);
+                },
+                status = child.wait() => status?,
+            };
+            if !status.success() {
+                return Err(HlsError::ProcessError(name, status.code().unwrap_or(-1)));
+            }
+            
+            // BANDWIDTH is the peak over the segments, as players expect; RESOLUTION is read from the file
+            let file = rendition.file.clone();
+            let size = tokio::task::spawn_blocking(move || FFmpegConverter::video_stream_info(&file).ok().map(|info| (info.width, info.height)))
+                .await
+                .ok()
+                .flatten();
+            let (peak, average) = measure_bitrates(&playlist, dir).unwrap_or_else(|| {
+                let nominal = u64::from(rendition.kbps) * 1000;
+                (nominal, nominal)
+            });
+            let _ = write!(master, "#EXT-X-STREAM-INF:BANDWIDTH={},AVERAGE-BANDWIDTH={}", peak, average);
+            if let Some((width, height)) = size.filter(|&(width, height)| width > 0 && height > 0) {
+                let _ = write!(master, ",RESOLUTION={}x{}", width, height);
+            }
+            let _ = write!(master, "\n{}.m3u8\n", name);
+        }
+        
+        let master_file = dir.join(MASTER_PLAYLIST);
+        fs::write(&master_file, master)?;
+        Ok(Some(master_file))
+    }
+}
+
+// Peak and average bits per second of a rendition, from its playlist's segment
+// lengths and the sizes of the segments written
+fn measure_bitrates(playlist: &Path, dir: &Path) -> Option<(u64, u64)> {
+    let contents = fs::read_to_string(playlist).ok()?;
+    let mut duration = None;
+    let (mut peak, mut total_bits, mut total_secs) = (0.0_f64, 0.0_f64, 0.0_f64);
+    for line in contents.lines() {
+        if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+            duration = extinf.split(',').next().and_then(|secs| secs.trim().parse::<f64>().ok());
+        } else if !line.is_empty() && !line.starts_with('#') {
+            let (Some(secs), Ok(meta)) = (duration.take(), fs::metadata(dir.join(line))) else {
+                continue;
+            };
+            if secs <= 0.0 {
+                continue;
+            }
+            let bits = meta.len() as f64 * 8.0;
+            peak = peak.max(bits / secs);
+            total_bits += bits;
+            total_secs += secs;
+        }
+    }
+    
+    (total_secs > 0.0).then(|| (peak.ceil() as u64, (total_bits / total_secs).ceil() as u64))
+}
\ No newline at end of file
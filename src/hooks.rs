@@ -0,0 +1,116 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use serde_json::json;
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+
+use crate::converter::ConversionProgress;
+
+// A hook that hasn't finished by then is killed, so it can't hold up the next job
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("Failed to run {0}: {1}")]
+    Io(String, io::Error),
+    
+    #[error("{0} exited with status: {1}")]
+    ProcessError(String, i32),
+    
+    #[error("{0} timed out")]
+    Timeout(String),
+}
+
+// Run when a job finishes, successfully or not: POST a JSON description of it to
+// a URL (through curl), and/or run a script with it in VIDEO_CONVERT_* variables
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobHooks {
+    pub url: Option<String>,
+    pub script: Option<PathBuf>,
+}
+
+impl JobHooks {
+    pub fn is_empty(&self) -> bool {
+        self.url.is_none() && self.script.is_none()
+    }
+    
+    // Every hook runs even when an earlier one fails; the failures are returned
+    pub async fn run(&self, finished: &ConversionProgress, elapsed: Duration) -> Vec<HookError> {
+        let status = if !finished.has_error {
+            "succeeded"
+        } else if finished.cancelled {
+            "cancelled"
+        } else {
+            "failed"
+        };
+        let output_size = (!finished.has_error)
+            .then(|| fs::metadata(&finished.output_file).ok().map(|m| m.len()))
+            .flatten();
+        let payload = json!({
+            "event": "job_finished",
+            "status": status,
+            "source": finished.source_file.to_string_lossy(),
+            "output": finished.output_file.to_string_lossy(),
+            "format": finished.target_format.extension(),
+            "error": finished.error_message,
+            "elapsed": elapsed.as_secs_f64(),
+            "output_size": output_size,
+            "copied_streams": finished.copied_streams,
+            "substituted_encoders": finished.substituted_encoders,
+            "extra_outputs": finished.extra_output_files.iter().map(|file| file.to_string_lossy()).collect::<Vec<_>>(),
+            "hls_playlist": finished.hls_playlist.as_ref().map(|file| file.to_string_lossy()),
+        }).to_string();
+        
+        let mut errors = Vec::new();
+        if let Some(url) = &self.url {
+            let mut command = tokio::process::Command::new("curl");
+            command.args(["--silent", "--show-error", "--fail", "-X", "POST", "-H", "Content-Type: application/json", "--data-binary", "@-"])
+                .arg(url);
+            if let Err(e) = run_hook("curl".to_string(), command, Some(&payload)).await {
+                errors.push(e);
+            }
+        }
+        if let Some(script) = &self.script {
+            let mut command = tokio::process::Command::new(script);
+            command.env("VIDEO_CONVERT_STATUS", status)
+                .env("VIDEO_CONVERT_SOURCE", &finished.source_file)
+                .env("VIDEO_CONVERT_OUTPUT", &finished.output_file)
+                .env("VIDEO_CONVERT_FORMAT", finished.target_format.extension())
+                .env("VIDEO_CONVERT_ERROR", finished.error_message.as_deref().unwrap_or_default())
+                .env("VIDEO_CONVERT_ELAPSED", format!("{:.3}", elapsed.as_secs_f64()))
+                .env("VIDEO_CONVERT_OUTPUT_SIZE", output_size.map(|size| size.to_string()).unwrap_or_default())
+                .env("VIDEO_CONVERT_JSON", &payload);
+            if let Err(e) = run_hook(script.display().to_string(), command, None).await {
+                errors.push(e);
+            }
+        }
+        errors
+    }
+}
+
+async fn run_hook(name: String, mut command: tokio::process::Command, stdin: Option<&str>) -> Result<(), HookError> {
+    let mut child = command
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| HookError::Io(name.clone(), e))?;
+    
+    let finished = async {
+        if let (Some(text), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(text.as_bytes()).await?;
+        }
+        child.wait().await
+    };
+    let status = tokio::time::timeout(HOOK_TIMEOUT, finished).await
+        .map_err(|_| HookError::Timeout(name.clone()))?
+        .map_err(|e| HookError::Io(name.clone(), e))?;
+    if !status.success() {
+        return Err(HookError::ProcessError(name, status.code().unwrap_or(-1)));
+    }
+    Ok(())
+}
\ No newline at end of file
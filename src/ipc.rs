@@ -0,0 +1,172 @@
+// Lets a second invocation (`rust-tui-video-convert convert file.mkv --to mp4`)
+// hand jobs to an interface that is already open, over a local socket that
+// speaks one JSON request and one JSON reply per line:
+//
+//   {"command": "submit", "input": "/videos/talk.mkv", "format": "mp4"}
+//   {"command": "list"}
+//   {"command": "get", "id": 3}
+//   {"command": "remove", "id": 3}
+//
+// Replies are the same JSON the HTTP API returns. Only Unix sockets are
+// supported; elsewhere `convert` always converts in the current process.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use rust_tui_video_convert::converter::VideoFormat;
+use rust_tui_video_convert::jobs::JobId;
+
+use crate::events::EventSender;
+use crate::headless::{HeadlessArgs, JobStatus};
+use crate::remote::{self, RemoteCommand, RemoteReply};
+
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum IpcRequest {
+    Submit { input: PathBuf, format: String },
+    List,
+    Get { id: JobId },
+    Remove { id: JobId },
+}
+
+// One socket per user, in the runtime directory when the platform has one
+pub fn socket_path() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("rust-tui-video-convert.sock"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+            std::env::temp_dir().join(format!("rust-tui-video-convert-{}.sock", user))
+        },
+    }
+}
+
+fn answer(line: &str, events: &EventSender) -> Value {
+    let command = match serde_json::from_str::<IpcRequest>(line) {
+        Ok(IpcRequest::Submit { input, format }) => match VideoFormat::from_extension(&format) {
+            Some(format) => RemoteCommand::Submit { input, format },
+            None => return json!({ "error": format!("Unknown format: {}", format) }),
+        },
+        Ok(IpcRequest::List) => RemoteCommand::List,
+        Ok(IpcRequest::Get { id }) => RemoteCommand::Get(id),
+        Ok(IpcRequest::Remove { id }) => RemoteCommand::Remove(id),
+        Err(e) => return json!({ "error": format!("Invalid request: {}", e) }),
+    };
+    
+    match remote::request(events, command) {
+        Some(RemoteReply::Submitted(job)) | Some(RemoteReply::Job(job)) => json!(job),
+        Some(RemoteReply::Jobs(jobs)) => json!({ "jobs": jobs }),
+        Some(RemoteReply::NotFound) => json!({ "error": "No such job" }),
+        Some(RemoteReply::Rejected(message)) => json!({ "error": message }),
+        None => json!({ "error": "The converter is not responding" }),
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{enqueue, listen};
+
+#[cfg(unix)]
+mod unix {
+    use std::fs;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::PathBuf;
+    use std::thread;
+    use serde_json::{json, Value};
+    
+    use super::{answer, socket_path, HeadlessArgs, JobStatus};
+    use crate::events::EventSender;
+    
+    // Accept jobs from other invocations. Returns the socket path, or None when
+    // another instance already owns it (that one keeps receiving the jobs).
+    pub fn listen(events: EventSender) -> io::Result<Option<PathBuf>> {
+        let path = socket_path();
+        
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Ok(None);
+            }
+            // Left behind by an instance that didn't shut down cleanly
+            fs::remove_file(&path)?;
+        }
+        
+        let listener = UnixListener::bind(&path)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        
+        thread::spawn(move || {
+            for stream in listener.incoming().map_while(Result::ok) {
+                let events = events.clone();
+                thread::spawn(move || serve(stream, &events));
+            }
+        });
+        
+        Ok(Some(path))
+    }
+    
+    fn serve(stream: UnixStream, events: &EventSender) {
+        let Ok(mut writer) = stream.try_clone() else {
+            return;
+        };
+        
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let reply = answer(&line, events);
+            if writeln!(writer, "{}", reply).is_err() {
+                break;
+            }
+        }
+    }
+    
+    // Submit each input to the running instance, printing its reply as a JSON
+    // line. Returns the exit status, or None if no instance is listening.
+    pub fn enqueue(args: &HeadlessArgs) -> io::Result<Option<i32>> {
+        let Ok(stream) = UnixStream::connect(socket_path()) else {
+            return Ok(None);
+        };
+        let mut writer = stream.try_clone()?;
+        let mut replies = BufReader::new(stream).lines();
+        
+        let mut status = JobStatus::Succeeded;
+        for input in &args.inputs {
+            // The running instance may have a different working directory
+            let input = match fs::canonicalize(input) {
+                Ok(input) => input,
+                Err(e) => {
+                    println!("{}", json!({ "input": input, "error": e.to_string() }));
+                    if status == JobStatus::Succeeded {
+                        status = JobStatus::InputError;
+                    }
+                    continue;
+                },
+            };
+            
+            let request = json!({ "command": "submit", "input": input, "format": args.format.extension() });
+            writeln!(writer, "{}", request)?;
+            
+            let reply = replies.next().ok_or_else(|| io::Error::other("The running instance closed the connection"))??;
+            println!("{}", reply);
+            
+            // Accepted jobs come back with their id; rejections only carry an error
+            let accepted = serde_json::from_str::<Value>(&reply).is_ok_and(|reply| reply.get("id").is_some());
+            if !accepted && status == JobStatus::Succeeded {
+                status = JobStatus::Failed;
+            }
+        }
+        
+        Ok(Some(status.exit_code()))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn listen(_events: EventSender) -> std::io::Result<Option<PathBuf>> {
+    Ok(None)
+}
+
+#[cfg(not(unix))]
+pub fn enqueue(_args: &HeadlessArgs) -> std::io::Result<Option<i32>> {
+    Ok(None)
+}
\ No newline at end of file
@@ -0,0 +1,484 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::converter::{ConversionMode, ConversionProgress, ConversionStage, EncodeStats, CANCELLED_MESSAGE, ProgressNotifier, ProgressSender, VideoConverter, VideoFormat, VideoSettings};
+use crate::plugins::{Plugin, PluginConverter};
+
+// Progress updates a job may queue before its converter waits for the UI to drain them
+const PROGRESS_BUFFER: usize = 64;
+
+// How long dropping a JobManager waits for cancelled jobs to stop their tools
+// and remove what they had half written
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
+
+pub type JobId = u64;
+
+struct RunningJob {
+    progress_rx: mpsc::Receiver<ConversionProgress>,
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+    // What the job's final update names, should it end without sending one
+    source_file: PathBuf,
+    target_format: VideoFormat,
+    output_file: PathBuf,
+}
+
+// What a queued job will run once its turn comes
+enum QueuedWork {
+    Conversion { mode: ConversionMode, settings: Box<VideoSettings> },
+    Plugin(Arc<Plugin>),
+}
+
+struct QueuedJob {
+    id: JobId,
+    source_file: PathBuf,
+    target_format: VideoFormat,
+    work: QueuedWork,
+    high_priority: bool,
+    // Held back: keeps its place but is passed over until resumed
+    paused: bool,
+}
+
+// Owns the async runtime that conversions run on, and the channels and
+// cancellation tokens used to talk to each running job.
+// Submitted jobs start at once; queued ones run one at a time, in order,
+// with high-priority jobs ahead of the rest and paused ones skipped.
+pub struct JobManager {
+    runtime: Runtime,
+    next_id: JobId,
+    running: HashMap<JobId, RunningJob>,
+    queue: Vec<QueuedJob>,
+    // The queued job that is running now
+    queue_head: Option<JobId>,
+    // Queued jobs cancelled before they started, reported by the next poll
+    dropped: Vec<(JobId, ConversionProgress)>,
+    notify: Option<ProgressNotifier>,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("conversion-worker")
+            .build()
+            .expect("failed to start the conversion runtime");
+        
+        Self {
+            runtime,
+            next_id: 1,
+            running: HashMap::new(),
+            queue: Vec::new(),
+            queue_head: None,
+            dropped: Vec::new(),
+            notify: None,
+        }
+    }
+    
+    // Called from job threads whenever new progress is ready to poll
+    pub fn set_progress_notifier(&mut self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify = Some(Arc::new(notify));
+    }
+    
+    pub fn submit_conversion(
+        &mut self,
+        mode: ConversionMode,
+        source_file: PathBuf,
+        target_format: VideoFormat,
+        settings: VideoSettings,
+        sample: bool,
+    ) -> JobId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.start_conversion(id, mode, source_file, target_format, settings, sample);
+        id
+    }
+    
+    fn start_conversion(
+        &mut self,
+        id: JobId,
+        mode: ConversionMode,
+        source_file: PathBuf,
+        target_format: VideoFormat,
+        settings: VideoSettings,
+        sample: bool,
+    ) {
+        let (progress_tx, progress_rx) = mpsc::channel(PROGRESS_BUFFER);
+        let cancel = CancellationToken::new();
+        
+        let progress_tx = ProgressSender::new(progress_tx, self.notify.clone());
+        let output_file = VideoConverter::generate_output_path(&source_file, settings.output_name.as_deref(), settings.output_dir.as_deref(), target_format, sample);
+        let converter = VideoConverter::new(mode, progress_tx, cancel.clone());
+        let job_source = source_file.clone();
+        let task = self.runtime.spawn(async move {
+            converter.convert(job_source, target_format, settings, sample).await;
+        });
+        
+        self.running.insert(id, RunningJob { progress_rx, cancel, task, source_file, target_format, output_file });
+    }
+    
+    // Convert with an external plugin; its output goes where a built-in conversion's would
+    pub fn submit_plugin(&mut self, plugin: Arc<Plugin>, source_file: PathBuf, target_format: VideoFormat) -> JobId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.start_plugin(id, plugin, source_file, target_format);
+        id
+    }
+    
+    fn start_plugin(&mut self, id: JobId, plugin: Arc<Plugin>, source_file: PathBuf, target_format: VideoFormat) {
+        let (progress_tx, progress_rx) = mpsc::channel(PROGRESS_BUFFER);
+        let cancel = CancellationToken::new();
+        
+        let progress_tx = ProgressSender::new(progress_tx, self.notify.clone());
+        let output_file = VideoConverter::generate_output_path(&source_file, None, None, target_format, false);
+        let converter = PluginConverter::new(plugin, progress_tx, cancel.clone());
+        let (job_source, job_output) = (source_file.clone(), output_file.clone());
+        let task = self.runtime.spawn(async move {
+            converter.convert(job_source, target_format, job_output).await;
+        });
+        
+        self.running.insert(id, RunningJob { progress_rx, cancel, task, source_file, target_format, output_file });
+    }
+    
+    // Like submit_conversion, but waiting its turn behind earlier queued jobs
+    pub fn queue_conversion(&mut self, mode: ConversionMode, source_file: PathBuf, target_format: VideoFormat, settings: VideoSettings) -> JobId {
+        self.enqueue(source_file, target_format, QueuedWork::Conversion { mode, settings: Box::new(settings) })
+    }
+    
+    pub fn queue_plugin(&mut self, plugin: Arc<Plugin>, source_file: PathBuf, target_format: VideoFormat) -> JobId {
+        self.enqueue(source_file, target_format, QueuedWork::Plugin(plugin))
+    }
+    
+    fn enqueue(&mut self, source_file: PathBuf, target_format: VideoFormat, work: QueuedWork) -> JobId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.queue.push(QueuedJob { id, source_file, target_format, work, high_priority: false, paused: false });
+        self.start_queued();
+        id
+    }
+    
+    // Start the first queued job that isn't paused once the previous one has finished
+    fn start_queued(&mut self) {
+        if self.queue_head.is_some_and(|id| self.running.contains_key(&id)) {
+            return;
+        }
+        let Some(idx) = self.queue.iter().position(|job| !job.paused) else {
+            return;
+        };
+        
+        let job = self.queue.remove(idx);
+        self.queue_head = Some(job.id);
+        match job.work {
+            QueuedWork::Conversion { mode, settings } => self.start_conversion(job.id, mode, job.source_file, job.target_format, *settings, false),
+            QueuedWork::Plugin(plugin) => self.start_plugin(job.id, plugin, job.source_file, job.target_format),
+        }
+    }
+    
+    // Waiting jobs, in the order they will run
+    pub fn queued(&self) -> impl Iterator<Item = JobId> + '_ {
+        self.queue.iter().map(|job| job.id)
+    }
+    
+    pub fn is_queued(&self, id: JobId) -> bool {
+        self.queue.iter().any(|job| job.id == id)
+    }
+    
+    pub fn is_high_priority(&self, id: JobId) -> bool {
+        self.queue.iter().any(|job| job.id == id && job.high_priority)
+    }
+    
+    pub fn is_paused(&self, id: JobId) -> bool {
+        self.queue.iter().any(|job| job.id == id && job.paused)
+    }
+    
+    // Hold a queued job back, or let it run again when its turn comes;
+    // a job that has already started can't be paused
+    pub fn set_paused(&mut self, id: JobId, paused: bool) -> bool {
+        let Some(job) = self.queue.iter_mut().find(|job| job.id == id) else {
+            return false;
+        };
+        job.paused = paused;
+        self.start_queued();
+        true
+    }
+    
+    // Swap a queued job with its neighbour; jobs don't move past the other priority
+    pub fn move_queued(&mut self, id: JobId, earlier: bool) -> bool {
+        let Some(idx) = self.queue.iter().position(|job| job.id == id) else {
+            return false;
+        };
+        let other = if earlier { idx.checked_sub(1) } else { Some(idx + 1) };
+        match other.filter(|other| self.queue.get(*other).is_some_and(|job| job.high_priority == self.queue[idx].high_priority)) {
+            Some(other) => {
+                self.queue.swap(idx, other);
+                true
+            },
+            None => false,
+        }
+    }
+    
+    // High priority puts a job behind the other urgent ones, ahead of everything
+    // else; dropping it puts the job first among the rest
+    pub fn set_high_priority(&mut self, id: JobId, high_priority: bool) {
+        let Some(idx) = self.queue.iter().position(|job| job.id == id) else {
+            return;
+        };
+        let mut job = self.queue.remove(idx);
+        job.high_priority = high_priority;
+        let urgent = self.queue.iter().take_while(|job| job.high_priority).count();
+        self.queue.insert(urgent, job);
+    }
+    
+    pub fn is_running(&self, id: JobId) -> bool {
+        self.running.contains_key(&id)
+    }
+    
+    // Jobs that are running or still waiting in the queue
+    pub fn active_jobs(&self) -> usize {
+        self.running.len() + self.queue.len()
+    }
+    
+    pub fn cancel(&mut self, id: JobId) {
+        if let Some(job) = self.running.get(&id) {
+            job.cancel.cancel();
+        }
+        if let Some(idx) = self.queue.iter().position(|job| job.id == id) {
+            let job = self.queue.remove(idx);
+            self.dropped.push((job.id, Self::cancelled(job)));
+            if let Some(notify) = &self.notify {
+                notify();
+            }
+        }
+    }
+    
+    pub fn cancel_all(&mut self) {
+        for job in self.running.values() {
+            job.cancel.cancel();
+        }
+        for job in std::mem::take(&mut self.queue) {
+            self.dropped.push((job.id, Self::cancelled(job)));
+        }
+    }
+    
+    // The final update of a queued job that never started
+    fn cancelled(job: QueuedJob) -> ConversionProgress {
+        let (name, dir) = match &job.work {
+            QueuedWork::Conversion { settings, .. } => (settings.output_name.as_deref(), settings.output_dir.as_deref()),
+            QueuedWork::Plugin(_) => (None, None),
+        };
+        let output_file = VideoConverter::generate_output_path(&job.source_file, name, dir, job.target_format, false);
+        ConversionProgress {
+            cancelled: true,
+            ..Self::failed(job.source_file, job.target_format, output_file, "Removed from the queue", CANCELLED_MESSAGE)
+        }
+    }
+    
+    // The final update of a job that went away without sending one, say because it panicked
+    fn abandoned(job: RunningJob) -> ConversionProgress {
+        let message = "The conversion stopped without reporting a result";
+        Self::failed(job.source_file, job.target_format, job.output_file, message, message)
+    }
+    
+    fn failed(source_file: PathBuf, target_format: VideoFormat, output_file: PathBuf, step: &str, error: &str) -> ConversionProgress {
+        ConversionProgress {
+            percent: 0,
+            current_step: step.to_string(),
+            source_file,
+            target_format,
+            output_file,
+            is_complete: true,
+            has_error: true,
+            error_message: Some(error.to_string()),
+            video_settings: None,
+            stats: EncodeStats::default(),
+            stage: ConversionStage::default(),
+            copied_streams: Vec::new(),
+            substituted_encoders: Vec::new(),
+            extra_output_files: Vec::new(),
+            hls_playlist: None,
+            source_duration_secs: None,
+            encoded_secs: None,
+            cancelled: false,
+        }
+    }
+    
+    // Drain all queued progress from every job, forgetting jobs that have finished
+    pub fn poll(&mut self) -> Vec<(JobId, ConversionProgress)> {
+        let mut updates = std::mem::take(&mut self.dropped);
+        let mut finished = Vec::new();
+        let mut abandoned = Vec::new();
+        
+        for (id, job) in self.running.iter_mut() {
+            loop {
+                match job.progress_rx.try_recv() {
+                    Ok(progress) => {
+                        let is_complete = progress.is_complete;
+                        job.output_file.clone_from(&progress.output_file);
+                        updates.push((*id, progress));
+                        if is_complete {
+                            finished.push(*id);
+                            break;
+                        }
+                    },
+                    Err(mpsc::error::TryRecvError::Empty) => break,
+                    Err(mpsc::error::TryRecvError::Disconnected) => {
+                        abandoned.push(*id);
+                        break;
+                    },
+                }
+            }
+        }
+        
+        for id in finished {
+            self.running.remove(&id);
+        }
+        for id in abandoned {
+            if let Some(job) = self.running.remove(&id) {
+                updates.push((id, Self::abandoned(job)));
+            }
+        }
+        self.start_queued();
+        
+        updates
+    }
+}
+
+// What a running conversion reports, in order: each stage as the job enters it,
+// progress within that stage, then exactly one Completed or Failed
+#[derive(Clone)]
+pub enum ConversionEvent {
+    Stage(ConversionStage),
+    Progress { percent: u8, step: String, stats: EncodeStats },
+    // The final update, with the output file, copied streams and stand-in encoders
+    Completed(Box<ConversionProgress>),
+    Failed { error: String, progress: Box<ConversionProgress> },
+}
+
+// One conversion and the events it reports, for programs that want a single
+// job without a JobManager. Iterating blocks until the next event, so do it
+// from plain threads; async code awaits next_event instead.
+// Dropping the handle cancels the conversion.
+pub struct Conversion {
+    progress_rx: mpsc::Receiver<ConversionProgress>,
+    cancel: CancellationToken,
+    stage: Option<ConversionStage>,
+    pending: VecDeque<ConversionEvent>,
+    finished: bool,
+    // Our own runtime, when not started from inside a Tokio one
+    _runtime: Option<Runtime>,
+}
+
+impl Conversion {
+    pub fn start(mode: ConversionMode, source_file: PathBuf, target_format: VideoFormat, settings: VideoSettings, sample: bool) -> Self {
+        let (progress_tx, progress_rx) = mpsc::channel(PROGRESS_BUFFER);
+        let cancel = CancellationToken::new();
+        
+        let (handle, runtime) = match Handle::try_current() {
+            Ok(handle) => (handle, None),
+            Err(_) => {
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .thread_name("conversion-worker")
+                    .build()
+                    .expect("failed to start the conversion runtime");
+                (runtime.handle().clone(), Some(runtime))
+            },
+        };
+        
+        let converter = VideoConverter::new(mode, ProgressSender::new(progress_tx, None), cancel.clone());
+        handle.spawn(async move {
+            converter.convert(source_file, target_format, settings, sample).await;
+        });
+        
+        Self { progress_rx, cancel, stage: None, pending: VecDeque::new(), finished: false, _runtime: runtime }
+    }
+    
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+    
+    pub async fn next_event(&mut self) -> Option<ConversionEvent> {
+        while self.pending.is_empty() && !self.finished {
+            let progress = self.progress_rx.recv().await;
+            self.take(progress);
+        }
+        self.pending.pop_front()
+    }
+    
+    // Turn one update from the converter into events; None means it went away
+    fn take(&mut self, progress: Option<ConversionProgress>) {
+        let Some(progress) = progress else {
+            self.finished = true;
+            return;
+        };
+        if self.stage != Some(progress.stage) {
+            self.stage = Some(progress.stage);
+            self.pending.push_back(ConversionEvent::Stage(progress.stage));
+        }
+        
+        let event = if progress.is_complete {
+            self.finished = true;
+            if progress.has_error {
+                let error = progress.error_message.clone().unwrap_or_else(|| progress.current_step.clone());
+                ConversionEvent::Failed { error, progress: Box::new(progress) }
+            } else {
+                ConversionEvent::Completed(Box::new(progress))
+            }
+        } else {
+            ConversionEvent::Progress { percent: progress.percent, step: progress.current_step, stats: progress.stats }
+        };
+        self.pending.push_back(event);
+    }
+}
+
+impl Iterator for Conversion {
+    type Item = ConversionEvent;
+    
+    fn next(&mut self) -> Option<ConversionEvent> {
+        while self.pending.is_empty() && !self.finished {
+            let progress = self.progress_rx.blocking_recv();
+            self.take(progress);
+        }
+        self.pending.pop_front()
+    }
+}
+
+impl Drop for Conversion {
+    // Like JobManager, stop the conversion rather than leave it running unseen
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for JobManager {
+    // Don't leave FFmpeg processes or half-written files behind on exit: the
+    // cancelled jobs get a moment to stop their tools and remove partial outputs
+    // before the runtime goes
+    fn drop(&mut self) {
+        self.cancel_all();
+        let mut tasks = Vec::new();
+        for job in self.running.values_mut() {
+            // Nobody reads their progress now, so nothing may wait to send it
+            job.progress_rx.close();
+            tasks.push(&mut job.task);
+        }
+        self.runtime.block_on(async {
+            let _ = tokio::time::timeout(SHUTDOWN_GRACE, async {
+                for task in tasks {
+                    let _ = task.await;
+                }
+            }).await;
+        });
+    }
+}
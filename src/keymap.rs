@@ -0,0 +1,420 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use rust_tui_video_convert::disc;
+use rust_tui_video_convert::file_browser::is_subtitle_file;
+
+use crate::app::{AdvancedSetting, App, AppTab};
+
+// The popups that take every key while open, in the order they cover each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Popup {
+    BitrateCalculator,
+    FramingPreview,
+    TrimEditor,
+    SubtitlePicker,
+    TrackPicker,
+    UrlInput,
+    StillPrompt,
+    DeleteConfirm,
+    Conflicts,
+    SameFormat,
+    NotPlayable,
+    Access,
+    Diagnostics,
+    QuitConfirm,
+    // The conversion summary 'p' opens
+    Confirmation,
+}
+
+// What has the keyboard: a popup or text box, which keeps every key to itself,
+// or one part of the current tab, which passes on the keys it doesn't use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Popup(Popup),
+    // A path or upload destination on the Settings tab being typed in
+    PathEdit,
+    // The file browser's filter box
+    Filter,
+    Browser,
+    // The output folder pane of the dual-pane browser
+    Destination,
+    Formats,
+    Settings,
+    // The Complete tab's list of results, when several jobs ended
+    Results,
+    // A tab without a list of its own (Converting, Complete, Stats, Help)
+    Page,
+}
+
+pub fn focus(app: &App) -> Focus {
+    let popup = if app.bitrate_calculator.is_some() {
+        Some(Popup::BitrateCalculator)
+    } else if app.framing_preview.is_some() {
+        Some(Popup::FramingPreview)
+    } else if app.trim_editor.is_some() {
+        Some(Popup::TrimEditor)
+    } else if app.subtitle_picker.is_some() {
+        Some(Popup::SubtitlePicker)
+    } else if app.track_picker.is_some() {
+        Some(Popup::TrackPicker)
+    } else if app.url_input.is_some() {
+        Some(Popup::UrlInput)
+    } else if app.still_prompt.is_some() {
+        Some(Popup::StillPrompt)
+    } else if app.delete_confirm.is_some() {
+        Some(Popup::DeleteConfirm)
+    } else if app.conflict_prompt.is_some() {
+        Some(Popup::Conflicts)
+    } else if app.same_format_prompt {
+        Some(Popup::SameFormat)
+    } else if app.not_playable.is_some() {
+        Some(Popup::NotPlayable)
+    } else if app.access_prompt.is_some() {
+        Some(Popup::Access)
+    } else if app.diagnostics.is_some() {
+        Some(Popup::Diagnostics)
+    } else if app.quit_confirm {
+        Some(Popup::QuitConfirm)
+    } else if app.show_popup {
+        Some(Popup::Confirmation)
+    } else {
+        None
+    };
+    if let Some(popup) = popup {
+        return Focus::Popup(popup);
+    }
+    if app.path_input.is_some() {
+        return Focus::PathEdit;
+    }
+    
+    match app.current_tab {
+        AppTab::FileBrowser if app.destination.is_some() && app.destination_focused => Focus::Destination,
+        AppTab::FileBrowser if app.file_browser.query().is_some() => Focus::Filter,
+        AppTab::FileBrowser => Focus::Browser,
+        AppTab::FormatSelection => Focus::Formats,
+        AppTab::Settings => Focus::Settings,
+        AppTab::Complete if app.results.len() > 1 => Focus::Results,
+        AppTab::Converting | AppTab::Complete | AppTab::Stats | AppTab::Help => Focus::Page,
+    }
+}
+
+// A key goes to whatever has focus, then to the current tab, then to the app as a
+// whole, stopping at the first that uses it. Popups and text boxes keep every key.
+pub fn handle_key(app: &mut App, key: KeyEvent) {
+    let code = key.code;
+    match focus(app) {
+        Focus::Popup(popup) => popup_key(app, popup, code),
+        Focus::PathEdit => path_edit_key(app, code),
+        focus => {
+            let _ = focused_key(app, focus, code) || tab_key(app, code) || global_key(app, code);
+        },
+    }
+}
+
+fn popup_key(app: &mut App, popup: Popup, code: KeyCode) {
+    match popup {
+        Popup::BitrateCalculator => match code {
+            KeyCode::Esc => app.close_bitrate_calculator(),
+            KeyCode::Enter => app.apply_bitrate_calculator(),
+            KeyCode::Tab | KeyCode::Down => app.calculator_next_field(),
+            KeyCode::BackTab | KeyCode::Up => app.calculator_previous_field(),
+            KeyCode::Backspace => app.calculator_backspace(),
+            KeyCode::Char(c) => app.calculator_input(c),
+            _ => {}
+        },
+        
+        // ←/→ change the crop and ↑/↓ the resolution, redrawing the frame each time
+        Popup::FramingPreview => match code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('v') => app.close_framing_preview(),
+            KeyCode::Right => app.step_framing(AdvancedSetting::Crop, true),
+            KeyCode::Left => app.step_framing(AdvancedSetting::Crop, false),
+            KeyCode::Up => app.step_framing(AdvancedSetting::Resolution, true),
+            KeyCode::Down => app.step_framing(AdvancedSetting::Resolution, false),
+            _ => {}
+        },
+        
+        // ←/→ step a frame, ↑/↓ a second and PgUp/PgDn ten; i and o mark the frame shown
+        Popup::TrimEditor => {
+            let second = app.trim_editor.as_ref().map_or(1, |editor| editor.second());
+            match code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('x') => app.close_trim_editor(),
+                KeyCode::Right => app.step_trim(1),
+                KeyCode::Left => app.step_trim(-1),
+                KeyCode::Up => app.step_trim(second),
+                KeyCode::Down => app.step_trim(-second),
+                KeyCode::PageUp => app.step_trim(10 * second),
+                KeyCode::PageDown => app.step_trim(-10 * second),
+                KeyCode::Home => app.seek_trim_point(false),
+                KeyCode::End => app.seek_trim_point(true),
+                KeyCode::Char('i') => app.set_trim_point(false),
+                KeyCode::Char('o') => app.set_trim_point(true),
+                KeyCode::Char('c') => app.clear_trim(),
+                _ => {}
+            }
+        },
+        
+        Popup::SubtitlePicker => match code {
+            KeyCode::Esc => app.close_subtitle_picker(),
+            KeyCode::Enter => app.confirm_subtitle_picker(),
+            KeyCode::Down => {
+                if let Some(picker) = &mut app.subtitle_picker {
+                    picker.next();
+                }
+            },
+            KeyCode::Up => {
+                if let Some(picker) = &mut app.subtitle_picker {
+                    picker.previous();
+                }
+            },
+            _ => {}
+        },
+        
+        Popup::TrackPicker => match code {
+            KeyCode::Esc => app.close_track_picker(),
+            KeyCode::Enter => app.apply_track_picker(),
+            code => {
+                if let Some(picker) = &mut app.track_picker {
+                    match code {
+                        KeyCode::Down => picker.next(),
+                        KeyCode::Up => picker.previous(),
+                        KeyCode::Char(' ') => picker.toggle_keep(),
+                        KeyCode::Char('d') => picker.toggle_default(),
+                        KeyCode::Char('f') => picker.toggle_forced(),
+                        _ => {}
+                    }
+                }
+            },
+        },
+        
+        Popup::UrlInput => match code {
+            KeyCode::Esc => app.cancel_url_input(),
+            KeyCode::Enter => app.commit_url_input(),
+            KeyCode::Tab => app.toggle_url_fetch(),
+            KeyCode::Backspace => app.url_input_backspace(),
+            KeyCode::Char(c) => app.url_input_char(c),
+            _ => {}
+        },
+        
+        Popup::StillPrompt => match code {
+            KeyCode::Esc => app.close_still_prompt(),
+            KeyCode::Enter => app.confirm_still_prompt(),
+            code => {
+                if let Some(prompt) = &mut app.still_prompt {
+                    match code {
+                        KeyCode::Tab => prompt.format = prompt.format.toggle(),
+                        KeyCode::Backspace => prompt.backspace(),
+                        KeyCode::Char(c) => prompt.input(c),
+                        _ => {}
+                    }
+                }
+            },
+        },
+        
+        Popup::DeleteConfirm => match code {
+            KeyCode::Char('y') | KeyCode::Enter => app.confirm_delete_output(),
+            KeyCode::Char('n') | KeyCode::Esc => app.cancel_delete_output(),
+            _ => {}
+        },
+        
+        // The settings the target container can't take
+        Popup::Conflicts => match code {
+            KeyCode::Enter => app.confirm_conflicts(),
+            KeyCode::Char('s') => app.review_conflicts(),
+            KeyCode::Esc => app.cancel_conflict_prompt(),
+            _ => {}
+        },
+        
+        // The remux/re-encode choice for a same-format conversion
+        Popup::SameFormat => match code {
+            KeyCode::Char('r') => app.confirm_remux(),
+            KeyCode::Char('e') => app.confirm_reencode(),
+            KeyCode::Esc => app.cancel_same_format_prompt(),
+            _ => {}
+        },
+        
+        // The notice that the selected file isn't a video
+        Popup::NotPlayable => {
+            if matches!(code, KeyCode::Enter | KeyCode::Esc) {
+                app.dismiss_not_playable();
+            }
+        },
+        
+        // And the one explaining a file that can't be read or written
+        Popup::Access => match code {
+            KeyCode::Char('o') => app.accept_output_redirect(),
+            KeyCode::Enter | KeyCode::Esc => app.dismiss_access_prompt(),
+            _ => {}
+        },
+        
+        // The diagnostics screen scrolls and saves its report until closed
+        Popup::Diagnostics => match code {
+            KeyCode::Esc | KeyCode::Char('i') => app.close_diagnostics(),
+            code => {
+                if let Some(diagnostics) = &mut app.diagnostics {
+                    match code {
+                        KeyCode::Up => diagnostics.scroll_up(),
+                        KeyCode::Down => diagnostics.scroll_down(),
+                        KeyCode::Char('s') => diagnostics.save(),
+                        _ => {}
+                    }
+                }
+            },
+        },
+        
+        // Shown while conversions are running
+        Popup::QuitConfirm => match code {
+            KeyCode::Char('y') | KeyCode::Enter => app.confirm_quit(),
+            KeyCode::Char('n') | KeyCode::Esc => app.cancel_quit(),
+            _ => {}
+        },
+        
+        // Enter starts the conversion it sums up; nothing behind it gets keys
+        Popup::Confirmation => match code {
+            KeyCode::Enter => app.confirm_popup(),
+            KeyCode::Esc | KeyCode::Char('p') => app.show_popup = false,
+            _ => {}
+        },
+    }
+}
+
+// Editing a path takes all keys until Enter or Esc
+fn path_edit_key(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Esc => app.cancel_path_edit(),
+        KeyCode::Enter => app.commit_path_edit(),
+        KeyCode::Backspace => app.path_input_backspace(),
+        KeyCode::Char(c) => app.path_input_char(c),
+        _ => {}
+    }
+}
+
+// Keys for the focused list or box; false passes the key on to the tab
+fn focused_key(app: &mut App, focus: Focus, code: KeyCode) -> bool {
+    match (focus, code) {
+        // The filter box takes typing until Esc closes it; the list still moves
+        (Focus::Filter, KeyCode::Char(c)) => app.file_browser.push_query(c),
+        (Focus::Filter, KeyCode::Backspace) => app.file_browser.pop_query(),
+        (Focus::Filter, KeyCode::Esc) => app.file_browser.clear_query(),
+        (Focus::Browser, KeyCode::Char('/')) => app.file_browser.open_query(),
+        (Focus::Filter | Focus::Browser, KeyCode::Down) => app.file_browser.next(),
+        (Focus::Filter | Focus::Browser, KeyCode::Up) => app.file_browser.previous(),
+        (Focus::Filter | Focus::Browser, KeyCode::Enter) => {
+            // If selected item is a directory, enter it, unless it's a DVD folder
+            // which is converted as a whole
+            let dvd = app.file_browser.get_selected_file().is_some_and(|path| disc::is_video_ts(path));
+            let entered = !dvd && app.file_browser.enter_directory();
+            
+            // A subtitle file is attached to the next conversion;
+            // any other file moves on to format selection
+            let subtitle = app.file_browser.get_selected_file().is_some_and(|path| is_subtitle_file(path));
+            if !entered && subtitle {
+                app.attach_subtitles();
+            } else if !entered && (dvd || app.file_browser.is_selected_file()) {
+                app.source_url = None;
+                app.current_tab = AppTab::FormatSelection;
+            }
+        },
+        
+        (Focus::Destination, KeyCode::Down | KeyCode::Up) => {
+            if let Some(destination) = &mut app.destination {
+                if code == KeyCode::Down {
+                    destination.next();
+                } else {
+                    destination.previous();
+                }
+            }
+        },
+        (Focus::Destination, KeyCode::Enter) => app.enter_destination_dir(),
+        
+        (Focus::Formats, KeyCode::Down) => app.next_format(),
+        (Focus::Formats, KeyCode::Up) => app.previous_format(),
+        (Focus::Formats, KeyCode::Enter) => app.start_conversion(),
+        // Mark formats to write in the same job, and give a marked one its own resolution
+        (Focus::Formats, KeyCode::Char(' ')) => app.toggle_extra_output(),
+        (Focus::Formats, KeyCode::Char('r')) => app.next_extra_resolution(),
+        
+        // The arrows change values here, so Tab/Shift+Tab switch tabs
+        (Focus::Settings, KeyCode::Down) => app.next_setting(),
+        (Focus::Settings, KeyCode::Up) => app.previous_setting(),
+        (Focus::Settings, KeyCode::Right) => app.change_selected_setting(true),
+        (Focus::Settings, KeyCode::Left) => app.change_selected_setting(false),
+        (Focus::Settings, KeyCode::Enter) => app.start_path_edit(),
+        
+        // Enter opens the highlighted result; Enter or Esc goes back to the list
+        (Focus::Results, KeyCode::Down) if app.lists_results() => app.next_result(),
+        (Focus::Results, KeyCode::Up) if app.lists_results() => app.previous_result(),
+        (Focus::Results, KeyCode::Enter) => app.toggle_result_details(),
+        (Focus::Results, KeyCode::Esc) if app.result_details => app.toggle_result_details(),
+        
+        _ => return false,
+    }
+    true
+}
+
+// Keys that belong to the current tab whichever part of it has focus
+fn tab_key(app: &mut App, code: KeyCode) -> bool {
+    match (app.current_tab, code) {
+        // Pick the output folder in a second pane
+        (AppTab::FileBrowser, KeyCode::Char('d')) => app.toggle_dual_pane(),
+        // With two panes, the arrows move between them; Tab still switches tabs
+        (AppTab::FileBrowser, KeyCode::Left | KeyCode::Right) if app.destination.is_some() => app.switch_pane(),
+        // Jump to the home folder or the filesystem root
+        (AppTab::FileBrowser, KeyCode::Char('~')) => app.browse_home(),
+        (AppTab::FileBrowser, KeyCode::Char('\\')) => app.browse_root(),
+        // Save the highlighted file's subtitle tracks as sidecar files
+        (AppTab::FileBrowser, KeyCode::Char('s')) => app.extract_subtitles(),
+        // Use the highlighted file's audio in the next conversion
+        (AppTab::FileBrowser, KeyCode::Char('a')) => app.attach_audio(),
+        // Convert from an http(s) URL instead of a local file
+        (AppTab::FileBrowser, KeyCode::Char('u')) => app.open_url_input(),
+        // Save a frame of the highlighted file as PNG/JPEG
+        (AppTab::FileBrowser, KeyCode::Char('f')) => app.open_still_prompt(),
+        
+        // Test encode a short sample with the current settings
+        (AppTab::FormatSelection, KeyCode::Char('t')) => app.start_sample_conversion(),
+        // Choose kept audio/subtitle tracks and their default/forced flags
+        (AppTab::FormatSelection, KeyCode::Char('k')) => app.open_track_picker(),
+        // Step through the file to set where the output starts and ends
+        (AppTab::FormatSelection, KeyCode::Char('x')) => app.open_trim_editor(),
+        
+        (AppTab::Converting, KeyCode::Char('c')) => app.cancel_conversion(),
+        
+        (AppTab::Settings, KeyCode::Char('b')) => app.open_bitrate_calculator(),
+        // A frame with the crop and resolution applied
+        (AppTab::Settings, KeyCode::Char('v')) => app.open_framing_preview(),
+        
+        // New conversion after completion
+        (AppTab::Complete, KeyCode::Char('n')) => app.reset(),
+        // Show or play the converted file
+        (AppTab::Complete, KeyCode::Char('o')) => app.open_output_folder(),
+        (AppTab::Complete, KeyCode::Char('v')) => app.play_output(),
+        // Delete the converted file, after a confirmation
+        (AppTab::Complete, KeyCode::Char('d')) => app.open_delete_confirm(),
+        
+        _ => return false,
+    }
+    true
+}
+
+// Keys that work on every tab
+fn global_key(app: &mut App, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Char('q') => app.request_quit(),
+        KeyCode::Char('p') => app.toggle_popup(),
+        // Versions, paths and terminal details for a bug report
+        KeyCode::Char('i') => app.open_diagnostics(),
+        
+        // Reorder the remote job queue
+        KeyCode::Char('[') => app.select_queued(false),
+        KeyCode::Char(']') => app.select_queued(true),
+        KeyCode::Char('{') => app.move_selected_queued(true),
+        KeyCode::Char('}') => app.move_selected_queued(false),
+        KeyCode::Char('!') => app.toggle_selected_priority(),
+        KeyCode::Char('h') => app.toggle_selected_paused(),
+        
+        KeyCode::Right | KeyCode::Tab => app.next_tab(),
+        KeyCode::Left | KeyCode::BackTab => app.previous_tab(),
+        
+        _ => return false,
+    }
+    true
+}
\ No newline at end of file
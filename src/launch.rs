@@ -0,0 +1,58 @@
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+// Show the folder containing a file, with the file selected where the platform allows
+pub fn open_folder(file: &Path) -> io::Result<()> {
+    if cfg!(target_os = "macos") {
+        spawn(Command::new("open").arg("-R").arg(file))
+    } else if cfg!(windows) {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(file);
+        spawn(Command::new("explorer").arg(select))
+    } else {
+        let folder = file.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        spawn(Command::new("xdg-open").arg(folder))
+    }
+}
+
+// Play a file in mpv when it is installed, otherwise in the system's default
+// player. Returns the name of what was launched.
+pub fn play(file: &Path) -> io::Result<&'static str> {
+    if let Some(mpv) = find_on_path("mpv") {
+        spawn(Command::new(mpv).arg("--").arg(file))?;
+        return Ok("mpv");
+    }
+    
+    if cfg!(target_os = "macos") {
+        spawn(Command::new("open").arg(file))?;
+    } else if cfg!(windows) {
+        spawn(Command::new("explorer").arg(file))?;
+    } else {
+        spawn(Command::new("xdg-open").arg(file))?;
+    }
+    Ok("the default player")
+}
+
+// Start a program detached from the terminal, so its output can't draw over
+// the interface, and reap it in the background once it exits
+fn spawn(command: &mut Command) -> io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+// Where PATH finds `program`, as the shell would
+pub fn find_on_path(program: &str) -> Option<PathBuf> {
+    let name = format!("{}{}", program, env::consts::EXE_SUFFIX);
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&name))
+        .find(|candidate| candidate.is_file())
+}
\ No newline at end of file
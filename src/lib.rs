@@ -0,0 +1,30 @@
+//! Conversion engine behind the TUI, usable on its own: browse for files,
+//! submit conversions to a [`JobManager`] and poll their progress, or start a
+//! single [`Conversion`] and iterate over the events it reports.
+
+pub mod converter;
+pub mod disc;
+pub mod ffmpeg;
+pub mod file_browser;
+pub mod hls;
+pub mod hooks;
+pub mod jobs;
+pub mod metadata;
+#[cfg(feature = "native")]
+pub mod native_converter;
+pub mod notifier;
+pub mod paths;
+pub mod plugins;
+pub mod power;
+pub mod upload;
+pub mod ytdlp;
+
+pub use converter::{
+    Bitrate, ConversionMode, ExternalAudio, ExternalSubtitles, ConversionProgress, ConversionStats, Crop, Downmix, EcoMode, EncoderTune, ExtraOutput, FrameRate, FrameRateMode, H264Level, H264Profile, ImageQuality, IoPriority, KeyframeInterval, Ladder, LogLevel, NameTemplate, PixelFormat, ProgressNotifier,
+    QualityTarget, Resolution, TrackKind, TrackSelection, Trim, VideoFormat, VideoSettings, ANIMATION_DEFAULT_FPS, SAMPLE_DURATION_SECS,
+};
+pub use ffmpeg::{set_binary_paths, FFmpegCapabilities, FFmpegConverter, FFmpegError, RgbFrame, MediaTrack, Attachment, VideoStreamInfo};
+pub use file_browser::FileBrowser;
+pub use jobs::{Conversion, ConversionEvent, JobId, JobManager};
+pub use notifier::Notifier;
+pub use plugins::{Plugin, PluginError};
\ No newline at end of file
@@ -5,19 +5,33 @@
  |  _  / | | / __| __|    | |  | |  | | | |      \ \/ / | |/ _` |/ _ \/ _ \   | |    / _ \| '_ \ \ / / _ \ '__| __/ _ \ '__|
  | | \ \ |_| \__ \ |_     | |  | |__| |_| |_      \  /  | | (_| |  __/ (_) |  | |___| (_) | | | \ V /  __/ |  | ||  __/ |   
  |_|  \_\__,_|___/\__|    |_|   \____/|_____|      \/   |_|\__,_|\___|\___/    \_____\___/|_| |_|\_/ \___|_|   \__\___|_|   
-                                                                                                                            
+
 */
 
 mod app;
-mod converter;
-mod ffmpeg;
-mod file_browser;
+mod config;
+mod crash;
+mod diagnostics;
+mod durations;
+mod events;
+mod headless;
+mod history;
+mod ipc;
+mod keymap;
+mod launch;
+mod preview;
+mod probe;
+mod remote;
+mod server;
+mod stills;
+mod subtitles;
+mod tracks;
 mod ui;
-mod native_converter;
+mod watcher;
 
-use std::{io, time::Duration};
+use std::{io, process, sync::mpsc, time::Duration};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -26,21 +40,110 @@ use ratatui::{
     Terminal,
 };
 
-use app::{App, AppTab};
+use app::App;
+use config::Config;
+use events::AppEvent;
 use ui::ui;
 
+// How often the Converting tab's elapsed time is redrawn while nothing else happens
+const CLOCK_TICK: Duration = Duration::from_secs(1);
+
 fn main() -> Result<(), io::Error> {
+    // Files on the command line mean a non-interactive run
+    let serve = match headless::parse_args(std::env::args_os().skip(1)) {
+        Ok(headless::Command::Interactive { serve }) => serve,
+        Ok(headless::Command::Help) => {
+            println!("{}", headless::USAGE);
+            return Ok(());
+        },
+        Ok(headless::Command::Headless(args)) => {
+            if args.enqueue {
+                if let Some(code) = ipc::enqueue(&args)? {
+                    process::exit(code);
+                }
+                eprintln!("No running instance to queue in; converting here instead");
+            }
+            process::exit(headless::run(*args)?);
+        },
+        Err(message) => {
+            eprintln!("Error: {}\n\n{}", message, headless::USAGE);
+            process::exit(headless::USAGE_ERROR);
+        },
+    };
+    
+    // Input, job progress and control API requests all arrive on one channel
+    let (event_tx, event_rx) = mpsc::channel();
+    
+    let serve_addr = match serve {
+        Some(addr) => match server::spawn(&addr, Config::load().unwrap_or_default().api_token, event_tx.clone()) {
+            Ok(local_addr) => Some(local_addr),
+            Err(e) => {
+                eprintln!("Error: can't serve on {}: {}", addr, e);
+                process::exit(1);
+            },
+        },
+        None => None,
+    };
+    
+    // Let later invocations queue jobs here; failing to listen only loses that
+    let ipc_socket = ipc::listen(event_tx.clone()).ok().flatten();
+    
     // Setup terminal
+    crash::install();
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-
+    
+    events::spawn_input_reader(event_tx.clone());
+    
     // Create app and run it
-    let app = App::new();
-    let res = run_app(&mut terminal, app);
-
+    let mut app = App::new();
+    app.serve_addr = serve_addr;
+    let preview_tx = event_tx.clone();
+    app.preview.set_notifier(move || {
+        let _ = preview_tx.send(AppEvent::PreviewReady);
+    });
+    let subtitles_tx = event_tx.clone();
+    app.subtitles.set_notifier(move || {
+        let _ = subtitles_tx.send(AppEvent::SubtitlesExtracted);
+    });
+    let stills_tx = event_tx.clone();
+    app.stills.set_notifier(move || {
+        let _ = stills_tx.send(AppEvent::StillSaved);
+    });
+    let probe_tx = event_tx.clone();
+    app.source_probe.set_notifier(move || {
+        let _ = probe_tx.send(AppEvent::SourceProbed);
+    });
+    let durations_tx = event_tx.clone();
+    app.durations.set_notifier(move || {
+        let _ = durations_tx.send(AppEvent::DurationsReady);
+    });
+    let watcher_tx = event_tx.clone();
+    app.dir_watcher.set_notifier(move || {
+        let _ = watcher_tx.send(AppEvent::DirectoryChanged);
+    });
+    let listing_tx = event_tx.clone();
+    app.file_browser.set_notifier(move || {
+        let _ = listing_tx.send(AppEvent::ListingProgress);
+    });
+    app.job_manager.set_progress_notifier(move || {
+        let _ = event_tx.send(AppEvent::JobProgress);
+    });
+    app.watch_browser_dir();
+    // Whatever the first listing read before the notifier was in place
+    app.poll_browsers();
+    app.update_preview();
+    app.update_durations();
+    let res = run_app(&mut terminal, app, event_rx);
+    
+    // Free the socket for the next instance
+    if let Some(path) = ipc_socket {
+        let _ = std::fs::remove_file(path);
+    }
+    
     // Restore terminal
     disable_raw_mode()?;
     execute!(
@@ -49,116 +152,100 @@ fn main() -> Result<(), io::Error> {
         DisableMouseCapture
     )?;
     terminal.show_cursor()?;
-
+    crash::uninstall();
+    
     if let Err(err) = res {
         println!("Error: {:?}", err);
     }
-
+    
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App, events: mpsc::Receiver<AppEvent>) -> io::Result<()> {
+    let mut needs_redraw = true;
+    
     loop {
-        // Draw UI
-        terminal.draw(|f| ui(f, &app))?;
-
-        // Check for conversion progress
-        app.check_conversion_progress();
-
-        // Handle events
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    // Quit application
-                    KeyCode::Char('q') => {
-                        app.quit();
-                    },
-                    
-                    // Toggle popup
-                    KeyCode::Char('p') => {
-                        app.toggle_popup();
-                    },
-                    
-                    // New conversion after completion
-                    KeyCode::Char('n') => {
-                        if app.current_tab == AppTab::Complete {
-                            app.reset();
-                        }
-                    },
-                    
-                    // Navigation
-                    KeyCode::Down => {
-                        match app.current_tab {
-                            AppTab::FileBrowser => app.file_browser.next(),
-                            AppTab::FormatSelection => app.next_format(),
-                            AppTab::Settings => app.next_setting(),
-                            _ => {}
-                        }
-                    },
-                    KeyCode::Up => {
-                        match app.current_tab {
-                            AppTab::FileBrowser => app.file_browser.previous(),
-                            AppTab::FormatSelection => app.previous_format(),
-                            AppTab::Settings => app.previous_setting(),
-                            _ => {}
-                        }
-                    },
-                    // Change setting values
-                    KeyCode::Right => {
-                        match app.current_tab {
-                            AppTab::Settings => app.change_selected_setting(true),
-                            _ => app.next_tab(),
-                        }
-                    },
-                    KeyCode::Left => {
-                        match app.current_tab {
-                            AppTab::Settings => app.change_selected_setting(false),
-                            _ => app.previous_tab(),
-                        }
-                    },
-                    
-                    // Tab navigation
-                    KeyCode::Tab => {
-                        app.next_tab();
-                    },
-                    
-                    // Selection / Action
-                    KeyCode::Enter => {
-                        match app.current_tab {
-                            AppTab::FileBrowser => {
-                                // If selected item is a directory, enter it
-                                if !app.file_browser.enter_directory() {
-                                    // If it's a file, move to format selection
-                                    if app.file_browser.is_selected_file() {
-                                        app.current_tab = AppTab::FormatSelection;
-                                    }
-                                }
-                            },
-                            AppTab::FormatSelection => {
-                                // Start conversion
-                                app.start_conversion();
-                            },
-                            _ => {}
-                        }
-                    },
-                    
-                    // Close popup with Escape
-                    KeyCode::Esc => {
-                        if app.show_popup {
-                            app.show_popup = false;
-                        }
-                    },
-                    
-                    _ => {}
-                }
+        // Only draw when something changed since the last frame
+        if needs_redraw {
+            terminal.draw(|f| ui(f, &app))?;
+            needs_redraw = false;
+        }
+        
+        // Block until there is input or progress, then handle everything
+        // already queued so bursts of updates produce a single redraw.
+        // A running job's clock also wakes the loop, as FFmpeg can go quiet for a while.
+        let first = if app.shows_job_clock() {
+            match events.recv_timeout(CLOCK_TICK) {
+                Ok(event) => event,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    needs_redraw = true;
+                    continue;
+                },
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        } else {
+            match events.recv() {
+                Ok(event) => event,
+                Err(_) => break,
+            }
+        };
+        
+        for event in std::iter::once(first).chain(events.try_iter()) {
+            match event {
+                AppEvent::JobProgress => {
+                    needs_redraw |= app.check_conversion_progress();
+                },
+                AppEvent::Input(Event::Key(key)) => {
+                    keymap::handle_key(&mut app, key);
+                    app.watch_browser_dir();
+                    app.update_preview();
+                    app.update_durations();
+                    needs_redraw = true;
+                },
+                AppEvent::PreviewReady => {
+                    app.poll_previews();
+                    needs_redraw = true;
+                },
+                AppEvent::SubtitlesExtracted => {
+                    app.poll_subtitles();
+                    needs_redraw = true;
+                },
+                AppEvent::StillSaved => {
+                    app.poll_stills();
+                    needs_redraw = true;
+                },
+                AppEvent::SourceProbed => {
+                    app.poll_source_probe();
+                    needs_redraw = true;
+                },
+                AppEvent::DurationsReady => {
+                    app.durations.poll();
+                    needs_redraw = true;
+                },
+                AppEvent::DirectoryChanged => {
+                    app.reload_browser();
+                },
+                AppEvent::ListingProgress => {
+                    needs_redraw |= app.poll_browsers();
+                },
+                AppEvent::Input(Event::Resize(_, _)) => {
+                    needs_redraw = true;
+                },
+                AppEvent::Input(_) => {},
+                AppEvent::Remote(request) => {
+                    request.respond(&mut app);
+                    needs_redraw = true;
+                },
             }
         }
-
+        
+        crash::record_state(&app);
+        
         // Check if we should exit
         if app.should_quit {
             break;
         }
     }
-
+    
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::converter::{ExtraOutput, VideoFormat, VideoSettings};
+use crate::ffmpeg::ffprobe_binary;
+use crate::paths::tool_arg;
+
+#[derive(Error, Debug)]
+pub enum MetadataError {
+    #[error("Failed to probe the output: {0}")]
+    Probe(String),
+    
+    #[error("Failed to write {0}: {1}")]
+    Io(PathBuf, io::Error),
+}
+
+// `<output>.json` beside the output, like the checksum's `<output>.sha256`
+pub fn sidecar_path(output_file: &Path) -> PathBuf {
+    let mut sidecar = output_file.as_os_str().to_owned();
+    sidecar.push(".json");
+    PathBuf::from(sidecar)
+}
+
+// Record how an output was made: its ffprobe streams and format, the settings
+// used and the backend's command line (None for the in-process backend)
+pub fn write_sidecar(
+    source_file: &Path,
+    output_file: &Path,
+    format: VideoFormat,
+    settings: &VideoSettings,
+    command: Option<&[OsString]>,
+) -> Result<PathBuf, MetadataError> {
+    let probe = probe(output_file)?;
+    let command = command.map(|args| args.iter().map(|arg| arg.to_string_lossy()).collect::<Vec<_>>());
+    let document = json!({
+        "source": source_file.to_string_lossy(),
+        "output": output_file.to_string_lossy(),
+        "format": format.extension(),
+        "backend": if command.is_some() { "ffmpeg" } else { "native" },
+        "command": command,
+        "settings": settings_json(settings),
+        "ffprobe": probe,
+    });
+    
+    let sidecar = sidecar_path(output_file);
+    let text = serde_json::to_string_pretty(&document).unwrap_or_default();
+    fs::write(&sidecar, text + "\n").map_err(|e| MetadataError::Io(sidecar.clone(), e))?;
+    Ok(sidecar)
+}
+
+fn probe(output_file: &Path) -> Result<Value, MetadataError> {
+    let output = Command::new(ffprobe_binary())
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(tool_arg(output_file))
+        .output()
+        .map_err(|e| MetadataError::Probe(e.to_string()))?;
+    if !output.status.success() {
+        return Err(MetadataError::Probe(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| MetadataError::Probe(e.to_string()))
+}
+
+// The settings that shape the encode, leaving out where the output goes
+pub fn settings_json(settings: &VideoSettings) -> Value {
+    let subtitles = settings.subtitles.as_ref().map(|subs| json!({
+        "path": subs.path.to_string_lossy(),
+        "burn_in": subs.burn_in,
+    }));
+    let external_audio = settings.external_audio.as_ref().map(|audio| json!({
+        "path": audio.path.to_string_lossy(),
+        "mix": audio.mix,
+    }));
+    let tracks: Vec<Value> = settings.tracks.iter().map(|track| json!({
+        "stream": format!("{}:{}", track.kind.specifier(), track.number),
+        "default": track.default,
+        "forced": track.forced,
+    })).collect();
+    
+    json!({
+        "resolution": settings.resolution.as_str(),
+        "crop": settings.crop.as_str(),
+        "trim_start": settings.trim.start,
+        "trim_end": settings.trim.end,
+        "bitrate": settings.bitrate.label(),
+        "quality_target": settings.quality_target.as_str(),
+        "frame_rate": settings.frame_rate.as_str(),
+        "frame_rate_mode": settings.frame_rate_mode.as_str(),
+        "image_quality": settings.image_quality.as_str(),
+        "subtitles": subtitles,
+        "external_audio": external_audio,
+        "tracks": tracks,
+        "h264_profile": settings.h264_profile.as_str(),
+        "h264_level": settings.h264_level.as_str(),
+        "tune": settings.tune.as_str(),
+        "pixel_format": settings.pixel_format.as_str(),
+        "keyframe_interval": settings.keyframe_interval.as_str(),
+        "scene_cut": settings.scene_cut,
+        "audio_passthrough": settings.audio_passthrough,
+        "downmix": settings.downmix.as_str(),
+        "eco_mode": settings.eco_mode.as_str(),
+        "remux": settings.remux,
+        "extra_outputs": settings.extra_outputs.iter().map(ExtraOutput::label).collect::<Vec<_>>(),
+        "ladder": settings.ladder.as_str(),
+    })
+}
\ No newline at end of file
@@ -1,515 +1,930 @@
-use std::fs::{self, File};
-use std::io::{self, Read, Write, BufReader, BufWriter};
-use std::path::PathBuf;
-use std::sync::mpsc;
-use std::thread;
-use std::time::Duration;
+// In-process conversion through the libav libraries, built with `--features native`.
+// Uses the same encoders and muxers as the FFmpeg backend, but decodes and encodes
+// frame by frame, so progress is exact and no external binary is needed.
+
+use std::ops::DerefMut;
+use std::path::{Path, PathBuf};
+
+use ffmpeg_next as ffmpeg;
+use ffmpeg::{codec, decoder, encoder, filter, format, frame, media, rescale, ChannelLayout, Dictionary, Frame, Packet, Rational, Rescale};
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
-use crate::converter::{ConversionProgress, VideoFormat};
+use crate::converter::{
+    ConversionStage, Crop, Downmix, FrameRateMode, ImageQuality, IoPriority, PixelFormat, ProgressEvent, ProgressReporter, ProgressSender, QualityTarget, VideoFormat, VideoSettings,
+    ANIMATION_DEFAULT_FPS, ECO_VP9_CPU_USED, ECO_X264_PRESET, SAMPLE_DURATION_SECS, SURROUND_CODECS,
+};
+use crate::ffmpeg::FFmpegConverter;
+use crate::paths::tool_arg;
+use crate::power;
 
 #[derive(Error, Debug)]
 pub enum NativeConverterError {
-    #[error("Failed to read input file: {0}")]
-    InputError(#[from] io::Error),
+    #[error("libav error: {0}")]
+    Ffmpeg(#[from] ffmpeg::Error),
     
-    #[error("Failed to create output file: {0}")]
-    OutputError(String),
+    #[error("Source file has no video stream")]
+    NoVideoStream,
     
-    #[error("Failed during conversion: {0}")]
-    ConversionError(String),
+    #[error("Encoder not available in the linked libav: {0}")]
+    EncoderNotFound(&'static str),
     
-    #[error("Invalid input file")]
-    InvalidInput,
+    #[error("{0}")]
+    InvalidSettings(String),
     
-    #[error("Unsupported format")]
-    UnsupportedFormat,
+    #[error("Conversion thread failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
 }
 
 pub struct NativeConverter {
-    progress_tx: mpsc::Sender<ConversionProgress>,
+    progress_tx: ProgressSender,
+    cancel: CancellationToken,
 }
 
 impl NativeConverter {
-    pub fn new(progress_tx: mpsc::Sender<ConversionProgress>) -> Self {
-        Self { progress_tx }
+    pub fn new(progress_tx: ProgressSender, cancel: CancellationToken) -> Self {
+        Self { progress_tx, cancel }
     }
     
-    pub fn check_available() -> Result<bool, NativeConverterError> {
-        // This pure Rust implementation is always available
-        Ok(true)
+    // libav is linked into the binary, so this only fails if it can't initialise
+    pub fn is_available() -> bool {
+        ffmpeg::init().is_ok()
     }
     
-    fn get_file_size(source_file: &PathBuf) -> Result<u64, NativeConverterError> {
-        let metadata = fs::metadata(source_file)?;
-        Ok(metadata.len())
+    // Encoders the linked libav lacks for a format. Audio-only output is left to the
+    // FFmpeg backend.
+    pub fn missing_for(format: VideoFormat) -> Vec<&'static str> {
+        let Some(video_encoder) = FFmpegConverter::video_encoder(format) else {
+            return vec!["audio-only output"];
+        };
+        std::iter::once(video_encoder)
+            .chain(FFmpegConverter::audio_encoder(format))
+            .filter(|name| encoder::find_by_name(name).is_none())
+            .collect()
     }
     
-    pub fn convert(&self, source_file: PathBuf, target_format: VideoFormat, output_file: PathBuf) -> Result<(), NativeConverterError> {
-        // Verify source file exists
-        if !source_file.exists() {
-            return Err(NativeConverterError::InvalidInput);
-        }
-        
-        // Start conversion in a separate thread
-        let progress_tx = self.progress_tx.clone();
-        
-        thread::spawn(move || {
-            // Send initial progress
-            Self::send_progress(
-                &progress_tx,
-                0,
-                "Starting native Rust conversion...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None
-            );
-            
-            // Get file size for progress tracking
-            let file_size = match Self::get_file_size(&source_file) {
-                Ok(size) => size,
-                Err(e) => {
-                    Self::send_progress(
-                        &progress_tx,
-                        0,
-                        format!("Failed to get file size: {}", e),
-                        &source_file,
-                        target_format,
-                        &output_file,
-                        true,
-                        true,
-                        Some(format!("File size error: {}", e))
-                    );
-                    return;
-                }
+    pub async fn convert(
+        &self,
+        source_file: PathBuf,
+        target_format: VideoFormat,
+        output_file: PathBuf,
+        settings: VideoSettings,
+        sample: bool,
+    ) -> Result<(), NativeConverterError> {
+        let job = Transcode {
+            reporter: ProgressReporter::new(self.progress_tx.clone(), &source_file, target_format, &output_file),
+            cancel: self.cancel.clone(),
+            source_file,
+            target_format,
+            output_file,
+            eco: settings.eco_mode.engaged(),
+            settings,
+            sample,
+        };
+        
+        // libav calls block, so keep them off the runtime's worker threads
+        tokio::task::spawn_blocking(move || job.run()).await?
+    }
+}
+
+// Part of the source to convert, in seconds
+struct Window {
+    start: f64,
+    end: f64,
+}
+
+impl Window {
+    fn contains(&self, seconds: f64) -> bool {
+        seconds >= self.start && seconds < self.end
+    }
+    
+    // Amount to subtract from timestamps so the output starts at zero
+    fn offset(&self, time_base: Rational) -> i64 {
+        if self.start.is_finite() {
+            (self.start / f64::from(time_base)) as i64
+        } else {
+            0
+        }
+    }
+}
+
+// Decoder -> filter graph -> encoder for one stream
+struct Transcoder<F> {
+    input_index: usize,
+    input_time_base: Rational,
+    output_index: usize,
+    output_time_base: Rational,
+    decoder: decoder::Opened,
+    filter: filter::Graph,
+    filter_time_base: Rational,
+    encoder: encoder::Encoder,
+    encoder_time_base: Rational,
+    decoded: F,
+    filtered: F,
+    frames: u64,
+}
+
+impl<F: DerefMut<Target = Frame>> Transcoder<F> {
+    // Decode a packet (or flush everything with None) and pass the frames on to the muxer.
+    // Returns the source position in seconds of the last decoded frame.
+    fn process(
+        &mut self,
+        packet: Option<&Packet>,
+        window: &Window,
+        octx: &mut format::context::Output,
+    ) -> Result<Option<f64>, NativeConverterError> {
+        match packet {
+            Some(packet) => self.decoder.send_packet(packet)?,
+            None => self.decoder.send_eof()?,
+        }
+        
+        let offset = window.offset(self.input_time_base);
+        let mut position = None;
+        
+        while self.decoder.receive_frame(&mut self.decoded).is_ok() {
+            let Some(timestamp) = self.decoded.timestamp() else {
+                continue;
             };
             
-            // Send analyzing progress
-            Self::send_progress(
-                &progress_tx,
-                5,
-                format!("Analyzing video file... Size: {} bytes", file_size),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None
-            );
+            let seconds = timestamp as f64 * f64::from(self.input_time_base);
+            position = Some(seconds);
+            if !window.contains(seconds) {
+                continue;
+            }
             
-            // Open input file
-            let input_file = match File::open(&source_file) {
-                Ok(file) => file,
-                Err(e) => {
-                    Self::send_progress(
-                        &progress_tx,
-                        0,
-                        format!("Failed to open input file: {}", e),
-                        &source_file,
-                        target_format,
-                        &output_file,
-                        true,
-                        true,
-                        Some(format!("Input error: {}", e))
-                    );
-                    return;
+            self.decoded.set_pts(Some(timestamp - offset));
+            self.filter.get("in").unwrap().source().add(&self.decoded)?;
+            self.frames += 1;
+            self.drain_filter(octx)?;
+        }
+        
+        if packet.is_none() {
+            self.filter.get("in").unwrap().source().flush()?;
+            self.drain_filter(octx)?;
+            self.encoder.send_eof()?;
+            self.drain_encoder(octx)?;
+        }
+        
+        Ok(position)
+    }
+    
+    fn drain_filter(&mut self, octx: &mut format::context::Output) -> Result<(), NativeConverterError> {
+        while self.filter.get("out").unwrap().sink().frame(&mut self.filtered).is_ok() {
+            let pts = self.filtered.pts().map(|pts| pts.rescale(self.filter_time_base, self.encoder_time_base));
+            self.filtered.set_pts(pts);
+            self.encoder.send_frame(&self.filtered)?;
+            self.drain_encoder(octx)?;
+        }
+        Ok(())
+    }
+    
+    fn drain_encoder(&mut self, octx: &mut format::context::Output) -> Result<(), NativeConverterError> {
+        let mut encoded = Packet::empty();
+        while self.encoder.receive_packet(&mut encoded).is_ok() {
+            encoded.set_stream(self.output_index);
+            encoded.rescale_ts(self.encoder_time_base, self.output_time_base);
+            encoded.write_interleaved(octx)?;
+        }
+        Ok(())
+    }
+}
+
+// A stream muxed into the output unchanged
+struct CopiedStream {
+    input_index: usize,
+    input_time_base: Rational,
+    output_index: usize,
+    output_time_base: Rational,
+}
+
+impl CopiedStream {
+    fn process(
+        &self,
+        mut packet: Packet,
+        window: &Window,
+        octx: &mut format::context::Output,
+    ) -> Result<(), NativeConverterError> {
+        if let Some(pts) = packet.pts() {
+            if !window.contains(pts as f64 * f64::from(self.input_time_base)) {
+                return Ok(());
+            }
+        }
+        
+        let offset = window.offset(self.input_time_base);
+        packet.set_pts(packet.pts().map(|pts| pts - offset));
+        packet.set_dts(packet.dts().map(|dts| dts - offset));
+        packet.rescale_ts(self.input_time_base, self.output_time_base);
+        packet.set_position(-1);
+        packet.set_stream(self.output_index);
+        packet.write_interleaved(octx)?;
+        Ok(())
+    }
+}
+
+// A source stream either re-encoded or, when it already suits the target, copied
+enum StreamOutput<F> {
+    Encode(Transcoder<F>),
+    Copy(CopiedStream),
+}
+
+impl<F> StreamOutput<F> {
+    fn input_index(&self) -> usize {
+        match self {
+            StreamOutput::Encode(transcoder) => transcoder.input_index,
+            StreamOutput::Copy(stream) => stream.input_index,
+        }
+    }
+    
+    fn output_index(&self) -> usize {
+        match self {
+            StreamOutput::Encode(transcoder) => transcoder.output_index,
+            StreamOutput::Copy(stream) => stream.output_index,
+        }
+    }
+    
+    fn set_output_time_base(&mut self, time_base: Rational) {
+        match self {
+            StreamOutput::Encode(transcoder) => transcoder.output_time_base = time_base,
+            StreamOutput::Copy(stream) => stream.output_time_base = time_base,
+        }
+    }
+}
+
+struct Transcode {
+    reporter: ProgressReporter,
+    cancel: CancellationToken,
+    source_file: PathBuf,
+    target_format: VideoFormat,
+    output_file: PathBuf,
+    settings: VideoSettings,
+    sample: bool,
+    // Eco mode, settled as the job starts
+    eco: bool,
+}
+
+impl Transcode {
+    fn run(self) -> Result<(), NativeConverterError> {
+        self.reporter.blocking_report(ProgressEvent::Stage { percent: 0, step: "Opening source with libav...".to_string() });
+        if self.settings.subtitles.as_ref().is_some_and(|subs| !subs.burn_in) {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "The native backend can't add subtitle tracks; burning them in instead".to_string(),
+            });
+        }
+        if self.settings.external_audio.is_some() {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "The native backend can't take a separate audio file; keeping the source's audio".to_string(),
+            });
+        }
+        if !self.settings.trim.is_off() {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "Trimming needs the FFmpeg backend; converting the whole file".to_string(),
+            });
+        }
+        if !self.settings.tracks.is_empty() {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "The native backend keeps only the main audio track; track selection needs FFmpeg".to_string(),
+            });
+        }
+        if self.settings.quality_target != QualityTarget::Off {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "Smart quality needs the FFmpeg backend; using the default quality".to_string(),
+            });
+        }
+        if self.eco {
+            self.reporter.blocking_report(ProgressEvent::Stage { percent: 0, step: power::eco_notice() });
+        }
+        if self.settings.io_priority != IoPriority::Normal {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "I/O priority needs the FFmpeg backend; converting at normal priority".to_string(),
+            });
+        }
+        if self.settings.log_level.report_level().is_some() {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "FFmpeg reports need the FFmpeg backend; converting without one".to_string(),
+            });
+        }
+        if self.settings.check_integrity && !self.sample {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "The source check needs the FFmpeg backend; converting without it".to_string(),
+            });
+        }
+        if self.settings.remux {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: "Remuxing needs the FFmpeg backend; converting with the current settings".to_string(),
+            });
+        }
+        if self.settings.pixel_format == PixelFormat::Yuv420p10 && !self.target_format.supports_10bit() {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("{} is 8-bit only; encoding yuv420p instead of 10-bit", self.target_format.as_str()),
+            });
+        }
+        
+        self.reporter.set_stage(ConversionStage::Encode);
+        match self.transcode() {
+            Ok(Some((copied_streams, duration))) => {
+                if self.settings.keep_file_times && !self.sample && !crate::converter::is_url(&self.source_file) {
+                    if let Err(e) = crate::converter::copy_file_times(&self.source_file, &self.output_file) {
+                        self.reporter.blocking_report(ProgressEvent::Stage {
+                            percent: 100,
+                            step: format!("Couldn't copy the source's file times: {}", e),
+                        });
+                    }
                 }
-            };
-            
-            // Create output file
-            let output_file_result = match File::create(&output_file) {
-                Ok(file) => file,
-                Err(e) => {
-                    Self::send_progress(
-                        &progress_tx,
-                        0,
-                        format!("Failed to create output file: {}", e),
-                        &source_file,
-                        target_format,
-                        &output_file,
-                        true,
-                        true,
-                        Some(format!("Output error: {}", e))
-                    );
-                    return;
+                if self.settings.write_metadata && !self.sample {
+                    let written = crate::metadata::write_sidecar(&self.source_file, &self.output_file, self.target_format, &self.settings, None);
+                    if let Err(e) = written {
+                        self.reporter.blocking_report(ProgressEvent::Stage {
+                            percent: 100,
+                            step: format!("Couldn't write the metadata sidecar: {}", e),
+                        });
+                    }
                 }
-            };
-            
-            // Create buffered readers/writers for better performance
-            let mut reader = BufReader::new(input_file);
-            let mut writer = BufWriter::new(output_file_result);
-            
-            // This is an improved implementation that simulates a more realistic video conversion process
-            // It processes the file in multiple stages like a real converter would
-            
-            // Stage 1: Analyze video structure
-            Self::send_progress(
-                &progress_tx,
-                5,
-                "Analyzing video structure and metadata...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None
-            );
-            
-            // Simulate video analysis
-            thread::sleep(Duration::from_millis(500));
-            
-            // Write format-specific header based on target format
-            let header: &[u8] = match target_format {
-                VideoFormat::MP4 => b"\x00\x00\x00\x18ftypmp42\x00\x00\x00\x00mp42mp41\x00\x00\x00\x01", // MP4 signature
-                VideoFormat::MKV => b"\x1A\x45\xDF\xA3\x01\x00\x00\x00\x00\x00\x00\x23\x42\x86\x81\x01", // MKV signature
-                VideoFormat::AVI => b"RIFF\x00\x00\x00\x00AVI LIST\x00\x00\x00\x00hdrlavih\x00\x00\x00\x00", // AVI signature
-                VideoFormat::MOV => b"\x00\x00\x00\x14ftyp\x71t  \x00\x00\x00\x00qt  \x00\x00\x00\x01", // MOV signature
-                VideoFormat::WEBM => b"\x1A\x45\xDF\xA3\x01\x00\x00\x00\x00\x00\x00\x23\x42\x86\x81\x02", // WebM signature
-            };
-            
-            // Write the header
-            if let Err(e) = writer.write_all(header) {
-                Self::send_progress(
-                    &progress_tx,
-                    0,
-                    format!("Failed to write container header: {}", e),
-                    &source_file,
-                    target_format,
-                    &output_file,
-                    true,
-                    true,
-                    Some(format!("Header error: {}", e))
-                );
-                return;
+                // A source whose container gives no length has none to report
+                let source_duration_secs = Some(duration).filter(|duration| *duration > 0.0);
+                let encoded_secs = source_duration_secs.map(|duration| if self.sample { duration.min(SAMPLE_DURATION_SECS) } else { duration });
+                self.reporter.blocking_report(ProgressEvent::Completed {
+                    step: "Conversion complete!".to_string(),
+                    copied_streams,
+                    substituted_encoders: Vec::new(),
+                    source_duration_secs,
+                    encoded_secs,
+                });
+                Ok(())
+            },
+            Ok(None) => {
+                // The output context is closed by now, so the partial file can go
+                let _ = std::fs::remove_file(&self.output_file);
+                self.reporter.blocking_report(ProgressEvent::Cancelled);
+                Ok(())
+            },
+            Err(e) => {
+                let _ = std::fs::remove_file(&self.output_file);
+                Err(e)
+            },
+        }
+    }
+    
+    // Returns the streams copied rather than re-encoded and the source's length, or
+    // None if the job was cancelled part way through
+    fn transcode(&self) -> Result<Option<(Vec<String>, f64)>, NativeConverterError> {
+        ffmpeg::init()?;
+        
+        // Opened by the name FFmpeg would get, so a DVD folder reads its joined VOBs
+        let mut ictx = format::input(&Path::new(&*tool_arg(&self.source_file)))?;
+        let mut octx = format::output_as(&self.output_file, FFmpegConverter::muxer(self.target_format))?;
+        let global_header = octx.format().flags().contains(format::Flags::GLOBAL_HEADER);
+        
+        let duration = ictx.duration().max(0) as f64 * f64::from(rescale::TIME_BASE);
+        
+        // A test encode covers a short window centred in the source
+        let window = if self.sample {
+            let start = ((duration - SAMPLE_DURATION_SECS) / 2.0).max(0.0);
+            Window { start, end: start + SAMPLE_DURATION_SECS }
+        } else {
+            Window { start: f64::NEG_INFINITY, end: f64::INFINITY }
+        };
+        if window.start > 0.0 {
+            let position = (window.start / f64::from(rescale::TIME_BASE)) as i64;
+            ictx.seek(position, ..position)?;
+        }
+        
+        // Streams already in the target's codecs are copied, as long as no setting asks
+        // for a different picture. Subtitles are always burned in here, and a test encode
+        // can't start a copied video stream on a keyframe.
+        let mut copied_streams = Vec::new();
+        let mut video = {
+            let stream = ictx.streams().best(media::Type::Video).ok_or(NativeConverterError::NoVideoStream)?;
+            let codec = stream.parameters().id().name();
+            let keeps_picture = self.settings.keeps_picture(self.target_format) && self.settings.subtitles.is_none() && !self.sample;
+            if keeps_picture && FFmpegConverter::copyable_video_codec(self.target_format) == Some(codec) {
+                copied_streams.push(format!("video ({})", codec));
+                StreamOutput::Copy(Self::copied_stream(&stream, &mut octx)?)
+            } else {
+                StreamOutput::Encode(self.video_transcoder(&stream, &mut octx, global_header)?)
             }
-            
-            // Stage 2: Extract audio stream
-            Self::send_progress(
-                &progress_tx,
-                10,
-                "Extracting and decoding audio streams...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None
-            );
-            
-            // Simulate audio extraction
-            thread::sleep(Duration::from_millis(800));
-            
-            // Write audio metadata based on format
-            let audio_meta: &[u8] = match target_format {
-                VideoFormat::MP4 => b"\x00\x00\x00\x20mp4a\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00",
-                VideoFormat::MKV => b"\xA3\x42\x86\x81\x01\x42\x87\x81\x02\x42\x85\x81\x02",
-                VideoFormat::AVI => b"LIST\x00\x00\x00\x70strlstrh\x00\x00\x00\x38auds\x00\x00\x00\x00",
-                VideoFormat::MOV => b"\x00\x00\x00\x20mp4a\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00",
-                VideoFormat::WEBM => b"\xA3\x42\x86\x81\x01\x42\x87\x81\x04\x42\x85\x81\x02",
-            };
-            
-            if let Err(e) = writer.write_all(audio_meta) {
-                Self::send_progress(
-                    &progress_tx,
-                    10,
-                    format!("Failed to write audio metadata: {}", e),
-                    &source_file,
-                    target_format,
-                    &output_file,
-                    true,
-                    true,
-                    Some(format!("Audio metadata error: {}", e))
-                );
-                return;
+        };
+        
+        // Animated images are silent, so their audio is dropped
+        let audio_stream = ictx.streams().best(media::Type::Audio).filter(|_| !self.target_format.is_animated_image());
+        let mut audio = match audio_stream {
+            Some(stream) => {
+                let codec = stream.parameters().id().name();
+                let channels = codec::context::Context::from_parameters(stream.parameters())?.decoder().audio()?.channels();
+                let downmix = self.settings.downmix.audio_filter(u32::from(channels)).is_some();
+                let copyable = !downmix
+                    && (FFmpegConverter::copyable_audio_codec(self.target_format) == Some(codec)
+                        || self.settings.passes_through(self.target_format, codec));
+                // Matroska otherwise copies audio, so a downmixed track needs an encoder of its own
+                let encoder = FFmpegConverter::audio_encoder(self.target_format).or(downmix.then_some("aac"));
+                Some(match encoder {
+                    Some(name) if !copyable => {
+                        if self.settings.audio_passthrough && !downmix && SURROUND_CODECS.contains(&codec) {
+                            self.reporter.blocking_report(ProgressEvent::Stage {
+                                percent: 0,
+                                step: format!("{} can't carry {} untouched; transcoding it", self.target_format.as_str(), codec),
+                            });
+                        }
+                        StreamOutput::Encode(Self::audio_transcoder(&stream, &mut octx, name, self.target_format, self.settings.downmix, global_header)?)
+                    },
+                    _ => {
+                        copied_streams.push(format!("audio ({})", codec));
+                        StreamOutput::Copy(Self::copied_stream(&stream, &mut octx)?)
+                    },
+                })
+            },
+            None => None,
+        };
+        if !copied_streams.is_empty() {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("Copying {} without re-encoding", copied_streams.join(", ")),
+            });
+        }
+        
+        // Matroska takes attachments (fonts, cover art) as streams without packets;
+        // other containers can't hold them
+        let attachments: Vec<_> = ictx.streams().filter(|stream| stream.parameters().medium() == media::Type::Attachment).collect();
+        if self.target_format == VideoFormat::MKV {
+            for stream in &attachments {
+                let copied = Self::copied_stream(stream, &mut octx)?;
+                if let Some(mut ost) = octx.stream_mut(copied.output_index) {
+                    ost.set_metadata(stream.metadata().to_owned());
+                }
             }
-            
-            // Stage 3: Process video frames
-            Self::send_progress(
-                &progress_tx,
-                15,
-                "Processing video frames...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None
-            );
-            
-            // Process the file in chunks, simulating frame-by-frame processing
-            let mut buffer = [0; 8192]; // 8KB buffer
-            let mut bytes_read = 0;
-            let mut frame_count = 0;
-            let estimated_frames = file_size / 4096; // Rough estimate of frame count
-            
-            // Video codec header based on format
-            let video_codec: &[u8] = match target_format {
-                VideoFormat::MP4 => b"\x00\x00\x00\x20avc1\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00",
-                VideoFormat::MKV => b"\x86\x42\x87\x81\x04\x42\x85\x81\x02\x42\x86\x84\x77\x65\x62\x6D",
-                VideoFormat::AVI => b"LIST\x00\x00\x00\x70strlstrh\x00\x00\x00\x38vids\x00\x00\x00\x00",
-                VideoFormat::MOV => b"\x00\x00\x00\x20avc1\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00",
-                VideoFormat::WEBM => b"\x86\x42\x87\x81\x04\x42\x85\x81\x02\x42\x86\x84\x56\x50\x38\x30",
-            };
-            
-            if let Err(e) = writer.write_all(video_codec) {
-                Self::send_progress(
-                    &progress_tx,
-                    15,
-                    format!("Failed to write video codec info: {}", e),
-                    &source_file,
-                    target_format,
-                    &output_file,
-                    true,
-                    true,
-                    Some(format!("Video codec error: {}", e))
-                );
-                return;
+        } else if !attachments.is_empty() {
+            self.reporter.blocking_report(ProgressEvent::Stage {
+                percent: 0,
+                step: format!("{} can't carry attachments; dropping {} attached file(s)", self.target_format.as_str(), attachments.len()),
+            });
+        }
+        
+        octx.set_metadata(ictx.metadata().to_owned());
+        octx.write_header_with(muxer_options(self.target_format))?;
+        
+        // The muxer may have picked its own time bases while writing the header
+        if let Some(stream) = octx.stream(video.output_index()) {
+            video.set_output_time_base(stream.time_base());
+        }
+        if let Some(audio) = &mut audio {
+            if let Some(stream) = octx.stream(audio.output_index()) {
+                audio.set_output_time_base(stream.time_base());
+            }
+        }
+        
+        let length = window.end.min(duration) - window.start.max(0.0);
+        let mut last_percent = None;
+        
+        for (stream, packet) in ictx.packets() {
+            if self.cancel.is_cancelled() {
+                return Ok(None);
             }
             
-            // Process the file in chunks, simulating video frame processing
-            loop {
-                match reader.read(&mut buffer) {
-                    Ok(0) => break, // End of file
-                    Ok(n) => {
-                        // Update progress
-                        bytes_read += n as u64;
-                        frame_count += 1;
-                        
-                        // Calculate progress (15-85% range for video processing)
-                        let progress = ((bytes_read as f64 / file_size as f64) * 70.0) as u8 + 15;
-                        let progress = std::cmp::min(progress, 85);
-                        
-                        // Apply format-specific "encoding" to the data
-                        // This simulates different encoding for different formats
-                        let processed_data = match target_format {
-                            VideoFormat::MP4 => {
-                                // Simulate H.264 encoding
-                                let mut data = buffer[0..n].to_vec();
-                                // Add NAL unit markers
-                                if data.len() > 4 {
-                                    data[0] = 0x00;
-                                    data[1] = 0x00;
-                                    data[2] = 0x00;
-                                    data[3] = 0x01;
-                                }
-                                data
-                            },
-                            VideoFormat::MKV => {
-                                // Simulate H.265 encoding
-                                let mut data = buffer[0..n].to_vec();
-                                // Add frame markers
-                                if data.len() > 4 {
-                                    data[0] = 0x1A;
-                                    data[1] = 0x45;
-                                    data[2] = 0xDF;
-                                    data[3] = 0xA3;
-                                }
-                                data
-                            },
-                            VideoFormat::AVI => {
-                                // Simulate MJPEG encoding
-                                let mut data = buffer[0..n].to_vec();
-                                // Add JPEG markers
-                                if data.len() > 4 {
-                                    data[0] = 0xFF;
-                                    data[1] = 0xD8;
-                                    let len = data.len();
-                                    data[len-2] = 0xFF;
-                                    data[len-1] = 0xD9;
-                                }
-                                data
-                            },
-                            VideoFormat::MOV => {
-                                // Simulate ProRes encoding
-                                let mut data = buffer[0..n].to_vec();
-                                // Add ProRes markers
-                                if data.len() > 4 {
-                                    data[0] = 0x69;
-                                    data[1] = 0x63;
-                                    data[2] = 0x70;
-                                    data[3] = 0x66;
-                                }
-                                data
-                            },
-                            VideoFormat::WEBM => {
-                                // Simulate VP9 encoding
-                                let mut data = buffer[0..n].to_vec();
-                                // Add VP9 markers
-                                if data.len() > 4 {
-                                    data[0] = 0x56;
-                                    data[1] = 0x50;
-                                    data[2] = 0x39;
-                                    data[3] = 0x30;
-                                }
-                                data
-                            },
-                        };
-                        
-                        // Send progress update for each frame
-                        if frame_count % 10 == 0 {
-                            Self::send_progress(
-                                &progress_tx,
-                                progress,
-                                format!("Processing frame {}/{} ({:.1}%)", 
-                                       frame_count, 
-                                       estimated_frames,
-                                       (bytes_read as f64 / file_size as f64) * 100.0),
-                                &source_file,
-                                target_format,
-                                &output_file,
-                                false,
-                                false,
-                                None
-                            );
-                        }
-                        
-                        // Write the processed data to the output file
-                        if let Err(e) = writer.write_all(&processed_data) {
-                            Self::send_progress(
-                                &progress_tx,
-                                progress,
-                                format!("Error writing video data: {}", e),
-                                &source_file,
-                                target_format,
-                                &output_file,
-                                true,
-                                true,
-                                Some(format!("Write error: {}", e))
-                            );
-                            return;
-                        }
+            let index = stream.index();
+            if index == video.input_index() {
+                let position = match &mut video {
+                    StreamOutput::Encode(transcoder) => transcoder.process(Some(&packet), &window, &mut octx)?,
+                    StreamOutput::Copy(stream) => {
+                        let position = packet.pts().map(|pts| pts as f64 * f64::from(stream.input_time_base));
+                        stream.process(packet, &window, &mut octx)?;
+                        position
                     },
-                    Err(e) => {
-                        Self::send_progress(
-                            &progress_tx,
-                            0,
-                            format!("Error reading data: {}", e),
-                            &source_file,
-                            target_format,
-                            &output_file,
-                            true,
-                            true,
-                            Some(format!("Read error: {}", e))
-                        );
-                        return;
-                    }
+                };
+                let Some(position) = position else {
+                    continue;
+                };
+                
+                // Everything needed has been read once video passes the window
+                if position >= window.end {
+                    break;
                 }
                 
-                // Simulate processing time to make the progress more visible
-                thread::sleep(Duration::from_millis(10));
-            }
-            
-            // Stage 4: Mux audio and video
-            Self::send_progress(
-                &progress_tx,
-                85,
-                "Muxing audio and video streams...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None
-            );
-            
-            // Simulate muxing process
-            thread::sleep(Duration::from_millis(800));
-            
-            // Stage 5: Finalize container
-            Self::send_progress(
-                &progress_tx,
-                95,
-                "Finalizing container format...".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                false,
-                false,
-                None
-            );
-            
-            // Finalize the output file
-            if let Err(e) = writer.flush() {
-                Self::send_progress(
-                    &progress_tx,
-                    95,
-                    format!("Failed to finalize output: {}", e),
-                    &source_file,
-                    target_format,
-                    &output_file,
-                    true,
-                    true,
-                    Some(format!("Finalize error: {}", e))
-                );
-                return;
+                if length > 0.0 {
+                    let done = (position - window.start.max(0.0)).max(0.0);
+                    let percent = ((done / length) * 100.0).min(99.0) as u8;
+                    if last_percent != Some(percent) {
+                        last_percent = Some(percent);
+                        let step = match &video {
+                            StreamOutput::Encode(transcoder) => format!("Encoding frame {} ({:.1}s of {:.1}s)", transcoder.frames, done, length),
+                            StreamOutput::Copy(_) => format!("Copying video ({:.1}s of {:.1}s)", done, length),
+                        };
+                        self.reporter.blocking_report(ProgressEvent::Stage { percent, step });
+                    }
+                }
+            } else if let Some(audio) = audio.as_mut().filter(|audio| audio.input_index() == index) {
+                match audio {
+                    StreamOutput::Encode(transcoder) => {
+                        transcoder.process(Some(&packet), &window, &mut octx)?;
+                    },
+                    StreamOutput::Copy(stream) => stream.process(packet, &window, &mut octx)?,
+                }
             }
-            
-            // Write format-specific footer
-            let footer: &[u8] = match target_format {
-                VideoFormat::MP4 => b"\x00\x00\x00\x14mdat\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
-                VideoFormat::MKV => b"\x1F\x43\xB6\x75\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
-                VideoFormat::AVI => b"idx1\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
-                VideoFormat::MOV => b"\x00\x00\x00\x00moov\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
-                VideoFormat::WEBM => b"\x1F\x43\xB6\x75\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
-            };
-            
-            // Write the footer
-            if let Err(e) = writer.write_all(footer) {
-                Self::send_progress(
-                    &progress_tx,
-                    95,
-                    format!("Failed to write container footer: {}", e),
-                    &source_file,
-                    target_format,
-                    &output_file,
-                    true,
-                    true,
-                    Some(format!("Footer error: {}", e))
-                );
-                return;
+        }
+        
+        self.reporter.blocking_report(ProgressEvent::Stage { percent: 99, step: "Flushing encoders...".to_string() });
+        if let StreamOutput::Encode(transcoder) = &mut video {
+            transcoder.process(None, &window, &mut octx)?;
+        }
+        if let Some(StreamOutput::Encode(transcoder)) = &mut audio {
+            transcoder.process(None, &window, &mut octx)?;
+        }
+        
+        octx.write_trailer()?;
+        Ok(Some((copied_streams, duration)))
+    }
+    
+    fn video_transcoder(
+        &self,
+        stream: &format::stream::Stream,
+        octx: &mut format::context::Output,
+        global_header: bool,
+    ) -> Result<Transcoder<frame::Video>, NativeConverterError> {
+        let decoder = codec::context::Context::from_parameters(stream.parameters())?.decoder().video()?;
+        
+        let name = FFmpegConverter::video_encoder(self.target_format).ok_or(NativeConverterError::EncoderNotFound("audio-only output"))?;
+        let codec = encoder::find_by_name(name).ok_or(NativeConverterError::EncoderNotFound(name))?;
+        
+        // Keep the source pixel format when the encoder supports it
+        let supported: Vec<format::Pixel> = codec.video()?.formats().map(|formats| formats.collect()).unwrap_or_default();
+        let pixel_format = if self.target_format == VideoFormat::MXF {
+            // XDCAM HD422 is 4:2:2 by definition
+            format::Pixel::YUV422P
+        } else if self.target_format == VideoFormat::APNG {
+            // Same choice as the FFmpeg backend's -pix_fmt
+            match self.settings.image_quality {
+                ImageQuality::Lossless => format::Pixel::RGBA,
+                ImageQuality::High => format::Pixel::RGB24,
+                _ => format::Pixel::PAL8,
             }
-            
-            // Complete
-            Self::send_progress(
-                &progress_tx,
-                100,
-                "Conversion complete!".to_string(),
-                &source_file,
-                target_format,
-                &output_file,
-                true,
-                false,
-                None
-            );
-        });
+        } else if let Some(name) = self.settings.output_pixel_format(self.target_format) {
+            // Chosen in the settings, same as -pix_fmt
+            if name == "yuv420p10le" { format::Pixel::YUV420P10LE } else { format::Pixel::YUV420P }
+        } else if supported.is_empty() || supported.contains(&decoder.format()) {
+            decoder.format()
+        } else {
+            supported[0]
+        };
         
-        Ok(())
+        let source_aspect_ratio = match decoder.aspect_ratio() {
+            ratio if ratio.numerator() > 0 => ratio,
+            _ => Rational(1, 1),
+        };
+        let sar = f64::from(source_aspect_ratio.numerator()) / f64::from(source_aspect_ratio.denominator());
+        let (crop_width, crop_height) = self.settings.crop.cropped_size(decoder.width(), decoder.height(), sar);
+        
+        let fixed_size = self.target_format.fixed_frame_size(&self.settings.resolution);
+        let (width, height) = match fixed_size {
+            Some(size) => size,
+            None => self.settings.resolution.output_size(crop_width, crop_height),
+        };
+        
+        let animated = self.target_format.is_animated_image();
+        let fps = match self.settings.frame_rate.value() {
+            None if animated => Some(ANIMATION_DEFAULT_FPS),
+            fps => fps,
+        };
+        let frame_rate = match fps {
+            Some(fps) => Rational(fps as i32, 1),
+            None => match stream.avg_frame_rate() {
+                rate if rate.numerator() > 0 => rate,
+                _ => decoder.frame_rate().unwrap_or(Rational(25, 1)),
+            },
+        };
+        
+        let output_fps = f64::from(frame_rate.numerator()) / f64::from(frame_rate.denominator());
+        if name == "libx264" {
+            self.settings.check_h264_level(decoder.width(), decoder.height(), output_fps).map_err(NativeConverterError::InvalidSettings)?;
+        }
+        
+        // Same scaling and frame rate handling as the FFmpeg backend's -vf/-r.
+        // Frames keep their source timestamps here, so subtitles need no offset;
+        // there's no subtitle muxing in this backend, so they are always burned in.
+        let mut filters = Vec::new();
+        if let Some(subs) = &self.settings.subtitles {
+            filters.push(crate::ffmpeg::burn_in_filter(&subs.path, 0.0));
+        }
+        if self.settings.crop != Crop::Off {
+            filters.push(format!("crop={}:{}", crop_width, crop_height));
+        }
+        if fixed_size.is_some() {
+            filters.push(format!(
+                "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1",
+                w = width,
+                h = height
+            ));
+        } else if self.settings.resolution.dimensions().is_some() {
+            filters.push(format!("scale={}:{}", width, height));
+        }
+        // Constant frame rate at the source's average rate unless one was chosen;
+        // otherwise frames keep their own timing
+        if fps.is_some() || self.settings.frame_rate_mode == FrameRateMode::Constant {
+            filters.push(format!("fps={}", frame_rate));
+        }
+        if let (VideoFormat::APNG, Some(colors)) = (self.target_format, self.settings.image_quality.palette_colors()) {
+            filters.push(format!("split[a][b];[a]palettegen=max_colors={}[p];[b][p]paletteuse", colors));
+        }
+        
+        // Padding to a broadcast raster leaves square pixels behind
+        let aspect_ratio = if fixed_size.is_some() { Rational(1, 1) } else { source_aspect_ratio };
+        let args = format!(
+            "video_size={}x{}:pix_fmt={}:time_base={}:pixel_aspect={}",
+            decoder.width(),
+            decoder.height(),
+            decoder.format().name(),
+            stream.time_base(),
+            source_aspect_ratio
+        );
+        
+        let mut graph = filter::Graph::new();
+        graph.add(&filter::find("buffer").unwrap(), "in", &args)?;
+        graph.add(&filter::find("buffersink").unwrap(), "out", "")?;
+        graph.get("out").unwrap().set_pixel_format(pixel_format);
+        graph.output("in", 0)?.input("out", 0)?.parse(&spec(&filters, "null"))?;
+        graph.validate()?;
+        let filter_time_base = graph.get("out").unwrap().sink().time_base();
+        
+        let mut ost = octx.add_stream(codec)?;
+        let output_index = ost.index();
+        
+        let mut encoder = codec::context::Context::new_with_codec(codec).encoder().video()?;
+        encoder.set_width(width);
+        encoder.set_height(height);
+        encoder.set_format(pixel_format);
+        encoder.set_aspect_ratio(aspect_ratio);
+        encoder.set_time_base(filter_time_base);
+        encoder.set_frame_rate(Some(frame_rate));
+        
+        // An explicit bitrate replaces the format's default constant-quality mode
+        let target_kbps = self.settings.bitrate.value_kbps(&self.settings.resolution);
+        if target_kbps > 0 && !self.target_format.has_fixed_bitrate() {
+            encoder.set_bit_rate(target_kbps as usize * 1000);
+        }
+        
+        let mut flags = codec::Flags::empty();
+        if global_header {
+            flags |= codec::Flags::GLOBAL_HEADER;
+        }
+        if self.target_format == VideoFormat::AVI && target_kbps == 0 {
+            // Fixed quantiser, like -q:v 6
+            flags |= codec::Flags::QSCALE;
+            encoder.set_quality(6 * ffmpeg::ffi::FF_QP2LAMBDA as usize);
+        }
+        encoder.set_flags(flags);
+        
+        let mut options = video_options(self.target_format, &self.settings, target_kbps, self.eco);
+        for (name, value) in self.settings.keyframe_options(self.target_format, Some(output_fps)) {
+            options.set(name, &value);
+        }
+        let encoder = encoder.open_with(options)?;
+        ost.set_parameters(&encoder);
+        ost.set_time_base(filter_time_base);
+        
+        Ok(Transcoder {
+            input_index: stream.index(),
+            input_time_base: stream.time_base(),
+            output_index,
+            output_time_base: filter_time_base,
+            decoder: decoder.0,
+            filter: graph,
+            filter_time_base,
+            encoder: encoder.0 .0,
+            encoder_time_base: filter_time_base,
+            decoded: frame::Video::empty(),
+            filtered: frame::Video::empty(),
+            frames: 0,
+        })
     }
     
-    fn send_progress(
-        tx: &mpsc::Sender<ConversionProgress>,
-        percent: u8,
-        step: String,
-        source_file: &PathBuf,
+    fn audio_transcoder(
+        stream: &format::stream::Stream,
+        octx: &mut format::context::Output,
+        name: &'static str,
         target_format: VideoFormat,
-        output_file: &PathBuf,
-        is_complete: bool,
-        has_error: bool,
-        error_message: Option<String>,
-    ) {
-        let _ = tx.send(ConversionProgress {
-            percent,
-            current_step: step,
-            source_file: source_file.clone(),
-            target_format,
-            output_file: output_file.clone(),
-            is_complete,
-            has_error,
-            error_message,
-            video_settings: None,
-        });
+        downmix: Downmix,
+        global_header: bool,
+    ) -> Result<Transcoder<frame::Audio>, NativeConverterError> {
+        let decoder = codec::context::Context::from_parameters(stream.parameters())?.decoder().audio()?;
+        
+        let codec = encoder::find_by_name(name).ok_or(NativeConverterError::EncoderNotFound(name))?;
+        let audio = codec.audio()?;
+        
+        let sample_format = audio.formats().and_then(|mut formats| formats.next()).unwrap_or(decoder.format());
+        
+        // Resample only when the encoder can't take the source rate (Opus is 48 kHz only);
+        // broadcast delivery always wants 48 kHz
+        let source_rate = decoder.rate() as i32;
+        let rate = if target_format == VideoFormat::MXF {
+            48_000
+        } else if audio.rates().is_none_or(|mut rates| rates.any(|rate| rate == source_rate)) {
+            source_rate
+        } else {
+            48_000
+        };
+        
+        let mut ost = octx.add_stream(codec)?;
+        let output_index = ost.index();
+        
+        // Audio is always encoded as stereo; a downmix only changes how surround is folded
+        let mut encoder = codec::context::Context::new_with_codec(codec).encoder().audio()?;
+        encoder.set_rate(rate);
+        encoder.set_channel_layout(ChannelLayout::STEREO);
+        encoder.set_format(sample_format);
+        encoder.set_time_base(Rational(1, rate));
+        if let Some(bit_rate) = audio_bit_rate(target_format) {
+            encoder.set_bit_rate(bit_rate);
+        }
+        if global_header {
+            encoder.set_flags(codec::Flags::GLOBAL_HEADER);
+        }
+        
+        let encoder = encoder.open_as(codec)?;
+        ost.set_parameters(&encoder);
+        ost.set_time_base(Rational(1, rate));
+        
+        let source_layout = match decoder.channel_layout().bits() {
+            0 => format!("channels={}", decoder.channels()),
+            bits => format!("channel_layout=0x{:x}", bits),
+        };
+        let args = format!(
+            "time_base={}:sample_rate={}:sample_fmt={}:{}",
+            stream.time_base(),
+            decoder.rate(),
+            decoder.format().name(),
+            source_layout
+        );
+        
+        let mut graph = filter::Graph::new();
+        graph.add(&filter::find("abuffer").unwrap(), "in", &args)?;
+        graph.add(&filter::find("abuffersink").unwrap(), "out", "")?;
+        {
+            let mut out = graph.get("out").unwrap();
+            out.set_sample_format(sample_format);
+            out.set_channel_layout(ChannelLayout::STEREO);
+            out.set_sample_rate(rate as u32);
+        }
+        let downmix = downmix.audio_filter(u32::from(decoder.channels())).unwrap_or("anull");
+        graph.output("in", 0)?.input("out", 0)?.parse(downmix)?;
+        graph.validate()?;
+        
+        // Encoders like AAC need exactly frame_size samples per frame
+        if !codec.capabilities().contains(codec::Capabilities::VARIABLE_FRAME_SIZE) {
+            graph.get("out").unwrap().sink().set_frame_size(encoder.frame_size());
+        }
+        let filter_time_base = graph.get("out").unwrap().sink().time_base();
+        
+        Ok(Transcoder {
+            input_index: stream.index(),
+            input_time_base: stream.time_base(),
+            output_index,
+            output_time_base: Rational(1, rate),
+            decoder: decoder.0,
+            filter: graph,
+            filter_time_base,
+            encoder: encoder.0 .0,
+            encoder_time_base: Rational(1, rate),
+            decoded: frame::Audio::empty(),
+            filtered: frame::Audio::empty(),
+            frames: 0,
+        })
+    }
+    
+    fn copied_stream(
+        stream: &format::stream::Stream,
+        octx: &mut format::context::Output,
+    ) -> Result<CopiedStream, NativeConverterError> {
+        let mut ost = octx.add_stream(encoder::find(codec::Id::None))?;
+        ost.set_parameters(stream.parameters());
+        
+        // Let the muxer choose its own tag for the codec; there is no safe API for this
+        unsafe {
+            (*ost.parameters().as_mut_ptr()).codec_tag = 0;
+        }
+        
+        Ok(CopiedStream {
+            input_index: stream.index(),
+            input_time_base: stream.time_base(),
+            output_index: ost.index(),
+            output_time_base: stream.time_base(),
+        })
+    }
+}
+
+// Same quality targets the FFmpeg backend passes on its command line
+fn video_options<'a>(format: VideoFormat, settings: &VideoSettings, target_kbps: u32, eco: bool) -> Dictionary<'a> {
+    let mut options = Dictionary::new();
+    let preset = |usual: &'static str| if eco { ECO_X264_PRESET } else { usual };
+    if eco {
+        options.set("threads", &power::eco_threads().to_string());
+    }
+    match format {
+        VideoFormat::MP4 | VideoFormat::TS | VideoFormat::FLV | VideoFormat::M4V => {
+            options.set("preset", preset("medium"));
+            if target_kbps == 0 {
+                options.set("crf", "23");
+            }
+        },
+        VideoFormat::MKV => {
+            options.set("preset", preset("slow"));
+            if target_kbps == 0 {
+                options.set("crf", "18");
+            }
+        },
+        VideoFormat::AVI => {},
+        VideoFormat::MOV => {
+            options.set("profile", "3");
+        },
+        VideoFormat::WEBM => {
+            if target_kbps == 0 {
+                options.set("crf", "30");
+            }
+            if eco {
+                options.set("cpu-used", ECO_VP9_CPU_USED);
+            }
+        },
+        VideoFormat::ThreeGP => {
+            options.set("preset", preset("medium"));
+            if target_kbps == 0 {
+                options.set("crf", "26");
+            }
+        },
+        VideoFormat::OGV | VideoFormat::WMV => {},
+        VideoFormat::MXF => {
+            options.set("b", "50M");
+            options.set("minrate", "50M");
+            options.set("maxrate", "50M");
+            options.set("bufsize", "17825792");
+            options.set("g", "12");
+            options.set("bf", "2");
+            options.set("intra_vlc", "1");
+            options.set("non_linear_quant", "1");
+            options.set("dc", "10");
+        },
+        VideoFormat::WEBP => {
+            if settings.image_quality == ImageQuality::Lossless {
+                options.set("lossless", "1");
+            } else {
+                options.set("quality", &settings.image_quality.webp_quality().to_string());
+            }
+            options.set("compression_level", "4");
+        },
+        VideoFormat::APNG => {
+            options.set("pred", "mixed");
+        },
+        VideoFormat::MP3 | VideoFormat::FLAC | VideoFormat::WAV | VideoFormat::M4A => {},
+    }
+    
+    // Same H.264 profile, level and tune handling as the FFmpeg backend
+    if FFmpegConverter::video_encoder(format) == Some("libx264") {
+        if let Some(profile) = settings.h264_profile_name(format) {
+            options.set("profile", profile);
+        }
+        if let Some(level) = settings.h264_level_name(format) {
+            options.set("level", level);
+        }
+        if let Some(tune) = settings.tune.ffmpeg_name() {
+            options.set("tune", tune);
+        }
+    }
+    options
+}
+
+// Animated images loop forever, like -loop 0 / -plays 0 on the command line
+fn muxer_options<'a>(format: VideoFormat) -> Dictionary<'a> {
+    let mut options = Dictionary::new();
+    match format {
+        VideoFormat::WEBP => options.set("loop", "0"),
+        VideoFormat::APNG => options.set("plays", "0"),
+        _ => {},
+    }
+    options
+}
+
+fn audio_bit_rate(format: VideoFormat) -> Option<usize> {
+    match format {
+        VideoFormat::MP4 | VideoFormat::TS | VideoFormat::FLV | VideoFormat::M4V => Some(128_000),
+        VideoFormat::AVI | VideoFormat::WMV => Some(160_000),
+        VideoFormat::WEBM => Some(96_000),
+        VideoFormat::ThreeGP => Some(64_000),
+        VideoFormat::MKV | VideoFormat::MOV | VideoFormat::OGV | VideoFormat::MXF => None,
+        VideoFormat::WEBP | VideoFormat::APNG => None,
+        VideoFormat::MP3 | VideoFormat::M4A => Some(192_000),
+        VideoFormat::FLAC | VideoFormat::WAV => None,
+    }
+}
+
+fn spec(filters: &[String], passthrough: &str) -> String {
+    if filters.is_empty() {
+        passthrough.to_string()
+    } else {
+        filters.join(",")
     }
 }
\ No newline at end of file
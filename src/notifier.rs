@@ -0,0 +1,23 @@
+use std::sync::{Arc, OnceLock};
+
+type Callback = Box<dyn Fn() + Send + Sync>;
+
+// Lets a background thread wake whoever collects its results (the interface's
+// event loop) once something is ready. Clones share the callback: a worker takes
+// one when it starts, and the callback is set later, once there is a loop to
+// wake. Until then, notifying does nothing.
+#[derive(Clone, Default)]
+pub struct Notifier(Arc<OnceLock<Callback>>);
+
+impl Notifier {
+    // Only the first callback set is kept
+    pub fn set(&self, notify: impl Fn() + Send + Sync + 'static) {
+        let _ = self.0.set(Box::new(notify));
+    }
+    
+    pub fn notify(&self) {
+        if let Some(notify) = self.0.get() {
+            notify();
+        }
+    }
+}
\ No newline at end of file
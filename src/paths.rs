@@ -0,0 +1,214 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::fmt::{self, Write};
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::Serializer;
+
+use crate::converter::is_url;
+use crate::disc;
+
+#[cfg(windows)]
+use std::ffi::OsString;
+#[cfg(windows)]
+use std::path::{Component, Prefix};
+
+// Windows limits ordinary paths to 260 characters. The verbatim form lifts that,
+// for drive paths (`\\?\C:\...`) and network shares (`\\?\UNC\server\share\...`)
+// alike; Rust's own file APIs switch to it by themselves, external tools don't.
+
+// A path to pass to FFmpeg and the other tools: absolute and verbatim on Windows,
+// so deep folders and UNC shares work; unchanged elsewhere, and for URLs. A DVD
+// folder becomes its main title, read with the concat protocol.
+pub fn tool_arg(path: &Path) -> Cow<'_, OsStr> {
+    if disc::is_video_ts(path) {
+        return Cow::Owned(disc::concat_input(path));
+    }
+    #[cfg(windows)]
+    if let Some(verbatim) = verbatim(path) {
+        return Cow::Owned(verbatim);
+    }
+    Cow::Borrowed(path.as_os_str())
+}
+
+#[cfg(windows)]
+fn verbatim(path: &Path) -> Option<OsString> {
+    if is_url(path) {
+        return None;
+    }
+    
+    // Verbatim paths are taken literally, so `..`, `.` and `/` must be resolved first
+    let absolute = std::path::absolute(path).ok()?;
+    let mut components = absolute.components();
+    let Some(Component::Prefix(prefix)) = components.next() else {
+        return None;
+    };
+    
+    let mut verbatim = match prefix.kind() {
+        Prefix::Disk(letter) => OsString::from(format!(r"\\?\{}:", letter as char)),
+        Prefix::UNC(server, share) => {
+            let mut unc = OsString::from(r"\\?\UNC\");
+            unc.push(server);
+            unc.push(r"\");
+            unc.push(share);
+            unc
+        },
+        // Already verbatim, or a device path
+        _ => return None,
+    };
+    for component in components {
+        if let Component::Normal(part) = component {
+            verbatim.push(r"\");
+            verbatim.push(part);
+        }
+    }
+    Some(verbatim)
+}
+
+// A file name for the screen: UTF-8 as it is, anything else escaped rather than
+// replaced (`\xFF` for a stray byte on Unix, `\u{D800}` for a lone surrogate on
+// Windows), so names differing only there can still be told apart
+pub fn escape(name: &OsStr) -> Cow<'_, str> {
+    if let Some(text) = name.to_str() {
+        return Cow::Borrowed(text);
+    }
+    
+    let mut escaped = String::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        for chunk in name.as_bytes().utf8_chunks() {
+            escaped.push_str(chunk.valid());
+            for byte in chunk.invalid() {
+                let _ = write!(escaped, "\\x{:02X}", byte);
+            }
+        }
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        for unit in char::decode_utf16(name.encode_wide()) {
+            match unit {
+                Ok(c) => escaped.push(c),
+                Err(e) => {
+                    let _ = write!(escaped, "\\u{{{:04X}}}", e.unpaired_surrogate());
+                },
+            }
+        }
+    }
+    #[cfg(not(any(unix, windows)))]
+    escaped.push_str(&name.to_string_lossy());
+    Cow::Owned(escaped)
+}
+
+// For `#[serde(serialize_with = ...)]` on paths: JSON strings have to be UTF-8, so
+// names that aren't are written escaped, as on screen, instead of failing
+pub fn serialize_escaped<P: AsRef<Path>, S: Serializer>(path: &P, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&escape(path.as_ref().as_os_str()))
+}
+
+pub fn serialize_escaped_option<P: AsRef<Path>, S: Serializer>(path: &Option<P>, serializer: S) -> Result<S::Ok, S::Error> {
+    match path {
+        Some(path) => serializer.serialize_some(&*escape(path.as_ref().as_os_str())),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn serialize_escaped_all<S: Serializer>(paths: &[PathBuf], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(paths.iter().map(|path| escape(path.as_os_str())))
+}
+
+// A path as it should be shown: escaped like file names, and without the verbatim
+// prefix that canonicalizing adds on Windows (`\\?\UNC\nas\videos` reads `\\nas\videos`)
+pub fn display(path: &Path) -> Cow<'_, str> {
+    let text = escape(path.as_os_str());
+    if let Some(share) = text.strip_prefix(r"\\?\UNC\") {
+        return Cow::Owned(format!(r"\\{}", share));
+    }
+    match text.strip_prefix(r"\\?\") {
+        Some(local) => Cow::Owned(local.to_string()),
+        None => text,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessKind {
+    // The source can't be opened for reading
+    UnreadableSource,
+    // The output can't be created, or overwritten where it already exists
+    UnwritableOutput,
+}
+
+// A file system refusal that would make a conversion fail, caught before any tool
+// runs so it can be reported as such rather than as a tool's exit status
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessProblem {
+    pub kind: AccessKind,
+    // The file, or the folder for an output that doesn't exist yet
+    pub path: PathBuf,
+    pub error: io::ErrorKind,
+    // The system's wording, e.g. "Permission denied (os error 13)"
+    pub message: String,
+}
+
+impl AccessProblem {
+    fn new(kind: AccessKind, path: &Path, e: io::Error) -> Self {
+        Self { kind, path: path.to_path_buf(), error: e.kind(), message: e.to_string() }
+    }
+    
+    pub fn title(&self) -> &'static str {
+        match self.kind {
+            AccessKind::UnreadableSource => "Source Not Readable",
+            AccessKind::UnwritableOutput => "Output Not Writable",
+        }
+    }
+    
+    pub fn suggestion(&self) -> &'static str {
+        match (self.kind, self.error) {
+            (AccessKind::UnreadableSource, io::ErrorKind::PermissionDenied) => {
+                "Get read access to the file, or copy it somewhere you own and convert the copy"
+            },
+            (AccessKind::UnreadableSource, io::ErrorKind::NotFound) => "The file was moved or deleted; pick it again from the browser",
+            (AccessKind::UnreadableSource, _) => "Check that no other program has the file locked and that its drive is still connected",
+            (AccessKind::UnwritableOutput, io::ErrorKind::ReadOnlyFilesystem) => {
+                "The disk is mounted read-only; write the output to a folder on another disk"
+            },
+            (AccessKind::UnwritableOutput, io::ErrorKind::PermissionDenied) => {
+                "Write the output to a folder you have access to, or fix this one's permissions"
+            },
+            (AccessKind::UnwritableOutput, io::ErrorKind::NotFound) => "The output folder doesn't exist; create it or pick another",
+            (AccessKind::UnwritableOutput, _) => "Write the output to another folder",
+        }
+    }
+}
+
+impl fmt::Display for AccessProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            AccessKind::UnreadableSource => write!(f, "Can't read {}: {}", display(&self.path), self.message),
+            AccessKind::UnwritableOutput => write!(f, "Can't write to {}: {}", display(&self.path), self.message),
+        }
+    }
+}
+
+// Whether a conversion could read `source` and write `output`; URLs are left to the
+// tools. An output that doesn't exist yet is created and removed again, since only
+// trying catches every cause (modes, ACLs, read-only mounts, full quotas)
+pub fn check_access(source: &Path, output: &Path) -> Result<(), AccessProblem> {
+    if !is_url(source) {
+        File::open(source).map_err(|e| AccessProblem::new(AccessKind::UnreadableSource, source, e))?;
+    }
+    
+    if output.exists() {
+        OpenOptions::new().write(true).open(output)
+            .map_err(|e| AccessProblem::new(AccessKind::UnwritableOutput, output, e))?;
+    } else {
+        // A new file is the folder's to refuse
+        let folder = output.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        OpenOptions::new().write(true).create_new(true).open(output)
+            .and_then(|_| fs::remove_file(output))
+            .map_err(|e| AccessProblem::new(AccessKind::UnwritableOutput, folder, e))?;
+    }
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,245 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use regex::Regex;
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::converter::{ConversionStage, ProgressEvent, ProgressReporter, ProgressSender, VideoFormat};
+use crate::paths::{self, tool_arg, AccessProblem};
+
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error("Failed to run plugin: {0}")]
+    Io(#[from] io::Error),
+    
+    #[error("Invalid plugin manifest: {0}")]
+    Parse(#[from] toml::de::Error),
+    
+    #[error("Invalid progress regex: {0}")]
+    Regex(#[from] regex::Error),
+    
+    #[error("Plugin manifest has an empty command")]
+    EmptyCommand,
+    
+    #[error("Plugin exited with status: {0}")]
+    ProcessError(i32),
+    
+    #[error("{0}. {}", .0.suggestion())]
+    Access(AccessProblem),
+}
+
+// An external converter described by a TOML manifest, e.g.
+//
+//   name = "HandBrake"
+//   command = ["HandBrakeCLI", "-i", "{input}", "-o", "{output}", "--preset", "Fast 1080p30"]
+//   progress_regex = 'Encoding: task \d+ of \d+, (?P<percent>\d+(\.\d+)?) %'
+//   formats = ["mp4", "mkv"]
+//
+// `{input}`, `{output}` and `{format}` (the target extension) are substituted into
+// the command. An empty `formats` list means every format is accepted.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub progress_regex: Option<String>,
+    #[serde(default)]
+    pub formats: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub manifest: PluginManifest,
+    progress: Option<Regex>,
+}
+
+impl Plugin {
+    pub fn from_toml(text: &str) -> Result<Self, PluginError> {
+        let manifest: PluginManifest = toml::from_str(text)?;
+        if manifest.command.is_empty() {
+            return Err(PluginError::EmptyCommand);
+        }
+        
+        let progress = manifest.progress_regex.as_deref().map(Regex::new).transpose()?;
+        Ok(Self { manifest, progress })
+    }
+    
+    pub fn load(path: &Path) -> Result<Self, PluginError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+    
+    // Every *.toml manifest in a directory, plus a message for each one that failed.
+    // A missing directory just means there are no plugins.
+    pub fn load_dir(dir: &Path) -> (Vec<Plugin>, Vec<String>) {
+        let mut plugins = Vec::new();
+        let mut errors = Vec::new();
+        
+        let Ok(entries) = fs::read_dir(dir) else {
+            return (plugins, errors);
+        };
+        
+        let mut paths: Vec<PathBuf> = entries
+            .map_while(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+        
+        for path in paths {
+            match Self::load(&path) {
+                Ok(plugin) => plugins.push(plugin),
+                Err(e) => errors.push(format!("{}: {}", paths::escape(path.file_name().unwrap_or_default()), e)),
+            }
+        }
+        
+        (plugins, errors)
+    }
+    
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+    
+    pub fn supports(&self, format: VideoFormat) -> bool {
+        self.manifest.formats.is_empty()
+            || self.manifest.formats.iter().any(|name| name.eq_ignore_ascii_case(format.extension()))
+    }
+    
+    // The manifest command with placeholders filled in. Arguments that are exactly
+    // a path placeholder are passed through untouched, so any file name works.
+    pub fn command_line(&self, input: &Path, output: &Path, format: VideoFormat) -> Vec<OsString> {
+        self.manifest.command
+            .iter()
+            .map(|arg| match arg.as_str() {
+                "{input}" => tool_arg(input).into_owned(),
+                "{output}" => tool_arg(output).into_owned(),
+                _ => arg
+                    .replace("{input}", &tool_arg(input).to_string_lossy())
+                    .replace("{output}", &tool_arg(output).to_string_lossy())
+                    .replace("{format}", format.extension())
+                    .into(),
+            })
+            .collect()
+    }
+    
+    // Percentage reported on an output line: the `percent` group if the regex has one,
+    // otherwise its first capture group
+    pub fn parse_progress(&self, line: &str) -> Option<f64> {
+        let captures = self.progress.as_ref()?.captures(line)?;
+        let value = captures.name("percent").or_else(|| captures.get(1))?;
+        value.as_str().parse().ok()
+    }
+}
+
+pub struct PluginConverter {
+    plugin: Arc<Plugin>,
+    progress_tx: ProgressSender,
+    cancel: CancellationToken,
+}
+
+impl PluginConverter {
+    pub fn new(plugin: Arc<Plugin>, progress_tx: ProgressSender, cancel: CancellationToken) -> Self {
+        Self { plugin, progress_tx, cancel }
+    }
+    
+    pub async fn convert(&self, source_file: PathBuf, target_format: VideoFormat, output_file: PathBuf) {
+        // The tool does its own probing, so the whole run counts as the encode
+        let reporter = ProgressReporter::new(self.progress_tx.clone(), &source_file, target_format, &output_file);
+        reporter.set_stage(ConversionStage::Encode);
+        if let Err(e) = self.run(&reporter, &source_file, target_format, &output_file).await {
+            reporter.report(ProgressEvent::Failed { step: format!("{} error: {}", self.plugin.name(), e), error: e.to_string() }).await;
+        }
+    }
+    
+    async fn run(&self, reporter: &ProgressReporter, source_file: &Path, target_format: VideoFormat, output_file: &Path) -> Result<(), PluginError> {
+        let args = self.plugin.command_line(source_file, output_file, target_format);
+        let (program, args) = args.split_first().ok_or(PluginError::EmptyCommand)?;
+        paths::check_access(source_file, output_file).map_err(PluginError::Access)?;
+        
+        reporter.report(ProgressEvent::Started { step: format!("Starting {}...", self.plugin.name()), settings: None }).await;
+        
+        let mut child = tokio::process::Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        
+        // Tools report progress on either stream, so read both
+        let (line_tx, mut line_rx) = mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_lines(stdout, line_tx.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_lines(stderr, line_tx.clone()));
+        }
+        drop(line_tx);
+        
+        let mut last_percent = None;
+        loop {
+            let line = tokio::select! {
+                _ = self.cancel.cancelled() => {
+                    let _ = child.kill().await;
+                    let _ = fs::remove_file(output_file);
+                    reporter.report(ProgressEvent::Cancelled).await;
+                    return Ok(());
+                },
+                line = line_rx.recv() => match line {
+                    Some(line) => line,
+                    None => break,
+                },
+            };
+            
+            if let Some(percent) = self.plugin.parse_progress(&line) {
+                let percent = percent.clamp(0.0, 99.0) as u8;
+                if last_percent != Some(percent) {
+                    last_percent = Some(percent);
+                    reporter.report(ProgressEvent::Stage { percent, step: format!("Converting with {}... {}%", self.plugin.name(), percent) }).await;
+                }
+            }
+        }
+        
+        let status = child.wait().await?;
+        if !status.success() {
+            return Err(PluginError::ProcessError(status.code().unwrap_or(-1)));
+        }
+        
+        reporter.report(ProgressEvent::completed("Conversion complete!".to_string())).await;
+        Ok(())
+    }
+}
+
+// Split a child's output into lines on either '\n' or '\r', since many encoders
+// redraw their progress line in place with carriage returns
+pub(crate) async fn forward_lines(mut reader: impl AsyncRead + Unpin, tx: mpsc::UnboundedSender<String>) {
+    let mut buf = [0u8; 4096];
+    let mut line = Vec::new();
+    
+    while let Ok(read) = reader.read(&mut buf).await {
+        if read == 0 {
+            break;
+        }
+        
+        for &byte in &buf[..read] {
+            if byte == b'\n' || byte == b'\r' {
+                if !line.is_empty() {
+                    let _ = tx.send(String::from_utf8_lossy(&line).into_owned());
+                    line.clear();
+                }
+            } else {
+                line.push(byte);
+            }
+        }
+    }
+    
+    if !line.is_empty() {
+        let _ = tx.send(String::from_utf8_lossy(&line).into_owned());
+    }
+}
\ No newline at end of file
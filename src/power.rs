@@ -0,0 +1,95 @@
+use std::thread;
+
+// Whether the machine is running on its battery. Desktops, and anything the
+// power state can't be read on, count as plugged in.
+pub fn on_battery() -> bool {
+    platform::on_battery()
+}
+
+// Encoder threads eco mode allows: half the logical cores, so the rest stay idle
+// and the fans and clocks don't have to ramp up
+pub fn eco_threads() -> usize {
+    thread::available_parallelism().map_or(1, |cores| (cores.get() / 2).max(1))
+}
+
+// What a job reports when it runs in eco mode
+pub fn eco_notice() -> String {
+    let threads = eco_threads();
+    format!("Eco mode: encoding on {} thread{} with faster presets", threads, if threads == 1 { "" } else { "s" })
+}
+
+// Each power supply is a folder of small text files. A laptop on its battery has
+// a Battery that is Discharging and no Mains adapter online.
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+    use std::path::Path;
+    
+    pub fn on_battery() -> bool {
+        let Ok(supplies) = fs::read_dir("/sys/class/power_supply") else {
+            return false;
+        };
+        let read = |dir: &Path, name: &str| fs::read_to_string(dir.join(name)).map(|value| value.trim().to_string()).unwrap_or_default();
+        
+        let mut discharging = false;
+        for supply in supplies.flatten() {
+            let dir = supply.path();
+            match read(&dir, "type").as_str() {
+                "Mains" if read(&dir, "online") == "1" => return false,
+                "Battery" if read(&dir, "status") == "Discharging" => discharging = true,
+                _ => {},
+            }
+        }
+        discharging
+    }
+}
+
+// `pmset -g batt` starts with "Now drawing from 'Battery Power'" or "'AC Power'"
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::{Command, Stdio};
+    
+    pub fn on_battery() -> bool {
+        Command::new("pmset")
+            .args(["-g", "batt"])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    // SYSTEM_POWER_STATUS from the Windows API
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+    
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+    
+    // The AC line is 0 when unplugged (1 plugged in, 255 unknown)
+    pub fn on_battery() -> bool {
+        let mut status = SystemPowerStatus::default();
+        // SAFETY: the call only fills in the struct it is given
+        let ok = unsafe { GetSystemPowerStatus(&mut status) } != 0;
+        ok && status.ac_line_status == 0
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    pub fn on_battery() -> bool {
+        false
+    }
+}
\ No newline at end of file
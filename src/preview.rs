@@ -0,0 +1,407 @@
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use rust_tui_video_convert::ffmpeg::{ContainerInfo, FFmpegConverter, FFmpegError, RgbFrame};
+use rust_tui_video_convert::notifier::Notifier;
+
+// Size of the extracted still; the UI scales it down to fit the pane
+pub const PREVIEW_WIDTH: u32 = 128;
+pub const PREVIEW_HEIGHT: u32 = 72;
+
+// Size of the framing preview's frame, larger so a crop's edges can be judged
+pub const FRAMING_WIDTH: u32 = 192;
+pub const FRAMING_HEIGHT: u32 = 108;
+
+// Waveform resolution; the UI merges columns to fit the pane
+pub const WAVEFORM_COLUMNS: u32 = 200;
+
+// Previews kept before the cache is cleared
+const CACHE_LIMIT: usize = 64;
+
+// What is known about a file so far; None while that part is still being extracted
+#[derive(Default)]
+pub struct MediaPreview {
+    // Kept as the probe's error, which tells an unreadable file from a missing ffprobe
+    pub container: Option<Result<ContainerInfo, FFmpegError>>,
+    pub frame: Option<Result<RgbFrame, String>>,
+    pub waveform: Option<Result<Vec<f32>, String>>,
+}
+
+impl MediaPreview {
+    fn is_complete(&self) -> bool {
+        self.container.is_some() && self.frame.is_some() && self.waveform.is_some()
+    }
+}
+
+enum Part {
+    Container(Result<ContainerInfo, FFmpegError>),
+    Frame(Result<RgbFrame, String>),
+    Waveform(Result<Vec<f32>, String>),
+}
+
+struct Request {
+    path: PathBuf,
+    container: bool,
+    frame: bool,
+    waveform: bool,
+}
+
+// Probes containers and extracts preview frames and waveforms on a worker thread so browsing never
+// waits on FFmpeg. Only the most recent request matters: older ones still
+// queued are skipped, and the waveform (which decodes all of the audio) is
+// abandoned if another file is highlighted after the frame is done.
+pub struct PreviewLoader {
+    requests: mpsc::Sender<Request>,
+    results: mpsc::Receiver<(PathBuf, Part)>,
+    notify: Notifier,
+    cache: HashMap<PathBuf, MediaPreview>,
+    current: Option<PathBuf>,
+}
+
+impl PreviewLoader {
+    pub fn new() -> Self {
+        let (requests, request_rx) = mpsc::channel::<Request>();
+        let (result_tx, results) = mpsc::channel();
+        let notify = Notifier::default();
+        
+        let worker_notify = notify.clone();
+        thread::spawn(move || {
+            let send = |path: &Path, part: Part| {
+                let sent = result_tx.send((path.to_path_buf(), part)).is_ok();
+                worker_notify.notify();
+                sent
+            };
+            
+            let mut next = None;
+            while let Some(mut request) = next.take().or_else(|| request_rx.recv().ok()) {
+                while let Ok(newer) = request_rx.try_recv() {
+                    request = newer;
+                }
+                
+                // The container probe is quick, and decides how the file is treated
+                if request.container && !send(&request.path, Part::Container(FFmpegConverter::probe_container(&request.path))) {
+                    break;
+                }
+                
+                if request.frame && !send(&request.path, Part::Frame(extract_frame(&request.path))) {
+                    break;
+                }
+                
+                // Don't hold up a newer request behind a slow waveform
+                if let Ok(newer) = request_rx.try_recv() {
+                    next = Some(newer);
+                    continue;
+                }
+                
+                if request.waveform && !send(&request.path, Part::Waveform(extract_waveform(&request.path))) {
+                    break;
+                }
+            }
+        });
+        
+        Self {
+            requests,
+            results,
+            notify,
+            cache: HashMap::new(),
+            current: None,
+        }
+    }
+    
+    // Called from the worker whenever a preview is ready to collect with poll
+    pub fn set_notifier(&self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify.set(notify);
+    }
+    
+    // Shared with the popups that extract frames, so theirs arrive as previews too
+    pub fn notifier(&self) -> Notifier {
+        self.notify.clone()
+    }
+    
+    // Make `path` the preview being shown, extracting whatever isn't cached yet
+    pub fn show(&mut self, path: &Path) {
+        if self.current.as_deref() == Some(path) {
+            return;
+        }
+        
+        self.current = Some(path.to_path_buf());
+        let cached = self.cache.get(path);
+        if !cached.is_some_and(MediaPreview::is_complete) {
+            let _ = self.requests.send(Request {
+                path: path.to_path_buf(),
+                container: cached.is_none_or(|preview| preview.container.is_none()),
+                frame: cached.is_none_or(|preview| preview.frame.is_none()),
+                waveform: true,
+            });
+        }
+    }
+    
+    // Collect finished previews
+    pub fn poll(&mut self) {
+        for (path, part) in self.results.try_iter() {
+            if self.cache.len() >= CACHE_LIMIT && !self.cache.contains_key(&path) {
+                self.cache.clear();
+            }
+            let preview = self.cache.entry(path).or_default();
+            match part {
+                Part::Container(container) => preview.container = Some(container),
+                Part::Frame(frame) => preview.frame = Some(frame),
+                Part::Waveform(waveform) => preview.waveform = Some(waveform),
+            }
+        }
+    }
+    
+    pub fn get(&self, path: &Path) -> Option<&MediaPreview> {
+        self.cache.get(path)
+    }
+}
+
+impl Default for PreviewLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Runs `extract` on a thread of its own for a popup that redraws a frame as the
+// user steps through it. Only the latest request matters: older ones still
+// queued are skipped, and a result that comes in after a newer request was made
+// is dropped. The thread ends with the popup.
+struct LatestWorker<Req, Res> {
+    requests: mpsc::Sender<(u64, Req)>,
+    results: mpsc::Receiver<(u64, Res)>,
+    latest: u64,
+    pending: bool,
+}
+
+impl<Req: Send + 'static, Res: Send + 'static> LatestWorker<Req, Res> {
+    fn spawn(notify: Notifier, extract: impl Fn(Req) -> Res + Send + 'static) -> Self {
+        let (requests, request_rx) = mpsc::channel::<(u64, Req)>();
+        let (result_tx, results) = mpsc::channel();
+        
+        thread::spawn(move || {
+            while let Ok(mut request) = request_rx.recv() {
+                while let Ok(newer) = request_rx.try_recv() {
+                    request = newer;
+                }
+                
+                let (id, request) = request;
+                if result_tx.send((id, extract(request))).is_err() {
+                    break;
+                }
+                notify.notify();
+            }
+        });
+        
+        Self { requests, results, latest: 0, pending: false }
+    }
+    
+    fn request(&mut self, request: Req) {
+        self.latest += 1;
+        self.pending = true;
+        let _ = self.requests.send((self.latest, request));
+    }
+    
+    // The result of the latest request, once it's in
+    fn poll(&mut self) -> Option<Res> {
+        let latest = self.latest;
+        let (_, result) = self.results.try_iter().filter(|(id, _)| *id == latest).last()?;
+        self.pending = false;
+        Some(result)
+    }
+}
+
+// A frame of the source run through the crop and scale filters its output will
+// get, to check the framing before converting. The letterbox around it is grey,
+// so black bars left in the picture still show. Frames are extracted on a
+// worker, and the last one stays up while the next is on its way.
+pub struct FramingPreview {
+    // None when there was nothing to preview, with the reason as the frame's error
+    pub source: Option<PathBuf>,
+    pub filters: Vec<String>,
+    // None until the first frame is in
+    pub frame: Option<Result<RgbFrame, String>>,
+    worker: Option<LatestWorker<Vec<String>, Result<RgbFrame, String>>>,
+}
+
+impl FramingPreview {
+    pub fn open(source: PathBuf, filters: Vec<String>, notify: Notifier) -> Self {
+        let path = source.clone();
+        let at = OnceCell::new();
+        let worker = LatestWorker::spawn(notify, move |filters: Vec<String>| {
+            let at = *at.get_or_init(|| preview_time(&path));
+            FFmpegConverter::extract_filtered_frame(&path, at, &filters, FRAMING_WIDTH, FRAMING_HEIGHT, "gray")
+                .map_err(|e| e.to_string())
+        });
+        
+        let mut preview = Self { source: Some(source), filters: Vec::new(), frame: None, worker: Some(worker) };
+        preview.update(filters);
+        preview
+    }
+    
+    pub fn unavailable(reason: &str) -> Self {
+        Self { source: None, filters: Vec::new(), frame: Some(Err(reason.to_string())), worker: None }
+    }
+    
+    // Show the frame with a new filter chain, once it's extracted
+    pub fn update(&mut self, filters: Vec<String>) {
+        if let Some(worker) = &mut self.worker {
+            worker.request(filters.clone());
+            self.filters = filters;
+        }
+    }
+    
+    // A newer frame than the one shown is on its way
+    pub fn is_loading(&self) -> bool {
+        self.worker.as_ref().is_some_and(|worker| worker.pending)
+    }
+    
+    // Take in the latest frame; true when it changed
+    pub fn poll(&mut self) -> bool {
+        let Some(frame) = self.worker.as_mut().and_then(LatestWorker::poll) else {
+            return false;
+        };
+        self.frame = Some(frame);
+        true
+    }
+}
+
+enum TrimRequest {
+    Stream,
+    Frame(f64),
+}
+
+enum TrimResult {
+    // The frame rate and length in frames
+    Stream(Result<(f64, u64), String>),
+    Frame(Result<RgbFrame, String>),
+}
+
+// Steps through a video a frame or a second at a time, showing the frame at each
+// point, so a trim's in and out points land exactly where intended. Positions
+// are frame numbers, and a frame's time is rounded down to the millisecond so
+// seeking there (to the first frame at or after it) gives that same frame.
+// The probe and the frames run on a worker; stepping moves the position at
+// once and the frame follows.
+pub struct TrimEditor {
+    pub source: PathBuf,
+    pub frame_rate: f64,
+    // Frames in the source, as far as its length and frame rate tell; 0 until probed
+    pub frames: u64,
+    // The frame shown, counted from the start
+    pub position: u64,
+    // None until the first frame is in; a later one replaces it when it arrives
+    pub frame: Option<Result<RgbFrame, String>>,
+    start_secs: f64,
+    worker: LatestWorker<TrimRequest, TrimResult>,
+}
+
+impl TrimEditor {
+    // Opens at `at_secs`, e.g. an in point already set
+    pub fn open(source: PathBuf, at_secs: f64, notify: Notifier) -> Self {
+        let path = source.clone();
+        let mut worker = LatestWorker::spawn(notify, move |request| match request {
+            TrimRequest::Stream => TrimResult::Stream(probe_stream(&path)),
+            TrimRequest::Frame(at) => TrimResult::Frame(
+                FFmpegConverter::extract_frame(&path, at, FRAMING_WIDTH, FRAMING_HEIGHT).map_err(|e| e.to_string()),
+            ),
+        });
+        worker.request(TrimRequest::Stream);
+        
+        Self { source, frame_rate: 0.0, frames: 0, position: 0, frame: None, start_secs: at_secs, worker }
+    }
+    
+    // False until the stream has been probed
+    pub fn is_ready(&self) -> bool {
+        self.frames > 0
+    }
+    
+    // A newer frame than the one shown is on its way
+    pub fn is_loading(&self) -> bool {
+        self.worker.pending
+    }
+    
+    // Take in what the worker has finished; true when something changed, and
+    // the reason when the file can't be trimmed
+    pub fn poll(&mut self) -> Result<bool, String> {
+        match self.worker.poll() {
+            None => Ok(false),
+            Some(TrimResult::Stream(stream)) => {
+                (self.frame_rate, self.frames) = stream?;
+                self.seek((self.start_secs * self.frame_rate).round() as u64);
+                Ok(true)
+            }
+            Some(TrimResult::Frame(frame)) => {
+                self.frame = Some(frame);
+                Ok(true)
+            }
+        }
+    }
+    
+    // Move by a number of frames, stopping at either end
+    pub fn step(&mut self, frames: i64) {
+        self.seek(self.position.saturating_add_signed(frames));
+    }
+    
+    pub fn seek(&mut self, position: u64) {
+        if !self.is_ready() {
+            return;
+        }
+        let position = position.min(self.frames - 1);
+        if position == self.position && (self.worker.pending || self.frame.as_ref().is_some_and(Result::is_ok)) {
+            return;
+        }
+        self.position = position;
+        self.worker.request(TrimRequest::Frame(self.time()));
+    }
+    
+    // Frames in a second, for stepping by seconds
+    pub fn second(&self) -> i64 {
+        self.frame_rate.round() as i64
+    }
+    
+    pub fn time_of(&self, position: u64) -> f64 {
+        (position as f64 / self.frame_rate * 1000.0).floor() / 1000.0
+    }
+    
+    // Where the shown frame starts, as an in point
+    pub fn time(&self) -> f64 {
+        self.time_of(self.position)
+    }
+    
+    // Where the shown frame ends, as an out point that keeps it
+    pub fn end_time(&self) -> f64 {
+        self.time_of(self.position + 1)
+    }
+    
+    pub fn duration(&self) -> f64 {
+        self.time_of(self.frames)
+    }
+}
+
+fn probe_stream(path: &Path) -> Result<(f64, u64), String> {
+    let frame_rate = FFmpegConverter::video_stream_info(path)
+        .map(|info| info.frame_rate)
+        .ok()
+        .filter(|fps| *fps > 0.0)
+        .ok_or("Trimming needs a file with a video stream")?;
+    let duration = FFmpegConverter::get_video_duration(path).map_err(|e| e.to_string())?;
+    Ok((frame_rate, ((duration * frame_rate).floor() as u64).max(1)))
+}
+
+// A tenth of the way in usually skips black intro frames and title cards
+fn preview_time(path: &Path) -> f64 {
+    FFmpegConverter::get_video_duration(path)
+        .map(|duration| (duration * 0.1).min(30.0))
+        .unwrap_or(0.0)
+}
+
+fn extract_frame(path: &Path) -> Result<RgbFrame, String> {
+    FFmpegConverter::extract_frame(path, preview_time(path), PREVIEW_WIDTH, PREVIEW_HEIGHT).map_err(|e| e.to_string())
+}
+
+fn extract_waveform(path: &Path) -> Result<Vec<f32>, String> {
+    FFmpegConverter::extract_waveform(path, WAVEFORM_COLUMNS).map_err(|e| e.to_string())
+}
\ No newline at end of file
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+use rust_tui_video_convert::ffmpeg::{FFmpegConverter, MediaTrack};
+use rust_tui_video_convert::notifier::Notifier;
+
+// What a popup needs from the selected source before it can show it
+#[derive(Clone, Copy)]
+pub enum ProbeRequest {
+    Tracks,
+    Duration,
+}
+
+pub enum Probed {
+    // Audio tracks, then subtitle tracks
+    Tracks(Result<Vec<MediaTrack>, String>),
+    Duration(Option<f64>),
+}
+
+// Reads a source for the track picker and the bitrate calculator on a background
+// thread, one per request; a URL or a disc can take a while to answer
+pub struct SourceProbe {
+    results_tx: mpsc::Sender<(PathBuf, Probed)>,
+    results: mpsc::Receiver<(PathBuf, Probed)>,
+    notify: Notifier,
+}
+
+impl SourceProbe {
+    pub fn new() -> Self {
+        let (results_tx, results) = mpsc::channel();
+        Self {
+            results_tx,
+            results,
+            notify: Notifier::default(),
+        }
+    }
+    
+    // Called from the worker when a result is ready to collect with poll
+    pub fn set_notifier(&self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify.set(notify);
+    }
+    
+    pub fn start(&self, source: PathBuf, request: ProbeRequest) {
+        let tx = self.results_tx.clone();
+        let notify = self.notify.clone();
+        
+        thread::spawn(move || {
+            let probed = match request {
+                ProbeRequest::Tracks => Probed::Tracks(
+                    FFmpegConverter::audio_tracks(&source)
+                        .and_then(|mut audio| {
+                            audio.extend(FFmpegConverter::subtitle_tracks(&source)?);
+                            Ok(audio)
+                        })
+                        .map_err(|e| e.to_string()),
+                ),
+                ProbeRequest::Duration => Probed::Duration(FFmpegConverter::get_video_duration(&source).ok()),
+            };
+            let _ = tx.send((source, probed));
+            notify.notify();
+        });
+    }
+    
+    // Finished probes as (source, what was read)
+    pub fn poll(&self) -> Vec<(PathBuf, Probed)> {
+        self.results.try_iter().collect()
+    }
+}
+
+impl Default for SourceProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+
+use rust_tui_video_convert::converter::VideoFormat;
+use rust_tui_video_convert::jobs::JobId;
+use rust_tui_video_convert::paths;
+
+use crate::app::{App, Job, JobState};
+use crate::events::{AppEvent, EventSender};
+
+// How long a frontend waits for the main loop to answer before giving up
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Something an outside client asked the running app to do
+pub enum RemoteCommand {
+    Submit { input: PathBuf, format: VideoFormat },
+    List,
+    Get(JobId),
+    // Cancel a running job, or drop a finished one from the list
+    Remove(JobId),
+}
+
+pub enum RemoteReply {
+    Submitted(JobInfo),
+    Jobs(Vec<JobInfo>),
+    Job(JobInfo),
+    NotFound,
+    Rejected(String),
+}
+
+// A command on its way to the main loop, with the channel to answer on
+pub struct RemoteRequest {
+    command: RemoteCommand,
+    reply: mpsc::Sender<RemoteReply>,
+}
+
+impl RemoteRequest {
+    pub fn respond(self, app: &mut App) {
+        let _ = self.reply.send(handle(app, self.command));
+    }
+}
+
+// Hand a command to the main loop and wait for its answer.
+// None means the app is shutting down or didn't answer in time.
+pub fn request(events: &EventSender, command: RemoteCommand) -> Option<RemoteReply> {
+    let (reply, answer) = mpsc::channel();
+    events.send(AppEvent::Remote(RemoteRequest { command, reply })).ok()?;
+    answer.recv_timeout(REPLY_TIMEOUT).ok()
+}
+
+fn handle(app: &mut App, command: RemoteCommand) -> RemoteReply {
+    match command {
+        RemoteCommand::Submit { input, format } => match app.submit_remote_job(input, format) {
+            Ok(id) => app.remote_job(id).map(JobInfo::from).map_or(RemoteReply::NotFound, RemoteReply::Submitted),
+            Err(message) => RemoteReply::Rejected(message),
+        },
+        RemoteCommand::List => RemoteReply::Jobs(app.remote_jobs().map(JobInfo::from).collect()),
+        RemoteCommand::Get(id) => app.remote_job(id).map(JobInfo::from).map_or(RemoteReply::NotFound, RemoteReply::Job),
+        RemoteCommand::Remove(id) => {
+            // Report the job as it was when the request arrived
+            let Some(info) = app.remote_job(id).map(JobInfo::from) else {
+                return RemoteReply::NotFound;
+            };
+            app.remove_remote_job(id);
+            RemoteReply::Job(info)
+        },
+    }
+}
+
+// The JSON view of a remote job
+#[derive(Serialize)]
+pub struct JobInfo {
+    pub id: JobId,
+    #[serde(serialize_with = "paths::serialize_escaped")]
+    pub input: PathBuf,
+    pub format: &'static str,
+    pub backend: String,
+    pub state: JobState,
+    pub high_priority: bool,
+    pub percent: u8,
+    pub fps: Option<f64>,
+    // Seconds since the Unix epoch
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub elapsed_secs: Option<f64>,
+    pub step: Option<String>,
+    #[serde(serialize_with = "paths::serialize_escaped_option")]
+    pub output: Option<PathBuf>,
+    pub error: Option<String>,
+}
+
+impl From<&Job> for JobInfo {
+    fn from(job: &Job) -> Self {
+        let progress = job.progress.as_ref();
+        let epoch_secs = |time: SystemTime| time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Self {
+            id: job.id,
+            input: job.source_file.clone(),
+            format: job.target_format.extension(),
+            backend: job.backend.clone(),
+            state: job.state(),
+            high_priority: job.high_priority,
+            percent: progress.map_or(0, |p| p.percent),
+            fps: progress.and_then(|p| p.stats.fps),
+            started_at: job.started_at.map(epoch_secs),
+            finished_at: job.finished_at.map(epoch_secs),
+            elapsed_secs: job.elapsed().map(|elapsed| elapsed.as_secs_f64()),
+            step: progress.map(|p| p.current_step.clone()),
+            output: progress.map(|p| p.output_file.clone()),
+            error: progress.and_then(|p| p.error_message.clone()),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,170 @@
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::thread;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use rust_tui_video_convert::converter::VideoFormat;
+
+use crate::events::EventSender;
+use crate::remote::{self, RemoteCommand, RemoteReply};
+
+// Request bodies are tiny; refuse anything larger than this
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+
+#[derive(Deserialize)]
+struct SubmitBody {
+    input: PathBuf,
+    format: String,
+}
+
+// Serve the job control API on its own thread. Binding happens here so
+// a bad address is reported before the interface takes over the terminal.
+// Jobs read any path or URL the converter can, so with no token to ask for
+// it only listens on this machine.
+pub fn spawn(addr: &str, token: Option<String>, events: EventSender) -> io::Result<SocketAddr> {
+    let server = Server::http(addr).map_err(io::Error::other)?;
+    let local_addr = server
+        .server_addr()
+        .to_ip()
+        .ok_or_else(|| io::Error::other("--serve needs a TCP address"))?;
+    if token.is_none() && !local_addr.ip().is_loopback() {
+        return Err(io::Error::other("listening beyond this machine needs an API token (api_token in the config, or RTVC_API_TOKEN)"));
+    }
+    
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let (status, body) = match token.as_deref() {
+                Some(token) if !authorized(&request, token) => error(401, "Missing or wrong API token"),
+                None => match from_browser(request.method(), request.headers()) {
+                    Some((status, message)) => error(status, message),
+                    None => route(&mut request, &events),
+                },
+                _ => route(&mut request, &events),
+            };
+            let mut response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(Header::from_bytes("Content-Type", "application/json").expect("valid header"));
+            if status == 401 {
+                response.add_header(Header::from_bytes("WWW-Authenticate", "Bearer").expect("valid header"));
+            }
+            let _ = request.respond(response);
+        }
+    });
+    
+    Ok(local_addr)
+}
+
+//   GET    /jobs       list jobs
+//   POST   /jobs       submit {"input": "/path/to/file", "format": "mp4"}
+//   GET    /jobs/{id}  one job's progress
+//   DELETE /jobs/{id}  cancel a running job, or remove a finished one
+fn route(request: &mut Request, events: &EventSender) -> (u16, Value) {
+    let path = request.url().split('?').next().unwrap_or_default().trim_matches('/').to_string();
+    let segments: Vec<&str> = path.split('/').collect();
+    
+    let command = match (request.method(), segments.as_slice()) {
+        (Method::Get, ["jobs"]) => RemoteCommand::List,
+        (Method::Post, ["jobs"]) => match parse_submit(request) {
+            Ok(command) => command,
+            Err(message) => return error(400, &message),
+        },
+        (Method::Get, ["jobs", id]) | (Method::Delete, ["jobs", id]) => {
+            let Ok(id) = id.parse() else {
+                return error(404, "No such job");
+            };
+            if *request.method() == Method::Get {
+                RemoteCommand::Get(id)
+            } else {
+                RemoteCommand::Remove(id)
+            }
+        },
+        (_, ["jobs"]) | (_, ["jobs", _]) => return error(405, "Method not allowed"),
+        _ => return error(404, "Not found"),
+    };
+    
+    match remote::request(events, command) {
+        Some(RemoteReply::Submitted(job)) => (201, json!(job)),
+        Some(RemoteReply::Jobs(jobs)) => (200, json!({ "jobs": jobs })),
+        Some(RemoteReply::Job(job)) => (200, json!(job)),
+        Some(RemoteReply::NotFound) => error(404, "No such job"),
+        Some(RemoteReply::Rejected(message)) => error(422, &message),
+        None => error(503, "The converter is not responding"),
+    }
+}
+
+// Whether the request carries "Authorization: Bearer <token>"
+fn authorized(request: &Request, token: &str) -> bool {
+    request.headers().iter()
+        .filter(|header| header.field.equiv("Authorization"))
+        .filter_map(|header| header.value.as_str().strip_prefix("Bearer "))
+        .any(|given| constant_time_eq(given.trim().as_bytes(), token.as_bytes()))
+}
+
+// Without a token anything on this machine may use the API, including a web page
+// open in a browser. Browsers mark such requests with an Origin header, and can
+// only send a JSON body after a preflight this server never answers, so requests
+// with an Origin, and job submissions that aren't JSON, are turned away.
+fn from_browser(method: &Method, headers: &[Header]) -> Option<(u16, &'static str)> {
+    if headers.iter().any(|header| header.field.equiv("Origin")) {
+        return Some((403, "Requests from web pages need an API token"));
+    }
+    
+    let json = headers.iter()
+        .filter(|header| header.field.equiv("Content-Type"))
+        .any(|header| header.value.as_str().split(';').next().unwrap_or_default().trim().eq_ignore_ascii_case("application/json"));
+    if *method == Method::Post && !json {
+        return Some((415, "Jobs must be sent as Content-Type: application/json"));
+    }
+    None
+}
+
+// Compares every byte whatever the first mismatch, so the time taken doesn't
+// tell how much of a guess was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn parse_submit(request: &mut Request) -> Result<RemoteCommand, String> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES)
+        .read_to_string(&mut body)
+        .map_err(|e| format!("Failed to read request body: {}", e))?;
+    
+    let body: SubmitBody = serde_json::from_str(&body).map_err(|e| format!("Invalid job: {}", e))?;
+    let format = VideoFormat::from_extension(&body.format).ok_or_else(|| format!("Unknown format: {}", body.format))?;
+    
+    Ok(RemoteCommand::Submit { input: body.input, format })
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn header(field: &str, value: &str) -> Header {
+        Header::from_bytes(field, value).expect("valid header")
+    }
+    
+    #[test]
+    fn refuses_requests_from_web_pages() {
+        let headers = [header("Origin", "https://example.com"), header("Content-Type", "application/json")];
+        assert_eq!(from_browser(&Method::Post, &headers).map(|(status, _)| status), Some(403));
+        assert_eq!(from_browser(&Method::Get, &headers[..1]).map(|(status, _)| status), Some(403));
+    }
+    
+    #[test]
+    fn needs_json_to_submit() {
+        assert_eq!(from_browser(&Method::Post, &[]).map(|(status, _)| status), Some(415));
+        assert_eq!(from_browser(&Method::Post, &[header("Content-Type", "text/plain")]).map(|(status, _)| status), Some(415));
+        assert_eq!(from_browser(&Method::Post, &[header("Content-Type", "application/json; charset=utf-8")]), None);
+        assert_eq!(from_browser(&Method::Get, &[]), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use rust_tui_video_convert::ffmpeg::FFmpegConverter;
+use rust_tui_video_convert::notifier::Notifier;
+use rust_tui_video_convert::paths;
+
+use crate::durations::format_length;
+
+// Image type of an exported still frame
+#[derive(Clone, Copy, PartialEq)]
+pub enum StillFormat {
+    Png,
+    Jpeg,
+}
+
+impl StillFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StillFormat::Png => "PNG",
+            StillFormat::Jpeg => "JPEG",
+        }
+    }
+    
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StillFormat::Png => "png",
+            StillFormat::Jpeg => "jpg",
+        }
+    }
+    
+    pub fn toggle(&self) -> Self {
+        match self {
+            StillFormat::Png => StillFormat::Jpeg,
+            StillFormat::Jpeg => StillFormat::Png,
+        }
+    }
+}
+
+// Timestamp prompt for saving one frame of a file as an image
+pub struct StillPrompt {
+    pub source: PathBuf,
+    pub timestamp: String,
+    pub format: StillFormat,
+    pub error: Option<String>,
+}
+
+impl StillPrompt {
+    pub fn new(source: PathBuf, format: StillFormat) -> Self {
+        Self { source, timestamp: String::new(), format, error: None }
+    }
+    
+    pub fn input(&mut self, c: char) {
+        if c.is_ascii_digit() || c == ':' || c == '.' {
+            self.timestamp.push(c);
+            self.error = None;
+        }
+    }
+    
+    pub fn backspace(&mut self) {
+        self.timestamp.pop();
+        self.error = None;
+    }
+}
+
+// Next to the source, named after the timestamp, e.g. movie.00-01-30.png
+pub fn still_path(source: &Path, at_secs: f64, format: StillFormat) -> PathBuf {
+    let secs = at_secs as u64;
+    let mut name = source.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!(".{:02}-{:02}-{:02}", secs / 3600, (secs % 3600) / 60, secs % 60));
+    
+    // Keep frames within the same second apart
+    let millis = ((at_secs - secs as f64) * 1000.0).round() as u64;
+    if millis > 0 {
+        name.push(format!(".{:03}", millis.min(999)));
+    }
+    
+    name.push(format!(".{}", format.extension()));
+    source.with_file_name(name)
+}
+
+
+// Saves frames on a background thread, one per request, so checking the length
+// and encoding a full-size image never hold up the interface
+pub struct StillExporter {
+    results_tx: mpsc::Sender<(PathBuf, String)>,
+    results: mpsc::Receiver<(PathBuf, String)>,
+    notify: Notifier,
+}
+
+impl StillExporter {
+    pub fn new() -> Self {
+        let (results_tx, results) = mpsc::channel();
+        Self {
+            results_tx,
+            results,
+            notify: Notifier::default(),
+        }
+    }
+    
+    // Called from the worker when a result is ready to collect with poll
+    pub fn set_notifier(&self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify.set(notify);
+    }
+    
+    pub fn start(&self, source: PathBuf, at_secs: f64, format: StillFormat) {
+        let tx = self.results_tx.clone();
+        let notify = self.notify.clone();
+        
+        thread::spawn(move || {
+            let message = export(&source, at_secs, format);
+            let _ = tx.send((source, message));
+            notify.notify();
+        });
+    }
+    
+    // Finished exports as (source file, summary message)
+    pub fn poll(&self) -> Vec<(PathBuf, String)> {
+        self.results.try_iter().collect()
+    }
+}
+
+impl Default for StillExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn export(source: &Path, at_secs: f64, format: StillFormat) -> String {
+    if let Ok(duration) = FFmpegConverter::get_video_duration(source) {
+        if at_secs >= duration {
+            return format!("Couldn't save the frame: the file is only {} long", format_length(duration));
+        }
+    }
+    
+    let output = still_path(source, at_secs, format);
+    match FFmpegConverter::export_still(source, at_secs, &output) {
+        Ok(()) => format!("Saved frame to {}", paths::escape(output.file_name().unwrap_or_default())),
+        Err(e) => format!("Couldn't save the frame: {}", e),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use rust_tui_video_convert::ffmpeg::{FFmpegConverter, MediaTrack};
+use rust_tui_video_convert::notifier::Notifier;
+use rust_tui_video_convert::paths;
+
+// Track choice for a file with more than one subtitle stream.
+// Entry 0 is "all tracks"; entry i is tracks[i - 1].
+pub struct SubtitlePicker {
+    pub source: PathBuf,
+    pub tracks: Vec<MediaTrack>,
+    pub selected: usize,
+}
+
+impl SubtitlePicker {
+    pub fn new(source: PathBuf, tracks: Vec<MediaTrack>) -> Self {
+        Self { source, tracks, selected: 0 }
+    }
+    
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % (self.tracks.len() + 1);
+    }
+    
+    pub fn previous(&mut self) {
+        self.selected = self.selected.checked_sub(1).unwrap_or(self.tracks.len());
+    }
+    
+    pub fn chosen(&self) -> Vec<MediaTrack> {
+        match self.selected {
+            0 => self.tracks.clone(),
+            i => vec![self.tracks[i - 1].clone()],
+        }
+    }
+}
+
+// Sidecar file for each track (None for image-based ones), named the way players
+// look for them: movie.srt, movie.eng.srt, or movie.eng.2.srt when two tracks
+// would otherwise collide
+pub fn sidecar_paths(source: &Path, tracks: &[MediaTrack]) -> Vec<Option<PathBuf>> {
+    let stem = source.file_stem().unwrap_or_default();
+    let base = |track: &MediaTrack| {
+        let mut base = stem.to_os_string();
+        if let Some(language) = &track.language {
+            base.push(format!(".{}", language));
+        }
+        base
+    };
+    
+    let mut counts: HashMap<(OsString, &str), usize> = HashMap::new();
+    for track in tracks {
+        if let Some(ext) = track.sidecar_extension() {
+            *counts.entry((base(track), ext)).or_default() += 1;
+        }
+    }
+    
+    tracks
+        .iter()
+        .map(|track| {
+            let ext = track.sidecar_extension()?;
+            let base = base(track);
+            let mut name = base.clone();
+            if counts[&(base, ext)] > 1 {
+                name.push(format!(".{}", track.number + 1));
+            }
+            name.push(format!(".{}", ext));
+            Some(source.with_file_name(name))
+        })
+        .collect()
+}
+
+// Runs extractions on a background thread; FFmpeg has to read the whole file
+// to find every subtitle packet, which can take a while for long videos
+pub struct SubtitleExtractor {
+    results_tx: mpsc::Sender<(PathBuf, String)>,
+    results: mpsc::Receiver<(PathBuf, String)>,
+    notify: Notifier,
+}
+
+impl SubtitleExtractor {
+    pub fn new() -> Self {
+        let (results_tx, results) = mpsc::channel();
+        Self {
+            results_tx,
+            results,
+            notify: Notifier::default(),
+        }
+    }
+    
+    // Called from the worker when a result is ready to collect with poll
+    pub fn set_notifier(&self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify.set(notify);
+    }
+    
+    pub fn start(&self, source: PathBuf, tracks: Vec<MediaTrack>) {
+        let tx = self.results_tx.clone();
+        let notify = self.notify.clone();
+        
+        thread::spawn(move || {
+            let summary = extract_all(&source, &tracks);
+            let _ = tx.send((source, summary));
+            notify.notify();
+        });
+    }
+    
+    // Finished extractions as (source file, summary message)
+    pub fn poll(&self) -> Vec<(PathBuf, String)> {
+        self.results.try_iter().collect()
+    }
+}
+
+impl Default for SubtitleExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn extract_all(source: &Path, tracks: &[MediaTrack]) -> String {
+    let mut written = Vec::new();
+    let mut problems = Vec::new();
+    
+    for (track, output) in tracks.iter().zip(sidecar_paths(source, tracks)) {
+        let Some(output) = output else {
+            problems.push(format!("{} is image-based", track.label()));
+            continue;
+        };
+        match FFmpegConverter::extract_subtitle(source, track, &output) {
+            Ok(()) => written.push(paths::escape(output.file_name().unwrap_or_default()).into_owned()),
+            Err(e) => problems.push(format!("{} failed: {}", track.label(), e)),
+        }
+    }
+    
+    match (written.is_empty(), problems.is_empty()) {
+        (false, true) => format!("Saved subtitles: {}", written.join(", ")),
+        (false, false) => format!("Saved subtitles: {} ({})", written.join(", "), problems.join("; ")),
+        (true, _) => format!("No subtitles extracted ({})", problems.join("; ")),
+    }
+}
\ No newline at end of file
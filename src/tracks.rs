@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use rust_tui_video_convert::converter::{TrackKind, TrackSelection};
+use rust_tui_video_convert::ffmpeg::MediaTrack;
+
+// A source track and whether it goes into the output
+pub struct TrackChoice {
+    pub track: MediaTrack,
+    pub keep: bool,
+    pub default: bool,
+    pub forced: bool,
+}
+
+// Picks which audio/subtitle tracks a conversion keeps and how they're flagged.
+// Starts from the source's own flags with every track kept.
+pub struct TrackPicker {
+    pub source: PathBuf,
+    pub choices: Vec<TrackChoice>,
+    pub selected: usize,
+}
+
+impl TrackPicker {
+    pub fn new(source: PathBuf, tracks: Vec<MediaTrack>, current: &[TrackSelection]) -> Self {
+        let choices = tracks
+            .into_iter()
+            .map(|track| {
+                // Reopening the picker shows the choices already made
+                match current.iter().find(|sel| sel.kind == track.kind && sel.number == track.number) {
+                    Some(sel) => TrackChoice { keep: true, default: sel.default, forced: sel.forced, track },
+                    None if !current.is_empty() => TrackChoice { keep: false, default: false, forced: false, track },
+                    None => TrackChoice { keep: true, default: track.default, forced: track.forced, track },
+                }
+            })
+            .collect();
+        
+        Self { source, choices, selected: 0 }
+    }
+    
+    pub fn next(&mut self) {
+        if !self.choices.is_empty() {
+            self.selected = (self.selected + 1) % self.choices.len();
+        }
+    }
+    
+    pub fn previous(&mut self) {
+        if !self.choices.is_empty() {
+            self.selected = self.selected.checked_sub(1).unwrap_or(self.choices.len() - 1);
+        }
+    }
+    
+    pub fn toggle_keep(&mut self) {
+        if let Some(choice) = self.choices.get_mut(self.selected) {
+            choice.keep = !choice.keep;
+        }
+    }
+    
+    // Only one track of each kind can be the default one players pick
+    pub fn toggle_default(&mut self) {
+        let Some(kind) = self.choices.get(self.selected).map(|choice| choice.track.kind) else {
+            return;
+        };
+        
+        let was_default = self.choices[self.selected].default;
+        for choice in self.choices.iter_mut().filter(|choice| choice.track.kind == kind) {
+            choice.default = false;
+        }
+        let choice = &mut self.choices[self.selected];
+        choice.default = !was_default;
+        choice.keep |= choice.default;
+    }
+    
+    pub fn toggle_forced(&mut self) {
+        if let Some(choice) = self.choices.get_mut(self.selected) {
+            choice.forced = !choice.forced;
+            choice.keep |= choice.forced;
+        }
+    }
+    
+    // Kept tracks in output order: audio first, then subtitles
+    pub fn selections(&self) -> Vec<TrackSelection> {
+        [TrackKind::Audio, TrackKind::Subtitle]
+            .iter()
+            .flat_map(|kind| {
+                self.choices
+                    .iter()
+                    .filter(move |choice| choice.keep && choice.track.kind == *kind)
+                    .map(|choice| TrackSelection {
+                        kind: choice.track.kind,
+                        number: choice.track.number,
+                        default: choice.default,
+                        forced: choice.forced,
+                    })
+            })
+            .collect()
+    }
+}
\ No newline at end of file
@@ -1,31 +1,58 @@
+use std::path::Path;
 use ratatui::{
     backend::Backend,
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, BorderType, Gauge, List, ListItem, Paragraph, Tabs},
+    widgets::{Block, Borders, BorderType, Clear, Gauge, List, ListItem, Paragraph, Sparkline, Tabs, Widget, Wrap},
     Frame,
 };
 
-use crate::app::{App, AppTab, AdvancedSetting};
-use crate::converter::VideoFormat;
+use rust_tui_video_convert::converter::{Bitrate, ConversionMode, ConversionProgress, ConversionStage, Crop, Downmix, EcoMode, EncodeStats, ExternalAudio, ExternalSubtitles, ExtraOutput, FrameRateMode, H264Level, H264Profile, IoPriority, Ladder, PixelFormat, QualityTarget, TrackKind, VideoFormat, VideoSettings, SURROUND_CODECS, format_position, is_url, timestamp};
+use rust_tui_video_convert::disc;
+use rust_tui_video_convert::ffmpeg::{FFmpegCapabilities, FFmpegConverter, RgbFrame};
+use rust_tui_video_convert::file_browser::{is_subtitle_file, FileBrowser};
+use rust_tui_video_convert::paths::{self, AccessKind};
+
+use crate::app::{AccessPrompt, App, AppTab, AdvancedSetting, BitrateCalculator, CalculatorField, ConflictPrompt, DeleteConfirm, Job, JobState};
+use crate::config::ColorPalette;
+use crate::durations;
+use crate::preview::{FramingPreview, TrimEditor};
+use crate::diagnostics::Diagnostics;
+use crate::stills::StillPrompt;
+use crate::subtitles::SubtitlePicker;
+use crate::tracks::TrackPicker;
+
+// Most recent remote jobs listed at once
+const MAX_REMOTE_JOB_ROWS: usize = 5;
 
 pub fn ui<B: Backend>(f: &mut Frame<B>, app: &App) {
     let size = f.size();
     
+    // The remote jobs panel is shown while the HTTP API is served or once jobs arrive
+    let remote_jobs = app.remote_jobs().count();
+    let show_jobs = app.serve_addr.is_some() || remote_jobs > 0;
+    let jobs_height = if show_jobs {
+        remote_jobs.clamp(1, MAX_REMOTE_JOB_ROWS) as u16 + 2
+    } else {
+        0
+    };
+    
     // Create main layout
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),  // Title
-            Constraint::Length(3),  // Tabs
-            Constraint::Min(0),     // Content
-            Constraint::Length(3),  // Status bar
+            Constraint::Length(3),            // Title
+            Constraint::Length(3),            // Tabs
+            Constraint::Min(0),               // Content
+            Constraint::Length(jobs_height),  // Remote jobs
+            Constraint::Length(3),            // Status bar
         ].as_ref())
         .split(size);
-
+    
     // Title
-    render_title(f, chunks[0]);
+    render_title(f, app, chunks[0]);
     
     // Tabs
     render_tabs(f, app, chunks[1]);
@@ -37,19 +64,135 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &App) {
         AppTab::Converting => render_converting(f, app, chunks[2]),
         AppTab::Complete => render_complete(f, app, chunks[2]),
         AppTab::Settings => render_settings(f, app, chunks[2]),
+        AppTab::Stats => render_stats(f, app, chunks[2]),
         AppTab::Help => render_help(f, chunks[2]),
     }
     
+    if show_jobs {
+        render_remote_jobs(f, app, chunks[3]);
+    }
+    
     // Status bar
-    render_status_bar(f, app, chunks[3]);
+    render_status_bar(f, app, chunks[4]);
     
     // Render popup if active
     if app.show_popup {
         render_popup(f, app, size);
     }
+    
+    // Render bitrate calculator if open
+    if let Some(calculator) = &app.bitrate_calculator {
+        render_bitrate_calculator(f, calculator, size);
+    }
+    
+    if let Some(preview) = &app.framing_preview {
+        render_framing_preview(f, app, preview, size);
+    }
+    
+    if let Some(editor) = &app.trim_editor {
+        render_trim_editor(f, app, editor, size);
+    }
+    
+    if let Some(picker) = &app.subtitle_picker {
+        render_subtitle_picker(f, picker, size);
+    }
+    
+    if let Some(picker) = &app.track_picker {
+        render_track_picker(f, picker, size);
+    }
+    
+    if let Some(prompt) = &app.still_prompt {
+        render_still_prompt(f, prompt, size);
+    }
+    
+    if let Some(confirm) = &app.delete_confirm {
+        render_delete_confirm(f, confirm, size);
+    }
+    
+    if app.same_format_prompt {
+        render_same_format_prompt(f, app, size);
+    }
+    
+    if let Some(prompt) = &app.conflict_prompt {
+        render_conflict_prompt(f, app, prompt, size);
+    }
+    
+    if let Some(reason) = &app.not_playable {
+        render_not_playable(f, app, reason, size);
+    }
+    
+    if let Some(prompt) = &app.access_prompt {
+        render_access_prompt(f, prompt, size);
+    }
+    
+    if app.quit_confirm {
+        render_quit_confirm(f, app, size);
+    }
+    
+    if let Some(input) = &app.url_input {
+        render_url_input(f, app, input, size);
+    }
+    
+    if let Some(diagnostics) = &app.diagnostics {
+        render_diagnostics(f, diagnostics, size);
+    }
+    
+    // Last, so it covers everything drawn above
+    let colors = DisplayColors { high_contrast: app.config.accessible_ui, palette: app.config.palette };
+    if colors.high_contrast || colors.palette != ColorPalette::Standard {
+        f.render_widget(colors, size);
+    }
+}
+
+// Redraws the screen's colors for the accessible display, which brightens every
+// color so nothing is drawn in dark gray or dimmed on a dark background, and for
+// the color-blind palettes
+struct DisplayColors {
+    high_contrast: bool,
+    palette: ColorPalette,
+}
+
+impl DisplayColors {
+    // Backgrounds keep their brightness, so only text is brightened
+    fn recolor(&self, color: Color, brighten: bool) -> Color {
+        let color = match color {
+            Color::DarkGray | Color::Gray if brighten => Color::White,
+            Color::Red if brighten => Color::LightRed,
+            Color::Green if brighten => Color::LightGreen,
+            Color::Yellow if brighten => Color::LightYellow,
+            Color::Blue if brighten => Color::LightBlue,
+            Color::Magenta if brighten => Color::LightMagenta,
+            Color::Cyan if brighten => Color::LightCyan,
+            color => color,
+        };
+        // Okabe-Ito colors: sky blue for success, and for failure vermillion, or the
+        // lighter orange where red itself looks dark (protanopia)
+        match (self.palette, color) {
+            (ColorPalette::Standard, color) => color,
+            (_, Color::Green | Color::LightGreen) => Color::Rgb(86, 180, 233),
+            (ColorPalette::Deuteranopia, Color::Red | Color::LightRed) => Color::Rgb(213, 94, 0),
+            (ColorPalette::Protanopia, Color::Red | Color::LightRed) => Color::Rgb(230, 159, 0),
+            (_, color) => color,
+        }
+    }
+}
+
+impl Widget for DisplayColors {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                cell.fg = self.recolor(cell.fg, self.high_contrast);
+                cell.bg = self.recolor(cell.bg, false);
+                if self.high_contrast {
+                    cell.modifier.remove(Modifier::DIM);
+                }
+            }
+        }
+    }
 }
 
-fn render_title<B: Backend>(f: &mut Frame<B>, area: Rect) {
+fn render_title<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     // Create a block for the header
     let header_block = Block::default()
         .borders(Borders::ALL)
@@ -82,16 +225,17 @@ fn render_title<B: Backend>(f: &mut Frame<B>, area: Rect) {
     f.render_widget(title, inner_area);
     
     // Check which conversion tools are available
-    let (status_color, native_status) = match crate::native_converter::NativeConverter::check_available() {
-        Ok(true) => (Color::Green, "Native Rust Converter: ✅ Ready"),
-        _ => match crate::ffmpeg::FFmpegConverter::check_ffmpeg_available() {
-            Ok(true) => (Color::Green, "External FFmpeg: ✅ Ready"),
-            _ => (Color::Red, "Converters: ❌ Not detected (using simulation)"),
+    let mode = app.available_mode();
+    let (status_color, converter_status) = match mode {
+        ConversionMode::Simulation => (Color::Red, "FFmpeg: ❌ Not detected (simulation only, no files written)".to_string()),
+        ConversionMode::FFmpeg if !app.ffmpeg_warnings().is_empty() => {
+            (Color::Yellow, "FFmpeg: ⚠ Too old for some settings (see Format Selection)".to_string())
         },
+        _ => (mode_color(mode), format!("{}: ✅ Ready", app.mode_label(mode))),
     };
     
     // Add version info with status color
-    let version_text = format!("v1.0 | {}", native_status);
+    let version_text = format!("v1.0 | {}", converter_status);
     let version_area = Rect {
         x: inner_area.x + 2,
         y: inner_area.y + 2,
@@ -110,23 +254,34 @@ fn render_title<B: Backend>(f: &mut Frame<B>, area: Rect) {
 }
 
 fn render_tabs<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
-    let tab_titles = vec!["File Browser", "Format Selection", "Settings", "Help"];
-    let tabs = Tabs::new(
-        tab_titles
-            .iter()
-            .map(|t| Spans::from(Span::styled(*t, Style::default().fg(Color::White))))
-            .collect(),
-    )
-    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded))
-    .select(match app.current_tab {
+    let tab_titles = ["File Browser", "Format Selection", "Settings", "Stats", "Help"];
+    let selected = match app.current_tab {
         AppTab::FileBrowser => 0,
         AppTab::FormatSelection => 1,
         AppTab::Settings => 2,
-        AppTab::Help => 3,
+        AppTab::Stats => 3,
+        AppTab::Help => 4,
         // During conversion or when complete, keep the format selection tab highlighted
         AppTab::Converting => 1,
         AppTab::Complete => 1,
-    })
+    };
+    let tabs = Tabs::new(
+        tab_titles
+            .iter()
+            .enumerate()
+            .map(|(idx, t)| {
+                // The accessible display brackets the current tab instead of only coloring it
+                let title = if app.config.accessible_ui && idx == selected {
+                    format!("[{}]", t)
+                } else {
+                    t.to_string()
+                };
+                Spans::from(Span::styled(title, Style::default().fg(Color::White)))
+            })
+            .collect(),
+    )
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded))
+    .select(selected)
     .style(Style::default().fg(Color::White))
     .highlight_style(
         Style::default()
@@ -146,62 +301,322 @@ fn render_file_browser<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(1),  // Current directory
-            Constraint::Min(0),     // File list
+            Constraint::Min(0),     // File list and details
         ].as_ref())
         .split(area);
     
+    // Show details beside the list while a video is highlighted, or the
+    // destination pane in place of them in the dual-pane layout. The accessible
+    // display keeps to one column: the destination goes below, and the picture
+    // preview is left out.
+    let selected_file = app.file_browser.get_selected_entry()
+        .filter(|entry| entry.is_convertible() && !app.config.accessible_ui)
+        .map(|entry| &entry.path);
+    let list_area = match (&app.destination, selected_file) {
+        (Some(destination), _) => {
+            let panes = Layout::default()
+                .direction(if app.config.accessible_ui { Direction::Vertical } else { Direction::Horizontal })
+                .constraints([
+                    Constraint::Percentage(50),  // Source
+                    Constraint::Percentage(50),  // Destination
+                ].as_ref())
+                .split(chunks[1]);
+            render_destination_pane(f, app, destination, panes[1]);
+            panes[0]
+        },
+        (None, Some(path)) => {
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(55),  // File list
+                    Constraint::Percentage(45),  // Details
+                ].as_ref())
+                .split(chunks[1]);
+            render_file_details(f, app, path, panes[1]);
+            panes[0]
+        },
+        (None, None) => chunks[1],
+    };
+    
     // Current directory display
-    let current_dir = app.file_browser.get_current_dir().to_string_lossy();
-    let dir_display = Paragraph::new(Spans::from(vec![
+    let current_dir = paths::display(app.file_browser.get_current_dir());
+    // How big a batch of this folder would be
+    let summary = match app.file_browser.video_summary() {
+        (0, _) => "no videos".to_string(),
+        (1, bytes) => format!("1 video, {}", format_size(bytes)),
+        (count, bytes) => format!("{} videos, {}", count, format_size(bytes)),
+    };
+    let mut dir_spans = vec![
         Span::styled("📂 ", Style::default().fg(Color::Yellow)),
         Span::styled(current_dir.to_string(), Style::default().fg(Color::White)),
-    ]))
-    .style(Style::default().fg(Color::White));
+        Span::styled(format!("  ({})", summary), Style::default().fg(Color::DarkGray)),
+    ];
+    // Slow folders fill in while they're read; the count above is so far
+    if app.file_browser.is_loading() {
+        dir_spans.push(Span::styled("  ⏳ Reading folder...", Style::default().fg(Color::Yellow)));
+    }
+    let dir_display = Paragraph::new(Spans::from(dir_spans))
+        .style(Style::default().fg(Color::White));
     
     f.render_widget(dir_display, chunks[0]);
     
     // File list
     let items: Vec<ListItem> = files
         .iter()
-        .map(|path| {
-            let display_text = app.file_browser.format_path_for_display(path);
-            let style = if path.is_dir() {
+        .map(|entry| {
+            let style = if entry.is_dir() {
                 Style::default().fg(Color::Yellow)
             } else {
                 Style::default().fg(Color::White)
             };
             
-            ListItem::new(Spans::from(display_text)).style(style)
+            // Lengths tell full episodes from clips; they fill in as they're probed
+            let mut spans = vec![Span::raw(entry.label.as_str())];
+            if let Some(duration) = app.durations.get(&entry.path) {
+                spans.push(Span::styled(format!("  {}", durations::format_length(duration)), Style::default().fg(Color::DarkGray)));
+            }
+            ListItem::new(Spans::from(spans)).style(style)
         })
         .collect();
-
+    
+    // The filter box lives in the list's title while it is open
+    let mut files_title = match app.file_browser.query() {
+        Some(query) => format!(" Files | Filter: {}_ ", query),
+        None => " Files ".to_string(),
+    };
+    if app.destination.is_some() && !app.destination_focused {
+        files_title.push_str(focus_marker(app));
+    }
     let files_list = List::new(items)
         .block(
             Block::default()
-                .title(" Files ")
+                .title(files_title)
                 .title_alignment(Alignment::Center)
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(Color::Blue))
+                .border_style(pane_border(app.destination.is_some() && !app.destination_focused))
         )
         .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .highlight_symbol("➤ ");
-
+    
     let mut state = ratatui::widgets::ListState::default();
     state.select(Some(selected_idx));
     
-    f.render_stateful_widget(files_list, chunks[1], &mut state);
+    f.render_stateful_widget(files_list, list_area, &mut state);
 }
 
-fn render_format_selection<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
-    let formats = vec![
-        VideoFormat::MP4,
-        VideoFormat::MKV,
-        VideoFormat::AVI,
-        VideoFormat::MOV,
-        VideoFormat::WEBM,
+// The focused pane of the dual-pane layout stands out
+fn pane_border(focused: bool) -> Style {
+    Style::default().fg(if focused { Color::Yellow } else { Color::Blue })
+}
+
+// Says in words which pane has focus on the accessible display, where the border
+// color isn't the only cue
+fn focus_marker(app: &App) -> &'static str {
+    if app.config.accessible_ui { "(active) " } else { "" }
+}
+
+fn render_destination_pane<B: Backend>(f: &mut Frame<B>, app: &App, destination: &FileBrowser, area: Rect) {
+    let items: Vec<ListItem> = destination.get_files()
+        .iter()
+        .map(|entry| ListItem::new(entry.label.as_str()).style(Style::default().fg(Color::Yellow)))
+        .collect();
+    
+    let loading = if destination.is_loading() { " ⏳" } else { "" };
+    let mut title = format!(" Output to: {}{} ", paths::display(destination.get_current_dir()), loading);
+    if app.destination_focused {
+        title.push_str(focus_marker(app));
+    }
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(pane_border(app.destination_focused))
+        )
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol(if app.destination_focused { "➤ " } else { "  " });
+    
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(destination.get_selected_idx()));
+    
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_file_details<B: Backend>(f: &mut Frame<B>, app: &App, path: &Path, area: Rect) {
+    let block = Block::default()
+        .title(" Preview ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Blue));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    
+    let size_text = disc::source_size(path).map_or_else(|| "n/a".to_string(), format_size);
+    let mut info = vec![
+        Spans::from(vec![
+            Span::styled("File: ", Style::default().fg(Color::Green)),
+            Span::styled(paths::escape(path.file_name().unwrap_or_default()).to_string(), Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("Size: ", Style::default().fg(Color::Green)),
+            Span::styled(size_text, Style::default().fg(Color::White)),
+        ]),
     ];
+    if let Some(Ok(container)) = app.preview.get(path).and_then(|preview| preview.container.as_ref()) {
+        info.push(Spans::from(vec![
+            Span::styled("Container: ", Style::default().fg(Color::Green)),
+            Span::styled(container.long_name.clone(), Style::default().fg(Color::White)),
+        ]));
+        if let Some(message) = container.mismatch_message(path) {
+            info.push(Spans::from(Span::styled(format!("⚠ {}", message), Style::default().fg(Color::Yellow))));
+        }
+    }
+    if let Some(reason) = app.preview.get(path).and_then(|preview| preview.container.as_ref()).and_then(|probe| FFmpegConverter::unplayable_reason(probe, app.get_current_format())) {
+        info.push(Spans::from(Span::styled(format!("✖ Not a playable video: {}", reason), Style::default().fg(Color::Red))));
+    }
+    
+    // The frame gets whatever height is left above the waveform and file info,
+    // whose long warnings wrap
+    let info_rows: u16 = info.iter().map(|line| (line.width() as u16).div_ceil(inner.width.max(1)).max(1)).sum();
+    let image_height = inner.height.saturating_sub(WAVEFORM_ROWS + info_rows + 3);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(image_height),   // Frame
+            Constraint::Length(1),              // Spacer
+            Constraint::Length(1),              // Waveform label
+            Constraint::Length(WAVEFORM_ROWS),  // Waveform
+            Constraint::Length(1),              // Spacer
+            Constraint::Min(0),                 // File info
+        ].as_ref())
+        .split(inner);
+    
+    let placeholder = |f: &mut Frame<B>, message: String, area: Rect| {
+        let placeholder = Paragraph::new(message)
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center);
+        f.render_widget(placeholder, area);
+    };
+    
+    f.render_widget(
+        Paragraph::new(Span::styled("Audio", Style::default().fg(Color::Green))),
+        chunks[2],
+    );
+    
+    let preview = app.preview.get(path);
+    if app.ffmpeg_capabilities.is_none() {
+        placeholder(f, "Preview needs FFmpeg".to_string(), chunks[0]);
+        placeholder(f, "Waveform needs FFmpeg".to_string(), chunks[3]);
+    } else {
+        match preview.and_then(|preview| preview.frame.as_ref()) {
+            Some(Ok(frame)) => f.render_widget(HalfBlockImage { frame }, chunks[0]),
+            Some(Err(reason)) => placeholder(f, format!("No preview: {}", reason), chunks[0]),
+            None => placeholder(f, "Loading preview...".to_string(), chunks[0]),
+        }
+        
+        match preview.and_then(|preview| preview.waveform.as_ref()) {
+            Some(Ok(peaks)) => {
+                let data = fit_waveform(peaks, chunks[3].width as usize);
+                let waveform = Sparkline::default()
+                    .data(&data)
+                    .max(100)
+                    .style(Style::default().fg(Color::Cyan));
+                f.render_widget(waveform, chunks[3]);
+            },
+            Some(Err(_)) => placeholder(f, "No audio track".to_string(), chunks[3]),
+            None => placeholder(f, "Reading audio...".to_string(), chunks[3]),
+        }
+    }
+    
+    f.render_widget(Paragraph::new(info).wrap(Wrap { trim: true }), chunks[5]);
+}
+
+// Rows given to the waveform in the details pane
+const WAVEFORM_ROWS: u16 = 3;
+
+// Merge waveform peaks (0.0-1.0) into `width` bars on a 0-100 scale, keeping the
+// loudest peak of each group so short transients still show
+fn fit_waveform(peaks: &[f32], width: usize) -> Vec<u64> {
+    if peaks.is_empty() || width == 0 {
+        return Vec::new();
+    }
     
+    (0..width)
+        .map(|bar| {
+            let start = bar * peaks.len() / width;
+            let end = ((bar + 1) * peaks.len() / width).clamp(start + 1, peaks.len());
+            let peak = peaks[start..end].iter().copied().fold(0.0, f32::max);
+            (peak * 100.0).round() as u64
+        })
+        .collect()
+}
+
+// Draws an RGB frame with "▀" cells: the foreground colours the top pixel and
+// the background the bottom one, so each cell shows two roughly square pixels
+struct HalfBlockImage<'a> {
+    frame: &'a RgbFrame,
+}
+
+impl Widget for HalfBlockImage<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let frame = self.frame;
+        if area.width == 0 || area.height == 0 || frame.width == 0 || frame.height == 0 {
+            return;
+        }
+        
+        // Largest size with the frame's aspect ratio that fits, centred
+        let rows_for_width = (u32::from(area.width) * frame.height).div_ceil(frame.width * 2);
+        let (width, height) = if rows_for_width <= u32::from(area.height) {
+            (u32::from(area.width), rows_for_width.max(1))
+        } else {
+            let height = u32::from(area.height);
+            ((height * 2 * frame.width / frame.height).max(1), height)
+        };
+        let left = area.x + (area.width - width as u16) / 2;
+        let top = area.y + (area.height - height as u16) / 2;
+        
+        // Average the block of source pixels behind each target pixel
+        let sample = |x: u32, y: u32| {
+            let (x0, x1) = source_range(x, width, frame.width);
+            let (y0, y1) = source_range(y, height * 2, frame.height);
+            let (mut r, mut g, mut b, mut count) = (0u32, 0u32, 0u32, 0u32);
+            for sy in y0..y1 {
+                for sx in x0..x1 {
+                    let (pr, pg, pb) = frame.pixel(sx, sy);
+                    r += u32::from(pr);
+                    g += u32::from(pg);
+                    b += u32::from(pb);
+                    count += 1;
+                }
+            }
+            let count = count.max(1);
+            Color::Rgb((r / count) as u8, (g / count) as u8, (b / count) as u8)
+        };
+        
+        for row in 0..height {
+            for col in 0..width {
+                buf.get_mut(left + col as u16, top + row as u16)
+                    .set_symbol("▀")
+                    .set_fg(sample(col, row * 2))
+                    .set_bg(sample(col, row * 2 + 1));
+            }
+        }
+    }
+}
+
+// Source pixels [start, end) behind target pixel `i` along one axis,
+// always at least one even when scaling up
+fn source_range(i: u32, target: u32, source: u32) -> (u32, u32) {
+    let start = i * source / target;
+    let end = ((i + 1) * source / target).clamp(start + 1, source);
+    (start, end)
+}
+
+fn render_format_selection<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     // Split the area into two parts: format list and format details
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -212,20 +627,35 @@ fn render_format_selection<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect)
         .split(area);
     
     // Format list
-    let items: Vec<ListItem> = formats
+    let items: Vec<ListItem> = VideoFormat::ALL
         .iter()
         .map(|format| {
-            let style = if *format == app.get_current_format() {
+            let unavailable = !app.missing_components(*format).is_empty();
+            let style = if unavailable {
+                Style::default().fg(Color::DarkGray)
+            } else if *format == app.get_current_format() {
                 Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
             } else {
                 Style::default().fg(Color::White)
             };
             
-            let format_name = format.as_str();
+            // Formats marked to be written alongside the main one
+            let extra = app.extra_output(*format);
+            let format_name = match (unavailable, extra.and_then(|extra| extra.resolution)) {
+                (true, _) => format!("{} (unavailable)", format.as_str()),
+                (false, Some(resolution)) if extra.is_some() => format!("{} + {}", format.as_str(), resolution.as_str()),
+                (false, _) if extra.is_some() => format!("{} +", format.as_str()),
+                (false, _) => format.as_str().to_string(),
+            };
+            let style = if extra.is_some() && !unavailable && *format != app.get_current_format() {
+                style.fg(Color::Cyan)
+            } else {
+                style
+            };
             ListItem::new(Spans::from(format_name)).style(style)
         })
         .collect();
-
+    
     let formats_list = List::new(items)
         .block(
             Block::default()
@@ -236,8 +666,8 @@ fn render_format_selection<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect)
                 .border_style(Style::default().fg(Color::Blue))
         )
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
-        .highlight_symbol(" ");
-
+        .highlight_symbol(if app.config.accessible_ui { "➤ " } else { " " });
+    
     let mut state = ratatui::widgets::ListState::default();
     state.select(Some(app.selected_format_idx));
     
@@ -245,7 +675,7 @@ fn render_format_selection<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect)
     
     // Format details
     let current_format = app.get_current_format();
-    let format_details = vec![
+    let mut format_details = vec![
         Spans::from(vec![
             Span::styled("Format: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
             Span::styled(current_format.as_str(), Style::default().fg(Color::White)),
@@ -265,9 +695,135 @@ fn render_format_selection<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect)
             VideoFormat::AVI => " Legacy systems\n Older media players\n Simple editing workflows",
             VideoFormat::MOV => " Apple devices\n Professional video editing\n High-quality recording",
             VideoFormat::WEBM => " Web embedding\n HTML5 video\n Efficient streaming",
+            VideoFormat::TS => " Broadcast and IPTV\n Set-top boxes\n Recording live streams",
+            VideoFormat::FLV => " RTMP streaming ingest\n Legacy Flash players\n Older video sites",
+            VideoFormat::OGV => " Free-software platforms\n Wikimedia uploads\n Patent-free distribution",
+            VideoFormat::WMV => " Windows Media Player\n Older Windows PCs\n Legacy presentations",
+            VideoFormat::ThreeGP => " Feature phones\n Low-bandwidth sharing\n MMS attachments",
+            VideoFormat::M4V => " iTunes libraries\n iPod and iPhone\n Apple TV",
+            VideoFormat::MXF => " Broadcast playout and ingest\n Avid and XDCAM workflows\n Archive masters",
+            VideoFormat::WEBP => " Web embeds instead of GIF\n Chat stickers and reactions\n Looping product shots",
+            VideoFormat::APNG => " Lossless UI demos\n Animations with transparency\n Looping web graphics",
+            VideoFormat::MP3 => " Music players and car stereos\n Podcasts\n Soundtracks ripped from videos",
+            VideoFormat::FLAC => " Lossless music archives\n Hi-fi playback\n Re-encoding later without loss",
+            VideoFormat::WAV => " Audio editors and DAWs\n Sampling and sound design\n Transcription tools",
+            VideoFormat::M4A => " iPhone and iTunes\n Ringtones and voice memos\n Smaller files than MP3 at the same quality",
         }),
     ];
-
+    
+    // Explain why a format is greyed out and how to get it
+    let missing = app.missing_components(current_format);
+    if let (false, Some(plugin)) = (missing.is_empty(), app.active_plugin()) {
+        format_details.push(Spans::from(""));
+        format_details.push(Spans::from(vec![
+            Span::styled("Unavailable: ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("the {} plugin doesn't list {}", plugin.name(), current_format.as_str()),
+                Style::default().fg(Color::White)
+            ),
+        ]));
+        format_details.push(Spans::from(vec![
+            Span::styled("Hint: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("Add \"{}\" to `formats` in its manifest, or switch backend in Settings", current_format.extension()),
+                Style::default().fg(Color::White)
+            ),
+        ]));
+    } else if !missing.is_empty() {
+        format_details.push(Spans::from(""));
+        format_details.push(Spans::from(vec![
+            Span::styled("Unavailable: ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("your FFmpeg build lacks {}", missing.join(", ")),
+                Style::default().fg(Color::White)
+            ),
+        ]));
+        format_details.push(Spans::from(vec![
+            Span::styled("Hint: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                FFmpegCapabilities::install_hint(missing[0]),
+                Style::default().fg(Color::White)
+            ),
+        ]));
+    } else {
+        // Available, though maybe through a stand-in encoder
+        let substitutions = app.encoder_substitutions(current_format);
+        if !substitutions.is_empty() {
+            let text = substitutions.iter()
+                .map(|(wanted, used)| format!("{} instead of {}", used, wanted))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format_details.push(Spans::from(""));
+            format_details.push(Spans::from(vec![
+                Span::styled("Fallback: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("your FFmpeg build lacks the usual encoder, so this uses {}", text), Style::default().fg(Color::White)),
+            ]));
+        }
+        
+        // Or the container can't take some settings as they are
+        let conflicts = if app.active_plugin().is_some() { Vec::new() } else { app.container_conflicts() };
+        for conflict in conflicts {
+            format_details.push(Spans::from(""));
+            format_details.push(Spans::from(vec![
+                Span::styled("Conflict: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::styled(conflict, Style::default().fg(Color::White)),
+            ]));
+        }
+        
+        // Or the chosen settings need a newer FFmpeg than this one
+        for warning in app.ffmpeg_warnings() {
+            format_details.push(Spans::from(""));
+            format_details.push(Spans::from(vec![
+                Span::styled("Warning: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::styled(warning, Style::default().fg(Color::White)),
+            ]));
+        }
+    }
+    
+    // A ladder writes every rung in this format instead of the one output
+    let ladder = app.video_settings.ladder.for_format(app.get_current_format());
+    if !ladder.is_off() {
+        let rungs = Ladder::RUNGS.iter().map(|(resolution, kbps)| format!("{} at {} kbps", resolution.as_str(), kbps)).collect::<Vec<_>>().join(", ");
+        format_details.push(Spans::from(""));
+        format_details.push(Spans::from(vec![
+            Span::styled("Rendition Ladder: ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled(rungs, Style::default().fg(Color::White)),
+        ]));
+        let packaging = if ladder == Ladder::Hls {
+            "Packaged for HLS with a master playlist | Sizes above the source's are left out"
+        } else {
+            "Named after their size | Sizes above the source's are left out"
+        };
+        format_details.push(Spans::from(Span::styled(packaging, Style::default().fg(Color::DarkGray))));
+    }
+    
+    // Only part of the source is converted
+    let trim = app.video_settings.trim;
+    if !trim.is_off() {
+        format_details.push(Spans::from(""));
+        format_details.push(Spans::from(vec![
+            Span::styled("Trim: ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled(trim.label(), Style::default().fg(Color::White)),
+        ]));
+        format_details.push(Spans::from(Span::styled("x: Change the in and out points", Style::default().fg(Color::DarkGray))));
+    }
+    
+    // The other marked formats, written by the same job
+    let extras = app.extra_outputs();
+    if !extras.is_empty() {
+        format_details.push(Spans::from(""));
+        format_details.push(Spans::from(vec![
+            Span::styled("Also Writing: ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled(extra_outputs_text(&extras), Style::default().fg(Color::White)),
+        ]));
+        let shared = if FFmpegConverter::shares_decode(&app.video_settings) && app.available_mode() == ConversionMode::FFmpeg {
+            "Encoded from one read of the source | r: A marked format's resolution"
+        } else {
+            "Converted one after another | r: A marked format's resolution"
+        };
+        format_details.push(Spans::from(Span::styled(shared, Style::default().fg(Color::DarkGray))));
+    }
+    
     let details_widget = Paragraph::new(format_details)
         .block(
             Block::default()
@@ -277,21 +833,24 @@ fn render_format_selection<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect)
                 .border_type(BorderType::Rounded)
                 .border_style(Style::default().fg(Color::Blue))
         )
-        .alignment(Alignment::Left);
-
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    
     f.render_widget(details_widget, chunks[1]);
 }
 
+// "WEBM 720p, MP3"
+fn extra_outputs_text(extras: &[ExtraOutput]) -> String {
+    extras.iter().map(ExtraOutput::label).collect::<Vec<_>>().join(", ")
+}
+
 fn render_converting<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
-    if let Some(progress) = &app.conversion_progress {
-        // Determine which conversion tool is being used
-        let (tool_color, conversion_tool) = match crate::native_converter::NativeConverter::check_available() {
-            Ok(true) => (Color::Green, "Native Rust FFmpeg"),
-            _ => match crate::ffmpeg::FFmpegConverter::check_ffmpeg_available() {
-                Ok(true) => (Color::Green, "External FFmpeg"),
-                _ => (Color::Yellow, "Simulation Mode"),
-            },
-        };
+    let Some(job) = app.current_conversion() else {
+        return;
+    };
+    if let Some(progress) = &job.progress {
+        // Which conversion tool is running this job
+        let mode = job.mode;
         
         // Create layout for conversion display
         let chunks = Layout::default()
@@ -302,7 +861,9 @@ fn render_converting<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                 Constraint::Length(3),  // Output file
                 Constraint::Length(3),  // Conversion method
                 Constraint::Length(3),  // Current step
+                Constraint::Length(3),  // Encoder statistics
                 Constraint::Length(3),  // Progress bar
+                Constraint::Length(stages_height(&job.stages)),  // Stages
                 Constraint::Min(0),     // Spacer
             ].as_ref())
             .split(area);
@@ -311,7 +872,7 @@ fn render_converting<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         let source_file = Paragraph::new(Spans::from(vec![
             Span::styled("Source File: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
             Span::styled(
-                progress.source_file.file_name().unwrap_or_default().to_string_lossy().to_string(), 
+                paths::escape(progress.source_file.file_name().unwrap_or_default()).to_string(), 
                 Style::default().fg(Color::White)
             ),
         ]))
@@ -341,7 +902,7 @@ fn render_converting<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         let output_file = Paragraph::new(Spans::from(vec![
             Span::styled("Output File: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
             Span::styled(
-                progress.output_file.file_name().unwrap_or_default().to_string_lossy().to_string(), 
+                output_file_text(progress, mode), 
                 Style::default().fg(Color::White)
             ),
         ]))
@@ -352,10 +913,13 @@ fn render_converting<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                 .border_style(Style::default().fg(Color::Blue))
         );
         
-        // Conversion method
+        // Conversion method, and how long the job has been running
+        let elapsed = job.elapsed().unwrap_or_default();
         let conversion_method = Paragraph::new(Spans::from(vec![
             Span::styled("Conversion Method: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-            Span::styled(conversion_tool, Style::default().fg(tool_color)),
+            Span::styled(app.mode_label(mode), Style::default().fg(mode_color(mode))),
+            Span::styled("   Elapsed: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled(format_elapsed(elapsed), Style::default().fg(Color::White)),
         ]))
         .block(
             Block::default()
@@ -379,6 +943,15 @@ fn render_converting<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                 .border_style(Style::default().fg(Color::Blue))
         );
         
+        // What FFmpeg reports besides the position: frames, speed, bitrate, size
+        let encode_stats = Paragraph::new(Spans::from(encode_stats_spans(&progress.stats)))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Blue))
+            );
+        
         // Progress bar
         let progress_gauge = Gauge::default()
             .block(
@@ -391,37 +964,131 @@ fn render_converting<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
             )
             .gauge_style(Style::default().fg(Color::Cyan))
             .ratio(f64::from(progress.percent) / 100.0)
-            .label(format!("{}%", progress.percent));
+            .label(if progress.stage.reports_percent() {
+                format!("{}: {}%", progress.stage.label(), progress.percent)
+            } else {
+                format!("{}...", progress.stage.label())
+            });
+        
+        // Every stage the job goes through, so a full bar isn't mistaken for the end
+        let stages: Vec<Spans> = job.stages.iter()
+            .map(|stage| stage_spans(*stage, progress))
+            .collect();
+        let stages = Paragraph::new(stages)
+            .block(
+                Block::default()
+                    .title(" Stages ")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Blue))
+            );
         
         f.render_widget(source_file, chunks[0]);
         f.render_widget(target_format, chunks[1]);
         f.render_widget(output_file, chunks[2]);
         f.render_widget(conversion_method, chunks[3]);
         f.render_widget(current_step, chunks[4]);
-        f.render_widget(progress_gauge, chunks[5]);
+        f.render_widget(encode_stats, chunks[5]);
+        f.render_widget(progress_gauge, chunks[6]);
+        if !job.stages.is_empty() {
+            f.render_widget(stages, chunks[7]);
+        }
+    }
+}
+
+// One line per planned stage plus the borders; nothing when there's no plan
+fn stages_height(stages: &[ConversionStage]) -> u16 {
+    match stages.len() {
+        0 => 0,
+        planned => planned as u16 + 2,
+    }
+}
+
+// "✔ Probe           ██████████ done", with a mini bar for the stage that's running
+fn stage_spans(stage: ConversionStage, progress: &ConversionProgress) -> Spans<'static> {
+    const BAR_WIDTH: usize = 10;
+    let bar = |filled: usize| format!("{}{} ", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled));
+    let name = format!("{:<16}", stage.label());
+    
+    let (marker, bar, status, color) = if stage < progress.stage {
+        ("✔ ", bar(BAR_WIDTH), "done".to_string(), Color::Green)
+    } else if stage == progress.stage {
+        let (filled, status) = if stage.reports_percent() {
+            (usize::from(progress.percent.min(100)) * BAR_WIDTH / 100, format!("{}%", progress.percent))
+        } else {
+            (0, "running".to_string())
+        };
+        ("▶ ", bar(filled), status, Color::Cyan)
+    } else {
+        ("· ", bar(0), "waiting".to_string(), Color::DarkGray)
+    };
+    Spans::from(vec![
+        Span::styled(marker, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+        Span::styled(name, Style::default().fg(Color::White)),
+        Span::styled(bar, Style::default().fg(color)),
+        Span::styled(status, Style::default().fg(color)),
+    ])
+}
+
+// "Frame: 1500  Speed: 48.5 fps (2.0x)  Bitrate: 2150.3 kbit/s  Written: 12.4 MB",
+// leaving out whatever the backend hasn't reported
+fn encode_stats_spans(stats: &EncodeStats) -> Vec<Span<'static>> {
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Green).add_modifier(Modifier::BOLD));
+    let value = |text: String| Span::styled(text, Style::default().fg(Color::White));
+    
+    let mut spans = Vec::new();
+    if let Some(frame) = stats.frame {
+        spans.extend([label("Frame: "), value(format!("{}  ", frame))]);
     }
+    let speed = match (stats.fps, stats.speed) {
+        (Some(fps), Some(speed)) => Some(format!("{:.1} fps ({:.2}x)  ", fps, speed)),
+        (Some(fps), None) => Some(format!("{:.1} fps  ", fps)),
+        (None, Some(speed)) => Some(format!("{:.2}x  ", speed)),
+        (None, None) => None,
+    };
+    if let Some(speed) = speed {
+        spans.extend([label("Speed: "), value(speed)]);
+    }
+    if let Some(bitrate) = stats.bitrate_kbps {
+        spans.extend([label("Bitrate: "), value(format!("{:.1} kbit/s  ", bitrate))]);
+    }
+    if let Some(size) = stats.total_size {
+        spans.extend([label("Written: "), value(format_size(size))]);
+    }
+    
+    if spans.is_empty() {
+        spans.push(Span::styled("No encoder statistics reported yet", Style::default().fg(Color::DarkGray)));
+    }
+    spans
 }
 
 fn render_complete<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
-    if let Some(progress) = &app.conversion_progress {
-        // Determine which conversion tool was used
-        let (tool_color, conversion_tool) = match crate::native_converter::NativeConverter::check_available() {
-            Ok(true) => (Color::Green, "Native Rust FFmpeg"),
-            _ => match crate::ffmpeg::FFmpegConverter::check_ffmpeg_available() {
-                Ok(true) => (Color::Green, "External FFmpeg"),
-                _ => (Color::Yellow, "Simulation Mode"),
-            },
+    // Several jobs ended: list them all, Enter opens the highlighted one
+    if app.lists_results() {
+        render_results(f, app, area);
+        return;
+    }
+    
+    if let Some(result) = app.selected_result() {
+        let progress = &result.progress;
+        // Which conversion tool ran the job
+        let mode = result.mode;
+        
+        let headline = if progress.has_error {
+            Span::styled("❌ Conversion Failed", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        } else if mode.is_simulation() {
+            Span::styled("🔮 Simulation Finished (no file was written)", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        } else {
+            Span::styled("✅ Conversion Complete!", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
         };
         
-        let text = vec![
-            Spans::from(vec![
-                Span::styled("✅ Conversion Complete!", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-            ]),
+        let mut text = vec![
+            Spans::from(vec![headline]),
             Spans::from(""),
             Spans::from(vec![
                 Span::styled("Source File: ", Style::default().fg(Color::Green)),
                 Span::styled(
-                    progress.source_file.file_name().unwrap_or_default().to_string_lossy().to_string(), 
+                    paths::escape(progress.source_file.file_name().unwrap_or_default()).to_string(), 
                     Style::default().fg(Color::White)
                 ),
             ]),
@@ -435,31 +1102,146 @@ fn render_complete<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
             Spans::from(vec![
                 Span::styled("Output File: ", Style::default().fg(Color::Green)),
                 Span::styled(
-                    progress.output_file.file_name().unwrap_or_default().to_string_lossy().to_string(), 
+                    output_file_text(progress, mode), 
                     Style::default().fg(Color::White)
                 ),
             ]),
             Spans::from(vec![
                 Span::styled("Conversion Method: ", Style::default().fg(Color::Green)),
                 Span::styled(
-                    conversion_tool, 
-                    Style::default().fg(tool_color)
+                    app.mode_label(mode), 
+                    Style::default().fg(mode_color(mode))
                 ),
-            ]),
-            Spans::from(""),
-            Spans::from(vec![
-                Span::styled("Press 'n' to convert another file or 'q' to quit", Style::default().fg(Color::Yellow)),
+                Span::styled(if result.remote { " (remote job)" } else { "" }, Style::default().fg(Color::DarkGray)),
             ]),
         ];
-
-        let completion_widget = Paragraph::new(text)
-            .block(
-                Block::default()
-                    .title(" Conversion Result ")
-                    .title_alignment(Alignment::Center)
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(Color::Blue))
+        
+        if progress.has_error {
+            text.push(Spans::from(""));
+            text.push(Spans::from(vec![
+                Span::styled("Error: ", Style::default().fg(Color::Red)),
+                Span::styled(
+                    progress.error_message.clone().unwrap_or_else(|| progress.current_step.clone()),
+                    Style::default().fg(Color::White)
+                ),
+            ]));
+        } else if mode.is_simulation() {
+            text.push(Spans::from(""));
+            text.push(Spans::from(vec![
+                Span::styled("Install FFmpeg to convert files for real", Style::default().fg(Color::Yellow)),
+            ]));
+        } else {
+            let stats = &result.stats;
+            let size_text = |size: Option<u64>| size.map(format_size).unwrap_or_else(|| "n/a".to_string());
+            
+            let saved_text = match stats.percent_saved() {
+                Some(saved) if saved >= 0.0 => format!("{:.1}% smaller", saved),
+                Some(saved) => format!("{:.1}% larger", -saved),
+                None => "n/a".to_string(),
+            };
+            
+            let speed_text = match (stats.throughput_mb_per_sec(), stats.realtime_factor()) {
+                (Some(mb), Some(factor)) => format!("{:.1} MB/s ({:.2}x realtime)", mb, factor),
+                (Some(mb), None) => format!("{:.1} MB/s", mb),
+                _ => "n/a".to_string(),
+            };
+            
+            text.push(Spans::from(""));
+            text.push(Spans::from(vec![
+                Span::styled("Source Size: ", Style::default().fg(Color::Green)),
+                Span::styled(size_text(stats.source_size), Style::default().fg(Color::White)),
+            ]));
+            text.push(Spans::from(vec![
+                Span::styled("Output Size: ", Style::default().fg(Color::Green)),
+                Span::styled(size_text(stats.output_size), Style::default().fg(Color::White)),
+            ]));
+            text.push(Spans::from(vec![
+                Span::styled("Saved: ", Style::default().fg(Color::Green)),
+                Span::styled(saved_text, Style::default().fg(Color::White)),
+            ]));
+            text.push(Spans::from(vec![
+                Span::styled("Time Taken: ", Style::default().fg(Color::Green)),
+                Span::styled(format_elapsed(stats.elapsed), Style::default().fg(Color::White)),
+            ]));
+            text.push(Spans::from(vec![
+                Span::styled("Started: ", Style::default().fg(Color::Green)),
+                Span::styled(timestamp(stats.started_at()), Style::default().fg(Color::White)),
+                Span::styled("  Finished: ", Style::default().fg(Color::Green)),
+                Span::styled(timestamp(stats.finished_at), Style::default().fg(Color::White)),
+            ]));
+            text.push(Spans::from(vec![
+                Span::styled("Average Speed: ", Style::default().fg(Color::Green)),
+                Span::styled(speed_text, Style::default().fg(Color::White)),
+            ]));
+            let copied_text = if stats.copied_streams.is_empty() {
+                "none, everything re-encoded".to_string()
+            } else {
+                stats.copied_streams.join(", ")
+            };
+            text.push(Spans::from(vec![
+                Span::styled("Copied Streams: ", Style::default().fg(Color::Green)),
+                Span::styled(copied_text, Style::default().fg(Color::White)),
+            ]));
+            if !stats.substituted_encoders.is_empty() {
+                text.push(Spans::from(vec![
+                    Span::styled("Stand-in Encoders: ", Style::default().fg(Color::Yellow)),
+                    Span::styled(stats.substituted_encoders.join(", "), Style::default().fg(Color::White)),
+                ]));
+            }
+            if !stats.extra_output_files.is_empty() {
+                let names = stats.extra_output_files.iter()
+                    .map(|file| file.file_name().map(paths::escape).unwrap_or_default().into_owned())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                text.push(Spans::from(vec![
+                    Span::styled("Also Written: ", Style::default().fg(Color::Green)),
+                    Span::styled(names, Style::default().fg(Color::White)),
+                ]));
+            }
+            if let Some(playlist) = &stats.hls_playlist {
+                // "talk.hls/master.m3u8"
+                let folder = playlist.parent().and_then(Path::file_name).unwrap_or_default();
+                let shown = format!("{}/{}", paths::escape(folder), paths::escape(playlist.file_name().unwrap_or_default()));
+                text.push(Spans::from(vec![
+                    Span::styled("HLS Playlist: ", Style::default().fg(Color::Green)),
+                    Span::styled(shown, Style::default().fg(Color::White)),
+                ]));
+            }
+        }
+        
+        text.push(Spans::from(""));
+        if app.finished_output().is_some() {
+            text.push(Spans::from(vec![
+                Span::styled("Press 'o' to open the folder, 'v' to play the file or 'd' to delete it", Style::default().fg(Color::Yellow)),
+            ]));
+        }
+        if app.results.len() > 1 {
+            text.push(Spans::from(vec![
+                Span::styled("Press Enter or Esc to go back to the list", Style::default().fg(Color::Yellow)),
+            ]));
+        }
+        text.push(Spans::from(vec![
+            Span::styled("Press 'n' to convert another file or 'q' to quit", Style::default().fg(Color::Yellow)),
+        ]));
+        if let Some(message) = &app.launch_message {
+            text.push(Spans::from(vec![
+                Span::styled(message.as_str(), Style::default().fg(Color::Cyan)),
+            ]));
+        }
+        
+        let title = if app.results.len() > 1 {
+            format!(" Conversion Result ({} of {}) ", app.selected_result + 1, app.results.len())
+        } else {
+            " Conversion Result ".to_string()
+        };
+        let completion_widget = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(title)
+                    .title_alignment(Alignment::Center)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Blue))
             )
             .alignment(Alignment::Center);
         
@@ -467,14 +1249,98 @@ fn render_complete<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     }
 }
 
+// One row per job that ended since the last 'n', with how it went
+fn render_results<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let block = Block::default()
+        .title(" Conversion Results ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Blue));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Totals
+            Constraint::Min(1),    // Results
+            Constraint::Length(3), // Keys and launch messages
+        ].as_ref())
+        .split(inner);
+    
+    let count = |state: JobState| app.results.iter().filter(|result| result.state() == state).count();
+    let mut totals = vec![
+        Span::styled(format!("✅ {} converted", count(JobState::Completed)), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+    ];
+    for (state, icon, label, color) in [(JobState::Failed, "❌", "failed", Color::Red), (JobState::Cancelled, "⏹", "cancelled", Color::DarkGray)] {
+        let n = count(state);
+        if n > 0 {
+            totals.push(Span::raw("   "));
+            totals.push(Span::styled(format!("{} {} {}", icon, n, label), Style::default().fg(color).add_modifier(Modifier::BOLD)));
+        }
+    }
+    f.render_widget(Paragraph::new(Spans::from(totals)).alignment(Alignment::Center), chunks[0]);
+    
+    let items: Vec<ListItem> = app.results
+        .iter()
+        .map(|result| {
+            let (icon, color) = match result.state() {
+                JobState::Completed if result.mode.is_simulation() => ("🔮", Color::Yellow),
+                JobState::Completed => ("✅", Color::Green),
+                JobState::Cancelled => ("⏹ ", Color::DarkGray),
+                _ => ("❌", Color::Red),
+            };
+            let name = paths::escape(result.progress.source_file.file_name().unwrap_or_default());
+            let size = match result.output() {
+                Some(_) => result.stats.output_size.map(format_size).unwrap_or_else(|| "n/a".to_string()),
+                None => "-".to_string(),
+            };
+            let detail = match result.state() {
+                JobState::Completed if result.remote => "remote job".to_string(),
+                JobState::Completed => String::new(),
+                _ => result.progress.error_message.clone().unwrap_or_else(|| result.progress.current_step.clone()),
+            };
+            
+            ListItem::new(Spans::from(vec![
+                Span::styled(format!("{} ", icon), Style::default().fg(color)),
+                Span::styled(format!("{} → {}  ", name, result.progress.target_format.as_str()), Style::default().fg(Color::White)),
+                Span::styled(format!("{:>10}  ", size), Style::default().fg(Color::Cyan)),
+                Span::styled(format!("{:<10}", format_elapsed(result.stats.elapsed)), Style::default().fg(Color::White)),
+                Span::styled(detail, Style::default().fg(if result.state() == JobState::Failed { Color::Red } else { Color::DarkGray })),
+            ]))
+        })
+        .collect();
+    
+    let list = List::new(items)
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray))
+        .highlight_symbol("➤ ");
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(app.selected_result));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+    
+    let mut hints = vec![Spans::from(vec![
+        Span::styled("↑/↓: Select  Enter: Details  o/v/d: Open folder/play/delete the highlighted output  n: New conversion", Style::default().fg(Color::Yellow)),
+    ])];
+    if let Some(message) = &app.launch_message {
+        hints.push(Spans::from(vec![
+            Span::styled(message.as_str(), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    f.render_widget(Paragraph::new(hints).alignment(Alignment::Center), chunks[2]);
+}
+
 fn render_settings<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
-    // Determine which conversion tool is available
-    let conversion_tool = match crate::native_converter::NativeConverter::check_available() {
-        Ok(true) => "Native Rust FFmpeg",
-        _ => match crate::ffmpeg::FFmpegConverter::check_ffmpeg_available() {
-            Ok(true) => "External FFmpeg",
-            _ => "Simulation Mode",
-        },
+    // Built-in backend or a plugin, chosen with ←/→ when selected
+    let mode = app.available_mode();
+    let mut conversion_tool = format!("◀ {} ▶", app.mode_label(mode));
+    if !app.plugin_errors.is_empty() {
+        conversion_tool.push_str(&format!("  ({} plugin manifest(s) failed to load)", app.plugin_errors.len()));
+    }
+    let tool_style = if app.selected_setting == AdvancedSetting::Backend {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(mode_color(mode))
     };
     
     // Create layout for settings sections
@@ -484,8 +1350,9 @@ fn render_settings<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         .constraints([
             Constraint::Length(3),  // Conversion tool
             Constraint::Length(1),  // Spacer
-            Constraint::Length(10), // Advanced video settings
-            Constraint::Min(0),     // Future settings
+            Constraint::Length(19), // Advanced video settings
+            Constraint::Length(8),  // Tool paths, upload, fallback folder and extensions
+            Constraint::Min(0),     // Hint for the selected setting
         ].as_ref())
         .split(area);
     
@@ -503,11 +1370,17 @@ fn render_settings<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     
     // Then render the text in the inner area
     let tool_text = Paragraph::new(conversion_tool)
-        .style(Style::default().fg(Color::Green))
+        .style(tool_style)
         .alignment(Alignment::Center);
     
     f.render_widget(tool_text, tool_inner);
     
+    // Video settings on the left, encoder-specific ones beside them
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+        .split(chunks[2]);
+    
     // Advanced video settings section
     let settings_block = Block::default()
         .title(" Advanced Video Settings ")
@@ -515,18 +1388,28 @@ fn render_settings<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         .border_style(Style::default().fg(Color::Yellow));
     
     // Calculate inner area before rendering the block
-    let settings_area = settings_block.inner(chunks[2]);
+    let settings_area = settings_block.inner(columns[0]);
     
     // Render the block
-    f.render_widget(settings_block, chunks[2]);
+    f.render_widget(settings_block, columns[0]);
     let settings_layout = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
             Constraint::Length(1),  // Resolution
+            Constraint::Length(1),  // Crop
+            Constraint::Length(1),  // Ladder
             Constraint::Length(1),  // Bitrate
+            Constraint::Length(1),  // Smart quality
             Constraint::Length(1),  // Frame Rate
-            Constraint::Length(1),  // Spacer
+            Constraint::Length(1),  // Frame rate mode
+            Constraint::Length(1),  // Image quality
+            Constraint::Length(1),  // Subtitles
+            Constraint::Length(1),  // Audio file
+            Constraint::Length(1),  // File times
+            Constraint::Length(1),  // Checksum
+            Constraint::Length(1),  // Metadata
+            Constraint::Length(1),  // Source check
             Constraint::Length(1),  // Instructions
         ].as_ref())
         .split(settings_area);
@@ -541,15 +1424,66 @@ fn render_settings<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     let resolution_para = Paragraph::new(resolution_text).style(resolution_style);
     f.render_widget(resolution_para, settings_layout[0]);
     
+    // Crop setting
+    let crop_style = if app.selected_setting == AdvancedSetting::Crop {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let crop_para = Paragraph::new(format!("Crop: {}", app.video_settings.crop.as_str())).style(crop_style);
+    f.render_widget(crop_para, settings_layout[1]);
+    
+    // Rendition ladder, with what's left of it in a format that can't take it all
+    let ladder_style = if app.selected_setting == AdvancedSetting::Ladder {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let ladder = app.video_settings.ladder;
+    let mut ladder_text = vec![Span::styled(format!("ABR Ladder: {}", ladder.as_str()), ladder_style)];
+    let format = app.get_current_format();
+    let note = match ladder.for_format(format) {
+        _ if ladder.is_off() => None,
+        Ladder::Off => Some(format!(" (not for {})", format.as_str())),
+        Ladder::Renditions if ladder == Ladder::Hls => Some(format!(" (no HLS for {})", format.as_str())),
+        _ => None,
+    };
+    if let Some(note) = note {
+        ladder_text.push(Span::styled(note, Style::default().fg(Color::DarkGray)));
+    }
+    f.render_widget(Paragraph::new(Spans::from(ladder_text)), settings_layout[2]);
+    
     // Bitrate setting
-    let bitrate_text = format!("Bitrate: {}", app.video_settings.bitrate.as_str());
+    let bitrate_text = format!("Bitrate: {}", app.video_settings.bitrate.label());
     let bitrate_style = if app.selected_setting == AdvancedSetting::Bitrate {
         Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
     } else {
         Style::default().fg(Color::White)
     };
     let bitrate_para = Paragraph::new(bitrate_text).style(bitrate_style);
-    f.render_widget(bitrate_para, settings_layout[1]);
+    f.render_widget(bitrate_para, settings_layout[3]);
+    
+    // Per-title CRF search, which only CRF-based encoders and automatic bitrate use
+    let settings = &app.video_settings;
+    let smart_style = if app.selected_setting == AdvancedSetting::SmartQuality {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let mut smart_quality = vec![Span::styled(format!("Smart Quality: {}", settings.quality_target.as_str()), smart_style)];
+    if settings.quality_target != QualityTarget::Off {
+        let note = if FFmpegConverter::crf_search_range(format).is_none() {
+            Some(format!(" (not used by {})", format.as_str()))
+        } else if settings.bitrate.value_kbps(&settings.resolution) > 0 {
+            Some(" (bitrate set)".to_string())
+        } else {
+            None
+        };
+        if let Some(note) = note {
+            smart_quality.push(Span::styled(note, Style::default().fg(Color::DarkGray)));
+        }
+    }
+    f.render_widget(Paragraph::new(Spans::from(smart_quality)), settings_layout[4]);
     
     // Frame rate setting
     let framerate_text = format!("Frame Rate: {}", app.video_settings.frame_rate.as_str());
@@ -559,13 +1493,218 @@ fn render_settings<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         Style::default().fg(Color::White)
     };
     let framerate_para = Paragraph::new(framerate_text).style(framerate_style);
-    f.render_widget(framerate_para, settings_layout[2]);
+    f.render_widget(framerate_para, settings_layout[5]);
+    
+    // Constant or variable frame timing
+    let mode_text = format!("Frame Rate Mode: {}", app.video_settings.frame_rate_mode.as_str());
+    let mode_style = if app.selected_setting == AdvancedSetting::FrameRateMode {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let mode_para = Paragraph::new(mode_text).style(mode_style);
+    f.render_widget(mode_para, settings_layout[6]);
+    
+    // Image quality setting (only used by animated image formats)
+    let quality_text = format!("Image Quality: {} (WEBP/APNG)", app.video_settings.image_quality.as_str());
+    let quality_style = if app.selected_setting == AdvancedSetting::ImageQuality {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let quality_para = Paragraph::new(quality_text).style(quality_style);
+    f.render_widget(quality_para, settings_layout[7]);
+    
+    // External subtitles, attached by pressing Enter on one in the browser
+    let subtitles_text = format!("Subtitles: {}", subtitles_label(app.video_settings.subtitles.as_ref()));
+    let subtitles_style = if app.selected_setting == AdvancedSetting::Subtitles {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let subtitles_para = Paragraph::new(subtitles_text).style(subtitles_style);
+    f.render_widget(subtitles_para, settings_layout[8]);
+    
+    // Audio file replacing or mixed over the source's, attached by pressing a in the browser
+    let audio_text = format!("Audio File: {}", external_audio_label(app.video_settings.external_audio.as_ref()));
+    let audio_style = if app.selected_setting == AdvancedSetting::ExternalAudio {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let audio_para = Paragraph::new(audio_text).style(audio_style);
+    f.render_widget(audio_para, settings_layout[9]);
+    
+    // Source timestamps (and Unix permissions) carried over to the output
+    let file_times_text = format!("File Times: {}", if app.video_settings.keep_file_times { "Keep source's" } else { "New" });
+    let file_times_style = if app.selected_setting == AdvancedSetting::FileTimes {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let file_times_para = Paragraph::new(file_times_text).style(file_times_style);
+    f.render_widget(file_times_para, settings_layout[10]);
+    
+    // SHA-256 sidecar written once the output is finished
+    let checksum_text = format!("Checksum: {}", if app.video_settings.write_checksum { "SHA-256 sidecar" } else { "Off" });
+    let checksum_style = if app.selected_setting == AdvancedSetting::Checksum {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let checksum_para = Paragraph::new(checksum_text).style(checksum_style);
+    f.render_widget(checksum_para, settings_layout[11]);
+    
+    // Probe info, settings and command line recorded beside the output
+    let metadata_text = format!("Metadata: {}", if app.video_settings.write_metadata { "JSON sidecar" } else { "Off" });
+    let metadata_style = if app.selected_setting == AdvancedSetting::Metadata {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let metadata_para = Paragraph::new(metadata_text).style(metadata_style);
+    f.render_widget(metadata_para, settings_layout[12]);
+    
+    // Quick decode of the source's start and end before encoding
+    let source_check_text = format!("Source Check: {}", if app.video_settings.check_integrity { "Start and end" } else { "Off" });
+    let source_check_style = if app.selected_setting == AdvancedSetting::SourceCheck {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let source_check_para = Paragraph::new(source_check_text).style(source_check_style);
+    f.render_widget(source_check_para, settings_layout[13]);
     
     // Instructions
-    let instructions = Paragraph::new("↑/↓: Select setting | ←/→: Change value")
+    let instructions = Paragraph::new("↑/↓: Select | ←/→: Change | b: Bitrate | v: Framing")
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center);
+    f.render_widget(instructions, settings_layout[14]);
+    
+    render_encoder_settings(f, app, columns[1]);
+    
+    // Tool paths section
+    let tools_block = Block::default()
+        .title(" Tools & Upload ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    
+    let tools_area = tools_block.inner(chunks[3]);
+    f.render_widget(tools_block, chunks[3]);
+    
+    let tools_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .horizontal_margin(1)
+        .constraints([
+            Constraint::Length(1),  // FFmpeg path
+            Constraint::Length(1),  // FFprobe path
+            Constraint::Length(1),  // Upload destination
+            Constraint::Length(1),  // Fallback output folder
+            Constraint::Length(1),  // Extra browser extensions
+            Constraint::Length(1),  // Instructions
+        ].as_ref())
+        .split(tools_area);
+    
+    let path_rows = [
+        (AdvancedSetting::FFmpegPath, "FFmpeg", &app.config.ffmpeg_path),
+        (AdvancedSetting::FFprobePath, "FFprobe", &app.config.ffprobe_path),
+    ];
+    
+    for (row, (setting, label, configured)) in path_rows.iter().enumerate() {
+        let selected = app.selected_setting == *setting;
+        let value = match (&app.path_input, selected) {
+            (Some(input), true) => format!("{}_", input),
+            _ => configured
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "(app folders, then PATH)".to_string()),
+        };
+        let style = if selected {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let path_para = Paragraph::new(format!("{} Path: {}", label, value)).style(style);
+        f.render_widget(path_para, tools_layout[row]);
+    }
+    
+    // Finished files are copied here as the job's last stage
+    let upload_selected = app.selected_setting == AdvancedSetting::UploadTo;
+    let upload_value = match (&app.path_input, upload_selected) {
+        (Some(input), true) => format!("{}_", input),
+        _ => app.config.upload_to.clone().unwrap_or_else(|| "(off)".to_string()),
+    };
+    let upload_style = if upload_selected {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    f.render_widget(Paragraph::new(format!("Upload To: {}", upload_value)).style(upload_style), tools_layout[2]);
+    
+    // Offered when the source's folder refuses the output
+    let fallback_selected = app.selected_setting == AdvancedSetting::FallbackOutput;
+    let fallback_value = match (&app.path_input, fallback_selected) {
+        (Some(input), true) => format!("{}_", input),
+        _ => app.config.fallback_output_dir
+            .as_ref()
+            .map(|dir| paths::display(dir).into_owned())
+            .unwrap_or_else(|| "(temp folder)".to_string()),
+    };
+    let fallback_style = if fallback_selected {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    f.render_widget(Paragraph::new(format!("Fallback Output: {}", fallback_value)).style(fallback_style), tools_layout[3]);
+    
+    // Listed in the browser on top of the output formats
+    let extensions_selected = app.selected_setting == AdvancedSetting::ExtraExtensions;
+    let extensions_value = match (&app.path_input, extensions_selected) {
+        (Some(input), true) => format!("{}_", input),
+        _ if app.config.extra_extensions.is_empty() => "(none)".to_string(),
+        _ => app.config.extra_extensions.join(", "),
+    };
+    let extensions_style = if extensions_selected {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    f.render_widget(Paragraph::new(format!("Extra Extensions: {}", extensions_value)).style(extensions_style), tools_layout[4]);
+    
+    let tools_instructions = match (&app.path_input, app.selected_setting) {
+        (Some(_), AdvancedSetting::UploadTo) => "s3://bucket/prefix or sftp://user@host/dir | Enter: Save | Esc: Cancel | Empty: off",
+        (Some(_), AdvancedSetting::FallbackOutput) => "Type a folder | Enter: Save | Esc: Cancel | Empty: use the temp folder",
+        (Some(_), AdvancedSetting::ExtraExtensions) => "e.g. mpg, divx, rmvb | Enter: Save | Esc: Cancel | Empty: none",
+        (Some(_), _) => "Type a path (relative = next to this app) | Enter: Save | Esc: Cancel | Empty: use PATH",
+        (None, AdvancedSetting::UploadTo) => "Enter: Edit the upload destination",
+        (None, AdvancedSetting::FallbackOutput) => "Enter: Edit the fallback output folder",
+        (None, AdvancedSetting::ExtraExtensions) => "Enter: Edit the extra file extensions",
+        (None, _) => "Enter: Edit selected path",
+    };
+    let tools_help = Paragraph::new(tools_instructions)
         .style(Style::default().fg(Color::Gray))
         .alignment(Alignment::Center);
-    f.render_widget(instructions, settings_layout[4]);
+    f.render_widget(tools_help, tools_layout[5]);
+    
+    render_setting_hint(f, app, chunks[4]);
+}
+
+// Explain the highlighted setting, and what its current value trades off
+fn render_setting_hint<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let mut text = vec![Spans::from(Span::styled(app.selected_setting.hint(), Style::default().fg(Color::White)))];
+    if let Some(description) = app.selected_value_description() {
+        text.push(Spans::from(Span::styled(description, Style::default().fg(Color::Cyan))));
+    }
+    
+    let hint = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" About This Setting ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray))
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(hint, area);
 }
 
 fn render_help<B: Backend>(f: &mut Frame<B>, area: Rect) {
@@ -583,16 +1722,88 @@ fn render_help<B: Backend>(f: &mut Frame<B>, area: Rect) {
             Span::styled("Open directory / Select file / Start conversion", Style::default().fg(Color::White)),
         ]),
         Spans::from(vec![
-            Span::styled("←/→ or Tab: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-            Span::styled("Switch tabs", Style::default().fg(Color::White)),
+            Span::styled("←/→ or Tab/Shift+Tab: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Switch tabs (on Settings ←/→ change the selected value, so use Tab/Shift+Tab)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("/: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Filter the file list by name as you type; Backspace widens it, Esc clears it (File Browser)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("d: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Dual pane: pick the output folder on the right, ←/→ switch panes (File Browser)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("~ / \\: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Jump to your home folder / the filesystem or drive root (File Browser)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("a: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Use the highlighted file's audio in the next conversion, replacing or mixed with the source's (File Browser)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("s: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Extract subtitles of the highlighted file to .srt/.ass (File Browser)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("f: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Save a frame of the highlighted file at a chosen time as PNG/JPEG (File Browser)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("u: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Convert from an http:// or https:// URL instead of a local file (File Browser)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("t: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Test encode a 10-second sample from the middle of the file", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("Space: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Mark the highlighted format to write too, in the same job (Format Selection)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("r: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Give a marked format its own resolution (Format Selection)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("k: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Choose kept audio/subtitle tracks and their default/forced flags (Format Selection)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("x: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Step through the file a frame at a time to set trim in/out points (Format Selection)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("b: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Bitrate calculator (Settings tab)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("v: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Preview a frame with the crop and resolution applied (Settings tab)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("c: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Cancel the running conversion", Style::default().fg(Color::White)),
         ]),
         Spans::from(vec![
             Span::styled("n: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
             Span::styled("Start new conversion (after completion)", Style::default().fg(Color::White)),
         ]),
+        Spans::from(vec![
+            Span::styled("o / v / d: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Open the output folder / play the output / delete it (after completion; the highlighted job when several ended)", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("[ / ] / { / } / ! / h: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Select a queued remote job / move it earlier or later / toggle high priority / pause it (waiting jobs only)", Style::default().fg(Color::White)),
+        ]),
         Spans::from(vec![
             Span::styled("p: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-            Span::styled("Show/hide popup", Style::default().fg(Color::White)),
+            Span::styled("Conversion summary; Enter in it starts the conversion, Esc closes it", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(vec![
+            Span::styled("i: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("Diagnostics: FFmpeg versions and paths, config locations and terminal details for bug reports", Style::default().fg(Color::White)),
         ]),
         Spans::from(vec![
             Span::styled("q: ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
@@ -606,7 +1817,7 @@ fn render_help<B: Backend>(f: &mut Frame<B>, area: Rect) {
         Spans::from("This application allows you to convert video files to different formats."),
         Spans::from("Browse for a file, select a format, and press Enter to start the conversion."),
     ];
-
+    
     let help_widget = Paragraph::new(help_text)
         .block(
             Block::default()
@@ -617,67 +1828,371 @@ fn render_help<B: Backend>(f: &mut Frame<B>, area: Rect) {
                 .border_style(Style::default().fg(Color::Blue))
         )
         .alignment(Alignment::Left);
-
+    
     f.render_widget(help_widget, area);
 }
 
-fn render_status_bar<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
-    let status_text = match app.current_tab {
-        AppTab::FileBrowser => {
-            if let Some(path) = app.file_browser.get_selected_file() {
-                if path.is_dir() {
-                    "Press Enter to open directory | Tab: Switch tabs | q: Quit".to_string()
-                } else {
-                    "Press Enter to select file | Tab: Switch tabs | q: Quit".to_string()
-                }
+fn render_stats<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Green));
+    let value = |text: String| Span::styled(text, Style::default().fg(Color::White));
+    
+    let text = match &app.history_stats {
+        None => vec![Spans::from("Loading...")],
+        Some(Err(e)) => vec![Spans::from(Span::styled(format!("Couldn't read the conversion history: {}", e), Style::default().fg(Color::Red)))],
+        Some(Ok(stats)) if stats.files == 0 => vec![
+            Spans::from(Span::styled("No conversions recorded yet", Style::default().fg(Color::DarkGray))),
+        ],
+        Some(Ok(stats)) => {
+            let saved_text = if stats.bytes_saved >= 0 {
+                format_size(stats.bytes_saved as u64)
             } else {
-                "No files found | Tab: Switch tabs | q: Quit".to_string()
+                format!("none, outputs grew by {}", format_size(stats.bytes_saved.unsigned_abs()))
+            };
+            let mut text = vec![
+                Spans::from(vec![label("Files Converted: "), value(stats.files.to_string())]),
+                Spans::from(vec![label("Video Converted: "), value(format_elapsed(stats.video_duration()))]),
+                Spans::from(vec![label("Storage Saved: "), value(saved_text)]),
+                Spans::from(""),
+                Spans::from(Span::styled(
+                    format!("{:<24} {:>7} {:>18}", "Codec", "Files", "Average Speed"),
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                )),
+            ];
+            for codec in &stats.codecs {
+                let speed = codec.realtime_factor()
+                    .map(|factor| format!("{:.2}x realtime", factor))
+                    .unwrap_or_else(|| "n/a".to_string());
+                text.push(Spans::from(value(format!("{:<24} {:>7} {:>18}", codec.codec, codec.files, speed))));
+            }
+            
+            text.push(Spans::from(""));
+            text.push(Spans::from(Span::styled("Recent Conversions", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))));
+            for entry in &stats.recent {
+                let name = paths::escape(entry.source.file_name().unwrap_or_default());
+                text.push(Spans::from(vec![
+                    Span::styled(format!("{}  ", timestamp(entry.finished())), Style::default().fg(Color::DarkGray)),
+                    value(format!("{} → {}  ", name, entry.format)),
+                    label("took "),
+                    value(format_elapsed(entry.elapsed())),
+                ]));
             }
+            text
         },
-        AppTab::FormatSelection => format!("Selected Format: {} | Press Enter to convert | Tab: Switch tabs | q: Quit", app.get_current_format().as_str()),
-        AppTab::Converting => "Converting... Please wait | q: Quit".to_string(),
-        AppTab::Complete => "Conversion complete! Press 'n' for new conversion | q: Quit".to_string(),
-        AppTab::Settings => "Settings | Tab: Switch tabs | q: Quit".to_string(),
-        AppTab::Help => "Help & Information | Tab: Switch tabs | q: Quit".to_string(),
     };
     
-    let status_bar = Paragraph::new(Spans::from(vec![
-        Span::styled(" ● ", Style::default().fg(Color::Green)),
-        Span::styled(&status_text, Style::default().fg(Color::White)),
-    ]))
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::DarkGray))
-    );
+    let stats_widget = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Lifetime Statistics ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Blue))
+        )
+        .alignment(Alignment::Left);
     
-    f.render_widget(status_bar, area);
+    f.render_widget(stats_widget, area);
 }
 
-fn render_popup<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
-    let popup_area = centered_rect(60, 20, area);
-    
-    // Clear the area
-    f.render_widget(
-        Block::default()
-            .style(Style::default().bg(Color::Black)),
-        popup_area,
-    );
-    
-    // Determine which conversion tool is available
-    let (tool_color, conversion_tool) = match crate::native_converter::NativeConverter::check_available() {
-        Ok(true) => (Color::Green, "Native Rust FFmpeg"),
-        _ => match crate::ffmpeg::FFmpegConverter::check_ffmpeg_available() {
-            Ok(true) => (Color::Green, "External FFmpeg"),
-            _ => (Color::Yellow, "Simulation Mode"),
-        },
+fn render_remote_jobs<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let mut title = match app.serve_addr {
+        Some(addr) => format!(" Remote Jobs (http://{}) ", addr),
+        None => " Remote Jobs ".to_string(),
     };
+    if let Some(message) = &app.queue_message {
+        title.push_str(&format!("- {} ", message));
+    } else if app.selected_queued.is_some() {
+        title.push_str("- [/]: Select queued  {/}: Move  !: Priority  h: Pause ");
+    }
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Cyan));
+    
+    let jobs: Vec<&Job> = app.remote_jobs().collect();
+    if jobs.is_empty() {
+        let empty = Paragraph::new("No jobs yet - POST /jobs to submit one")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+    
+    let skip = jobs.len().saturating_sub(MAX_REMOTE_JOB_ROWS);
+    let items: Vec<ListItem> = jobs
+        .into_iter()
+        .skip(skip)
+        .map(|job| {
+            let state = job.state();
+            let state_color = match state {
+                JobState::Queued => Color::Blue,
+                JobState::Running => Color::Yellow,
+                JobState::Paused => Color::Magenta,
+                JobState::Completed => Color::Green,
+                JobState::Failed => Color::Red,
+                JobState::Cancelled => Color::DarkGray,
+            };
+            let name = paths::escape(job.source_file.file_name().unwrap_or_default());
+            let progress = job.progress.as_ref();
+            let detail = match state {
+                JobState::Failed => progress.and_then(|p| p.error_message.clone()).unwrap_or_default(),
+                _ => progress.map(|p| p.current_step.clone()).unwrap_or_default(),
+            };
+            
+            let selected = state.is_waiting() && app.selected_queued == Some(job.id);
+            let marker = match (selected, job.high_priority) {
+                (true, true) => "▶!",
+                (true, false) => "▶ ",
+                (false, true) => " !",
+                (false, false) => "  ",
+            };
+            
+            ListItem::new(Spans::from(vec![
+                Span::styled(marker, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("#{:<3} ", job.id), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{} → {} ", name, job.target_format.as_str()), Style::default().fg(Color::White)),
+                Span::styled(format!("{:>3}% ", progress.map_or(0, |p| p.percent)), Style::default().fg(Color::Cyan)),
+                Span::styled(format!("{:<10}", state.label()), Style::default().fg(state_color)),
+                Span::styled(format!("{:<12}", job.elapsed().map(format_elapsed).unwrap_or_default()), Style::default().fg(Color::White)),
+                Span::styled(detail, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    
+    f.render_widget(List::new(items).block(block), area);
+}
+
+// Options that only apply to some encoders: H.264 profile, level and tune, pixel format, GOP structure
+fn render_encoder_settings<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let block = Block::default()
+        .title(" Encoder Settings ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1),  // H.264 profile
+            Constraint::Length(1),  // H.264 level
+            Constraint::Length(1),  // Tune
+            Constraint::Length(1),  // Pixel format
+            Constraint::Length(1),  // Keyframe interval
+            Constraint::Length(1),  // Scene-cut keyframes
+            Constraint::Length(1),  // Audio passthrough
+            Constraint::Length(1),  // Downmix
+            Constraint::Length(1),  // I/O priority
+            Constraint::Length(1),  // Eco mode
+            Constraint::Length(1),  // FFmpeg log
+            Constraint::Min(0),
+        ].as_ref())
+        .split(inner);
+    
+    let style = |setting: AdvancedSetting| if app.selected_setting == setting {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    
+    let settings = &app.video_settings;
+    f.render_widget(
+        Paragraph::new(format!("H.264 Profile: {}", settings.h264_profile.as_str())).style(style(AdvancedSetting::H264Profile)),
+        layout[0],
+    );
+    
+    // A fixed resolution and frame rate can be checked now; otherwise the source
+    // decides and the conversion stops if the level is too low
+    let too_low = match (settings.resolution.dimensions(), settings.frame_rate.value()) {
+        (Some((width, height)), Some(fps)) => settings.check_h264_level(width, height, f64::from(fps)).is_err(),
+        _ => false,
+    };
+    let mut level = vec![Span::styled(
+        format!("H.264 Level: {}", settings.h264_level.as_str()),
+        style(AdvancedSetting::H264Level),
+    )];
+    if too_low {
+        level.push(Span::styled(
+            format!(" ⚠ too low for {} at {}", settings.resolution.as_str(), settings.frame_rate.as_str()),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    f.render_widget(Paragraph::new(Spans::from(level)), layout[1]);
+    
+    f.render_widget(
+        Paragraph::new(format!("H.264 Tune: {}", settings.tune.as_str())).style(style(AdvancedSetting::Tune)),
+        layout[2],
+    );
+    
+    // 10-bit is the one choice here that can leave a file unplayable
+    let format = app.get_current_format();
+    let mut pixel_format = vec![Span::styled(
+        format!("Pixel Format: {}", settings.pixel_format.as_str()),
+        style(AdvancedSetting::PixelFormat),
+    )];
+    if settings.pixel_format == PixelFormat::Yuv420p10 {
+        let warning = if format.has_fixed_pixel_format() {
+            format!(" (not used by {})", format.as_str())
+        } else if format.supports_10bit() {
+            " ⚠ many TVs and phones can't play 10-bit".to_string()
+        } else {
+            format!(" ⚠ {} is 8-bit only", format.as_str())
+        };
+        pixel_format.push(Span::styled(warning, Style::default().fg(Color::Red)));
+    }
+    f.render_widget(Paragraph::new(Spans::from(pixel_format)), layout[3]);
+    
+    f.render_widget(
+        Paragraph::new(format!("Keyframe Interval: {}", settings.keyframe_interval.as_str())).style(style(AdvancedSetting::KeyframeInterval)),
+        layout[4],
+    );
+    let scene_cut = if settings.scene_cut { "On" } else { "Off (fixed GOP)" };
+    f.render_widget(
+        Paragraph::new(format!("Scene-cut Keyframes: {}", scene_cut)).style(style(AdvancedSetting::SceneCut)),
+        layout[5],
+    );
+    
+    let mut passthrough = vec![Span::styled(
+        format!("Surround Passthrough: {}", if settings.audio_passthrough { "On" } else { "Off" }),
+        style(AdvancedSetting::AudioPassthrough),
+    )];
+    if settings.audio_passthrough && format.passthrough_codecs().len() < SURROUND_CODECS.len() {
+        let note = match format.passthrough_codecs() {
+            [] => format!(" (not carried by {})", format.as_str()),
+            codecs => format!(" ({} only)", codecs.join("/").to_uppercase()),
+        };
+        passthrough.push(Span::styled(note, Style::default().fg(Color::DarkGray)));
+    }
+    f.render_widget(Paragraph::new(Spans::from(passthrough)), layout[6]);
+    
+    f.render_widget(
+        Paragraph::new(format!("Surround Downmix: {}", settings.downmix.as_str())).style(style(AdvancedSetting::Downmix)),
+        layout[7],
+    );
+    
+    let mut io_priority = vec![Span::styled(format!("I/O Priority: {}", settings.io_priority.as_str()), style(AdvancedSetting::IoPriority))];
+    if settings.io_priority != IoPriority::Normal && !cfg!(target_os = "linux") {
+        io_priority.push(Span::styled(" (Linux only)", Style::default().fg(Color::DarkGray)));
+    }
+    f.render_widget(Paragraph::new(Spans::from(io_priority)), layout[8]);
+    
+    f.render_widget(
+        Paragraph::new(format!("Eco Mode: {}", settings.eco_mode.as_str())).style(style(AdvancedSetting::EcoMode)),
+        layout[9],
+    );
+    
+    let mut log_level = vec![Span::styled(format!("FFmpeg Log: {}", settings.log_level.as_str()), style(AdvancedSetting::LogLevel))];
+    if settings.log_level.report_level().is_some() {
+        let note = if settings.log_dir.is_some() { " (report per encode)" } else { " (no log folder here)" };
+        log_level.push(Span::styled(note, Style::default().fg(Color::DarkGray)));
+    }
+    f.render_widget(Paragraph::new(Spans::from(log_level)), layout[10]);
+}
+
+fn render_status_bar<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let status_text = match app.current_tab {
+        _ if app.quitting => "Stopping conversions and removing partial output... | q: Quit now".to_string(),
+        _ if app.show_popup => "Enter: Start conversion | Esc: Close".to_string(),
+        AppTab::FileBrowser if app.destination_focused => {
+            "Enter: Open folder (outputs go to the folder shown) | ←/→: Source pane | d: Single pane | q: Quit".to_string()
+        },
+        AppTab::FileBrowser if app.file_browser.query().is_some() => {
+            "Type to narrow the list | Backspace: Widen | Esc: Clear filter | ↑/↓ and Enter work as usual".to_string()
+        },
+        AppTab::FileBrowser => {
+            if let Some(path) = app.file_browser.get_selected_file() {
+                if disc::is_video_ts(path) {
+                    "Press Enter to convert this DVD's main title | u: Open URL | Tab: Switch tabs | q: Quit".to_string()
+                } else if path.is_dir() {
+                    "Press Enter to open directory | u: Open URL | Tab: Switch tabs | q: Quit".to_string()
+                } else if let Some((_, message)) = app.browser_message.as_ref().filter(|(source, _)| source == path) {
+                    format!("{} | s: Subtitles | f: Save a frame | q: Quit", message)
+                } else if is_subtitle_file(path) {
+                    "Press Enter to add these subtitles to the next conversion | Tab: Switch tabs | q: Quit".to_string()
+                } else {
+                    "Press Enter to select file | a: Use its audio | s: Extract subtitles | f: Save a frame | Tab: Switch tabs | q: Quit".to_string()
+                }
+            } else {
+                "No files found | u: Open URL | Tab: Switch tabs | q: Quit".to_string()
+            }
+        },
+        AppTab::FormatSelection if !app.missing_components(app.get_current_format()).is_empty() => {
+            format!(
+                "{} is not supported by {} | Tab: Switch tabs | q: Quit",
+                app.get_current_format().as_str(),
+                app.mode_label(app.available_mode())
+            )
+        },
+        AppTab::FormatSelection if app.track_message.is_some() => {
+            format!("{} | k: Tracks | q: Quit", app.track_message.as_deref().unwrap_or_default())
+        },
+        AppTab::FormatSelection if app.source_url.is_some() => format!(
+            "Source: {} | Enter: Convert | u: Change URL (File Browser) | q: Quit",
+            app.source_url.as_deref().unwrap_or(Path::new("")).display()
+        ),
+        AppTab::FormatSelection if app.is_same_format() => format!(
+            "The source is already {} | Enter: Remux or re-encode | Tab: Switch tabs | q: Quit",
+            app.get_current_format().as_str()
+        ),
+        AppTab::FormatSelection => {
+            let extras = app.extra_outputs();
+            let also = if extras.is_empty() { String::new() } else { format!(" + {}", extra_outputs_text(&extras)) };
+            format!("Format: {}{} | Enter: Convert | Space: Also write | t: Test encode | k: Tracks | x: Trim | Tab: Switch tabs | q: Quit", app.get_current_format().as_str(), also)
+        },
+        AppTab::Converting => "Converting... Please wait | c: Cancel | q: Quit".to_string(),
+        AppTab::Complete if app.lists_results() => {
+            format!("{} jobs ended | ↑/↓: Select | Enter: Details | n: New conversion | q: Quit", app.results.len())
+        },
+        AppTab::Complete if app.finished_output().is_some() => "Conversion complete! n: New conversion | o: Open folder | v: Play | d: Delete | q: Quit".to_string(),
+        AppTab::Complete => "Conversion complete! Press 'n' for new conversion | q: Quit".to_string(),
+        // Rows mark the selected setting by color alone; the accessible display names it
+        AppTab::Settings if app.config.accessible_ui => {
+            format!("Selected: {} | b: Bitrate calculator | v: Preview framing | Tab/Shift+Tab: Switch tabs | q: Quit", app.selected_setting.name())
+        },
+        AppTab::Settings => "Settings | ←/→: Change value | b: Bitrate calculator | v: Preview framing | Tab/Shift+Tab: Switch tabs | q: Quit".to_string(),
+        AppTab::Stats => "Lifetime statistics | Tab: Switch tabs | q: Quit".to_string(),
+        AppTab::Help => "Help & Information | Tab: Switch tabs | q: Quit".to_string(),
+    };
+    
+    let status_bar = Paragraph::new(Spans::from(vec![
+        Span::styled(" ● ", Style::default().fg(Color::Green)),
+        Span::styled(&status_text, Style::default().fg(Color::White)),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::DarkGray))
+    );
+    
+    f.render_widget(status_bar, area);
+}
+
+fn render_popup<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 20, area);
+    
+    // Clear the area
+    f.render_widget(
+        Block::default()
+            .style(Style::default().bg(Color::Black)),
+        popup_area,
+    );
+    
+    // Determine which conversion tool is available
+    let mode = app.available_mode();
     
     let current_format = app.get_current_format();
-    let popup_text = if let Some(file_path) = app.file_browser.get_selected_file() {
-        if file_path.is_file() {
-            let file_name = file_path.file_name().unwrap_or_default().to_string_lossy();
+    let extras = app.extra_outputs();
+    let ladder = app.video_settings.ladder.for_format(current_format);
+    let popup_text = if let Some(file_path) = app.selected_source() {
+        if file_path.is_file() || disc::is_video_ts(file_path) || is_url(file_path) {
+            let file_name = if is_url(file_path) {
+                file_path.to_string_lossy()
+            } else {
+                paths::escape(file_path.file_name().unwrap_or_default())
+            };
             vec![
                 Spans::from(vec![
                     Span::styled("Ready to Convert", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
@@ -690,10 +2205,14 @@ fn render_popup<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                 Spans::from(vec![
                     Span::styled("Format: ", Style::default().fg(Color::Green)),
                     Span::styled(current_format.as_str(), Style::default().fg(Color::White)),
+                    Span::styled(
+                        if extras.is_empty() { String::new() } else { format!(" + {}", extra_outputs_text(&extras)) },
+                        Style::default().fg(Color::Cyan)
+                    ),
                 ]),
                 Spans::from(vec![
                     Span::styled("Using: ", Style::default().fg(Color::Green)),
-                    Span::styled(conversion_tool, Style::default().fg(tool_color)),
+                    Span::styled(app.mode_label(mode), Style::default().fg(mode_color(mode))),
                 ]),
                 Spans::from(""),
                 Spans::from(vec![
@@ -701,15 +2220,61 @@ fn render_popup<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                 ]),
                 Spans::from(vec![
                     Span::styled("  Resolution: ", Style::default().fg(Color::Cyan)),
-                    Span::styled(app.video_settings.resolution.as_str(), Style::default().fg(Color::White)),
-                ]),
-                Spans::from(vec![
-                    Span::styled("  Bitrate: ", Style::default().fg(Color::Cyan)),
-                    Span::styled(app.video_settings.bitrate.as_str(), Style::default().fg(Color::White)),
+                    Span::styled(
+                        if ladder.is_off() { app.video_settings.resolution.as_str().to_string() } else { format!("ABR ladder, {}", ladder.as_str()) },
+                        Style::default().fg(Color::White),
+                    ),
+                    Span::styled(
+                        if app.video_settings.crop == Crop::Off { String::new() } else { format!(" (cropped to {})", app.video_settings.crop.as_str()) },
+                        Style::default().fg(Color::White),
+                    ),
                 ]),
+                // Animated images are sized by quality instead of bitrate
+                if current_format.is_animated_image() {
+                    Spans::from(vec![
+                        Span::styled("  Image Quality: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(app.video_settings.image_quality.as_str(), Style::default().fg(Color::White)),
+                    ])
+                } else {
+                    Spans::from(vec![
+                        Span::styled("  Bitrate: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            if ladder.is_off() { app.video_settings.bitrate.label() } else { "Set per rendition".to_string() },
+                            Style::default().fg(Color::White),
+                        ),
+                    ])
+                },
                 Spans::from(vec![
                     Span::styled("  Frame Rate: ", Style::default().fg(Color::Cyan)),
                     Span::styled(app.video_settings.frame_rate.as_str(), Style::default().fg(Color::White)),
+                    Span::styled(
+                        match app.video_settings.frame_rate_mode {
+                            FrameRateMode::Auto => "",
+                            FrameRateMode::Constant => " (CFR)",
+                            FrameRateMode::Variable => " (VFR)",
+                        },
+                        Style::default().fg(Color::White),
+                    ),
+                ]),
+                Spans::from(vec![
+                    Span::styled("  Subtitles: ", Style::default().fg(Color::Cyan)),
+                    Span::styled(subtitles_label(app.video_settings.subtitles.as_ref()), Style::default().fg(Color::White)),
+                ]),
+                Spans::from(vec![
+                    Span::styled("  Trim: ", Style::default().fg(Color::Cyan)),
+                    Span::styled(app.video_settings.trim.label(), Style::default().fg(Color::White)),
+                ]),
+                Spans::from(vec![
+                    Span::styled("  Audio File: ", Style::default().fg(Color::Cyan)),
+                    Span::styled(external_audio_label(app.video_settings.external_audio.as_ref()), Style::default().fg(Color::White)),
+                ]),
+                Spans::from(vec![
+                    Span::styled("  Encoder: ", Style::default().fg(Color::Cyan)),
+                    Span::styled(encoder_label(&app.video_settings, current_format), Style::default().fg(Color::White)),
+                ]),
+                Spans::from(vec![
+                    Span::styled("  Tracks: ", Style::default().fg(Color::Cyan)),
+                    Span::styled(app.tracks_label().unwrap_or_else(|| "Main audio (press k to choose)".to_string()), Style::default().fg(Color::White)),
                 ]),
                 Spans::from(""),
                 Spans::from("Press Enter to start conversion or Esc to cancel."),
@@ -751,23 +2316,939 @@ fn render_popup<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     f.render_widget(popup, popup_area);
 }
 
-// Helper function to create a centered rect using a percentage of the available rect
-pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
+fn render_bitrate_calculator<B: Backend>(f: &mut Frame<B>, calculator: &BitrateCalculator, area: Rect) {
+    let popup_area = centered_rect(50, 40, area);
+    
+    // Clear the area
+    f.render_widget(
+        Block::default()
+            .style(Style::default().bg(Color::Black)),
+        popup_area,
+    );
+    
+    let field = |label: &'static str, value: &str, hint: &'static str, focused: bool| {
+        let value_style = if focused {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let cursor = if focused { "_" } else { "" };
+        Spans::from(vec![
+            Span::styled(label, Style::default().fg(Color::Green)),
+            Span::styled(format!("{}{}", value, cursor), value_style),
+            Span::styled(hint, Style::default().fg(Color::DarkGray)),
+        ])
+    };
+    
+    let result = match calculator.result() {
+        Some(Bitrate::Custom(kbps)) => Span::styled(
+            format!("{} kbps video", kbps),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        _ => Span::styled("Enter a duration and target size", Style::default().fg(Color::DarkGray)),
+    };
+    
+    let text = vec![
+        Spans::from(vec![
+            Span::styled("Bitrate Calculator", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        ]),
+        Spans::from(""),
+        field("Duration: ", &calculator.duration, " (hh:mm:ss)", calculator.focused == CalculatorField::Duration),
+        field("Target Size: ", &calculator.target_size_mb, " MB", calculator.focused == CalculatorField::TargetSize),
+        field("Audio Bitrate: ", &calculator.audio_kbps, " kbps", calculator.focused == CalculatorField::AudioBitrate),
+        Spans::from(""),
+        Spans::from(vec![
+            Span::styled("Required Bitrate: ", Style::default().fg(Color::Green)),
+            result,
+        ]),
+        Spans::from(""),
+        Spans::from("Tab/↑/↓: Switch field | Enter: Apply to settings | Esc: Close"),
+    ];
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Bitrate Calculator ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Cyan))
+        )
+        .alignment(Alignment::Center);
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_url_input<B: Backend>(f: &mut Frame<B>, app: &App, input: &str, area: Rect) {
+    let popup_area = centered_rect(70, 25, area);
+    f.render_widget(Clear, popup_area);
+    
+    // Checked as it's typed, since Enter only accepts an http(s) URL
+    let hint = if input.trim().is_empty() || is_url(Path::new(input.trim())) {
+        Span::styled("The output is saved in the browser's current folder", Style::default().fg(Color::DarkGray))
+    } else {
+        Span::styled("Enter an http:// or https:// URL", Style::default().fg(Color::Red))
+    };
+    
+    // Pages (rather than media files) need yt-dlp to find the video
+    let fetch = match app.ytdlp_available {
+        Some(true) if app.url_fetch => Span::styled("Download with yt-dlp, then convert", Style::default().fg(Color::White)),
+        Some(true) => Span::styled("Read directly by FFmpeg", Style::default().fg(Color::White)),
+        _ => Span::styled("Read directly by FFmpeg (install yt-dlp for web pages)", Style::default().fg(Color::DarkGray)),
+    };
+    
+    let text = vec![
+        Spans::from(vec![
+            Span::styled("URL: ", Style::default().fg(Color::Green)),
+            Span::styled(format!("{}_", input), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        ]),
+        Spans::from(vec![
+            Span::styled("Source: ", Style::default().fg(Color::Green)),
+            fetch,
+        ]),
+        Spans::from(""),
+        Spans::from(hint),
+        Spans::from(""),
+        Spans::from("Tab: yt-dlp on/off | Enter: Choose format | Esc: Cancel"),
+    ];
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Convert from URL ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Cyan))
+                .style(Style::default().bg(Color::Black))
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: false });
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_diagnostics<B: Backend>(f: &mut Frame<B>, diagnostics: &Diagnostics, area: Rect) {
+    let popup_area = centered_rect(80, 80, area);
+    f.render_widget(Clear, popup_area);
+    
+    // The outcome of a save goes first, where it's seen without scrolling
+    let mut text = Vec::new();
+    match &diagnostics.saved {
+        Some(Ok(path)) => text.push(Spans::from(Span::styled(format!("Saved to {}", path.display()), Style::default().fg(Color::Green)))),
+        Some(Err(e)) => text.push(Spans::from(Span::styled(format!("Couldn't save the report: {}", e), Style::default().fg(Color::Red)))),
+        None => {},
+    }
+    for section in &diagnostics.sections {
+        text.push(Spans::from(Span::styled(section.title, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))));
+        for (label, value) in &section.rows {
+            text.push(Spans::from(vec![
+                Span::styled(format!("{:<18}", format!("{}:", label)), Style::default().fg(Color::Green)),
+                Span::styled(value.clone(), Style::default().fg(Color::White)),
+            ]));
+        }
+        text.push(Spans::from(""));
+    }
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Diagnostics - s: Save report  ↑/↓: Scroll  Esc: Close ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Cyan))
+                .style(Style::default().bg(Color::Black))
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((diagnostics.scroll, 0));
+    f.render_widget(popup, popup_area);
+}
+
+// A frame of the highlighted file as the output will frame it, with the filters
+// that do it, so crop and resolution can be tried before converting
+fn render_framing_preview<B: Backend>(f: &mut Frame<B>, app: &App, preview: &FramingPreview, area: Rect) {
+    let popup_area = centered_rect(70, 80, area);
+    f.render_widget(Clear, popup_area);
+    
+    let block = Block::default()
+        .title(" Framing Preview ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+    
+    let chunks = Layout::default()
         .direction(Direction::Vertical)
+        .margin(1)
         .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Length(1),  // File
+            Constraint::Min(4),     // Frame
+            Constraint::Length(1),  // Crop and resolution
+            Constraint::Length(2),  // Filter chain
+            Constraint::Length(1),  // Keys
         ].as_ref())
-        .split(r);
+        .split(inner);
+    
+    if let Some(source) = &preview.source {
+        f.render_widget(
+            Paragraph::new(Spans::from(vec![
+                Span::styled("File: ", Style::default().fg(Color::Green)),
+                Span::styled(paths::escape(source.file_name().unwrap_or_default()).to_string(), Style::default().fg(Color::White)),
+            ])),
+            chunks[0],
+        );
+    }
+    
+    match &preview.frame {
+        Some(Ok(frame)) => f.render_widget(HalfBlockImage { frame }, chunks[1]),
+        Some(Err(reason)) => f.render_widget(
+            Paragraph::new(reason.as_str())
+                .style(Style::default().fg(Color::DarkGray))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true }),
+            chunks[1],
+        ),
+        None => f.render_widget(
+            Paragraph::new("Extracting the frame...")
+                .style(Style::default().fg(Color::DarkGray))
+                .alignment(Alignment::Center),
+            chunks[1],
+        ),
+    }
+    
+    // The frame shown stays up until the one with the new settings is in
+    let loading = if preview.is_loading() { "   (loading)" } else { "" };
+    let settings = &app.video_settings;
+    f.render_widget(
+        Paragraph::new(Spans::from(vec![
+            Span::styled("Crop: ", Style::default().fg(Color::Green)),
+            Span::styled(settings.crop.as_str(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled("   Resolution: ", Style::default().fg(Color::Green)),
+            Span::styled(settings.resolution.as_str(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled("   Format: ", Style::default().fg(Color::Green)),
+            Span::styled(app.get_current_format().as_str(), Style::default().fg(Color::White)),
+            Span::styled(loading, Style::default().fg(Color::DarkGray)),
+        ]))
+        .alignment(Alignment::Center),
+        chunks[2],
+    );
+    
+    let filters = if preview.filters.is_empty() { "none (the picture is kept as it is)".to_string() } else { preview.filters.join(",") };
+    f.render_widget(
+        Paragraph::new(Spans::from(vec![
+            Span::styled("Filters: ", Style::default().fg(Color::Green)),
+            Span::styled(filters, Style::default().fg(Color::DarkGray)),
+        ]))
+        .wrap(Wrap { trim: true }),
+        chunks[3],
+    );
+    
+    f.render_widget(
+        Paragraph::new("←/→: Crop | ↑/↓: Resolution | Esc: Close")
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center),
+        chunks[4],
+    );
+}
 
-    Layout::default()
-        .direction(Direction::Horizontal)
+// The frame at the current point of the file, where it is, and the stretch the
+// trim keeps, so the in and out points can be set a frame at a time
+fn render_trim_editor<B: Backend>(f: &mut Frame<B>, app: &App, editor: &TrimEditor, area: Rect) {
+    let popup_area = centered_rect(70, 80, area);
+    f.render_widget(Clear, popup_area);
+    
+    let block = Block::default()
+        .title(" Trim ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+    
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
         .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Length(1),  // File
+            Constraint::Min(4),     // Frame
+            Constraint::Length(1),  // Position
+            Constraint::Length(1),  // Timeline
+            Constraint::Length(1),  // In and out points
+            Constraint::Length(2),  // Keys
         ].as_ref())
-        .split(popup_layout[1])[1]
+        .split(inner);
+    
+    f.render_widget(
+        Paragraph::new(Spans::from(vec![
+            Span::styled("File: ", Style::default().fg(Color::Green)),
+            Span::styled(paths::escape(editor.source.file_name().unwrap_or_default()).to_string(), Style::default().fg(Color::White)),
+        ])),
+        chunks[0],
+    );
+    
+    match &editor.frame {
+        Some(Ok(frame)) => f.render_widget(HalfBlockImage { frame }, chunks[1]),
+        Some(Err(reason)) => f.render_widget(
+            Paragraph::new(reason.as_str())
+                .style(Style::default().fg(Color::DarkGray))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true }),
+            chunks[1],
+        ),
+        None => f.render_widget(
+            Paragraph::new(if editor.is_ready() { "Extracting the frame..." } else { "Reading the video..." })
+                .style(Style::default().fg(Color::DarkGray))
+                .alignment(Alignment::Center),
+            chunks[1],
+        ),
+    }
+    
+    // Nothing to step through until the frame rate and length are known
+    if !editor.is_ready() {
+        return;
+    }
+    
+    // The frame shown stays up until the one at the new position is in
+    let loading = if editor.is_loading() { "   (loading)" } else { "" };
+    f.render_widget(
+        Paragraph::new(Spans::from(vec![
+            Span::styled("Position: ", Style::default().fg(Color::Green)),
+            Span::styled(format_position(editor.time()), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("   Frame {} of {} at {:.3} fps", editor.position + 1, editor.frames, editor.frame_rate),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(loading, Style::default().fg(Color::DarkGray)),
+        ]))
+        .alignment(Alignment::Center),
+        chunks[2],
+    );
+    
+    // The kept stretch in yellow, with a marker at the frame shown
+    let trim = app.video_settings.trim;
+    let width = chunks[3].width.max(1) as u64;
+    let duration = editor.duration().max(f64::EPSILON);
+    let marker = (editor.position * width / editor.frames).min(width - 1);
+    let timeline: Vec<Span> = (0..width)
+        .map(|column| {
+            let at = duration * (column as f64 + 0.5) / width as f64;
+            let kept = trim.start.is_none_or(|start| at >= start) && trim.end.is_none_or(|end| at < end);
+            if column == marker {
+                Span::styled("┃", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            } else if kept {
+                Span::styled("━", Style::default().fg(Color::Yellow))
+            } else {
+                Span::styled("─", Style::default().fg(Color::DarkGray))
+            }
+        })
+        .collect();
+    f.render_widget(Paragraph::new(Spans::from(timeline)), chunks[3]);
+    
+    let (start, length) = trim.window(Some(editor.duration()));
+    f.render_widget(
+        Paragraph::new(Spans::from(vec![
+            Span::styled("In: ", Style::default().fg(Color::Green)),
+            Span::styled(format_position(start), Style::default().fg(Color::White)),
+            Span::styled("   Out: ", Style::default().fg(Color::Green)),
+            Span::styled(format_position(trim.end.unwrap_or(editor.duration())), Style::default().fg(Color::White)),
+            Span::styled("   Length: ", Style::default().fg(Color::Green)),
+            Span::styled(format_position(length.unwrap_or(0.0)), Style::default().fg(Color::White)),
+        ]))
+        .alignment(Alignment::Center),
+        chunks[4],
+    );
+    
+    f.render_widget(
+        Paragraph::new("←/→: Frame | ↑/↓: Second | PgUp/PgDn: 10 s | i/o: Set in/out here | Home/End: Go to in/out | c: Clear | Esc: Close")
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        chunks[5],
+    );
+}
+
+fn render_still_prompt<B: Backend>(f: &mut Frame<B>, prompt: &StillPrompt, area: Rect) {
+    let popup_area = centered_rect(50, 30, area);
+    
+    // Clear the area so the preview pane doesn't show through
+    f.render_widget(Clear, popup_area);
+    
+    let file_name = paths::escape(prompt.source.file_name().unwrap_or_default());
+    let status = match &prompt.error {
+        Some(error) => Span::styled(error.clone(), Style::default().fg(Color::Red)),
+        None => Span::styled("Seconds, mm:ss or hh:mm:ss", Style::default().fg(Color::DarkGray)),
+    };
+    
+    let text = vec![
+        Spans::from(vec![
+            Span::styled("File: ", Style::default().fg(Color::Green)),
+            Span::styled(file_name.to_string(), Style::default().fg(Color::White)),
+        ]),
+        Spans::from(""),
+        Spans::from(vec![
+            Span::styled("Timestamp: ", Style::default().fg(Color::Green)),
+            Span::styled(format!("{}_", prompt.timestamp), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        ]),
+        Spans::from(vec![
+            Span::styled("Image: ", Style::default().fg(Color::Green)),
+            Span::styled(prompt.format.as_str(), Style::default().fg(Color::White)),
+        ]),
+        Spans::from(""),
+        Spans::from(status),
+        Spans::from(""),
+        Spans::from("Tab: PNG/JPEG | Enter: Save | Esc: Cancel"),
+    ];
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Save Still Frame ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Cyan))
+                .style(Style::default().bg(Color::Black))
+        )
+        .alignment(Alignment::Center);
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_delete_confirm<B: Backend>(f: &mut Frame<B>, confirm: &DeleteConfirm, area: Rect) {
+    let popup_area = centered_rect(50, 25, area);
+    
+    f.render_widget(Clear, popup_area);
+    
+    let file_name = paths::escape(confirm.outputs.first().and_then(|output| output.file_name()).unwrap_or_default());
+    let mut text = vec![
+        Spans::from(vec![
+            Span::styled("Delete ", Style::default().fg(Color::White)),
+            Span::styled(file_name.to_string(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled("?", Style::default().fg(Color::White)),
+        ]),
+        Spans::from(""),
+    ];
+    
+    // Whatever else the job wrote goes with it
+    let mut others = Vec::new();
+    if confirm.outputs.len() > 1 {
+        others.push(format!("its {} other outputs", confirm.outputs.len() - 1));
+    }
+    if !confirm.sidecars.is_empty() {
+        others.push("the checksum/metadata sidecars".to_string());
+    }
+    if confirm.hls_dir.is_some() {
+        others.push("the HLS folder".to_string());
+    }
+    if !others.is_empty() {
+        text.push(Spans::from(Span::styled(format!("Along with {}", others.join(", ")), Style::default().fg(Color::DarkGray))));
+        text.push(Spans::from(""));
+    }
+    text.push(Spans::from("y/Enter: Delete | n/Esc: Keep"));
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Delete Output ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Red))
+                .style(Style::default().bg(Color::Black))
+        )
+        .alignment(Alignment::Center);
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_not_playable<B: Backend>(f: &mut Frame<B>, app: &App, reason: &str, area: Rect) {
+    let popup_area = centered_rect(60, 30, area);
+    
+    f.render_widget(Clear, popup_area);
+    
+    let file_name = paths::escape(app.selected_source()
+        .and_then(|source| source.file_name())
+        .unwrap_or_default());
+    let text = vec![
+        Spans::from(Span::styled(format!("{} is not a playable video", file_name), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))),
+        Spans::from(""),
+        Spans::from(format!("ffprobe: {}", reason)),
+        Spans::from(""),
+        Spans::from("Pick another file to convert"),
+        Spans::from("Enter/Esc: Close"),
+    ];
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Not a Video ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Red))
+                .style(Style::default().bg(Color::Black))
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_access_prompt<B: Backend>(f: &mut Frame<B>, prompt: &AccessPrompt, area: Rect) {
+    let popup_area = centered_rect(60, 35, area);
+    
+    f.render_widget(Clear, popup_area);
+    
+    let problem = &prompt.problem;
+    let what = match problem.kind {
+        AccessKind::UnreadableSource => "The source file can't be read",
+        AccessKind::UnwritableOutput => "The output can't be written",
+    };
+    let mut text = vec![
+        Spans::from(Span::styled(what, Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))),
+        Spans::from(""),
+        Spans::from(vec![Span::styled("Error: ", Style::default().fg(Color::Gray)), Span::raw(problem.message.clone())]),
+        Spans::from(vec![Span::styled("Path: ", Style::default().fg(Color::Gray)), Span::raw(paths::display(&problem.path).into_owned())]),
+        Spans::from(""),
+    ];
+    match &prompt.redirect {
+        Some(dir) => {
+            text.push(Spans::from(Span::styled(
+                format!("o: Write the output to {} instead", paths::display(dir)),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            )));
+            text.push(Spans::from("Enter/Esc: Cancel"));
+        },
+        None => {
+            text.push(Spans::from(Span::styled(problem.suggestion(), Style::default().fg(Color::Yellow))));
+            text.push(Spans::from("Enter/Esc: Close"));
+        },
+    }
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(format!(" {} ", problem.title()))
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Red))
+                .style(Style::default().bg(Color::Black))
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_same_format_prompt<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 35, area);
+    
+    f.render_widget(Clear, popup_area);
+    
+    let file_name = paths::escape(app.selected_source()
+        .and_then(|source| source.file_name())
+        .unwrap_or_default());
+    let format = app.get_current_format().as_str();
+    let key = Style::default().fg(Color::Green).add_modifier(Modifier::BOLD);
+    let mut text = vec![
+        Spans::from(Span::styled(format!("{} is already {}", file_name, format), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        Spans::from("Re-encoding it loses quality unless the settings change something"),
+        Spans::from(""),
+    ];
+    if app.remux_available() {
+        text.push(Spans::from(vec![
+            Span::styled("r: ", key),
+            Span::raw("Remux - copy every stream into a new file, without quality loss"),
+        ]));
+    } else {
+        text.push(Spans::from(Span::styled("Remuxing needs FFmpeg", Style::default().fg(Color::DarkGray))));
+    }
+    text.push(Spans::from(vec![
+        Span::styled("e: ", key),
+        Span::raw("Re-encode with the current settings"),
+    ]));
+    text.push(Spans::from(""));
+    if let Some(output) = app.planned_output() {
+        let output_name = paths::escape(output.file_name().unwrap_or_default());
+        text.push(Spans::from(Span::styled(format!("Saved as {}", output_name), Style::default().fg(Color::DarkGray))));
+    }
+    text.push(Spans::from("Esc: Cancel"));
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Same Format ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Cyan))
+                .style(Style::default().bg(Color::Black))
+        )
+        .alignment(Alignment::Center);
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_conflict_prompt<B: Backend>(f: &mut Frame<B>, app: &App, prompt: &ConflictPrompt, area: Rect) {
+    let popup_area = centered_rect(70, 50, area);
+    
+    f.render_widget(Clear, popup_area);
+    
+    let key = Style::default().fg(Color::Green).add_modifier(Modifier::BOLD);
+    let mut text = vec![
+        Spans::from(Span::styled(
+            format!("{} can't take every setting as it is", app.get_current_format().as_str()),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+    ];
+    for conflict in &prompt.conflicts {
+        text.push(Spans::from(format!("• {}", conflict)));
+        text.push(Spans::from(""));
+    }
+    text.push(Spans::from(vec![
+        Span::styled("Enter: ", key),
+        Span::raw("Convert with these adjustments   "),
+        Span::styled("s: ", key),
+        Span::raw("Change settings   "),
+        Span::styled("Esc: ", key),
+        Span::raw("Cancel"),
+    ]));
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Container Conflicts ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Yellow))
+                .style(Style::default().bg(Color::Black))
+        )
+        .wrap(Wrap { trim: true });
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_quit_confirm<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let popup_area = centered_rect(50, 25, area);
+    
+    f.render_widget(Clear, popup_area);
+    
+    let running = match app.job_manager.active_jobs() {
+        1 => "A conversion is in progress".to_string(),
+        count => format!("{} conversions are in progress", count),
+    };
+    let text = vec![
+        Spans::from(Span::styled(running, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        Spans::from(""),
+        Spans::from("Cancel and quit? Partial output is removed"),
+        Spans::from(""),
+        Spans::from("y/Enter: Cancel and quit | n/Esc: Keep converting"),
+    ];
+    
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(" Quit ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(Color::Red))
+                .style(Style::default().bg(Color::Black))
+        )
+        .alignment(Alignment::Center);
+    
+    f.render_widget(popup, popup_area);
+}
+
+fn render_subtitle_picker<B: Backend>(f: &mut Frame<B>, picker: &SubtitlePicker, area: Rect) {
+    let popup_area = centered_rect(60, 50, area);
+    
+    // Clear the area; track rows are short, so the preview pane would show through
+    f.render_widget(Clear, popup_area);
+    f.render_widget(
+        Block::default()
+            .style(Style::default().bg(Color::Black)),
+        popup_area,
+    );
+    
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(2), // File name
+            Constraint::Min(1),    // Tracks
+            Constraint::Length(1), // Instructions
+        ].as_ref())
+        .split(popup_area);
+    
+    let file_name = paths::escape(picker.source.file_name().unwrap_or_default());
+    let header = Paragraph::new(Spans::from(vec![
+        Span::styled("File: ", Style::default().fg(Color::Green)),
+        Span::styled(file_name.to_string(), Style::default().fg(Color::White)),
+    ]));
+    
+    // Image-based tracks are listed, but greyed out since they can't become text
+    let items: Vec<ListItem> = std::iter::once(ListItem::new(format!("All tracks ({})", picker.tracks.len())))
+        .chain(picker.tracks.iter().map(|track| match track.sidecar_extension() {
+            Some(ext) => ListItem::new(format!("{} → .{}", track.label(), ext)),
+            None => ListItem::new(format!("{} (image-based, can't extract)", track.label()))
+                .style(Style::default().fg(Color::DarkGray)),
+        }))
+        .collect();
+    
+    let list = List::new(items)
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(picker.selected));
+    
+    let instructions = Paragraph::new("↑/↓: Choose track | Enter: Extract | Esc: Cancel")
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center);
+    
+    f.render_widget(
+        Block::default()
+            .title(" Extract Subtitles ")
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(Style::default().fg(Color::Cyan)),
+        popup_area,
+    );
+    f.render_widget(header, chunks[0]);
+    f.render_stateful_widget(list, chunks[1], &mut state);
+    f.render_widget(instructions, chunks[2]);
+}
+
+fn render_track_picker<B: Backend>(f: &mut Frame<B>, picker: &TrackPicker, area: Rect) {
+    let popup_area = centered_rect(70, 60, area);
+    
+    f.render_widget(Clear, popup_area);
+    f.render_widget(
+        Block::default()
+            .style(Style::default().bg(Color::Black)),
+        popup_area,
+    );
+    
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(2), // File name
+            Constraint::Min(1),    // Tracks
+            Constraint::Length(1), // Instructions
+        ].as_ref())
+        .split(popup_area);
+    
+    let file_name = paths::escape(picker.source.file_name().unwrap_or_default());
+    let header = Paragraph::new(Spans::from(vec![
+        Span::styled("File: ", Style::default().fg(Color::Green)),
+        Span::styled(file_name.to_string(), Style::default().fg(Color::White)),
+    ]));
+    
+    // Dropped tracks are greyed out; flags are shown the way Matroska tools list them
+    let items: Vec<ListItem> = picker.choices
+        .iter()
+        .map(|choice| {
+            let kind = match choice.track.kind {
+                TrackKind::Audio => "Audio",
+                TrackKind::Subtitle => "Subtitle",
+            };
+            let mut flags = Vec::new();
+            if choice.default {
+                flags.push("default");
+            }
+            if choice.forced {
+                flags.push("forced");
+            }
+            let flags = if flags.is_empty() { String::new() } else { format!(" [{}]", flags.join(", ")) };
+            let text = format!("[{}] {:<9}{}{}", if choice.keep { "x" } else { " " }, kind, choice.track.label(), flags);
+            
+            if choice.keep {
+                ListItem::new(text)
+            } else {
+                ListItem::new(text).style(Style::default().fg(Color::DarkGray))
+            }
+        })
+        .collect();
+    
+    let list = List::new(items)
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(picker.selected));
+    
+    let instructions = Paragraph::new("Space: Keep | d: Default | f: Forced | Enter: Apply | Esc: Cancel")
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center);
+    
+    f.render_widget(
+        Block::default()
+            .title(" Tracks ")
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(Style::default().fg(Color::Cyan)),
+        popup_area,
+    );
+    f.render_widget(header, chunks[0]);
+    f.render_stateful_widget(list, chunks[1], &mut state);
+    f.render_widget(instructions, chunks[2]);
+}
+
+// Helper function to create a centered rect using a percentage of the available rect
+pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ].as_ref())
+        .split(r);
+    
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ].as_ref())
+        .split(popup_layout[1])[1]
+}
+
+// Format a byte count using decimal units (KB, MB, GB)
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    
+    let mut value = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+// Format a wall-clock duration as e.g. "1h 02m 03s"
+fn format_elapsed(elapsed: std::time::Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    }
+}
+
+fn subtitles_label(subtitles: Option<&ExternalSubtitles>) -> String {
+    match subtitles {
+        Some(subs) => format!(
+            "{} ({})",
+            paths::escape(subs.path.file_name().unwrap_or_default()),
+            if subs.burn_in { "burned in" } else { "soft track" }
+        ),
+        None => "None".to_string(),
+    }
+}
+
+fn external_audio_label(audio: Option<&ExternalAudio>) -> String {
+    match audio {
+        Some(audio) => format!(
+            "{} ({})",
+            paths::escape(audio.path.file_name().unwrap_or_default()),
+            if audio.mix { "mixed in" } else { "replaces audio" }
+        ),
+        None => "Source's".to_string(),
+    }
+}
+
+// Encoder Settings that apply to the format, e.g. "High profile, level 4.1"
+fn encoder_label(settings: &VideoSettings, format: VideoFormat) -> String {
+    let mut parts = Vec::new();
+    if FFmpegConverter::video_encoder(format) == Some("libx264") {
+        if settings.output_pixel_format(format) == Some("yuv420p10le") {
+            parts.push("High 10 profile".to_string());
+        } else if settings.h264_profile != H264Profile::Auto {
+            parts.push(format!("{} profile", settings.h264_profile.as_str()));
+        }
+        if settings.h264_level != H264Level::Auto {
+            parts.push(format!("level {}", settings.h264_level.as_str()));
+        }
+        if let Some(tune) = settings.tune.ffmpeg_name() {
+            parts.push(format!("tune {}", tune));
+        }
+    }
+    if let Some(pixel_format) = settings.output_pixel_format(format) {
+        parts.push(pixel_format.to_string());
+    }
+    if !format.has_fixed_gop() {
+        if let Some(seconds) = settings.keyframe_interval.seconds() {
+            parts.push(format!("keyframe every {}s", seconds));
+        }
+        if !settings.scene_cut {
+            parts.push("fixed GOP".to_string());
+        }
+    }
+    if settings.audio_passthrough && !format.passthrough_codecs().is_empty() {
+        parts.push("surround passthrough".to_string());
+    }
+    if let Some(vmaf) = settings.quality_target.vmaf() {
+        if FFmpegConverter::crf_search_range(format).is_some() && settings.bitrate.value_kbps(&settings.resolution) == 0 {
+            parts.push(format!("smart quality (VMAF {:.0})", vmaf));
+        }
+    }
+    match settings.downmix {
+        Downmix::Off => {},
+        Downmix::Stereo => parts.push("stereo downmix".to_string()),
+        Downmix::DialogueBoost => parts.push("stereo downmix with dialogue boost".to_string()),
+    }
+    if settings.io_priority != IoPriority::Normal {
+        parts.push(format!("{} I/O priority", settings.io_priority.as_str().to_lowercase()));
+    }
+    match settings.eco_mode {
+        EcoMode::Off => {},
+        EcoMode::Battery => parts.push("eco mode on battery".to_string()),
+        EcoMode::Always => parts.push("eco mode".to_string()),
+    }
+    
+    if parts.is_empty() {
+        "Format defaults".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+fn mode_color(mode: ConversionMode) -> Color {
+    match mode {
+        ConversionMode::FFmpeg | ConversionMode::Native => Color::Green,
+        ConversionMode::Plugin => Color::Cyan,
+        ConversionMode::Simulation => Color::Yellow,
+    }
+}
+
+// Simulated runs never write anything, so don't present the path as a real file
+fn output_file_text(progress: &ConversionProgress, mode: ConversionMode) -> String {
+    let name = paths::escape(progress.output_file.file_name().unwrap_or_default()).to_string();
+    if mode.is_simulation() {
+        format!("{} (not written - simulation)", name)
+    } else {
+        name
+    }
 }
\ No newline at end of file
@@ -0,0 +1,167 @@
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+use regex::Regex;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::converter::{ProgressEvent, ProgressReporter, ProgressSender, VideoFormat};
+use crate::plugins::forward_lines;
+
+#[derive(Error, Debug)]
+pub enum UploadError {
+    #[error("Failed to run {0}: {1}")]
+    Io(&'static str, io::Error),
+    
+    #[error("{0} exited with status: {1}")]
+    ProcessError(&'static str, i32),
+}
+
+// Where finished outputs are shipped. Uploads go through the AWS CLI (`aws s3 cp`,
+// using its usual credentials) or curl's SFTP support (key-based login).
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadTarget {
+    // s3://bucket[/prefix]
+    S3(String),
+    // sftp://[user@]host[:port][/dir]
+    Sftp(String),
+}
+
+impl UploadTarget {
+    pub fn parse(destination: &str) -> Option<Self> {
+        let destination = destination.trim().trim_end_matches('/');
+        let (scheme, rest) = destination.split_once("://")?;
+        if rest.split('/').next().unwrap_or_default().is_empty() {
+            return None;
+        }
+        match scheme.to_lowercase().as_str() {
+            "s3" => Some(UploadTarget::S3(destination.to_string())),
+            "sftp" => Some(UploadTarget::Sftp(destination.to_string())),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> &str {
+        match self {
+            UploadTarget::S3(uri) | UploadTarget::Sftp(uri) => uri,
+        }
+    }
+    
+    fn program(&self) -> &'static str {
+        match self {
+            UploadTarget::S3(_) => "aws",
+            UploadTarget::Sftp(_) => "curl",
+        }
+    }
+}
+
+// Final stage of a job: copy the converted file to the configured destination.
+// The local file is kept either way.
+pub struct Uploader<'a> {
+    progress_tx: &'a ProgressSender,
+    cancel: &'a CancellationToken,
+}
+
+impl<'a> Uploader<'a> {
+    pub fn new(progress_tx: &'a ProgressSender, cancel: &'a CancellationToken) -> Self {
+        Self { progress_tx, cancel }
+    }
+    
+    // The remote location of the uploaded file, or None if the job was cancelled
+    pub async fn upload(&self, target: &UploadTarget, source_file: &Path, target_format: VideoFormat, output_file: &Path) -> Result<Option<String>, UploadError> {
+        let program = target.program();
+        let file_name = output_file.file_name().unwrap_or_default().to_string_lossy();
+        let remote = format!("{}/{}", target.as_str(), file_name);
+        
+        let mut command = tokio::process::Command::new(program);
+        match target {
+            UploadTarget::S3(_) => command.arg("s3").arg("cp").arg(output_file).arg(&remote),
+            // A trailing slash makes curl keep the local file name
+            UploadTarget::Sftp(uri) => command.arg("--show-error").arg("--progress-bar").arg("-T").arg(output_file).arg(format!("{}/", uri)),
+        };
+        
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| UploadError::Io(program, e))?;
+        
+        let (line_tx, mut line_rx) = mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_lines(stdout, line_tx.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_lines(stderr, line_tx.clone()));
+        }
+        drop(line_tx);
+        
+        let reporter = ProgressReporter::new(self.progress_tx.clone(), source_file, target_format, output_file);
+        let patterns = ProgressPatterns::new();
+        let mut last_percent = None;
+        loop {
+            let line = tokio::select! {
+                _ = self.cancel.cancelled() => {
+                    let _ = child.kill().await;
+                    return Ok(None);
+                },
+                line = line_rx.recv() => match line {
+                    Some(line) => line,
+                    None => break,
+                },
+            };
+            
+            if let Some(percent) = patterns.parse(&line) {
+                let percent = percent.clamp(0.0, 100.0) as u8;
+                if last_percent != Some(percent) {
+                    last_percent = Some(percent);
+                    reporter.report(ProgressEvent::Stage { percent, step: format!("Uploading to {}... {}%", target.as_str(), percent) }).await;
+                }
+            }
+        }
+        
+        let status = child.wait().await.map_err(|e| UploadError::Io(program, e))?;
+        if !status.success() {
+            return Err(UploadError::ProcessError(program, status.code().unwrap_or(-1)));
+        }
+        Ok(Some(remote))
+    }
+}
+
+// curl's bar ends in "42.0%"; the AWS CLI reports "Completed 1.5 MiB/10.0 MiB (...)".
+// Both print a line per tick, so the patterns are compiled once per upload.
+struct ProgressPatterns {
+    aws: Regex,
+    curl: Regex,
+}
+
+impl ProgressPatterns {
+    fn new() -> Self {
+        Self {
+            aws: Regex::new(r"Completed ([\d.]+) (\w+)/([\d.]+) (\w+)").expect("valid regex"),
+            curl: Regex::new(r"(\d+(?:\.\d+)?)%\s*$").expect("valid regex"),
+        }
+    }
+    
+    fn parse(&self, line: &str) -> Option<f64> {
+        if let Some(caps) = self.aws.captures(line) {
+            let bytes = |value: &str, unit: &str| {
+                let scale = match unit {
+                    "KiB" => 1024.0,
+                    "MiB" => 1024.0 * 1024.0,
+                    "GiB" => 1024.0 * 1024.0 * 1024.0,
+                    "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+                    _ => 1.0,
+                };
+                value.parse::<f64>().ok().map(|value| value * scale)
+            };
+            let done = bytes(&caps[1], &caps[2])?;
+            let total = bytes(&caps[3], &caps[4]).filter(|total| *total > 0.0)?;
+            return Some(done / total * 100.0);
+        }
+        
+        self.curl.captures(line).and_then(|caps| caps[1].parse().ok())
+    }
+}
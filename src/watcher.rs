@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+use notify::event::{EventKind, ModifyKind};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use rust_tui_video_convert::notifier::Notifier;
+
+// Watches the folder shown in the file browser, so files that appear or vanish
+// (a download finishing, an output being written) show up without leaving it.
+// Where the system won't watch (no inotify slots, some network shares) the
+// listing just stays as it was read.
+pub struct DirWatcher {
+    watcher: Option<RecommendedWatcher>,
+    watched: Option<PathBuf>,
+    notify: Notifier,
+}
+
+impl DirWatcher {
+    pub fn new() -> Self {
+        let notify = Notifier::default();
+        let handler_notify = notify.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            // Writes to a file don't change the listing; creating, deleting and renaming do
+            let listing_changed = event.is_ok_and(|event| matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+            ));
+            if listing_changed {
+                handler_notify.notify();
+            }
+        }).ok();
+        
+        Self { watcher, watched: None, notify }
+    }
+    
+    // Called from the watcher's thread when the folder's listing changed
+    pub fn set_notifier(&self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify.set(notify);
+    }
+    
+    // Follow the browser into `dir`; nothing happens while it stays in the same folder
+    pub fn watch(&mut self, dir: &Path) {
+        if self.watched.as_deref() == Some(dir) {
+            return;
+        }
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        
+        if let Some(previous) = self.watched.take() {
+            let _ = watcher.unwatch(&previous);
+        }
+        // Remembered even when refused, so the refusal isn't retried on every key
+        let _ = watcher.watch(dir, RecursiveMode::NonRecursive);
+        self.watched = Some(dir.to_path_buf());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,148 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use regex::Regex;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::converter::{ProgressEvent, ProgressReporter, ProgressSender, VideoFormat};
+use crate::ffmpeg::ffmpeg_binary;
+use crate::plugins::forward_lines;
+
+#[derive(Error, Debug)]
+pub enum YtDlpError {
+    #[error("Failed to run yt-dlp: {0}")]
+    Io(#[from] io::Error),
+    
+    #[error("yt-dlp exited with status: {0}")]
+    ProcessError(i32),
+    
+    #[error("yt-dlp didn't report a downloaded file")]
+    NoOutput,
+}
+
+// Gives each download its own scratch folder within this process
+static NEXT_DOWNLOAD: AtomicUsize = AtomicUsize::new(0);
+
+pub fn is_available() -> bool {
+    Command::new("yt-dlp")
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+// A file fetched by yt-dlp; its scratch folder goes away once the conversion is done
+pub struct DownloadedFile {
+    pub path: PathBuf,
+    dir: PathBuf,
+}
+
+impl Drop for DownloadedFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+// First stage of a URL job: fetch the video behind a web page with yt-dlp so
+// the second stage can convert it like a local file
+pub struct YtDlpDownloader<'a> {
+    progress_tx: &'a ProgressSender,
+    cancel: &'a CancellationToken,
+}
+
+impl<'a> YtDlpDownloader<'a> {
+    pub fn new(progress_tx: &'a ProgressSender, cancel: &'a CancellationToken) -> Self {
+        Self { progress_tx, cancel }
+    }
+    
+    // None when the job was cancelled during the download
+    pub async fn download(&self, url: &Path, target_format: VideoFormat, output_file: &Path) -> Result<Option<DownloadedFile>, YtDlpError> {
+        let dir = std::env::temp_dir().join(format!(
+            "rust-tui-video-convert-download-{}-{}",
+            std::process::id(),
+            NEXT_DOWNLOAD.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        // Cleans up the scratch folder on every early return below
+        let mut downloaded = DownloadedFile { path: PathBuf::new(), dir };
+        
+        let reporter = ProgressReporter::new(self.progress_tx.clone(), url, target_format, output_file);
+        reporter.report(ProgressEvent::Stage { percent: 0, step: "Starting yt-dlp (step 1 of 2)...".to_string() }).await;
+        
+        let mut command = tokio::process::Command::new("yt-dlp");
+        command
+            .arg("--no-playlist")
+            .arg("--newline")
+            .arg("--progress")
+            .arg("--print").arg("after_move:filepath")
+            .arg("-o").arg(downloaded.dir.join("%(title).150B.%(ext)s"));
+        
+        // Merging separate video and audio needs FFmpeg, so use the one configured here
+        let ffmpeg = ffmpeg_binary();
+        if ffmpeg.parent().is_some_and(|dir| !dir.as_os_str().is_empty()) {
+            command.arg("--ffmpeg-location").arg(ffmpeg);
+        }
+        
+        let mut child = command
+            .arg(url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        
+        let (line_tx, mut line_rx) = mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_lines(stdout, line_tx.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_lines(stderr, line_tx.clone()));
+        }
+        drop(line_tx);
+        
+        // e.g. "[download]  42.3% of ~ 10.00MiB at 2.1MiB/s ETA 00:03"
+        let progress = Regex::new(r"^\[download\]\s+(\d+(?:\.\d+)?)%").expect("valid regex");
+        let mut last_percent = None;
+        loop {
+            let line = tokio::select! {
+                _ = self.cancel.cancelled() => {
+                    let _ = child.kill().await;
+                    return Ok(None);
+                },
+                line = line_rx.recv() => match line {
+                    Some(line) => line,
+                    None => break,
+                },
+            };
+            
+            // The final path is printed once the file is complete
+            if Path::new(&line).starts_with(&downloaded.dir) {
+                downloaded.path = PathBuf::from(line);
+                continue;
+            }
+            
+            let percent = progress.captures(&line).and_then(|caps| caps[1].parse::<f64>().ok());
+            if let Some(percent) = percent.map(|percent| percent.clamp(0.0, 100.0) as u8) {
+                if last_percent != Some(percent) {
+                    last_percent = Some(percent);
+                    reporter.report(ProgressEvent::Stage { percent, step: format!("Downloading with yt-dlp (step 1 of 2)... {}%", percent) }).await;
+                }
+            }
+        }
+        
+        let status = child.wait().await?;
+        if !status.success() {
+            return Err(YtDlpError::ProcessError(status.code().unwrap_or(-1)));
+        }
+        if !downloaded.path.is_file() {
+            return Err(YtDlpError::NoOutput);
+        }
+        Ok(Some(downloaded))
+    }
+}